@@ -0,0 +1,182 @@
+// WAL group commit benchmark.
+// Starts the server with BARUS_WAL_ALWAYS_USE_FSYNC=true for each BARUS_WAL_GROUP_COMMIT_US setting,
+// drives concurrent gRPC Put calls and prints p50/p99 latency and throughput.
+//
+// cargo bench --bench wal_group_commit
+// BENCH_WRITERS=64 BENCH_OPS=500 BENCH_DATA_DIR=/mnt/disk/tmp cargo bench --bench wal_group_commit
+use std::{
+    net::TcpListener,
+    path::PathBuf,
+    process::{Child, Command},
+    time::{Duration, Instant},
+};
+
+use barus::client::{
+    CreateTableRequest, HealthRequest, PutRequest, barus_service_client::BarusServiceClient,
+};
+use tonic::transport::Channel;
+
+// BARUS_WAL_GROUP_COMMIT_US values. "off": fsync per record
+const GROUP_COMMIT_SETTINGS: &[&str] = &["off", "0", "100", "500", "1000", "2000"];
+
+const TABLE: &str = "bench";
+
+fn env_or(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+struct Server {
+    child: Child,
+    data_dir: PathBuf,
+    grpc_port: u16,
+}
+
+impl Server {
+    fn start(group_commit: &str) -> Self {
+        let base_dir = std::env::var("BENCH_DATA_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| std::env::temp_dir());
+        let data_dir = base_dir.join(format!(
+            "barus_bench_group_commit_{}_{}",
+            group_commit,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&data_dir);
+
+        let grpc_port = free_port();
+
+        let child = Command::new(env!("CARGO_BIN_EXE_barus"))
+            .arg("serve")
+            .env("BARUS_DATA_DIR", &data_dir)
+            .env("BARUS_HTTP_PORT", free_port().to_string())
+            .env("BARUS_GRPC_PORT", grpc_port.to_string())
+            .env("BARUS_WAL_ALWAYS_USE_FSYNC", "true")
+            .env("BARUS_WAL_GROUP_COMMIT_US", group_commit)
+            .env("RUST_LOG", "warn")
+            .spawn()
+            .expect("failed to start barus");
+
+        Self {
+            child,
+            data_dir,
+            grpc_port,
+        }
+    }
+
+    async fn connect(&self) -> BarusServiceClient<Channel> {
+        let address = format!("http://127.0.0.1:{}", self.grpc_port);
+
+        for _ in 0..100 {
+            if let Ok(mut client) = BarusServiceClient::connect(address.clone()).await
+                && client.health(HealthRequest {}).await.is_ok()
+            {
+                return client;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        panic!("barus did not start on {}", address);
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        unsafe {
+            libc::kill(self.child.id() as libc::pid_t, libc::SIGTERM);
+        }
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.data_dir);
+    }
+}
+
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    let index = (sorted.len() * percent / 100).min(sorted.len() - 1);
+    sorted[index]
+}
+
+async fn run(group_commit: &str, writers: usize, ops_per_writer: usize) {
+    let server = Server::start(group_commit);
+    let mut client = server.connect().await;
+
+    client
+        .create_table(CreateTableRequest {
+            table: TABLE.into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let value = "v".repeat(100);
+    let started_at = Instant::now();
+
+    let mut tasks = vec![];
+    for writer in 0..writers {
+        let mut client = client.clone();
+        let value = value.clone();
+
+        tasks.push(tokio::spawn(async move {
+            let mut latencies = Vec::with_capacity(ops_per_writer);
+
+            for op in 0..ops_per_writer {
+                let request_started_at = Instant::now();
+                client
+                    .put(PutRequest {
+                        table: TABLE.into(),
+                        key: format!("key-{}-{}", writer, op),
+                        value: value.clone(),
+                        value_bytes: None,
+                    })
+                    .await
+                    .unwrap();
+                latencies.push(request_started_at.elapsed());
+            }
+
+            latencies
+        }));
+    }
+
+    let mut latencies = vec![];
+    for task in tasks {
+        latencies.extend(task.await.unwrap());
+    }
+    let elapsed = started_at.elapsed();
+    latencies.sort();
+
+    println!(
+        "{:>8} | {:>10.2?} | {:>10.2?} | {:>10.0}",
+        group_commit,
+        percentile(&latencies, 50),
+        percentile(&latencies, 99),
+        latencies.len() as f64 / elapsed.as_secs_f64(),
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    let writers = env_or("BENCH_WRITERS", 32);
+    let ops_per_writer = env_or("BENCH_OPS", 200);
+
+    println!(
+        "{} writers x {} puts, BARUS_WAL_ALWAYS_USE_FSYNC=true",
+        writers, ops_per_writer
+    );
+    println!(
+        "{:>8} | {:>10} | {:>10} | {:>10}",
+        "window", "p50", "p99", "puts/s"
+    );
+
+    for group_commit in GROUP_COMMIT_SETTINGS {
+        run(group_commit, writers, ops_per_writer).await;
+    }
+}
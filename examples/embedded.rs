@@ -0,0 +1,61 @@
+// Uses barus as a library inside the process, without the HTTP/gRPC servers.
+//
+// cargo run --example embedded
+use barus::{CreateTableOptions, DBEngine, ErrorCodes, ScanOptions, ValueType};
+
+#[tokio::main]
+async fn main() -> barus::Result<()> {
+    let data_dir = std::env::temp_dir().join(format!("barus_example_{}", std::process::id()));
+
+    let db = DBEngine::initialize(data_dir.clone()).await?;
+
+    match db
+        .create_table("users", CreateTableOptions::default())
+        .await
+    {
+        Ok(()) => {}
+        Err(error) if matches!(error.error_code, ErrorCodes::TableAlreadyExists) => {}
+        Err(error) => return Err(error),
+    }
+
+    db.put_value("users".into(), "user1".into(), "Alice".into())
+        .await?;
+    db.put_typed_value(
+        "users".into(),
+        "user2".into(),
+        r#"{"name": "Bob"}"#.into(),
+        ValueType::Json,
+    )
+    .await?;
+
+    let user = db.get_value("users", "user1").await?;
+    println!("user1 = {} (version {})", user.text(), user.version);
+
+    db.delete_value("users".into(), "user1".into()).await?;
+    match db.get_value("users", "user1").await {
+        Err(error) if matches!(error.error_code, ErrorCodes::ValueNotFound) => {
+            println!("user1 deleted")
+        }
+        other => println!(
+            "unexpected: {:?}",
+            other.map(|user| user.text().into_owned())
+        ),
+    }
+
+    let scan = db.scan("users", ScanOptions::default()).await?;
+    for item in scan.items {
+        println!(
+            "{} = {} ({})",
+            item.key,
+            String::from_utf8_lossy(&item.value),
+            item.value_type.as_str()
+        );
+    }
+
+    // flushes the WAL and the memtable. the data is there on the next initialize
+    db.shutdown().await?;
+
+    let _ = std::fs::remove_dir_all(&data_dir);
+
+    Ok(())
+}
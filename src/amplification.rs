@@ -0,0 +1,108 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+// Bytes written per table, to measure write amplification: every value is written to the WAL,
+// then again to a segment by the flush, and again whenever a record is rewritten (barus migrate).
+// Shared by the WAL and disktable managers of an engine. Counted since startup.
+#[derive(Debug, Default)]
+pub struct WriteAmplification {
+    tables: Mutex<HashMap<String, TableWriteBytes>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
+pub struct TableWriteBytes {
+    // keys and values of the writes made by clients
+    pub user_bytes: u64,
+    // WAL records, headers included
+    pub wal_bytes: u64,
+    // segment records written by flushes and large value write-throughs
+    pub flush_bytes: u64,
+    // segment records rewritten from older ones
+    pub rewrite_bytes: u64,
+}
+
+impl TableWriteBytes {
+    // bytes written to storage per byte written by clients. None before the first write
+    pub fn write_amplification(&self) -> Option<f64> {
+        if self.user_bytes == 0 {
+            return None;
+        }
+
+        let storage_bytes = self.wal_bytes + self.flush_bytes + self.rewrite_bytes;
+
+        Some(storage_bytes as f64 / self.user_bytes as f64)
+    }
+}
+
+// What a segment write is for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentWriteKind {
+    Flush,
+    Rewrite,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct TableWriteAmplification {
+    #[serde(flatten)]
+    pub bytes: TableWriteBytes,
+    pub write_amplification: Option<f64>,
+}
+
+impl WriteAmplification {
+    pub fn add_user_bytes(&self, table: &str, bytes: usize) {
+        self.update(table, |table| table.user_bytes += bytes as u64);
+    }
+
+    pub fn add_wal_bytes(&self, table: &str, bytes: usize) {
+        self.update(table, |table| table.wal_bytes += bytes as u64);
+    }
+
+    pub fn add_segment_bytes(&self, table: &str, kind: SegmentWriteKind, bytes: u32) {
+        self.update(table, |table| match kind {
+            SegmentWriteKind::Flush => table.flush_bytes += bytes as u64,
+            SegmentWriteKind::Rewrite => table.rewrite_bytes += bytes as u64,
+        });
+    }
+
+    // the table was dropped
+    pub fn remove_table(&self, table: &str) {
+        self.tables.lock().unwrap().remove(table);
+    }
+
+    pub fn table(&self, table: &str) -> TableWriteBytes {
+        self.tables
+            .lock()
+            .unwrap()
+            .get(table)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, TableWriteAmplification> {
+        self.tables
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(table, bytes)| {
+                (
+                    table.clone(),
+                    TableWriteAmplification {
+                        bytes: *bytes,
+                        write_amplification: bytes.write_amplification(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn update(&self, table: &str, update: impl FnOnce(&mut TableWriteBytes)) {
+        let mut tables = self.tables.lock().unwrap();
+
+        match tables.get_mut(table) {
+            Some(table) => update(table),
+            None => update(tables.entry(table.to_owned()).or_default()),
+        }
+    }
+}
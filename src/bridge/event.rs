@@ -1,9 +1,45 @@
-use crate::{memtable::MemtableMap, wal::SharedWALState};
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use tokio::sync::Notify;
+
+use crate::{
+    memtable::MemtableMap,
+    wal::{SharedWALState, state::WALPosition},
+};
 
-#[derive(Default)]
 pub struct MemtableFlushEvent {
     pub memtable: MemtableMap,
+    // where unflushed entries go back if the flush gives up
+    pub active_memtable: MemtableMap,
+    pub memtable_current_size: Arc<AtomicU64>,
     pub wal_state: SharedWALState,
+    // last WAL record when the memtables were swapped. the flushing memtable holds the records up to it
+    pub flushed_up_to: WALPosition,
+    pub size: u64, // memtable size at the time of the flush trigger
+    pub completion: FlushCompletion,
+}
+
+// Held by the flush worker until the flushing memtable has been written.
+// Dropping it lets the next flush swap the memtables.
+pub struct FlushCompletion {
+    pending: Arc<AtomicBool>,
+    done: Arc<Notify>,
+}
+
+impl FlushCompletion {
+    pub fn new(pending: Arc<AtomicBool>, done: Arc<Notify>) -> Self {
+        Self { pending, done }
+    }
+}
+
+impl Drop for FlushCompletion {
+    fn drop(&mut self) {
+        self.pending.store(false, Ordering::SeqCst);
+        self.done.notify_waiters();
+    }
 }
 
 impl MemtableFlushEvent {
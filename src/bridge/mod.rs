@@ -1,19 +1,48 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::{
-    bridge::event::{MemtableFlushEvent, MemtableFlushEventReceiver},
+    bridge::{
+        event::{MemtableFlushEvent, MemtableFlushEventReceiver},
+        status::{FlushStatus, FlushStatusTracker},
+    },
+    config::{FLUSH_MAX_RETRIES, FLUSH_RETRY_BACKOFF, FLUSH_RETRY_MAX_BACKOFF},
     disktable::DiskTableManager,
     errors,
-    memtable::MemtableManager,
+    memtable::{self, MemtableManager},
+    slowlog::{SlowOp, SlowOpLog},
+    storage::StorageHealth,
     wal::WALManager,
 };
 
 pub mod event;
+pub mod status;
+
+// How a failed memtable flush is retried before its entries go back to the active memtable
+#[derive(Debug, Clone, Copy)]
+pub struct FlushRetryPolicy {
+    pub max_retries: u32,
+    // doubled after each retry, up to FLUSH_RETRY_MAX_BACKOFF
+    pub backoff: Duration,
+}
+
+impl Default for FlushRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: *FLUSH_MAX_RETRIES,
+            backoff: *FLUSH_RETRY_BACKOFF,
+        }
+    }
+}
 
 // Mediates mutual calls between different layers.
+// Single owner of background maintenance: memtable flush now, compaction scheduling later.
 #[derive(Debug)]
 pub struct BridgeController {
     memtable_flush_receiver: MemtableFlushEventReceiver,
+    flush_status: FlushStatusTracker,
+    flush_retry_policy: FlushRetryPolicy,
+    slow_ops: Arc<SlowOpLog>,
+    storage_health: Arc<StorageHealth>,
 
     disktable_manager: Arc<DiskTableManager>,
     wal_manager: Arc<WALManager>,
@@ -27,15 +56,38 @@ impl BridgeController {
     ) -> Self {
         let (sender, receiver) = MemtableFlushEvent::make_channel();
 
-        memtable_manager.memtable_flush_sender = sender;
+        memtable_manager.attach_flush_sender(sender);
 
         BridgeController {
             memtable_flush_receiver: receiver,
+            flush_status: FlushStatusTracker::default(),
+            flush_retry_policy: FlushRetryPolicy::default(),
+            slow_ops: Arc::new(SlowOpLog::default()),
+            storage_health: Arc::new(StorageHealth::default()),
             disktable_manager: disktable_manager.clone(),
             wal_manager,
         }
     }
 
+    pub fn with_flush_retry_policy(mut self, flush_retry_policy: FlushRetryPolicy) -> Self {
+        self.flush_retry_policy = flush_retry_policy;
+        self
+    }
+
+    pub fn with_slow_ops(mut self, slow_ops: Arc<SlowOpLog>) -> Self {
+        self.slow_ops = slow_ops;
+        self
+    }
+
+    pub fn with_storage_health(mut self, storage_health: Arc<StorageHealth>) -> Self {
+        self.storage_health = storage_health;
+        self
+    }
+
+    pub fn flush_status(&self) -> FlushStatus {
+        self.flush_status.get()
+    }
+
     // Start background tasks for the bridge controller.
     pub fn start_background(&mut self) -> errors::Result<()> {
         self.start_memtable_flush_task();
@@ -52,27 +104,346 @@ impl BridgeController {
         let disk_manager = self.disktable_manager.clone();
         let wal_manager = self.wal_manager.clone();
         let wal_state_write_handles = self.wal_manager.wal_state_write_handles.clone();
+        let flush_status = self.flush_status.clone();
+        let flush_retry_policy = self.flush_retry_policy;
+        let slow_ops = self.slow_ops.clone();
+        let storage_health = self.storage_health.clone();
 
         tokio::spawn(async move {
             while let Some(event) = memtable_flush_receiver.recv().await {
                 // Handle memtable flush event
-                log::info!("Memtable flush event received");
+                log::info!(bytes = event.size; "Memtable flush event received");
+
+                flush_status.start(event.size);
+                let started_at = std::time::Instant::now();
 
-                if let Err(error) = disk_manager
+                let mut result = disk_manager
                     .write_memtable(
-                        event.memtable,
-                        event.wal_state,
+                        event.memtable.clone(),
+                        event.wal_state.clone(),
+                        event.flushed_up_to.clone(),
                         wal_state_write_handles.clone(),
                     )
-                    .await
-                {
-                    log::error!("Failed to write memtable: {}", error);
+                    .await;
+
+                // tables flushed before the failure are cleared, so a retry only writes the rest
+                let mut backoff = flush_retry_policy.backoff;
+                for retry in 1..=flush_retry_policy.max_retries {
+                    let Err(error) = &result else {
+                        break;
+                    };
+                    log::warn!(
+                        error:% = error, retry = retry;
+                        "Memtable flush failed. Retrying in {:?}: {}",
+                        backoff,
+                        error
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(FLUSH_RETRY_MAX_BACKOFF);
+
+                    result = disk_manager
+                        .write_memtable(
+                            event.memtable.clone(),
+                            event.wal_state.clone(),
+                            event.flushed_up_to.clone(),
+                            wal_state_write_handles.clone(),
+                        )
+                        .await;
+                }
+
+                // give up: keep the entries readable and flush them with the next flush.
+                // the WAL checkpoint did not move, so they also survive a crash
+                if result.is_err() {
+                    let restored = memtable::restore_unflushed(
+                        &event.active_memtable,
+                        &event.memtable,
+                        &event.memtable_current_size,
+                    )
+                    .await;
+                    log::error!(
+                        entries = restored as u64;
+                        "Memtable flush gave up. {} entries copied back to the active memtable",
+                        restored
+                    );
+                }
+
+                // every table of the memtable, retries included
+                slow_ops.record(SlowOp::Flush, "*", 0, started_at.elapsed());
+
+                // the next flush can swap the memtables now
+                drop(event.completion);
+
+                flush_status.complete(&result);
+
+                if let Err(error) = result {
+                    storage_health.observe(&error);
+                    log::error!(error:% = error; "Failed to write memtable: {}", error);
                 }
 
                 if let Err(error) = wal_manager.remove_old_wal_segments().await {
-                    log::error!("Failed to remove old WAL segments: {}", error);
+                    log::error!(error:% = error; "Failed to remove old WAL segments: {}", error);
                 }
             }
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::Duration,
+    };
+
+    use super::{BridgeController, FlushRetryPolicy};
+    use crate::{
+        disktable::{DiskTableManager, DisktableGetResult, table::CreateTableOptions},
+        memtable::{MemtableManager, table::MemtableGetValueResult},
+        system::get_system_info,
+        value_type::ValueType,
+        wal::{WALManager, WALOptions, encode::WALRecordBincodeCodec},
+    };
+
+    // waits until a flush completes after the given one
+    async fn wait_for_flush(
+        bridge_controller: &BridgeController,
+        previous: Option<std::time::SystemTime>,
+    ) {
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while bridge_controller.flush_status().last_completed_at == previous {
+            assert!(std::time::Instant::now() < deadline, "flush timed out");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    // memtable, disktable and a running flush task with a table "items" holding key0..key9
+    async fn start_bridge(
+        base_path: &std::path::Path,
+        flush_retry_policy: FlushRetryPolicy,
+    ) -> (
+        Arc<MemtableManager>,
+        Arc<DiskTableManager>,
+        BridgeController,
+    ) {
+        let _ = std::fs::remove_dir_all(base_path);
+
+        let wal_manager = Arc::new(
+            WALManager::initialize(
+                Box::new(WALRecordBincodeCodec {}),
+                base_path.to_path_buf(),
+                WALOptions::default(),
+            )
+            .await
+            .unwrap(),
+        );
+        let mut memtable_manager = MemtableManager::new(&get_system_info(), &wal_manager);
+        let disktable_manager = Arc::new(DiskTableManager::new(base_path.to_path_buf()));
+        disktable_manager.initialize().await.unwrap();
+        disktable_manager
+            .create_table("items", &CreateTableOptions::default())
+            .await
+            .unwrap();
+        memtable_manager.create_table("items").await.unwrap();
+
+        let mut bridge_controller = BridgeController::new(
+            wal_manager,
+            &mut memtable_manager,
+            disktable_manager.clone(),
+        )
+        .with_flush_retry_policy(flush_retry_policy);
+        bridge_controller.start_background().unwrap();
+
+        for i in 0..10 {
+            memtable_manager
+                .put(
+                    "items".into(),
+                    format!("key{}", i),
+                    format!("value{}", i).into_bytes(),
+                    ValueType::Raw,
+                    i + 1,
+                    0,
+                )
+                .await
+                .unwrap();
+        }
+
+        (
+            Arc::new(memtable_manager),
+            disktable_manager,
+            bridge_controller,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_failed_flush_keeps_acknowledged_writes() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_flush_retry_{}", std::process::id()));
+        let (memtable_manager, disktable_manager, bridge_controller) = start_bridge(
+            &base_path,
+            FlushRetryPolicy {
+                max_retries: 2,
+                backoff: Duration::from_millis(10),
+            },
+        )
+        .await;
+
+        // the first try and both retries fail
+        disktable_manager
+            .injected_flush_failures
+            .store(3, Ordering::SeqCst);
+        memtable_manager.trigger_flush().await.unwrap();
+        wait_for_flush(&bridge_controller, None).await;
+
+        assert!(bridge_controller.flush_status().last_error.is_some());
+        assert_eq!(
+            disktable_manager
+                .injected_flush_failures
+                .load(Ordering::SeqCst),
+            0
+        );
+        assert!(memtable_manager.get_memtable_current_size().unwrap() > 0);
+        for i in 0..10 {
+            let key = format!("key{}", i);
+            assert!(matches!(
+                memtable_manager.get_value("items", &key).await.unwrap(),
+                MemtableGetValueResult::Found { value, .. } if value == format!("value{}", i).as_bytes()
+            ));
+            assert!(matches!(
+                disktable_manager.get_value("items", &key).await.unwrap(),
+                DisktableGetResult::NotFound
+            ));
+        }
+
+        // a failure within the retries is not reported, and the next flush writes the restored entries
+        disktable_manager
+            .injected_flush_failures
+            .store(1, Ordering::SeqCst);
+        let previous = bridge_controller.flush_status().last_completed_at;
+        memtable_manager.trigger_flush().await.unwrap();
+        wait_for_flush(&bridge_controller, previous).await;
+
+        assert!(bridge_controller.flush_status().last_error.is_none());
+        for i in 0..10 {
+            assert!(matches!(
+                disktable_manager
+                    .get_value("items", &format!("key{}", i))
+                    .await
+                    .unwrap(),
+                DisktableGetResult::Found { value, .. } if value == format!("value{}", i).as_bytes()
+            ));
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_flush_on_full_disk_degrades_storage() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_flush_disk_full_{}", std::process::id()));
+        let (memtable_manager, disktable_manager, bridge_controller) = start_bridge(
+            &base_path,
+            FlushRetryPolicy {
+                max_retries: 0,
+                backoff: Duration::from_millis(10),
+            },
+        )
+        .await;
+
+        disktable_manager
+            .injected_flush_storage_full
+            .store(true, Ordering::SeqCst);
+        disktable_manager
+            .injected_flush_failures
+            .store(1, Ordering::SeqCst);
+        memtable_manager.trigger_flush().await.unwrap();
+        wait_for_flush(&bridge_controller, None).await;
+
+        assert!(bridge_controller.flush_status().last_error.is_some());
+        assert!(bridge_controller.storage_health.is_degraded());
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_reads_during_failed_flush() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_flush_reads_{}", std::process::id()));
+        let (memtable_manager, disktable_manager, bridge_controller) = start_bridge(
+            &base_path,
+            FlushRetryPolicy {
+                max_retries: 3,
+                backoff: Duration::from_millis(5),
+            },
+        )
+        .await;
+
+        // same order as DBEngine::get_value: active, flushing, then disk
+        let reader = |memtable_manager: Arc<MemtableManager>,
+                      disktable_manager: Arc<DiskTableManager>,
+                      stop: Arc<AtomicBool>| async move {
+            let mut reads = 0;
+            while !stop.load(Ordering::SeqCst) {
+                for i in 0..10 {
+                    let key = format!("key{}", i);
+                    let expected = format!("value{}", i).into_bytes();
+
+                    let found = match memtable_manager.get_value("items", &key).await.unwrap() {
+                        MemtableGetValueResult::Found { value, .. } => Some(value),
+                        _ => match memtable_manager
+                            .get_value_from_flushing("items", &key)
+                            .await
+                            .unwrap()
+                        {
+                            MemtableGetValueResult::Found { value, .. } => Some(value),
+                            _ => match disktable_manager.get_value("items", &key).await.unwrap() {
+                                DisktableGetResult::Found { value, .. } => Some(value),
+                                _ => None,
+                            },
+                        },
+                    };
+                    assert_eq!(found, Some(expected), "{} was not readable", key);
+                    reads += 1;
+                }
+                tokio::task::yield_now().await;
+            }
+            reads
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                tokio::spawn(reader(
+                    memtable_manager.clone(),
+                    disktable_manager.clone(),
+                    stop.clone(),
+                ))
+            })
+            .collect();
+
+        // gives up, then the next flush succeeds after a retry
+        disktable_manager
+            .injected_flush_failures
+            .store(4, Ordering::SeqCst);
+        memtable_manager.trigger_flush().await.unwrap();
+        wait_for_flush(&bridge_controller, None).await;
+        assert!(bridge_controller.flush_status().last_error.is_some());
+
+        disktable_manager
+            .injected_flush_failures
+            .store(1, Ordering::SeqCst);
+        let previous = bridge_controller.flush_status().last_completed_at;
+        memtable_manager.trigger_flush().await.unwrap();
+        wait_for_flush(&bridge_controller, previous).await;
+        assert!(bridge_controller.flush_status().last_error.is_none());
+
+        stop.store(true, Ordering::SeqCst);
+        for reader in readers {
+            assert!(reader.await.unwrap() > 0);
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use crate::errors;
+
+// Memtable flush progress
+#[derive(Debug, Clone, Default)]
+pub struct FlushStatus {
+    pub started_at: Option<SystemTime>, // Some while a flush is running
+    pub flushing_bytes: u64,
+    pub last_completed_at: Option<SystemTime>,
+    pub last_duration: Option<Duration>,
+    pub last_flushed_bytes: u64,
+    pub last_error: Option<String>,
+}
+
+impl FlushStatus {
+    pub fn is_flushing(&self) -> bool {
+        self.started_at.is_some()
+    }
+}
+
+// Shared between the flush task (writer) and DBEngine (reader)
+#[derive(Debug, Clone, Default)]
+pub struct FlushStatusTracker {
+    status: Arc<Mutex<FlushStatus>>,
+}
+
+impl FlushStatusTracker {
+    pub fn get(&self) -> FlushStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn start(&self, bytes: u64) {
+        let mut status = self.status.lock().unwrap();
+
+        status.started_at = Some(SystemTime::now());
+        status.flushing_bytes = bytes;
+    }
+
+    pub fn complete(&self, result: &errors::Result<()>) {
+        let mut status = self.status.lock().unwrap();
+
+        let now = SystemTime::now();
+        let started_at = status.started_at.take().unwrap_or(now);
+
+        status.last_completed_at = Some(now);
+        status.last_duration = Some(now.duration_since(started_at).unwrap_or_default());
+        status.last_flushed_bytes = std::mem::take(&mut status.flushing_bytes);
+        status.last_error = result.as_ref().err().map(|error| error.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FlushStatusTracker;
+
+    #[test]
+    fn test_flush_status_transitions() {
+        let tracker = FlushStatusTracker::default();
+        assert!(!tracker.get().is_flushing());
+
+        tracker.start(1024);
+        let status = tracker.get();
+        assert!(status.is_flushing());
+        assert_eq!(status.flushing_bytes, 1024);
+
+        tracker.complete(&Ok(()));
+        let status = tracker.get();
+        assert!(!status.is_flushing());
+        assert!(status.last_completed_at.is_some());
+        assert!(status.last_duration.is_some());
+        assert_eq!(status.last_flushed_bytes, 1024);
+        assert!(status.last_error.is_none());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::config::VACUUM_DEFAULT_MIN_DEAD_RATIO;
+
+// Command line subcommands.
+// serve is the default, the rest run against BARUS_DATA_DIR without starting the servers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Serve,
+    VerifyWal,
+    Reindex { table: String },
+    Dump { table: String },
+    Compact { table: String, min_dead_ratio: f64 },
+    Migrate { dry_run: bool },
+    Help,
+}
+
+pub const USAGE: &str = "Usage: barus [COMMAND]
+
+Commands:
+  serve              start the HTTP and gRPC servers (default)
+  verify-wal         check the WAL for corruption
+  reindex <table>    rebuild the primary and secondary indexes of a table
+  dump <table>       print the flushed records of a table as JSON lines
+  compact <table> [--min-dead-ratio <ratio>]
+                     vacuum the segments of a table whose dead record ratio is at least
+                     ratio (0 ~ 1, default 0.5), relocating their live records
+  migrate [--dry-run]
+                     rewrite records written in an older format in the current one,
+                     and flush the WAL into the tables. --dry-run only reports them
+  help               print this message
+
+The data directory is read from BARUS_DATA_DIR.
+Stop the server before running maintenance commands against the same directory.";
+
+impl Command {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+
+        let Some(command) = args.next() else {
+            return Ok(Command::Serve);
+        };
+
+        let command = match command.as_str() {
+            "serve" => Command::Serve,
+            // kept for compatibility with the old flag
+            "verify-wal" | "--verify-wal" => Command::VerifyWal,
+            "reindex" => Command::Reindex {
+                table: Self::table_arg(&command, args.next())?,
+            },
+            "dump" => Command::Dump {
+                table: Self::table_arg(&command, args.next())?,
+            },
+            "compact" => Command::Compact {
+                table: Self::table_arg(&command, args.next())?,
+                min_dead_ratio: match args.next().as_deref() {
+                    None => VACUUM_DEFAULT_MIN_DEAD_RATIO,
+                    Some("--min-dead-ratio") => Self::ratio_arg(args.next())?,
+                    Some(arg) => return Err(format!("unexpected argument '{}'", arg)),
+                },
+            },
+            "migrate" => match args.next().as_deref() {
+                None => Command::Migrate { dry_run: false },
+                Some("--dry-run") => Command::Migrate { dry_run: true },
+                Some(arg) => return Err(format!("unexpected argument '{}'", arg)),
+            },
+            "help" | "--help" | "-h" => Command::Help,
+            _ => return Err(format!("unknown command '{}'", command)),
+        };
+
+        if let Some(extra) = args.next() {
+            return Err(format!("unexpected argument '{}'", extra));
+        }
+
+        Ok(command)
+    }
+
+    fn table_arg(command: &str, table: Option<String>) -> Result<String, String> {
+        match table {
+            Some(table) if !table.is_empty() => Ok(table),
+            _ => Err(format!("'{}' requires a table name", command)),
+        }
+    }
+
+    fn ratio_arg(ratio: Option<String>) -> Result<f64, String> {
+        match ratio.as_deref().map(str::parse::<f64>) {
+            Some(Ok(ratio)) if (0.0..=1.0).contains(&ratio) => Ok(ratio),
+            _ => Err("'--min-dead-ratio' requires a ratio (0 <= ratio <= 1)".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        Command::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse(&[]), Ok(Command::Serve));
+        assert_eq!(parse(&["serve"]), Ok(Command::Serve));
+        assert_eq!(parse(&["verify-wal"]), Ok(Command::VerifyWal));
+        assert_eq!(parse(&["--verify-wal"]), Ok(Command::VerifyWal));
+        assert_eq!(
+            parse(&["reindex", "users"]),
+            Ok(Command::Reindex {
+                table: "users".to_string()
+            })
+        );
+        assert_eq!(
+            parse(&["dump", "users"]),
+            Ok(Command::Dump {
+                table: "users".to_string()
+            })
+        );
+
+        assert_eq!(
+            parse(&["compact", "users"]),
+            Ok(Command::Compact {
+                table: "users".to_string(),
+                min_dead_ratio: VACUUM_DEFAULT_MIN_DEAD_RATIO
+            })
+        );
+        assert_eq!(
+            parse(&["compact", "users", "--min-dead-ratio", "0.2"]),
+            Ok(Command::Compact {
+                table: "users".to_string(),
+                min_dead_ratio: 0.2
+            })
+        );
+
+        assert_eq!(parse(&["migrate"]), Ok(Command::Migrate { dry_run: false }));
+        assert_eq!(
+            parse(&["migrate", "--dry-run"]),
+            Ok(Command::Migrate { dry_run: true })
+        );
+
+        assert!(parse(&["reindex"]).is_err());
+        assert!(parse(&["compact"]).is_err());
+        assert!(parse(&["compact", "users", "--min-dead-ratio"]).is_err());
+        assert!(parse(&["compact", "users", "--min-dead-ratio", "1.5"]).is_err());
+        assert!(parse(&["migrate", "--force"]).is_err());
+        assert!(parse(&["dump", "users", "extra"]).is_err());
+        assert!(parse(&["unknown"]).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+// Implementations of the command line subcommands (see cli::Command)
+use std::{io::Write, path::PathBuf, sync::Arc};
+
+use crate::{
+    cli::{self, Command},
+    config::{REPLICATION_LEADER, SHUTDOWN_TIMEOUT},
+    db::DBEngine,
+    disktable::DiskTableManager,
+    errors, grpc, http, os, replication,
+    wal::{WALManager, encode::WALRecordBincodeCodec},
+};
+
+pub async fn run(command: Command) -> errors::Result<()> {
+    match command {
+        Command::Serve => serve().await,
+        Command::VerifyWal => verify_wal().await,
+        Command::Reindex { table } => reindex(&table).await,
+        Command::Dump { table } => dump(&table).await,
+        Command::Compact {
+            table,
+            min_dead_ratio,
+        } => compact(&table, min_dead_ratio).await,
+        Command::Migrate { dry_run } => migrate(dry_run).await,
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+    }
+}
+
+pub fn get_data_dir() -> PathBuf {
+    let path = std::env::var("BARUS_DATA_DIR").unwrap_or_else(|_| "data".to_string());
+    PathBuf::from(path)
+}
+
+// Offline WAL check. Exits non-zero when corruption is found
+async fn verify_wal() -> errors::Result<()> {
+    let data_dir = get_data_dir();
+
+    log::info!("Verifying WAL in {}...", data_dir.display());
+
+    let report = WALManager::verify(&WALRecordBincodeCodec {}, &data_dir).await?;
+
+    log::info!("Segments: {}", report.segment_count);
+    log::info!("Records: {}", report.total_records);
+    log::info!("Corrupt records: {}", report.corrupt_records);
+    log::info!("Out of order records: {}", report.out_of_order_records);
+    log::info!("Truncated segments: {:?}", report.truncated_segments);
+    log::info!(
+        "Recoverable record ID range: {:?} ~ {:?}",
+        report.first_record_id,
+        report.last_record_id
+    );
+
+    if !report.is_healthy() {
+        log::error!("WAL verification failed");
+        std::process::exit(1);
+    }
+
+    log::info!("WAL verification passed");
+
+    Ok(())
+}
+
+// Rebuild table indexes from segment files. Only the disk table layer is loaded
+async fn reindex(table: &str) -> errors::Result<()> {
+    let disktable_manager = DiskTableManager::new(get_data_dir());
+    disktable_manager.initialize().await?;
+
+    log::info!("Rebuilding indexes of table '{}'...", table);
+
+    let indexed_count = disktable_manager.rebuild_index(table).await?;
+
+    log::info!("Reindexed {} records", indexed_count);
+
+    Ok(())
+}
+
+// Vacuum the segments of a table. Only the disk table layer is loaded,
+// records still in the WAL are relocated by the next vacuum after they are flushed
+async fn compact(table: &str, min_dead_ratio: f64) -> errors::Result<()> {
+    let disktable_manager = DiskTableManager::new(get_data_dir());
+    disktable_manager.initialize().await?;
+
+    log::info!(
+        "Compacting table '{}' (min dead ratio {})...",
+        table,
+        min_dead_ratio
+    );
+
+    let report = disktable_manager
+        .vacuum_table(table, min_dead_ratio)
+        .await?;
+
+    log::info!(
+        "Table '{}': {} segments, {} removed, {} records relocated, {} bytes reclaimed",
+        table,
+        report.segments,
+        report.removed_segments,
+        report.relocated_records,
+        report.reclaimed_bytes
+    );
+
+    Ok(())
+}
+
+// Print flushed records as JSON lines. Records still in the WAL are not included
+async fn dump(table: &str) -> errors::Result<()> {
+    let disktable_manager = DiskTableManager::new(get_data_dir());
+    disktable_manager.initialize().await?;
+
+    if !disktable_manager.table_exists(table).await? {
+        return Err(errors::Errors::new(errors::ErrorCodes::TableNotFound)
+            .with_message(format!("Table '{}' does not exist", table)));
+    }
+
+    let mut stdout = std::io::stdout().lock();
+
+    for record in disktable_manager.scan_table(table).await? {
+        let line = serde_json::json!({
+            "key": record.key,
+            "value": record.value,
+            "value_type": record.value_type,
+            "version": record.version,
+            "written_at": record.written_at,
+        });
+
+        if let Err(error) = writeln!(stdout, "{}", line) {
+            // stdout가 닫힌 경우 (ex: | head)
+            log::warn!("Failed to write dump output: {}", error);
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+// Rewrite records written in an older format in the current one.
+// The WAL is replayed by the engine and flushed on shutdown, so its records end up in the tables too
+async fn migrate(dry_run: bool) -> errors::Result<()> {
+    let db_engine = DBEngine::initialize(get_data_dir()).await?;
+
+    let tables = db_engine.list_tables().await?.tables;
+    let mut outdated_count = 0;
+
+    for (table_index, table) in tables.iter().enumerate() {
+        log::info!(
+            "Migrating table '{}' ({}/{})...",
+            table.table_name,
+            table_index + 1,
+            tables.len()
+        );
+
+        let report = db_engine.migrate_table(&table.table_name, dry_run).await?;
+        outdated_count += report.outdated_count();
+
+        log::info!(
+            "Table '{}': {} segments, {} live records, outdated {:?}, {} rewritten",
+            table.table_name,
+            report.segments,
+            report.live_records,
+            report.outdated_records,
+            report.rewritten_records
+        );
+    }
+
+    if dry_run {
+        log::info!(
+            "Dry run: {} records would be rewritten. WAL records after the checkpoint are flushed by a real run",
+            outdated_count
+        );
+        return Ok(());
+    }
+
+    db_engine.shutdown().await?;
+    log::info!("Migration completed: {} records rewritten", outdated_count);
+
+    Ok(())
+}
+
+async fn serve() -> errors::Result<()> {
+    log::info!("Initializing DB Engine...");
+
+    // DB Engine 초기화 (한 번만)
+    let db_engine = DBEngine::initialize(get_data_dir()).await?;
+
+    // Arc로 감싸서 여러 서버가 공유
+    let shared_db = Arc::new(db_engine);
+
+    log::info!("Starting servers...");
+
+    let (shutdown_sender, shutdown_receiver) = os::shutdown_channel();
+
+    // HTTP 서버와 gRPC 서버를 동시에 실행
+    let http_db = shared_db.clone();
+    let http_shutdown = shutdown_receiver.clone();
+    let mut http_server = tokio::spawn(async move {
+        if let Err(e) = http::run_server(http_db, http_shutdown).await {
+            log::error!("HTTP server error: {}", e);
+        }
+    });
+
+    let grpc_db = shared_db.clone();
+    let grpc_shutdown = shutdown_receiver.clone();
+    let mut grpc_server = tokio::spawn(async move {
+        if let Err(e) = grpc::run_grpc_server(grpc_db, grpc_shutdown).await {
+            log::error!("gRPC server error: {}", e);
+        }
+    });
+
+    // follower mode: read-only, applies the leader's writes
+    let follower = REPLICATION_LEADER.clone().map(|leader| {
+        log::info!("Running as a follower of {}", leader);
+        shared_db.set_replica();
+
+        tokio::spawn(replication::run_follower(
+            shared_db.clone(),
+            leader,
+            shutdown_receiver.clone(),
+        ))
+    });
+
+    // 종료 시그널을 받거나, 둘 중 하나라도 종료되면 프로그램 종료
+    tokio::select! {
+        _ = os::handle_shutdown() => log::info!("Graceful shutdown started"),
+        _ = &mut http_server => log::info!("HTTP server stopped"),
+        _ = &mut grpc_server => log::info!("gRPC server stopped"),
+    }
+
+    // 종료가 멈추더라도 (ex: 디스크 장애) 제한 시간 안에는 프로세스가 끝나도록
+    if let Some(timeout) = *SHUTDOWN_TIMEOUT {
+        os::start_shutdown_watchdog(timeout);
+    }
+
+    // 새 연결을 막고 처리 중인 요청이 끝날 때까지 대기
+    let _ = shutdown_sender.send(true);
+
+    if !http_server.is_finished() {
+        let _ = http_server.await;
+        log::info!("HTTP server stopped");
+    }
+
+    if !grpc_server.is_finished() {
+        let _ = grpc_server.await;
+        log::info!("gRPC server stopped");
+    }
+
+    if let Some(follower) = follower {
+        let _ = follower.await;
+        log::info!("Replication stopped");
+    }
+
+    // 서버가 모두 멈춘 뒤에 쓰기를 막고 WAL과 memtable을 flush
+    if let Err(error) = shared_db.shutdown().await {
+        log::error!("Failed to flush on shutdown: {}", error);
+    }
+
+    log::info!("Graceful shutdown completed");
+
+    Ok(())
+}
@@ -15,29 +15,497 @@ pub static GRPC_PORT: LazyLock<u16> = LazyLock::new(|| {
         .and_then(|val| val.parse().ok())
         .unwrap_or(GRPC_DEFAULT_PORT)
 });
+// HTTP handlers running longer than this get 504 (seconds). None: no limit (unset, "0" or "off")
+pub static HTTP_REQUEST_TIMEOUT: LazyLock<Option<std::time::Duration>> = LazyLock::new(|| {
+    let value = std::env::var("BARUS_HTTP_REQUEST_TIMEOUT").ok()?;
 
-pub const WAL_SEGMENT_SIZE: u32 = 1024 * 1024 * 32; // 32MB
+    match value.trim() {
+        "0" | "off" => None,
+        value => match value.parse::<u64>() {
+            Ok(seconds) => Some(std::time::Duration::from_secs(seconds)),
+            Err(_) => {
+                log::warn!(
+                    "Invalid BARUS_HTTP_REQUEST_TIMEOUT '{}'. Request timeout disabled",
+                    value
+                );
+                None
+            }
+        },
+    }
+});
+// gzip HTTP responses for clients that send Accept-Encoding: gzip
+pub static HTTP_COMPRESSION: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("BARUS_HTTP_COMPRESSION")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(false)
+});
+// Admin endpoints that expose table internals (ex: GET /tables/{table}/key-map). 403 when disabled
+pub static ADMIN_API_ENABLED: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("BARUS_ADMIN_API")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(false)
+});
+// gRPC-web (HTTP/1.1 + CORS) on the gRPC port, for browser clients
+pub static GRPC_WEB_ENABLED: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("BARUS_GRPC_WEB")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(false)
+});
+
+pub const WAL_DEFAULT_SEGMENT_SIZE: u32 = 1024 * 1024 * 32; // 32MB
+pub static WAL_SEGMENT_SIZE: LazyLock<u32> = LazyLock::new(|| {
+    std::env::var("BARUS_WAL_SEGMENT_SIZE")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(WAL_DEFAULT_SEGMENT_SIZE)
+});
+pub static WAL_ALWAYS_USE_FSYNC: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("BARUS_WAL_ALWAYS_USE_FSYNC")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(false)
+});
+// shrink closed segments to their used size (saves disk space, gives up the preallocation)
+pub static WAL_TRUNCATE_ON_ROTATE: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("BARUS_WAL_TRUNCATE_ON_ROTATE")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(false)
+});
+// Group commit window for BARUS_WAL_ALWAYS_USE_FSYNC, in microseconds.
+// Appends wait this long and share one fsync. None (unset or "off"): every append is synced on its own
+pub static WAL_GROUP_COMMIT_WINDOW: LazyLock<Option<std::time::Duration>> = LazyLock::new(|| {
+    let value = std::env::var("BARUS_WAL_GROUP_COMMIT_US").ok()?;
+
+    match value.trim() {
+        "off" => None,
+        value => match value.parse::<u64>() {
+            Ok(micros) => Some(std::time::Duration::from_micros(micros)),
+            Err(_) => {
+                log::warn!(
+                    "Invalid BARUS_WAL_GROUP_COMMIT_US '{}'. Group commit disabled",
+                    value
+                );
+                None
+            }
+        },
+    }
+});
+pub const WAL_DEFAULT_FSYNC_INTERVAL_SECS: u64 = 10;
+// None: background fsync disabled ("0" or "off")
+pub static WAL_FSYNC_INTERVAL: LazyLock<Option<std::time::Duration>> = LazyLock::new(|| {
+    let default_interval = Some(std::time::Duration::from_secs(
+        WAL_DEFAULT_FSYNC_INTERVAL_SECS,
+    ));
+
+    let Ok(value) = std::env::var("BARUS_WAL_FSYNC_INTERVAL_SECS") else {
+        return default_interval;
+    };
+
+    match value.trim() {
+        "0" | "off" => None,
+        value => match value.parse::<u64>() {
+            Ok(seconds) => Some(std::time::Duration::from_secs(seconds)),
+            Err(_) => {
+                log::warn!(
+                    "Invalid BARUS_WAL_FSYNC_INTERVAL_SECS '{}'. Using default {}s",
+                    value,
+                    WAL_DEFAULT_FSYNC_INTERVAL_SECS
+                );
+                default_interval
+            }
+        },
+    }
+});
+pub const SHUTDOWN_DEFAULT_TIMEOUT_SECS: u64 = 30;
+// None: wait for the graceful shutdown forever ("0" or "off")
+pub static SHUTDOWN_TIMEOUT: LazyLock<Option<std::time::Duration>> = LazyLock::new(|| {
+    let default_timeout = Some(std::time::Duration::from_secs(
+        SHUTDOWN_DEFAULT_TIMEOUT_SECS,
+    ));
+
+    let Ok(value) = std::env::var("BARUS_SHUTDOWN_TIMEOUT_SECS") else {
+        return default_timeout;
+    };
+
+    match value.trim() {
+        "0" | "off" => None,
+        value => match value.parse::<u64>() {
+            Ok(seconds) => Some(std::time::Duration::from_secs(seconds)),
+            Err(_) => {
+                log::warn!(
+                    "Invalid BARUS_SHUTDOWN_TIMEOUT_SECS '{}'. Using default {}s",
+                    value,
+                    SHUTDOWN_DEFAULT_TIMEOUT_SECS
+                );
+                default_timeout
+            }
+        },
+    }
+});
+// Background task delays are randomized by ±this fraction (0.1 = ±10%) so periodic tasks do not align
+pub static SCHEDULER_JITTER: LazyLock<f64> = LazyLock::new(|| {
+    std::env::var("BARUS_SCHEDULER_JITTER")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .filter(|jitter: &f64| (0.0..=1.0).contains(jitter))
+        .unwrap_or(0.1)
+});
+// Comma-separated background task names that are not started (ex: "wal_fsync")
+pub static SCHEDULER_DISABLED_TASKS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    std::env::var("BARUS_SCHEDULER_DISABLED_TASKS")
+        .map(|val| {
+            val.split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+});
+pub const WAL_FSYNC_UNHEALTHY_THRESHOLD: u32 = 5; // consecutive background fsync failures
+pub const WAL_FSYNC_MAX_BACKOFF: std::time::Duration = std::time::Duration::from_secs(300);
 pub const WAL_DIRECTORY: &str = "wal";
 pub const WAL_STATE_PATH: &str = "wal_state.json";
 pub const WAL_RECORD_HEADER_SIZE: usize = 4; // 4 bytes for record length
+pub const WAL_RECORD_FIXED_FIELDS_MAX_SIZE: usize = 64; // record id, record type, length prefixes
+// A segment must hold at least one record of the maximum size
+pub const WAL_SEGMENT_MIN_SIZE: u32 = (WAL_RECORD_HEADER_SIZE
+    + WAL_RECORD_FIXED_FIELDS_MAX_SIZE
+    + TABLE_NAME_MAX_SIZE
+    + KEY_BYTES_MAX_SIZE
+    + VALUE_BYTES_MAX_SIZE) as u32;
 
 pub const TABLES_DIRECTORY: &str = "tables";
 
 pub const TABLES_SEGMENT_DIRECTORY: &str = "segments";
+pub const TABLES_SEGMENT_FILE_EXTENSION: &str = "seg"; // files created before it have no extension
 pub const TABLES_INDEX_DIRECTORY: &str = "indices";
 
+// Point reads reject records without a checksum (written before checksums existed) as invalid.
+// Leave off while such records are left
+pub static REQUIRE_RECORD_CHECKSUM: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("BARUS_REQUIRE_RECORD_CHECKSUM")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(false)
+});
+
+// sync_data the segment and index files written by a memtable flush before the WAL checkpoint moves.
+// Turning it off is faster, but an OS crash or power loss right after a flush can lose flushed data
+// whose WAL records were already skipped by the checkpoint.
+pub static FSYNC_ON_FLUSH: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("BARUS_FSYNC_ON_FLUSH")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(true)
+});
+
+// Values of at least this many bytes bypass the memtable and are written straight to the disktable.
+// None (unset or 0) disables it
+pub static LARGE_VALUE_THRESHOLD: LazyLock<Option<usize>> = LazyLock::new(|| {
+    std::env::var("BARUS_LARGE_VALUE_THRESHOLD")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .filter(|threshold| *threshold > 0)
+});
+
+// Default share of records (0 < rate <= 1) read into GET /tables/{table}/size-histogram
+pub static SIZE_HISTOGRAM_SAMPLE_RATE: LazyLock<f64> = LazyLock::new(|| {
+    std::env::var("BARUS_SIZE_HISTOGRAM_SAMPLE_RATE")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .filter(|rate| *rate > 0.0 && *rate <= 1.0)
+        .unwrap_or(1.0)
+});
+
 pub const MEMTABLE_SIZE_SOFT_LIMIT_RATE: f64 = 0.3; // 시스템 메모리의 30%
 pub const MEMTABLE_SIZE_HARD_LIMIT_RATE: f64 = 0.5; // 시스템 메모리의 50%
 
+// Each table's memtable is split into this many shards with their own locks, so writes to different keys
+// of a table run in parallel. None (unset): one shard per CPU core
+pub static MEMTABLE_SHARDS: LazyLock<Option<usize>> = LazyLock::new(|| {
+    std::env::var("BARUS_MEMTABLE_SHARDS")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .filter(|val| *val > 0)
+});
+
+// Table memtables keep their keys sorted (BTreeMap), so range scans don't sort every shard.
+// Point reads and writes are O(log n) instead of O(1)
+pub static MEMTABLE_ORDERED: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("BARUS_MEMTABLE_ORDERED")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(false)
+});
+
+// A failed memtable flush is retried this many times, waiting FLUSH_RETRY_BACKOFF and then twice as long each time.
+// When all retries fail, the flushing entries go back to the active memtable and wait for the next flush
+pub const FLUSH_DEFAULT_MAX_RETRIES: u32 = 3;
+pub static FLUSH_MAX_RETRIES: LazyLock<u32> = LazyLock::new(|| {
+    std::env::var("BARUS_FLUSH_MAX_RETRIES")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(FLUSH_DEFAULT_MAX_RETRIES)
+});
+pub const FLUSH_DEFAULT_RETRY_BACKOFF_MS: u64 = 1000;
+pub static FLUSH_RETRY_BACKOFF: LazyLock<std::time::Duration> = LazyLock::new(|| {
+    std::time::Duration::from_millis(
+        std::env::var("BARUS_FLUSH_RETRY_BACKOFF_MS")
+            .ok()
+            .and_then(|val| val.parse().ok())
+            .unwrap_or(FLUSH_DEFAULT_RETRY_BACKOFF_MS),
+    )
+});
+pub const FLUSH_RETRY_MAX_BACKOFF: std::time::Duration = std::time::Duration::from_secs(30);
+
 pub const DISKTABLE_SEGMENT_SIZE: u32 = 1024 * 1024 * 1024; // 1GB
 pub const DISKTABLE_PAGE_SIZE: u32 = 1024 * 1024; // 1MB
 pub const DISKTABLE_PAGE_COUNT_PER_SEGMENT: u32 = DISKTABLE_SEGMENT_SIZE / DISKTABLE_PAGE_SIZE; // 1024 pages
 
+// At the end of a memtable flush, segment files whose records are all deleted are removed
+// (the current segment is kept for appends)
+pub static RECLAIM_DEAD_SEGMENTS: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var("BARUS_RECLAIM_DEAD_SEGMENTS")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(true)
+});
+
+// POST /tables/{table}/vacuum rewrites older segments with at least this share of dead records
+pub const VACUUM_DEFAULT_MIN_DEAD_RATIO: f64 = 0.5;
+
+// Segment file handles kept open per table. Past the limit the least recently used one is closed,
+// and reopened on its next access
+pub const MAX_OPEN_SEGMENTS_PER_TABLE_DEFAULT: usize = 64;
+pub static MAX_OPEN_SEGMENTS_PER_TABLE: LazyLock<usize> = LazyLock::new(|| {
+    std::env::var("BARUS_MAX_OPEN_SEGMENTS_PER_TABLE")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .filter(|val| *val > 0)
+        .unwrap_or(MAX_OPEN_SEGMENTS_PER_TABLE_DEFAULT)
+});
+
+// A disk read from an older segment whose share of deleted records is at least this ratio (0 < ratio <= 1)
+// re-appends the record to the current segment, so hot keys leave mostly-dead segments behind.
+// None (unset) disables it
+pub static READ_PROMOTION_DEAD_RATIO: LazyLock<Option<f64>> = LazyLock::new(|| {
+    std::env::var("BARUS_READ_PROMOTION_DEAD_RATIO")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .filter(|ratio| *ratio > 0.0 && *ratio <= 1.0)
+});
+// Records promoted per second at most
+pub const READ_PROMOTION_DEFAULT_MAX_PER_SECOND: u32 = 10;
+pub static READ_PROMOTION_MAX_PER_SECOND: LazyLock<u32> = LazyLock::new(|| {
+    std::env::var("BARUS_READ_PROMOTION_MAX_PER_SECOND")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .filter(|val| *val > 0)
+        .unwrap_or(READ_PROMOTION_DEFAULT_MAX_PER_SECOND)
+});
+
+// Vacuum segment writes per second at most, in bytes. Unset or 0: unlimited
+pub static COMPACTION_MAX_BYTES_PER_SEC: LazyLock<Option<u64>> = LazyLock::new(|| {
+    std::env::var("BARUS_COMPACTION_MAX_BYTES_PER_SEC")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .filter(|val| *val > 0)
+});
+
+pub const KEY_LOCK_STRIPE_COUNT: usize = 1024;
+
+// change events buffered per subscriber. slower subscribers lag and must resubscribe
+pub const CHANGE_STREAM_CAPACITY: usize = 4096;
+
+// records a streaming scan reads ahead of its consumer (ex: a slow export download)
+pub const SCAN_STREAM_CAPACITY: usize = 1024;
+
+// keys a scan reads from the memtable and the index at a time
+pub const SCAN_PAGE_KEYS: usize = 1024;
+
+pub const SCAN_DEFAULT_MAX_BYTES: usize = 1024 * 1024 * 16; // 16MB
+// A scan stops once its keys and values reach this many bytes and returns a cursor to resume from.
+// None: no limit ("0" or "off")
+pub static SCAN_MAX_BYTES: LazyLock<Option<usize>> = LazyLock::new(|| {
+    let Ok(value) = std::env::var("BARUS_SCAN_MAX_BYTES") else {
+        return Some(SCAN_DEFAULT_MAX_BYTES);
+    };
+
+    match value.trim() {
+        "0" | "off" => None,
+        value => match value.parse::<usize>() {
+            Ok(bytes) => Some(bytes),
+            Err(_) => {
+                log::warn!(
+                    "Invalid BARUS_SCAN_MAX_BYTES '{}'. Using default {}",
+                    value,
+                    SCAN_DEFAULT_MAX_BYTES
+                );
+                Some(SCAN_DEFAULT_MAX_BYTES)
+            }
+        },
+    }
+});
+pub const SCAN_DEFAULT_MAX_DURATION_MS: u64 = 5000;
+// A scan running longer than this stops and returns a cursor to resume from.
+// None: no limit ("0" or "off")
+pub static SCAN_MAX_DURATION: LazyLock<Option<std::time::Duration>> = LazyLock::new(|| {
+    let default_duration = Some(std::time::Duration::from_millis(
+        SCAN_DEFAULT_MAX_DURATION_MS,
+    ));
+
+    let Ok(value) = std::env::var("BARUS_SCAN_MAX_DURATION_MS") else {
+        return default_duration;
+    };
+
+    match value.trim() {
+        "0" | "off" => None,
+        value => match value.parse::<u64>() {
+            Ok(millis) => Some(std::time::Duration::from_millis(millis)),
+            Err(_) => {
+                log::warn!(
+                    "Invalid BARUS_SCAN_MAX_DURATION_MS '{}'. Using default {}ms",
+                    value,
+                    SCAN_DEFAULT_MAX_DURATION_MS
+                );
+                default_duration
+            }
+        },
+    }
+});
+
+// Operations slower than these are logged at warn level (slowlog.rs). "0" or "off" disables one
+pub const SLOW_PUT_DEFAULT_THRESHOLD_MS: u64 = 500;
+pub const SLOW_GET_DEFAULT_THRESHOLD_MS: u64 = 500;
+pub const SLOW_DELETE_DEFAULT_THRESHOLD_MS: u64 = 500;
+pub const SLOW_FLUSH_DEFAULT_THRESHOLD_MS: u64 = 10_000;
+pub const SLOW_COMPACTION_DEFAULT_THRESHOLD_MS: u64 = 60_000;
+pub const SLOW_BATCH_DEFAULT_THRESHOLD_MS: u64 = 2_000;
+
+pub static SLOW_PUT_THRESHOLD: LazyLock<Option<std::time::Duration>> =
+    LazyLock::new(|| slow_op_threshold("BARUS_SLOW_PUT_MS", SLOW_PUT_DEFAULT_THRESHOLD_MS));
+pub static SLOW_GET_THRESHOLD: LazyLock<Option<std::time::Duration>> =
+    LazyLock::new(|| slow_op_threshold("BARUS_SLOW_GET_MS", SLOW_GET_DEFAULT_THRESHOLD_MS));
+pub static SLOW_DELETE_THRESHOLD: LazyLock<Option<std::time::Duration>> =
+    LazyLock::new(|| slow_op_threshold("BARUS_SLOW_DELETE_MS", SLOW_DELETE_DEFAULT_THRESHOLD_MS));
+pub static SLOW_FLUSH_THRESHOLD: LazyLock<Option<std::time::Duration>> =
+    LazyLock::new(|| slow_op_threshold("BARUS_SLOW_FLUSH_MS", SLOW_FLUSH_DEFAULT_THRESHOLD_MS));
+pub static SLOW_COMPACTION_THRESHOLD: LazyLock<Option<std::time::Duration>> = LazyLock::new(|| {
+    slow_op_threshold(
+        "BARUS_SLOW_COMPACTION_MS",
+        SLOW_COMPACTION_DEFAULT_THRESHOLD_MS,
+    )
+});
+pub static SLOW_BATCH_THRESHOLD: LazyLock<Option<std::time::Duration>> =
+    LazyLock::new(|| slow_op_threshold("BARUS_SLOW_BATCH_MS", SLOW_BATCH_DEFAULT_THRESHOLD_MS));
+
+fn slow_op_threshold(name: &str, default_ms: u64) -> Option<std::time::Duration> {
+    let default_threshold = Some(std::time::Duration::from_millis(default_ms));
+
+    let Ok(value) = std::env::var(name) else {
+        return default_threshold;
+    };
+
+    match value.trim() {
+        "0" | "off" => None,
+        value => match value.parse::<u64>() {
+            Ok(millis) => Some(std::time::Duration::from_millis(millis)),
+            Err(_) => {
+                log::warn!(
+                    "Invalid {} '{}'. Using default {}ms",
+                    name,
+                    value,
+                    default_ms
+                );
+                default_threshold
+            }
+        },
+    }
+}
+
+// gRPC address of the leader (ex: "http://leader:53001"). Set to run as a read-only follower
+pub static REPLICATION_LEADER: LazyLock<Option<String>> = LazyLock::new(|| {
+    std::env::var("BARUS_REPLICATION_LEADER")
+        .ok()
+        .filter(|leader| !leader.is_empty())
+});
+pub const REPLICATION_RECONNECT_MAX_BACKOFF: std::time::Duration =
+    std::time::Duration::from_secs(30);
+
+pub const MAX_CONCURRENT_WRITES_DEFAULT: usize = 1024;
+pub static MAX_CONCURRENT_WRITES: LazyLock<usize> = LazyLock::new(|| {
+    std::env::var("BARUS_MAX_CONCURRENT_WRITES")
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .filter(|val| *val > 0)
+        .unwrap_or(MAX_CONCURRENT_WRITES_DEFAULT)
+});
+
+// What to do with a write when MAX_CONCURRENT_WRITES writes are already in flight
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WriteLimitPolicy {
+    Queue,  // wait for a permit (default)
+    Reject, // fail immediately with TooManyWrites
+}
+
+pub static WRITE_LIMIT_POLICY: LazyLock<WriteLimitPolicy> =
+    LazyLock::new(
+        || match std::env::var("BARUS_WRITE_LIMIT_POLICY").ok().as_deref() {
+            Some("reject") => WriteLimitPolicy::Reject,
+            _ => WriteLimitPolicy::Queue,
+        },
+    );
+
+// What startup does about corrupt index files and unreadable WAL records
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StartupIntegrity {
+    Strict, // refuse to start
+    Repair, // recreate the index (rebuild it with reindex) and skip the records (default)
+}
+
+pub static STARTUP_INTEGRITY: LazyLock<StartupIntegrity> = LazyLock::new(|| {
+    match std::env::var("BARUS_STARTUP_INTEGRITY").ok().as_deref() {
+        Some("strict") => StartupIntegrity::Strict,
+        None | Some("repair") => StartupIntegrity::Repair,
+        Some(value) => {
+            log::warn!(
+                "Invalid BARUS_STARTUP_INTEGRITY '{}' (expected strict or repair). Using repair",
+                value
+            );
+            StartupIntegrity::Repair
+        }
+    }
+});
+
+// Log line format
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Text, // env_logger default (default)
+    Json, // one JSON object per line, for log aggregators
+}
+
+pub static LOG_FORMAT: LazyLock<LogFormat> =
+    LazyLock::new(|| match std::env::var("BARUS_LOG_FORMAT").ok().as_deref() {
+        Some("json") => LogFormat::Json,
+        _ => LogFormat::Text,
+    });
+
 pub const KEY_BYTES_MAX_SIZE: usize = 1024; // 1KB
 pub const VALUE_BYTES_MAX_SIZE: usize = 512 * 1024; // 512KB
 pub const TABLE_NAME_MAX_SIZE: usize = 255; // 255 bytes
+pub const MULTI_GET_MAX_KEYS: usize = 1000;
+pub const BATCH_MAX_WRITES: usize = 1000;
 
 pub const TABLE_SEGMENT_RECORD_FLAG_HEADER_SIZE: u32 = 1;
 pub const TABLE_SEGMENT_RECORD_SIZE_HEADER_SIZE: u32 = 4;
-pub const TABLE_SEGMENT_RECORD_HEADER_SIZE: u32 =
+pub const TABLE_SEGMENT_RECORD_CHECKSUM_HEADER_SIZE: u32 = 4; // CRC32 of the encoded payload
+pub const TABLE_SEGMENT_RECORD_HEADER_SIZE: u32 = TABLE_SEGMENT_RECORD_FLAG_HEADER_SIZE
+    + TABLE_SEGMENT_RECORD_SIZE_HEADER_SIZE
+    + TABLE_SEGMENT_RECORD_CHECKSUM_HEADER_SIZE;
+// records written before checksums existed
+pub const TABLE_SEGMENT_LEGACY_RECORD_HEADER_SIZE: u32 =
     TABLE_SEGMENT_RECORD_FLAG_HEADER_SIZE + TABLE_SEGMENT_RECORD_SIZE_HEADER_SIZE;
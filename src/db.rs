@@ -1,20 +1,52 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    ops::Bound,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore, SemaphorePermit, TryAcquireError};
+use tokio_stream::{StreamExt, wrappers::ReceiverStream};
 
 use crate::{
-    bridge::BridgeController,
-    disktable::{DiskTableManager, DisktableGetResult, table::TableInfo},
+    amplification::{TableWriteAmplification, WriteAmplification},
+    bridge::{BridgeController, status::FlushStatus},
+    config::{
+        BATCH_MAX_WRITES, KEY_LOCK_STRIPE_COUNT, LARGE_VALUE_THRESHOLD, MAX_CONCURRENT_WRITES,
+        MULTI_GET_MAX_KEYS, SCAN_PAGE_KEYS, SCAN_STREAM_CAPACITY, STARTUP_INTEGRITY,
+        StartupIntegrity, WRITE_LIMIT_POLICY, WriteLimitPolicy,
+    },
+    disktable::{
+        DiskTableManager, DisktableGetResult, MigrateTableReport, RecordPositionInfo,
+        VacuumTableReport, VerifyTableReport,
+        index::{BloomFilterStats, secondary::extract_json_field},
+        segment::{
+            position::TableRecordPosition, record::RecordStateFlags, segment_id::TableSegmentID,
+        },
+        table::{CreateTableOptions, TableInfo},
+        throttle::CompactionStats,
+    },
     errors,
+    histogram::{SizeHistogram, is_sampled},
+    lock::{KeyLock, LockLevel, ordered},
     memtable::{MemtableManager, table::MemtableGetValueResult},
-    os::handle_shutdown,
-    system::{SystemInfo, get_system_info},
-    validate::{validate_key, validate_table_name, validate_value},
+    replication::{ChangeEvent, ChangeSubscriber},
+    scheduler::Scheduler,
+    slowlog::{SlowOp, SlowOpLog},
+    storage::StorageHealth,
+    system::{SystemInfo, get_system_info, unix_millis_now},
+    validate::{validate_key, validate_secondary_indexes, validate_table_name, validate_value},
+    value_type::{ValueType, value_to_text},
     wal::{
-        self, WALManager,
+        self, WALManager, WALOptions,
         encode::WALRecordBincodeCodec,
         record::{WALPayload, WALRecord},
         segment_id::WALSegmentID,
+        status::WALFsyncStatus,
     },
 };
 
@@ -27,11 +59,49 @@ pub struct DBEngine {
     wal_manager: Arc<WALManager>,
     memtable_manager: Arc<MemtableManager>,
     disktable_manager: Arc<DiskTableManager>,
-    compaction_manager: Arc<Mutex<BridgeController>>,
+    bridge_controller: Arc<Mutex<BridgeController>>,
+    key_locks: Arc<KeyLock>,
+    read_only: Arc<AtomicBool>,
+    write_limiter: Arc<Semaphore>,
+    write_limit_policy: WriteLimitPolicy,
+    large_value_threshold: Option<usize>,
+    replica: Arc<AtomicBool>,
+    storage_health: Arc<StorageHealth>, // disk full or read-only filesystem. writes are rejected
+    shutting_down: Arc<AtomicBool>,     // set by shutdown. writes are rejected
+    write_stats: Arc<WriteAmplification>,
+    slow_ops: Arc<SlowOpLog>, // operations slower than BARUS_SLOW_*_MS are logged
 }
 
+#[derive(Debug)]
 pub struct GetResponse {
-    pub value: String,
+    pub value: Vec<u8>,
+    pub value_type: ValueType,
+    pub version: u64,
+    pub written_at: u64, // unix timestamp (ms), 0 if unknown
+}
+
+// One write of a batch (write_batch). None deletes the key
+#[derive(Debug, Clone)]
+pub struct BatchWrite {
+    pub table: String,
+    pub key: String,
+    pub value: Option<Vec<u8>>,
+    pub value_type: ValueType,
+}
+
+// Outcome of one write of a batch, in the order of the batch
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BatchWriteResult {
+    pub key: String,
+    pub version: Option<u64>, // record ID of the write. None if it was rejected
+    pub error: Option<String>,
+}
+
+impl GetResponse {
+    /// The value as text: base64 for Binary values, the UTF-8 string otherwise.
+    pub fn text(&self) -> Cow<'_, str> {
+        value_to_text(&self.value, self.value_type)
+    }
 }
 
 pub struct ListTablesResponse {
@@ -42,15 +112,143 @@ pub struct ListTablesResponseItem {
     pub table_name: String,
 }
 
+pub struct FindByIndexResponse {
+    pub items: Vec<FindByIndexResponseItem>,
+}
+
+pub struct FindByIndexResponseItem {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    // only keys written after this unix timestamp (ms)
+    pub modified_since: Option<u64>,
+    // only keys after this one (the next_cursor of a truncated scan)
+    pub after: Option<String>,
+    // stop once the returned keys and values reach this many bytes
+    pub max_bytes: Option<usize>,
+    // stop once the scan has run this long
+    pub max_duration: Option<std::time::Duration>,
+}
+
+// Where get_value_from looks for a key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadSource {
+    // memtable -> flushing memtable -> disk
+    #[default]
+    Default,
+    // active and flushing memtables only
+    MemtableOnly,
+    // flushed data only (segment files via the indexes). Newer memtable writes and deletes are ignored.
+    DiskOnly,
+}
+
+impl std::str::FromStr for ReadSource {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(ReadSource::Default),
+            "memtable" => Ok(ReadSource::MemtableOnly),
+            "disk" => Ok(ReadSource::DiskOnly),
+            _ => Err(()),
+        }
+    }
+}
+
+// a key of a scan page with its memtable entry and its index position
+type ScanPageEntry = (
+    String,
+    Option<MemtableGetValueResult>,
+    Option<TableRecordPosition>,
+);
+
+pub struct ScanResponse {
+    pub items: Vec<ScanResponseItem>,
+    // a limit was reached before the end of the table
+    pub truncated: bool,
+    // resume with ScanOptions::after = next_cursor. Some only if truncated
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanResponseItem {
+    pub key: String,
+    pub value: Vec<u8>,
+    pub value_type: ValueType,
+    pub version: u64,
+    pub written_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyLocation {
+    pub key: String,
+    pub position: RecordPositionInfo,
+    pub state: RecordStateFlags,
+}
+
+pub struct PositionedRecord {
+    pub position: RecordPositionInfo,
+    pub state: RecordStateFlags,
+    pub key: String,
+    pub value: Vec<u8>,
+    pub value_type: ValueType,
+    pub version: u64,
+    pub written_at: u64,
+}
+
+pub struct SizeHistogramResponse {
+    pub sample_rate: f64,
+    pub total_records: u64,
+    pub key_sizes: SizeHistogram,   // bytes
+    pub value_sizes: SizeHistogram, // bytes
+}
+
+pub struct MetricsResponse {
+    pub max_concurrent_writes: usize,
+    pub available_write_permits: usize,
+    pub flush_status: FlushStatus,
+    pub bloom_filter: BloomFilterStats,
+    pub open_segment_files: std::collections::BTreeMap<String, usize>,
+    pub write_amplification: std::collections::BTreeMap<String, TableWriteAmplification>,
+    pub wal_fsync: WALFsyncStatus,
+    pub storage_degraded: bool,
+    pub flush_synced_files: u64,
+    pub read_promotions: u64,
+    pub compaction: CompactionStats,
+}
+
 pub struct DBStatusResponse {
     pub memtable_size: u64,
     pub table_count: usize,
     pub wal_total_size: u64,
 }
 
+pub struct WALCheckpointResponse {
+    pub last_checkpoint_segment_id: u64,
+    pub last_checkpoint_record_id: u64,
+    pub last_segment_id: u64,
+    pub last_record_id: u64,
+    pub last_flushed_record_id: u64,
+    pub last_flushed_segment_id: u64,
+}
+
 impl DBEngine {
     /// Initializes the DBEngine with the given base path.
     pub async fn initialize(base_path: PathBuf) -> errors::Result<Self> {
+        Self::initialize_with_startup_integrity(base_path, *STARTUP_INTEGRITY).await
+    }
+
+    /// Initializes the DBEngine, choosing what to do about corruption found on the way.
+    /// Strict fails with IntegrityCheckFailed on a corrupt index file or an unreadable record in a replayed WAL segment.
+    /// Repair recreates the index (reindex rebuilds it from the segments) and skips the records.
+    /// A partially written WAL tail left by a crash is not corruption and is cleared in both modes.
+    pub async fn initialize_with_startup_integrity(
+        base_path: PathBuf,
+        startup_integrity: StartupIntegrity,
+    ) -> errors::Result<Self> {
         // 1. Load System Info
         let system_info = get_system_info();
 
@@ -70,12 +268,21 @@ impl DBEngine {
             }
         })?;
 
+        // bytes written per table, counted by the WAL and disktable managers
+        let write_stats = Arc::new(WriteAmplification::default());
+        let storage_health = Arc::new(StorageHealth::default());
+
         // 3. Initialize and load the WAL manager
         log::info!("Initializing WAL manager...");
         let wal_manager = {
-            let wal_manager =
-                WALManager::initialize(Box::new(WALRecordBincodeCodec {}), base_path.clone())
-                    .await?;
+            let wal_manager = WALManager::initialize(
+                Box::new(WALRecordBincodeCodec {}),
+                base_path.clone(),
+                WALOptions::default(),
+            )
+            .await?
+            .with_write_stats(write_stats.clone())
+            .with_storage_health(storage_health.clone());
 
             Arc::new(wal_manager)
         };
@@ -87,20 +294,27 @@ impl DBEngine {
         // 5. Disktable Load
         log::info!("Initializing disktable manager...");
         let disktable_manager = {
-            let disktable_manager = Arc::new(DiskTableManager::new(base_path.clone()));
+            let disktable_manager = Arc::new(
+                DiskTableManager::new(base_path.clone())
+                    .with_startup_integrity(startup_integrity)
+                    .with_write_stats(write_stats.clone()),
+            );
 
             disktable_manager.initialize().await?;
 
             disktable_manager
         };
 
-        // 6. compaction manager load
-        log::info!("Initializing compaction manager...");
-        let compaction_manager = BridgeController::new(
+        // 6. bridge controller load
+        log::info!("Initializing bridge controller...");
+        let slow_ops = Arc::new(SlowOpLog::default());
+        let bridge_controller = BridgeController::new(
             wal_manager.clone(),
             &mut memtable_manager,
             disktable_manager.clone(),
-        );
+        )
+        .with_slow_ops(slow_ops.clone())
+        .with_storage_health(storage_health.clone());
 
         // 7. Load table list
         log::info!("Loading table list...");
@@ -114,7 +328,11 @@ impl DBEngine {
         {
             let segment_files = wal_manager.list_segment_files().await?;
 
-            let state = { wal_manager.wal_state.lock().await.clone() };
+            let state = {
+                ordered(LockLevel::WALState, wal_manager.wal_state.lock())
+                    .await
+                    .clone()
+            };
 
             let last_checkpoint_segment = state.last_checkpoint_segment_id.clone();
             let last_checkpoint_record_id = state.last_checkpoint_record_id;
@@ -127,9 +345,34 @@ impl DBEngine {
                     continue;
                 }
 
-                let (records, _) = wal_manager.scan_records(segment_file.as_str()).await?;
+                let scan_result = wal_manager.scan_records(segment_file.as_str()).await?;
+
+                if scan_result.corrupt_records > 0 && startup_integrity == StartupIntegrity::Strict
+                {
+                    return Err(
+                        errors::Errors::new(errors::ErrorCodes::IntegrityCheckFailed).with_message(
+                            format!(
+                                "WAL segment '{}' has {} unreadable records ({} bytes)",
+                                segment_file,
+                                scan_result.corrupt_records,
+                                scan_result.skipped_bytes
+                            ),
+                        ),
+                    );
+                }
+
+                if scan_result.truncated {
+                    log::warn!(
+                        segment = segment_file.as_str(),
+                        skipped_bytes = scan_result.skipped_bytes as u64;
+                        "WAL segment '{}' has unreadable records ({} bytes skipped)",
+                        segment_file,
+                        scan_result.skipped_bytes
+                    );
+                }
 
-                let filtered_records = records
+                let filtered_records = scan_result
+                    .records
                     .into_iter()
                     .filter(|record| record.record_id > last_checkpoint_record_id)
                     .collect();
@@ -144,7 +387,17 @@ impl DBEngine {
             wal_manager,
             memtable_manager: Arc::new(memtable_manager),
             disktable_manager,
-            compaction_manager: Arc::new(Mutex::new(compaction_manager)),
+            bridge_controller: Arc::new(Mutex::new(bridge_controller)),
+            key_locks: Arc::new(KeyLock::new(KEY_LOCK_STRIPE_COUNT)),
+            read_only: Arc::new(AtomicBool::new(false)),
+            write_limiter: Arc::new(Semaphore::new(*MAX_CONCURRENT_WRITES)),
+            write_limit_policy: *WRITE_LIMIT_POLICY,
+            large_value_threshold: *LARGE_VALUE_THRESHOLD,
+            replica: Arc::new(AtomicBool::new(false)),
+            storage_health,
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            write_stats,
+            slow_ops,
         };
 
         log::info!("Starting Background Workers...");
@@ -156,29 +409,236 @@ impl DBEngine {
     }
 
     async fn start_background(&mut self) -> errors::Result<()> {
+        let scheduler = Scheduler::default();
+
         {
-            self.wal_manager.start_background()?;
+            self.wal_manager.start_background(&scheduler)?;
         }
 
         {
-            self.compaction_manager.lock().await.start_background()?;
+            self.bridge_controller.lock().await.start_background()?;
         }
 
+        Ok(())
+    }
+
+    /// Persists pending state before the process exits.
+    /// Servers should already be drained when this is called. New writes are rejected with `EngineReadOnly`,
+    /// writes already past the write check finish first, then the WAL and the memtable are flushed.
+    pub async fn shutdown(&self) -> errors::Result<()> {
+        // 1. refuse new writes, and wait for the accepted ones
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        if let Ok(permits) = self
+            .write_limiter
+            .acquire_many(*MAX_CONCURRENT_WRITES as u32)
+            .await
         {
-            let wal_manager = self.wal_manager.clone();
+            permits.forget();
+        }
+        // writes queued behind the shutdown fail instead of waiting forever
+        self.write_limiter.close();
+        log::info!("Writes stopped");
+
+        // 2. flush WAL
+        self.wal_manager.flush_wal().await?;
+        log::info!("WAL flushed");
+
+        // 3. flush memtable, so the next startup has nothing to replay
+        self.memtable_manager.flush().await?;
+        let flush_status = self.flush_status().await;
+        match flush_status.last_error {
+            Some(error) => log::warn!(
+                "Memtable flush failed: {}. The writes are replayed from the WAL on the next startup",
+                error
+            ),
+            None => log::info!("Memtable flushed"),
+        }
+
+        Ok(())
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
 
-            tokio::spawn(async move {
-                handle_shutdown().await;
-                log::info!("Graceful shutdown started");
+    /// Turns read-only (maintenance) mode on or off.
+    /// Reads keep working; user writes fail with `EngineReadOnly`. Internal flushes continue.
+    /// Turning it off also clears the degraded state left by a full disk (ex: after freeing space).
+    pub fn set_read_only(&self, enabled: bool) {
+        self.read_only.store(enabled, Ordering::SeqCst);
 
-                if let Err(error) = wal_manager.flush_wal().await {
-                    log::error!("Failed to flush WAL: {}", error);
+        if !enabled && self.storage_health.recover() {
+            log::info!("Storage degraded mode cleared");
+        }
+
+        log::info!(
+            "Read-only mode {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
+    /// Whether writes are disabled because the disk is full or the filesystem is read-only.
+    pub fn is_storage_degraded(&self) -> bool {
+        self.storage_health.is_degraded()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn observe_storage_error(&self, error: &errors::Errors) {
+        self.storage_health.observe(error);
+    }
+
+    // A full disk or a read-only filesystem puts the engine in degraded mode,
+    // so writes fail fast instead of failing deep in the WAL/segment layers on every request.
+    fn check_storage<T>(&self, result: errors::Result<T>) -> errors::Result<T> {
+        if let Err(error) = &result {
+            self.storage_health.observe(error);
+        }
+
+        result
+    }
+
+    /// Returns runtime metrics
+    pub async fn get_metrics(&self) -> MetricsResponse {
+        MetricsResponse {
+            max_concurrent_writes: *MAX_CONCURRENT_WRITES,
+            available_write_permits: self.write_limiter.available_permits(),
+            flush_status: self.flush_status().await,
+            bloom_filter: self.disktable_manager.bloom_filter_stats(),
+            open_segment_files: self.disktable_manager.open_segment_counts(),
+            write_amplification: self.write_stats.snapshot(),
+            wal_fsync: self.wal_fsync_status(),
+            storage_degraded: self.is_storage_degraded(),
+            flush_synced_files: self.disktable_manager.flush_synced_files(),
+            read_promotions: self.disktable_manager.read_promotions(),
+            compaction: self.disktable_manager.compaction_stats(),
+        }
+    }
+
+    /// Subscribes to the writes logged to the WAL from now on, in record ID order.
+    /// Records replicated from a leader are not published.
+    pub fn subscribe_changes(&self) -> ChangeSubscriber {
+        ChangeSubscriber::new(self.wal_manager.subscribe())
+    }
+
+    /// Writes after `record_id` that are still in the WAL (resuming a subscription).
+    /// Fails with `WALRecordsUnavailable` if some of them were already removed.
+    pub async fn changes_after(&self, record_id: u64) -> errors::Result<Vec<ChangeEvent>> {
+        let records = self.wal_manager.records_after(record_id.into()).await?;
+
+        Ok(records.into_iter().map(ChangeEvent::from).collect())
+    }
+
+    /// ID of the last record in the WAL
+    pub async fn last_record_id(&self) -> u64 {
+        self.wal_manager.last_record_id().await.into()
+    }
+
+    /// Turns follower (read replica) mode on. User writes fail with `EngineReadOnly`,
+    /// and only `apply_replicated_record` changes the data.
+    pub fn set_replica(&self) {
+        self.replica.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_replica(&self) -> bool {
+        self.replica.load(Ordering::SeqCst)
+    }
+
+    /// Applies a record received from the replication leader to the WAL and memtable.
+    /// The leader's record ID is kept, so the follower can resume after its last record.
+    /// Returns false if the record was already applied. Tables are created on first use
+    /// with default options (table options are not replicated).
+    pub async fn apply_replicated_record(&self, record: WALRecord) -> errors::Result<bool> {
+        let table = record.data.table.clone();
+
+        if !self.disktable_manager.table_exists(&table).await? {
+            self.disktable_manager
+                .create_table(&table, &CreateTableOptions::default())
+                .await?;
+            self.memtable_manager.create_table(&table).await?;
+        }
+
+        if !self.wal_manager.append_replicated(record.clone()).await? {
+            return Ok(false);
+        }
+        self.write_stats.add_user_bytes(
+            &table,
+            record.data.key.len() + record.data.value.as_ref().map_or(0, Vec::len),
+        );
+
+        match record.record_type {
+            wal::record::RecordType::Truncate => {
+                self.disktable_manager.truncate_table(&table).await?;
+                self.memtable_manager.truncate_table(&table).await?;
+            }
+            _ => self.memtable_manager.load_wal_records(vec![record]).await?,
+        }
+
+        Ok(true)
+    }
+
+    /// False after repeated background WAL fsync failures (ex: disk full) or in storage degraded mode
+    pub fn is_healthy(&self) -> bool {
+        self.wal_fsync_status().healthy && !self.is_storage_degraded()
+    }
+
+    /// Returns the background WAL fsync status
+    pub fn wal_fsync_status(&self) -> WALFsyncStatus {
+        self.wal_manager.fsync_status()
+    }
+
+    /// Returns the memtable flush progress (running flush and the last completed one)
+    pub async fn flush_status(&self) -> FlushStatus {
+        self.bridge_controller.lock().await.flush_status()
+    }
+
+    // Limits in-flight writes. Queues or rejects by the write limit policy.
+    async fn acquire_write_permit(&self) -> errors::Result<SemaphorePermit<'_>> {
+        match self.write_limit_policy {
+            // the limiter is closed only by shutdown
+            WriteLimitPolicy::Queue => self.write_limiter.acquire().await.map_err(|_| {
+                errors::Errors::new(errors::ErrorCodes::EngineReadOnly)
+                    .with_message("Engine is shutting down".to_string())
+            }),
+            WriteLimitPolicy::Reject => self.write_limiter.try_acquire().map_err(|e| match e {
+                TryAcquireError::Closed => errors::Errors::new(errors::ErrorCodes::EngineReadOnly)
+                    .with_message("Engine is shutting down".to_string()),
+                TryAcquireError::NoPermits => {
+                    errors::Errors::new(errors::ErrorCodes::TooManyWrites).with_message(format!(
+                        "Too many concurrent writes (limit: {})",
+                        *MAX_CONCURRENT_WRITES
+                    ))
                 }
-                log::info!("WAL flushed");
+            }),
+        }
+    }
 
-                log::info!("Graceful shutdown completed");
-                std::process::exit(0);
-            });
+    fn ensure_writable(&self) -> errors::Result<()> {
+        if self.is_shutting_down() {
+            return Err(errors::Errors::new(errors::ErrorCodes::EngineReadOnly)
+                .with_message("Engine is shutting down".to_string()));
+        }
+
+        if self.is_replica() {
+            return Err(errors::Errors::new(errors::ErrorCodes::EngineReadOnly)
+                .with_message("Engine is a read replica".to_string()));
+        }
+
+        if self.is_read_only() {
+            return Err(errors::Errors::new(errors::ErrorCodes::EngineReadOnly)
+                .with_message("Engine is in read-only mode".to_string()));
+        }
+
+        if self.is_storage_degraded() {
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::StorageUnavailable).with_message(
+                    "Disk is full or the filesystem is read-only. Writes are disabled".to_string(),
+                ),
+            );
         }
 
         Ok(())
@@ -223,12 +683,18 @@ impl DBEngine {
 
     /// Create Table
     /// Error occurs if table already exists
-    pub async fn create_table(&self, table: &str) -> errors::Result<()> {
+    pub async fn create_table(
+        &self,
+        table: &str,
+        options: CreateTableOptions,
+    ) -> errors::Result<()> {
         // 1. Validation
+        self.ensure_writable()?;
         validate_table_name(table)?;
+        validate_secondary_indexes(&options.secondary_indexes)?;
 
         // 2. Create table in Disktable Manager
-        self.disktable_manager.create_table(table).await?;
+        self.check_storage(self.disktable_manager.create_table(table, &options).await)?;
 
         // 3. Create table in Memtable Manager
         self.memtable_manager.create_table(table).await?;
@@ -240,6 +706,7 @@ impl DBEngine {
     /// No error occurs if table does not exist
     pub async fn delete_table(&self, table: &str) -> errors::Result<()> {
         // 1. Validation
+        self.ensure_writable()?;
         validate_table_name(table)?;
 
         // 2. Delete table in Disktable Manager
@@ -255,139 +722,3394 @@ impl DBEngine {
     /// Deletes all data in the table
     pub async fn truncate_table(&self, table: &str) -> errors::Result<()> {
         // 1. Validation
+        self.ensure_writable()?;
         validate_table_name(table)?;
 
         // 2. Truncate table in WAL Manager
-        self.wal_manager.truncate_table(table).await?;
+        let change = self.check_storage(self.wal_manager.truncate_table(table).await)?;
 
         // 3. Truncate table in Disktable Manager
-        self.disktable_manager.truncate_table(table).await?;
+        self.check_storage(self.disktable_manager.truncate_table(table).await)?;
 
         // 4. Truncate table in Memtable Manager
         self.memtable_manager.truncate_table(table).await?;
 
+        // 5. Publish to the change subscribers
+        change.publish();
+
         Ok(())
     }
 
-    /// Gets the value for the given table and key.
-    pub async fn get_value(&self, table: &str, key: &str) -> errors::Result<GetResponse> {
+    /// Reindex Table
+    /// Rebuilds the table's indexes from segment files. Returns the number of indexed records.
+    pub async fn reindex_table(&self, table: &str) -> errors::Result<usize> {
         // 1. Validation
         validate_table_name(table)?;
-        validate_key(key)?;
 
-        // 2. Try to get from Memtable
+        // 2. Rebuild index in Disktable Manager
+        let indexed_count = self.disktable_manager.rebuild_index(table).await?;
+
+        Ok(indexed_count)
+    }
+
+    /// Verify Table
+    /// Checks that the table's primary index and segment files agree. Data still in the memtable is not checked.
+    pub async fn verify_table(&self, table: &str) -> errors::Result<VerifyTableReport> {
+        // 1. Validation
+        validate_table_name(table)?;
+
+        // 2. Compare index entries and segment records in Disktable Manager
+        self.disktable_manager.verify_table(table).await
+    }
+
+    /// Vacuum Table
+    /// Removes older segment files with at least `min_dead_ratio` of dead records,
+    /// moving their live records to the current segment first.
+    pub async fn vacuum_table(
+        &self,
+        table: &str,
+        min_dead_ratio: f64,
+    ) -> errors::Result<VacuumTableReport> {
+        self.slow_ops
+            .time(SlowOp::Compaction, table, 0, async {
+                // 1. Validation
+                self.ensure_writable()?;
+                validate_table_name(table)?;
+
+                // 2. Compact segments in Disktable Manager
+                self.check_storage(
+                    self.disktable_manager
+                        .vacuum_table(table, min_dead_ratio)
+                        .await,
+                )
+            })
+            .await
+    }
+
+    /// Migrate Table
+    /// Rewrites flushed records stored in an older payload layout in the current one. A dry run only counts them.
+    pub async fn migrate_table(
+        &self,
+        table: &str,
+        dry_run: bool,
+    ) -> errors::Result<MigrateTableReport> {
+        // 1. Validation
+        validate_table_name(table)?;
+
+        // 2. Rewrite outdated records in Disktable Manager
+        self.disktable_manager.migrate_table(table, dry_run).await
+    }
+
+    /// Gets the value for the given table and key.
+    pub async fn get_value(&self, table: &str, key: &str) -> errors::Result<GetResponse> {
+        self.get_value_from(table, key, ReadSource::Default).await
+    }
+
+    /// Gets a value from the given source only. Useful to check whether a write has been flushed to disk.
+    pub async fn get_value_from(
+        &self,
+        table: &str,
+        key: &str,
+        source: ReadSource,
+    ) -> errors::Result<GetResponse> {
+        self.slow_ops
+            .time(SlowOp::Get, table, key.len(), async {
+                // 1. Validation
+                let key = self.normalize_key(table, key);
+                let key = key.as_ref();
+                validate_table_name(table)?;
+                validate_key(key)?;
+
+                if source == ReadSource::DiskOnly {
+                    return self.get_value_from_disk(table, key).await;
+                }
+
+                // 2. Try to get from Memtable, then from flushing Memtable
+                match self.get_value_from_memtables(table, key).await? {
+                    MemtableGetValueResult::Deleted => {
+                        Err(errors::Errors::new(errors::ErrorCodes::ValueNotFound)
+                            .with_message(format!("Key not found (deleted): {}", key)))
+                    }
+                    MemtableGetValueResult::Found {
+                        value,
+                        value_type,
+                        version,
+                        written_at,
+                    } => Ok(GetResponse {
+                        value,
+                        value_type,
+                        version,
+                        written_at,
+                    }),
+                    // OnDisk: a large value written through to disk
+                    MemtableGetValueResult::OnDisk { .. } | MemtableGetValueResult::NotFound => {
+                        if source == ReadSource::MemtableOnly {
+                            return Err(errors::Errors::new(errors::ErrorCodes::ValueNotFound)
+                                .with_message(format!("Key not found in memtable: {}", key)));
+                        }
+
+                        // 3. Try to get from disk area
+                        self.get_value_from_disk(table, key).await
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn get_value_from_memtables(
+        &self,
+        table: &str,
+        key: &str,
+    ) -> errors::Result<MemtableGetValueResult> {
         let memtable_result = self.memtable_manager.get_value(table, key).await?;
 
-        match memtable_result {
-            MemtableGetValueResult::Deleted => {
-                return Err(errors::Errors::new(errors::ErrorCodes::ValueNotFound)
-                    .with_message(format!("Key not found (deleted): {}", key)));
-            }
-            MemtableGetValueResult::Found(value) => {
-                return Ok(GetResponse { value });
-            }
-            MemtableGetValueResult::NotFound => {}
+        if !matches!(memtable_result, MemtableGetValueResult::NotFound) {
+            return Ok(memtable_result);
         }
 
-        let memtable_result = self
-            .memtable_manager
+        self.memtable_manager
             .get_value_from_flushing(table, key)
-            .await?;
+            .await
+    }
 
-        // 3. Try to get from flushing Memtable
-        match memtable_result {
-            MemtableGetValueResult::Deleted => {
-                return Err(errors::Errors::new(errors::ErrorCodes::ValueNotFound)
-                    .with_message(format!("Key not found (deleted): {}", key)));
-            }
-            MemtableGetValueResult::Found(value) => {
-                return Ok(GetResponse { value });
-            }
-            MemtableGetValueResult::NotFound => {}
+    /// Gets several keys of a table in one call. Results are in the order of `keys`,
+    /// None for missing or deleted keys.
+    pub async fn get_many(
+        &self,
+        table: &str,
+        keys: &[String],
+    ) -> errors::Result<Vec<Option<GetResponse>>> {
+        // 1. Validation
+        validate_table_name(table)?;
+        if keys.len() > MULTI_GET_MAX_KEYS {
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::TooManyKeys).with_message(format!(
+                    "{} keys requested, at most {} allowed",
+                    keys.len(),
+                    MULTI_GET_MAX_KEYS
+                )),
+            );
+        }
+        for key in keys {
+            validate_key(key)?;
         }
 
-        // 4. Try to get from disk area
-        {
-            let disktable_result = self.disktable_manager.get_value(table, key).await?;
+        // a missing table would otherwise look like all keys are missing
+        if !self.disktable_manager.table_exists(table).await? {
+            return Err(errors::Errors::new(errors::ErrorCodes::TableNotFound)
+                .with_message(format!("Table '{}' not found", table)));
+        }
 
-            match disktable_result {
-                DisktableGetResult::Found(value) => Ok(GetResponse { value }),
-                _ => Err(errors::Errors::new(errors::ErrorCodes::ValueNotFound)
-                    .with_message(format!("Key not found: {}", key))),
+        // 2. Get each key
+        let mut results = Vec::with_capacity(keys.len());
+        for key in keys {
+            match self.get_value(table, key).await {
+                Ok(result) => results.push(Some(result)),
+                Err(error) if matches!(error.error_code, errors::ErrorCodes::ValueNotFound) => {
+                    results.push(None)
+                }
+                Err(error) => return Err(error),
             }
         }
+
+        Ok(results)
     }
 
-    /// Puts the given key-value pair into the specified table.
-    pub async fn put_value(&self, table: String, key: String, value: String) -> errors::Result<()> {
-        // 1. Validation
-        validate_table_name(&table)?;
-        validate_key(&key)?;
-        validate_value(&value)?;
+    async fn get_value_from_disk(&self, table: &str, key: &str) -> errors::Result<GetResponse> {
+        // promotion re-appends the record, so it is a write
+        let disktable_result = self
+            .disktable_manager
+            .read_value(table, key, self.ensure_writable().is_ok())
+            .await?;
 
-        let wal_record = WALRecord {
-            record_id: 0.into(),
-            record_type: wal::record::RecordType::Put,
-            data: WALPayload {
-                table: table.clone(),
-                key: key.clone(),
-                value: Some(value.clone()),
-            },
-        };
+        match disktable_result {
+            DisktableGetResult::Found {
+                value,
+                value_type,
+                version,
+                written_at,
+            } => Ok(GetResponse {
+                value,
+                value_type,
+                version,
+                written_at,
+            }),
+            _ => Err(errors::Errors::new(errors::ErrorCodes::ValueNotFound)
+                .with_message(format!("Key not found: {}", key))),
+        }
+    }
 
-        // 2. WAL write
-        {
-            self.wal_manager.append(wal_record).await?;
+    /// Gets the value `previous` writes back: 0 is the current value, n the n-th prior version.
+    /// Prior versions are kept on disk for tables created with `keep_versions`, up to that many.
+    /// Writes collapsed in the memtable before a flush keep only their last value.
+    pub async fn get_version(
+        &self,
+        table: &str,
+        key: &str,
+        previous: u32,
+    ) -> errors::Result<GetResponse> {
+        if previous == 0 {
+            return self.get_value(table, key).await;
         }
 
-        // 3. Memtable update
+        // 1. Validation
+        let key = self.normalize_key(table, key);
+        let key = key.as_ref();
+        validate_table_name(table)?;
+        validate_key(key)?;
+
+        let keep_versions = self.disktable_manager.get_table(table).await?.keep_versions;
+        if previous > keep_versions {
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::ValueNotFound).with_message(format!(
+                    "Table '{}' keeps {} prior versions of a key",
+                    table, keep_versions
+                )),
+            );
+        }
+
+        // 2. A newer write in the memtables makes the value on disk the first prior version
+        let mut versions = vec![];
+        let pending_version = match self.get_value_from_memtables(table, key).await? {
+            MemtableGetValueResult::Found { version, .. } => Some(Some(version)),
+            MemtableGetValueResult::Deleted => Some(None),
+            MemtableGetValueResult::OnDisk { .. } | MemtableGetValueResult::NotFound => None,
+        };
+        if let Some(pending_version) = pending_version
+            && let DisktableGetResult::Found {
+                value,
+                value_type,
+                version,
+                written_at,
+            } = self.disktable_manager.get_value(table, key).await?
+            && pending_version.is_none_or(|pending_version| pending_version > version)
         {
-            self.memtable_manager.put(table, key, value).await?;
+            versions.push(GetResponse {
+                value,
+                value_type,
+                version,
+                written_at,
+            });
         }
 
-        Ok(())
+        // 3. Prior versions on disk
+        versions.extend(
+            self.disktable_manager
+                .value_versions(table, key)
+                .await?
+                .into_iter()
+                .map(|record| GetResponse {
+                    value: record.value,
+                    value_type: record.value_type,
+                    version: record.version,
+                    written_at: record.written_at,
+                }),
+        );
+
+        versions
+            .into_iter()
+            .take(keep_versions as usize)
+            .nth(previous as usize - 1)
+            .ok_or_else(|| {
+                errors::Errors::new(errors::ErrorCodes::ValueNotFound).with_message(format!(
+                    "Key '{}' has no version {} writes back",
+                    key, previous
+                ))
+            })
     }
 
-    /// Deletes the given key from the specified table.
-    pub async fn delete_value(&self, table: String, key: String) -> errors::Result<()> {
-        // 1 Validation
-        validate_table_name(&table)?;
-        validate_key(&key)?;
+    /// Gets the value only if its version is greater than `version`.
+    /// Returns `None` when the stored value is not newer (not modified).
+    pub async fn get_if_version_gt(
+        &self,
+        table: &str,
+        key: &str,
+        version: u64,
+    ) -> errors::Result<Option<GetResponse>> {
+        let response = self.get_value(table, key).await?;
 
-        let wal_record = WALRecord {
-            record_id: 0.into(),
-            record_type: wal::record::RecordType::Delete,
-            data: WALPayload {
-                table: table.to_string(),
-                key: key.to_string(),
-                value: None,
-            },
+        if response.version > version {
+            Ok(Some(response))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Finds all key-value pairs whose indexed JSON field equals `field_value`.
+    pub async fn find_by_index(
+        &self,
+        table: &str,
+        index_name: &str,
+        field_value: &str,
+    ) -> errors::Result<FindByIndexResponse> {
+        // 1. Validation
+        validate_table_name(table)?;
+
+        let table_info = self.disktable_manager.get_table(table).await?;
+        let Some(index) = table_info
+            .secondary_indexes
+            .iter()
+            .find(|index| index.name == index_name)
+        else {
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::SecondaryIndexNotFound)
+                    .with_message(format!("Index '{}' not found in '{}'", index_name, table)),
+            );
         };
 
-        // 2. WAL write
-        {
-            self.wal_manager.append(wal_record).await?;
-        }
+        let matches = |value: &[u8]| {
+            extract_json_field(value, &index.json_path).as_deref() == Some(field_value)
+        };
 
-        // 3. Memtable update
-        {
-            self.memtable_manager.delete_value(table, key).await?;
+        // 2. Collect candidates from Disktable index and Memtable
+        let mut keys = self
+            .disktable_manager
+            .find_by_index(table, index_name, field_value)
+            .await?;
+        keys.extend(self.memtable_manager.find_keys(table, matches).await?);
+        keys.sort();
+        keys.dedup();
+
+        // 3. Re-check the latest value (memtable can shadow disk entries)
+        let mut items = vec![];
+        for key in keys {
+            match self.get_value(table, &key).await {
+                Ok(response) if matches(&response.value) => {
+                    // a JSON document, so valid UTF-8
+                    items.push(FindByIndexResponseItem {
+                        key,
+                        value: String::from_utf8_lossy(&response.value).into_owned(),
+                    });
+                }
+                Ok(_) => {}
+                Err(error) if matches!(error.error_code, errors::ErrorCodes::ValueNotFound) => {}
+                Err(error) => return Err(error),
+            }
         }
 
-        Ok(())
+        Ok(FindByIndexResponse { items })
     }
 
-    /// Flushes the WAL to disk.
-    pub async fn flush_wal(&self) -> errors::Result<()> {
-        self.wal_manager.flush_wal().await?;
+    /// Scans the live key-value pairs of a table, ordered by key.
+    /// There is no time index, so `modified_since` filters while reading every record of the table.
+    /// Values written before write timestamps were recorded have `written_at` 0 and never match `modified_since`.
+    /// The scan walks the primary index and reads one record at a time, so it can stop early:
+    /// once the returned keys and values reach `max_bytes` or the scan has run for `max_duration`,
+    /// it returns `truncated` with the last key it looked at as `next_cursor`. Pass it as `after` to resume.
+    /// At least one key is looked at per call, so resuming always makes progress.
+    pub async fn scan(&self, table: &str, options: ScanOptions) -> errors::Result<ScanResponse> {
+        let started_at = std::time::Instant::now();
 
-        Ok(())
+        // 1. Validation
+        validate_table_name(table)?;
+        self.disktable_manager.get_table(table).await?;
+
+        let after = options
+            .after
+            .as_deref()
+            .map(|after| self.normalize_key(table, after));
+        let after = after.as_deref();
+
+        let mut items = vec![];
+        let mut item_bytes = 0;
+        let mut last_key: Option<String> = None;
+        let mut truncated = false;
+
+        // 2. A page of keys at a time from the memtable and the index
+        let mut cursor = after.map(str::to_owned);
+        'pages: loop {
+            let (entries, page_end) = self.scan_page(table, cursor.as_deref()).await?;
+
+            for (key, entry, position) in entries {
+                // 3. Limits. checked before each key after the first, so the cursor always moves forward
+                if last_key.is_some()
+                    && (options
+                        .max_bytes
+                        .is_some_and(|max_bytes| item_bytes >= max_bytes)
+                        || options
+                            .max_duration
+                            .is_some_and(|max_duration| started_at.elapsed() >= max_duration))
+                {
+                    truncated = true;
+                    break 'pages;
+                }
+                last_key = Some(key.clone());
+
+                // 4. Disk record, shadowed by a newer memtable entry
+                let record = match position {
+                    Some(position) => self.disktable_manager.read_record(table, position).await?,
+                    None => None,
+                };
+
+                let item = match (record, entry) {
+                    (Some(record), entry)
+                        if entry.as_ref().is_none_or(|entry| match entry {
+                            MemtableGetValueResult::Found { version, .. } => {
+                                record.version >= *version
+                            }
+                            // written through: the disk record is the value
+                            MemtableGetValueResult::OnDisk { .. } => true,
+                            MemtableGetValueResult::Deleted => false,
+                            MemtableGetValueResult::NotFound => true,
+                        }) =>
+                    {
+                        ScanResponseItem {
+                            key,
+                            value: record.value,
+                            value_type: record.value_type,
+                            version: record.version,
+                            written_at: record.written_at,
+                        }
+                    }
+                    (
+                        _,
+                        Some(MemtableGetValueResult::Found {
+                            value,
+                            value_type,
+                            version,
+                            written_at,
+                        }),
+                    ) => ScanResponseItem {
+                        key,
+                        value,
+                        value_type,
+                        version,
+                        written_at,
+                    },
+                    // deleted
+                    _ => continue,
+                };
+
+                // 5. Filter
+                if options
+                    .modified_since
+                    .is_some_and(|modified_since| item.written_at <= modified_since)
+                {
+                    continue;
+                }
+
+                item_bytes += item.key.len() + item.value.len();
+                items.push(item);
+            }
+
+            match page_end {
+                Some(page_end) => cursor = Some(page_end),
+                None => break,
+            }
+        }
+
+        Ok(ScanResponse {
+            items,
+            truncated,
+            next_cursor: if truncated { last_key } else { None },
+        })
     }
 
-    /// Trigger memtable flush
-    pub async fn trigger_memtable_flush(&self) -> errors::Result<()> {
-        self.memtable_manager.trigger_flush().await?;
+    // Keys after `after` from the memtable and the primary index, merged in key order, a page at a time.
+    // Each key comes with its memtable entry and its index position (either may be missing).
+    // Returns the last key of the page to continue after, None once there are no more keys
+    async fn scan_page(
+        &self,
+        table: &str,
+        after: Option<&str>,
+    ) -> errors::Result<(Vec<ScanPageEntry>, Option<String>)> {
+        // memtable entries first. a flush running meanwhile only moves them to disk
+        let start = after.map_or(Bound::Unbounded, |after| Bound::Excluded(after.to_owned()));
+        let memtable_entries = self
+            .memtable_manager
+            .scan(table, start, Bound::Unbounded, SCAN_PAGE_KEYS)
+            .await?;
 
-        Ok(())
+        // then the indexed disk keys of the same range
+        let disk_entries = self
+            .disktable_manager
+            .list_indexed_records(table, after, SCAN_PAGE_KEYS)
+            .await?;
+
+        // keys past the last key of a full page may be on the other side, in its next page
+        let full_page_end = |keys: Vec<&String>| {
+            (keys.len() == SCAN_PAGE_KEYS)
+                .then(|| keys.last().map(|key| (*key).clone()))
+                .flatten()
+        };
+        let page_end = [
+            full_page_end(memtable_entries.iter().map(|(key, _)| key).collect()),
+            full_page_end(disk_entries.iter().map(|(key, _)| key).collect()),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        let in_page = |key: &String| page_end.as_ref().is_none_or(|end| key <= end);
+
+        let mut memtable_entries = memtable_entries
+            .into_iter()
+            .take_while(|(key, _)| in_page(key))
+            .peekable();
+        let mut disk_entries = disk_entries
+            .into_iter()
+            .take_while(|(key, _)| in_page(key))
+            .peekable();
+
+        let mut entries = vec![];
+        loop {
+            let entry = match (memtable_entries.peek(), disk_entries.peek()) {
+                (None, None) => break,
+                (Some((memtable_key, _)), Some((disk_key, _))) if memtable_key == disk_key => {
+                    let (key, entry) = memtable_entries.next().unwrap();
+                    let (_, position) = disk_entries.next().unwrap();
+                    (key, Some(entry), Some(position))
+                }
+                (Some((memtable_key, _)), Some((disk_key, _))) if memtable_key > disk_key => {
+                    let (key, position) = disk_entries.next().unwrap();
+                    (key, None, Some(position))
+                }
+                (None, Some(_)) => {
+                    let (key, position) = disk_entries.next().unwrap();
+                    (key, None, Some(position))
+                }
+                (Some(_), _) => {
+                    let (key, entry) = memtable_entries.next().unwrap();
+                    (key, Some(entry), None)
+                }
+            };
+            entries.push(entry);
+        }
+
+        Ok((entries, page_end))
+    }
+
+    /// Streams the live records of a table in no particular order (ex: exports of large tables).
+    /// Unlike scan, only the table's memtable entries and one segment file are held in memory at a time,
+    /// and the stream waits while the consumer is SCAN_STREAM_CAPACITY records behind.
+    /// A flush running meanwhile only moves entries to disk; writes made after the call may or may not be included.
+    pub async fn scan_stream(
+        &self,
+        table: &str,
+    ) -> errors::Result<ReceiverStream<errors::Result<ScanResponseItem>>> {
+        // 1. Validation
+        validate_table_name(table)?;
+        self.disktable_manager.get_table(table).await?;
+
+        // 2. Memtable entries first. they shadow the disk records of the same keys
+        let mut memtable_entries = self.memtable_manager.scan_entries(table).await?;
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(SCAN_STREAM_CAPACITY);
+        let disktable_manager = self.disktable_manager.clone();
+        let table = table.to_string();
+
+        tokio::spawn(async move {
+            let result: errors::Result<()> = async {
+                // 3. Disk records, one segment file at a time
+                for segment_file in disktable_manager.list_segment_files(&table).await? {
+                    let records = disktable_manager
+                        .scan_segment_live_records(&table, &segment_file)
+                        .await?;
+
+                    for record in records {
+                        match memtable_entries.get(&record.key) {
+                            // written through: the disk record is the value
+                            Some(entry) if entry.on_disk => {}
+                            Some(entry) if entry.version >= record.version => continue,
+                            // the disk record is newer than the memtable entry
+                            Some(_) => {
+                                memtable_entries.remove(&record.key);
+                            }
+                            None => {}
+                        }
+
+                        let item = ScanResponseItem {
+                            key: record.key,
+                            value: record.value,
+                            value_type: record.value_type,
+                            version: record.version,
+                            written_at: record.written_at,
+                        };
+                        if sender.send(Ok(item)).await.is_err() {
+                            // the consumer is gone
+                            return Ok(());
+                        }
+                    }
+                }
+
+                // 4. Memtable values (tombstones and write-through markers have nothing to add)
+                for (key, entry) in memtable_entries {
+                    let Some(value) = entry.value else {
+                        continue;
+                    };
+
+                    let item = ScanResponseItem {
+                        key,
+                        value,
+                        value_type: entry.value_type,
+                        version: entry.version,
+                        written_at: entry.written_at,
+                    };
+                    if sender.send(Ok(item)).await.is_err() {
+                        return Ok(());
+                    }
+                }
+
+                Ok(())
+            }
+            .await;
+
+            if let Err(error) = result {
+                log::error!("Streaming scan of table '{}' failed: {}", table, error);
+                let _ = sender.send(Err(error)).await;
+            }
+        });
+
+        Ok(ReceiverStream::new(receiver))
+    }
+
+    /// Reads the record at a segment position (from key_locations), deleted or not. For debugging.
+    /// Fails with RecordPositionInvalid unless a record starts there.
+    pub async fn record_at(
+        &self,
+        table: &str,
+        segment_id: u64,
+        offset: u32,
+    ) -> errors::Result<PositionedRecord> {
+        validate_table_name(table)?;
+
+        let position = TableRecordPosition {
+            segment_id: TableSegmentID::new(segment_id),
+            offset,
+        };
+        let (state, record) = self
+            .disktable_manager
+            .record_at(table, position.clone())
+            .await?;
+
+        Ok(PositionedRecord {
+            position: RecordPositionInfo::from(&position),
+            state,
+            key: record.key,
+            value: record.value,
+            value_type: record.value_type,
+            version: record.version,
+            written_at: record.written_at,
+        })
+    }
+
+    /// Streams the segment position and record state of every indexed key of a table, ordered by key.
+    /// For audits of data placement (ex: keys still in old segments). Only the index entries are held in memory.
+    pub async fn key_locations(
+        &self,
+        table: &str,
+    ) -> errors::Result<ReceiverStream<errors::Result<KeyLocation>>> {
+        validate_table_name(table)?;
+        self.disktable_manager.get_table(table).await?;
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(SCAN_STREAM_CAPACITY);
+        let disktable_manager = self.disktable_manager.clone();
+        let table = table.to_string();
+
+        tokio::spawn(async move {
+            let result: errors::Result<()> = async {
+                let locations = disktable_manager.key_locations(&table).await?;
+                tokio::pin!(locations);
+
+                while let Some(location) = locations.next().await {
+                    let (key, position, state) = location?;
+                    let location = KeyLocation {
+                        key,
+                        position: RecordPositionInfo::from(&position),
+                        state,
+                    };
+                    if sender.send(Ok(location)).await.is_err() {
+                        // the consumer is gone
+                        return Ok(());
+                    }
+                }
+
+                Ok(())
+            }
+            .await;
+
+            if let Err(error) = result {
+                log::error!(
+                    "Listing key locations of table '{}' failed: {}",
+                    table,
+                    error
+                );
+                let _ = sender.send(Err(error)).await;
+            }
+        });
+
+        Ok(ReceiverStream::new(receiver))
+    }
+
+    /// Key and value size distribution of the live records of a table, from a sample of `sample_rate` (0 < rate <= 1) of the keys.
+    /// Every key is walked in the memtable and the primary index, but only the records of sampled keys are read from disk.
+    pub async fn size_histogram(
+        &self,
+        table: &str,
+        sample_rate: f64,
+    ) -> errors::Result<SizeHistogramResponse> {
+        validate_table_name(table)?;
+        self.disktable_manager.get_table(table).await?;
+
+        let mut total_records = 0;
+        let mut key_sizes = vec![];
+        let mut value_sizes = vec![];
+
+        let mut cursor = None;
+        loop {
+            let (entries, page_end) = self.scan_page(table, cursor.as_deref()).await?;
+
+            for (key, entry, position) in entries {
+                let is_live = match (&entry, &position) {
+                    (Some(MemtableGetValueResult::Found { .. }), _) => true,
+                    (Some(MemtableGetValueResult::Deleted), _) | (_, None) => false,
+                    _ => true,
+                };
+                if !is_live {
+                    continue;
+                }
+                total_records += 1;
+
+                if !is_sampled(&key, sample_rate) {
+                    continue;
+                }
+
+                let value_size = match (entry, position) {
+                    (Some(MemtableGetValueResult::Found { value, .. }), _) => value.len(),
+                    (_, Some(position)) => {
+                        match self.disktable_manager.read_record(table, position).await? {
+                            Some(record) => record.value.len(),
+                            // deleted since the key was listed
+                            None => continue,
+                        }
+                    }
+                    _ => continue,
+                };
+                key_sizes.push(key.len());
+                value_sizes.push(value_size);
+            }
+
+            match page_end {
+                Some(page_end) => cursor = Some(page_end),
+                None => break,
+            }
+        }
+
+        Ok(SizeHistogramResponse {
+            sample_rate,
+            total_records,
+            key_sizes: SizeHistogram::from_sizes(key_sizes),
+            value_sizes: SizeHistogram::from_sizes(value_sizes),
+        })
+    }
+
+    /// Puts the given key-value pair into the specified table.
+    pub async fn put_value(&self, table: String, key: String, value: String) -> errors::Result<()> {
+        self.put_typed_value(table, key, value.into_bytes(), ValueType::Raw)
+            .await
+    }
+
+    /// Puts arbitrary bytes as a Binary value.
+    pub async fn put_bytes(&self, table: String, key: String, value: &[u8]) -> errors::Result<()> {
+        self.put_typed_value(table, key, value.to_vec(), ValueType::Binary)
+            .await
+    }
+
+    /// Puts a value tagged with its type. Fails with ValueTypeMismatch if the value is not of that type.
+    pub async fn put_typed_value(
+        &self,
+        table: String,
+        key: String,
+        value: Vec<u8>,
+        value_type: ValueType,
+    ) -> errors::Result<()> {
+        let (table_name, key_len) = (table.clone(), key.len());
+        self.slow_ops
+            .time(SlowOp::Put, &table_name, key_len, async move {
+                // 1. Validation
+                let key = self.normalize_owned_key(&table, key);
+                self.ensure_writable()?;
+                validate_table_name(&table)?;
+                validate_key(&key)?;
+                validate_value(&value)?;
+                value_type.validate(&value)?;
+
+                let _write_permit = self.acquire_write_permit().await?;
+
+                // 2. Serialize with other writes to the same key
+                let _key_lock = self.key_locks.lock(&table, &key).await;
+
+                self.write_put(table, key, value, value_type).await
+            })
+            .await
+    }
+
+    /// Puts the value only if it differs from the current one, so no-op updates log nothing.
+    /// Returns whether the write happened.
+    pub async fn put_if_changed(
+        &self,
+        table: String,
+        key: String,
+        value: String,
+    ) -> errors::Result<bool> {
+        // 1. Validation
+        let key = self.normalize_owned_key(&table, key);
+        self.ensure_writable()?;
+        validate_table_name(&table)?;
+        validate_key(&key)?;
+        validate_value(value.as_bytes())?;
+
+        let _write_permit = self.acquire_write_permit().await?;
+
+        // 2. Compare and write under the key lock
+        let _key_lock = self.key_locks.lock(&table, &key).await;
+
+        match self.get_value(&table, &key).await {
+            Ok(current)
+                if current.value == value.as_bytes() && current.value_type == ValueType::Raw =>
+            {
+                return Ok(false);
+            }
+            Ok(_) => {}
+            Err(error) => match error.error_code {
+                errors::ErrorCodes::ValueNotFound => {}
+                _ => return Err(error),
+            },
+        }
+
+        self.write_put(table, key, value.into_bytes(), ValueType::Raw)
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Deletes the given key from the specified table.
+    pub async fn delete_value(&self, table: String, key: String) -> errors::Result<()> {
+        let (table_name, key_len) = (table.clone(), key.len());
+        self.slow_ops
+            .time(SlowOp::Delete, &table_name, key_len, async move {
+                // 1 Validation
+                let key = self.normalize_owned_key(&table, key);
+                self.ensure_writable()?;
+                validate_table_name(&table)?;
+                validate_key(&key)?;
+
+                let _write_permit = self.acquire_write_permit().await?;
+
+                // 2. Serialize with other writes to the same key
+                let _key_lock = self.key_locks.lock(&table, &key).await;
+
+                self.write_delete(table, key).await
+            })
+            .await
+    }
+
+    /// Puts several values, possibly into different tables. See `write_batch`.
+    pub async fn put_batch(
+        &self,
+        puts: Vec<(String, String, String)>,
+    ) -> errors::Result<Vec<BatchWriteResult>> {
+        let writes = puts
+            .into_iter()
+            .map(|(table, key, value)| BatchWrite {
+                table,
+                key,
+                value: Some(value.into_bytes()),
+                value_type: ValueType::Raw,
+            })
+            .collect();
+
+        self.write_batch(writes).await
+    }
+
+    /// Applies several puts and deletes with one WAL lock acquisition, so their records get
+    /// contiguous record IDs, in the order of `writes`.
+    /// A write that fails validation is reported in its result and skipped; the others are still applied.
+    /// So is a write that fails to apply after the batch was logged to the WAL.
+    /// A key both put and deleted in the batch fails the whole batch with `BatchKeyConflict`.
+    pub async fn write_batch(
+        &self,
+        writes: Vec<BatchWrite>,
+    ) -> errors::Result<Vec<BatchWriteResult>> {
+        // the table of the batch, * if it writes to several
+        let table_name = match writes.first() {
+            Some(first) if writes.iter().all(|write| write.table == first.table) => {
+                first.table.clone()
+            }
+            _ => "*".to_string(),
+        };
+
+        self.slow_ops
+            .time(SlowOp::Batch, &table_name, 0, async move {
+                // 1. Validation of the batch
+                self.ensure_writable()?;
+                if writes.len() > BATCH_MAX_WRITES {
+                    return Err(
+                        errors::Errors::new(errors::ErrorCodes::TooManyKeys).with_message(format!(
+                            "{} writes in the batch, at most {} allowed",
+                            writes.len(),
+                            BATCH_MAX_WRITES
+                        )),
+                    );
+                }
+
+                let writes: Vec<_> = writes
+                    .into_iter()
+                    .map(|write| BatchWrite {
+                        key: self.normalize_owned_key(&write.table, write.key),
+                        ..write
+                    })
+                    .collect();
+
+                // a key both put and deleted (after normalization) would end up deleted
+                let put_keys: HashSet<_> = writes
+                    .iter()
+                    .filter(|write| write.value.is_some())
+                    .map(|write| (write.table.as_str(), write.key.as_str()))
+                    .collect();
+                if let Some(write) = writes.iter().find(|write| {
+                    write.value.is_none()
+                        && put_keys.contains(&(write.table.as_str(), write.key.as_str()))
+                }) {
+                    return Err(errors::Errors::new(errors::ErrorCodes::BatchKeyConflict)
+                        .with_message(format!(
+                            "Key '{}' of table '{}' is both put and deleted in the batch",
+                            write.key, write.table
+                        )));
+                }
+
+                let _write_permit = self.acquire_write_permit().await?;
+
+                // 2. Serialize with other writes to the same keys
+                let _key_locks = self
+                    .key_locks
+                    .lock_many(
+                        writes
+                            .iter()
+                            .map(|write| (write.table.as_str(), write.key.as_str())),
+                    )
+                    .await;
+
+                // 3. Validation of each write
+                let mut results = Vec::with_capacity(writes.len());
+                let mut accepted = vec![];
+                let mut table_infos: HashMap<String, TableInfo> = HashMap::new();
+                let mut batch_keys = HashSet::new();
+                for (index, write) in writes.iter().enumerate() {
+                    results.push(BatchWriteResult {
+                        key: write.key.clone(),
+                        version: None,
+                        error: None,
+                    });
+
+                    match self
+                        .check_batch_write(write, &mut table_infos, &batch_keys)
+                        .await
+                    {
+                        Ok(()) => {
+                            batch_keys.insert((write.table.clone(), write.key.clone()));
+                            accepted.push(index);
+                        }
+                        Err(error) => results[index].error = Some(error.to_string()),
+                    }
+                }
+
+                // 4. WAL write (the record IDs become the versions of the values)
+                let written_at = unix_millis_now();
+                let records = accepted
+                    .iter()
+                    .map(|&index| {
+                        let write = &writes[index];
+                        WALRecord {
+                            record_id: 0.into(),
+                            record_type: match write.value {
+                                Some(_) => wal::record::RecordType::Put,
+                                None => wal::record::RecordType::Delete,
+                            },
+                            data: WALPayload {
+                                table: write.table.clone(),
+                                key: write.key.clone(),
+                                value: write.value.clone(),
+                                value_type: write.value_type,
+                                written_at,
+                            },
+                        }
+                    })
+                    .collect();
+                let appended = self.check_storage(self.wal_manager.append_batch(records).await)?;
+                log::debug!(
+                    writes = appended.changes.len() as u64;
+                    "Batch logged to WAL"
+                );
+
+                // the writes after a failure were not logged. the logged ones are still applied below
+                if let Some(error) = appended.error {
+                    self.storage_health.observe(&error);
+                    log::error!(
+                        error:% = error, written = appended.changes.len() as u64;
+                        "Batch stopped partway through the WAL write: {}",
+                        error
+                    );
+                    for &index in &accepted[appended.changes.len()..] {
+                        results[index].error = Some(error.to_string());
+                    }
+                }
+                let changes = appended.changes;
+
+                // 5. Memtable update
+                let mut writes: Vec<_> = writes.into_iter().map(Some).collect();
+                for (index, change) in accepted.into_iter().zip(changes) {
+                    let Some(write) = writes[index].take() else {
+                        continue;
+                    };
+                    let version = u64::from(change.record_id());
+
+                    // a failed apply is reported in its result, and the rest of the batch is still applied
+                    let applied = match write.value {
+                        Some(value) => {
+                            self.write_stats
+                                .add_user_bytes(&write.table, write.key.len() + value.len());
+                            self.apply_put(
+                                write.table,
+                                write.key,
+                                value,
+                                write.value_type,
+                                version,
+                                written_at,
+                            )
+                            .await
+                        }
+                        None => {
+                            self.write_stats
+                                .add_user_bytes(&write.table, write.key.len());
+                            self.memtable_manager
+                                .delete_value(write.table, write.key, version, written_at)
+                                .await
+                        }
+                    };
+                    if let Err(error) = applied {
+                        log::error!(
+                            error:% = error, version = version;
+                            "Failed to apply batch write: {}",
+                            error
+                        );
+                        results[index].error = Some(error.to_string());
+                        continue;
+                    }
+                    results[index].version = Some(version);
+
+                    // 6. Publish to the change subscribers
+                    change.publish();
+                }
+
+                Ok(results)
+            })
+            .await
+    }
+
+    // batch_keys: keys put or deleted by the earlier writes of the batch
+    async fn check_batch_write(
+        &self,
+        write: &BatchWrite,
+        table_infos: &mut HashMap<String, TableInfo>,
+        batch_keys: &HashSet<(String, String)>,
+    ) -> errors::Result<()> {
+        validate_table_name(&write.table)?;
+        validate_key(&write.key)?;
+        if let Some(value) = &write.value {
+            validate_value(value)?;
+            write.value_type.validate(value)?;
+        }
+
+        if !table_infos.contains_key(&write.table) {
+            let table_info = self.get_table_for_write(&write.table).await?;
+            table_infos.insert(write.table.clone(), table_info);
+        }
+        let table_info = &table_infos[&write.table];
+
+        match write.value {
+            Some(_)
+                if table_info.append_only
+                    && batch_keys.contains(&(write.table.clone(), write.key.clone())) =>
+            {
+                Err(
+                    errors::Errors::new(errors::ErrorCodes::TableIsAppendOnly).with_message(
+                        format!(
+                            "Key '{}' already exists in append-only table '{}'",
+                            write.key, write.table
+                        ),
+                    ),
+                )
+            }
+            Some(_) => self.check_put_allowed(table_info, &write.key).await,
+            None => self.check_delete_allowed(table_info),
+        }
+    }
+
+    /// Deletes the given key only if its current value equals `expected`.
+    /// Returns whether the delete happened.
+    pub async fn delete_if(
+        &self,
+        table: String,
+        key: String,
+        expected: String,
+    ) -> errors::Result<bool> {
+        // 1. Validation
+        let key = self.normalize_owned_key(&table, key);
+        self.ensure_writable()?;
+        validate_table_name(&table)?;
+        validate_key(&key)?;
+
+        let _write_permit = self.acquire_write_permit().await?;
+
+        // 2. Compare and delete under the key lock
+        let _key_lock = self.key_locks.lock(&table, &key).await;
+
+        let current = match self.get_value(&table, &key).await {
+            Ok(current) => current,
+            Err(error) => match error.error_code {
+                errors::ErrorCodes::ValueNotFound => return Ok(false),
+                _ => return Err(error),
+            },
+        };
+
+        if current.value != expected.as_bytes() {
+            return Ok(false);
+        }
+
+        self.write_delete(table, key).await?;
+
+        Ok(true)
+    }
+
+    /// Appends `suffix` to the current value (creating it if absent).
+    /// This reads and rewrites the whole value, so it costs O(value size).
+    /// The value keeps its type, so the result must still be a valid value of it.
+    pub async fn append_value(
+        &self,
+        table: String,
+        key: String,
+        suffix: String,
+    ) -> errors::Result<()> {
+        // 1. Validation
+        let key = self.normalize_owned_key(&table, key);
+        self.ensure_writable()?;
+        validate_table_name(&table)?;
+        validate_key(&key)?;
+
+        let _write_permit = self.acquire_write_permit().await?;
+
+        // 2. Read, concatenate and write under the key lock
+        let _key_lock = self.key_locks.lock(&table, &key).await;
+
+        let (mut value, value_type) = match self.get_value(&table, &key).await {
+            Ok(current) => (current.value, current.value_type),
+            Err(error) => match error.error_code {
+                errors::ErrorCodes::ValueNotFound => (vec![], ValueType::Raw),
+                _ => return Err(error),
+            },
+        };
+        value.extend_from_slice(suffix.as_bytes());
+
+        validate_value(&value)?;
+        value_type.validate(&value)?;
+
+        self.write_put(table, key, value, value_type).await
+    }
+
+    // Case-insensitive tables compare keys in lowercase. Applied before anything else sees the key
+    fn normalize_key<'a>(&self, table: &str, key: &'a str) -> Cow<'a, str> {
+        self.disktable_manager
+            .key_normalization(table)
+            .normalize(key)
+    }
+
+    fn normalize_owned_key(&self, table: &str, key: String) -> String {
+        let normalized = match self.normalize_key(table, &key) {
+            Cow::Owned(normalized) => Some(normalized),
+            Cow::Borrowed(_) => None,
+        };
+
+        normalized.unwrap_or(key)
+    }
+
+    // Fails before the WAL append, so no orphan record is logged for a missing table.
+    // The table info is cached by the disktable manager, so this doesn't read the table file
+    async fn get_table_for_write(&self, table: &str) -> errors::Result<TableInfo> {
+        self.disktable_manager.get_table(table).await
+    }
+
+    // WAL write + Memtable update for a put. The caller must hold the key lock.
+    async fn write_put(
+        &self,
+        table: String,
+        key: String,
+        value: Vec<u8>,
+        value_type: ValueType,
+    ) -> errors::Result<()> {
+        let table_info = self.get_table_for_write(&table).await?;
+        self.check_put_allowed(&table_info, &key).await?;
+
+        let written_at = unix_millis_now();
+        let wal_record = WALRecord {
+            record_id: 0.into(),
+            record_type: wal::record::RecordType::Put,
+            data: WALPayload {
+                table: table.clone(),
+                key: key.clone(),
+                value: Some(value.clone()),
+                value_type,
+                written_at,
+            },
+        };
+
+        // 1. WAL write (the record ID becomes the version of the value)
+        let change = self.check_storage(self.wal_manager.append(wal_record).await)?;
+        let record_id = change.record_id();
+        self.write_stats
+            .add_user_bytes(&table, key.len() + value.len());
+        log::debug!(
+            table = table.as_str(),
+            key_length = key.len() as u64,
+            record_id = u64::from(record_id);
+            "Put logged to WAL"
+        );
+
+        // 2. Memtable update
+        self.apply_put(table, key, value, value_type, record_id.into(), written_at)
+            .await?;
+
+        // 3. Publish to the change subscribers, now that the value is readable
+        change.publish();
+
+        Ok(())
+    }
+
+    // append-only 테이블은 기존 키 덮어쓰기 불가
+    async fn check_put_allowed(&self, table_info: &TableInfo, key: &str) -> errors::Result<()> {
+        if !table_info.append_only {
+            return Ok(());
+        }
+
+        match self.get_value(&table_info.name, key).await {
+            Ok(_) => Err(
+                errors::Errors::new(errors::ErrorCodes::TableIsAppendOnly).with_message(format!(
+                    "Key '{}' already exists in append-only table '{}'",
+                    key, table_info.name
+                )),
+            ),
+            Err(error) if matches!(error.error_code, errors::ErrorCodes::ValueNotFound) => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn check_delete_allowed(&self, table_info: &TableInfo) -> errors::Result<()> {
+        if table_info.append_only {
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::TableIsAppendOnly).with_message(format!(
+                    "Can't delete from append-only table '{}'",
+                    table_info.name
+                )),
+            );
+        }
+
+        Ok(())
+    }
+
+    // Memtable update for a put logged to the WAL with the given record ID
+    async fn apply_put(
+        &self,
+        table: String,
+        key: String,
+        value: Vec<u8>,
+        value_type: ValueType,
+        version: u64,
+        written_at: u64,
+    ) -> errors::Result<()> {
+        // large values are written straight to disk, and the memtable only keeps a marker
+        if self
+            .large_value_threshold
+            .is_some_and(|threshold| value.len() >= threshold)
+        {
+            self.check_storage(
+                self.disktable_manager
+                    .write_value(&table, &key, &value, value_type, version, written_at)
+                    .await,
+            )?;
+            self.memtable_manager
+                .mark_on_disk(table, key, version, written_at)
+                .await?;
+        } else {
+            self.memtable_manager
+                .put(table, key, value, value_type, version, written_at)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // WAL write + Memtable update for a delete. The caller must hold the key lock.
+    async fn write_delete(&self, table: String, key: String) -> errors::Result<()> {
+        let table_info = self.get_table_for_write(&table).await?;
+        self.check_delete_allowed(&table_info)?;
+
+        let written_at = unix_millis_now();
+        let wal_record = WALRecord {
+            record_id: 0.into(),
+            record_type: wal::record::RecordType::Delete,
+            data: WALPayload {
+                table: table.to_string(),
+                key: key.to_string(),
+                value: None,
+                value_type: ValueType::Raw,
+                written_at,
+            },
+        };
+
+        // 1. WAL write
+        let change = self.check_storage(self.wal_manager.append(wal_record).await)?;
+        let record_id = change.record_id();
+        self.write_stats.add_user_bytes(&table, key.len());
+        log::debug!(
+            table = table.as_str(),
+            key_length = key.len() as u64,
+            record_id = u64::from(record_id);
+            "Delete logged to WAL"
+        );
+
+        // 2. Memtable update
+        {
+            self.memtable_manager
+                .delete_value(table, key, record_id.into(), written_at)
+                .await?;
+        }
+
+        // 3. Publish to the change subscribers
+        change.publish();
+
+        Ok(())
+    }
+
+    /// Returns the WAL checkpoint and the last written segment/record IDs.
+    pub async fn wal_checkpoint(&self) -> WALCheckpointResponse {
+        let state = ordered(LockLevel::WALState, self.wal_manager.wal_state.lock()).await;
+
+        WALCheckpointResponse {
+            last_checkpoint_segment_id: state.last_checkpoint_segment_id.clone().into(),
+            last_checkpoint_record_id: state.last_checkpoint_record_id.into(),
+            last_segment_id: state.last_segment_id.clone().into(),
+            last_record_id: state.last_record_id.into(),
+            last_flushed_record_id: state.last_flushed_record_id.into(),
+            last_flushed_segment_id: state.last_flushed_segment_id.clone().into(),
+        }
+    }
+
+    /// Moves the WAL checkpoint (ex: after an external backup).
+    /// Records up to the checkpoint are no longer replayed on startup.
+    ///
+    /// Moving it backward makes the next startup replay the records after it again
+    /// (ex: after restoring segment files), as long as their WAL segments were not removed yet.
+    /// It cannot move past the last flushed record or the segment holding it (`WALCheckpointOutOfRange`):
+    /// records after it are only in the WAL and would be lost on restart.
+    pub async fn move_wal_checkpoint(&self, segment_id: u64, record_id: u64) -> errors::Result<()> {
+        let previous = self.wal_checkpoint().await;
+
+        self.wal_manager
+            .move_checkpoint(WALSegmentID::new(segment_id), record_id.into())
+            .await?;
+
+        log::warn!(
+            "WAL checkpoint moved manually: ({}, {}) -> ({}, {})",
+            previous.last_checkpoint_segment_id,
+            previous.last_checkpoint_record_id,
+            segment_id,
+            record_id
+        );
+
+        Ok(())
+    }
+
+    /// Flushes the WAL to disk.
+    pub async fn flush_wal(&self) -> errors::Result<()> {
+        self.wal_manager.flush_wal().await?;
+
+        Ok(())
+    }
+
+    /// Closes the current WAL segment and starts a new one. Returns the new segment file name.
+    pub async fn rotate_wal(&self) -> errors::Result<String> {
+        let segment_id = self.wal_manager.rotate_segment().await?;
+
+        Ok((&segment_id).into())
+    }
+
+    /// Trigger memtable flush
+    pub async fn trigger_memtable_flush(&self) -> errors::Result<()> {
+        self.memtable_manager.trigger_flush().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use tokio_stream::StreamExt;
+
+    use super::{BatchWrite, DBEngine, ReadSource, ScanOptions, ScanResponseItem};
+    use crate::config::StartupIntegrity;
+    use crate::memtable::table::MemtableGetValueResult;
+    use crate::replication::ChangeOp;
+    use crate::{
+        config::{
+            FSYNC_ON_FLUSH, MAX_CONCURRENT_WRITES, MULTI_GET_MAX_KEYS, SCAN_PAGE_KEYS,
+            TABLES_DIRECTORY, WriteLimitPolicy,
+        },
+        disktable::table::{CreateTableOptions, SecondaryIndexInfo, TableKeyNormalization},
+        errors,
+        value_type::ValueType,
+    };
+
+    // 테스트마다 독립된 데이터 디렉토리를 사용
+    fn test_base_path(name: &str) -> PathBuf {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+        base_path
+    }
+
+    // flush 이벤트가 background task에서 처리될 때까지 대기
+    async fn wait_for_flush(db: &DBEngine) {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        loop {
+            let status = db.flush_status().await;
+            if status.last_completed_at.is_some() {
+                assert!(status.last_error.is_none(), "{:?}", status.last_error);
+                break;
+            }
+            assert!(std::time::Instant::now() < deadline, "flush timed out");
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+
+    // flush the memtable and wait until that flush (not an earlier one) completes
+    async fn flush_and_wait(db: &DBEngine) {
+        let previous = db.flush_status().await.last_completed_at;
+        db.trigger_memtable_flush().await.unwrap();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        loop {
+            let status = db.flush_status().await;
+            if status.last_completed_at != previous && !status.is_flushing() {
+                assert!(status.last_error.is_none(), "{:?}", status.last_error);
+                break;
+            }
+            assert!(std::time::Instant::now() < deadline, "flush timed out");
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_if() {
+        let base_path = test_base_path("delete_if");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("locks", CreateTableOptions::default())
+            .await
+            .unwrap();
+        db.put_value("locks".into(), "lock1".into(), "owner-a".into())
+            .await
+            .unwrap();
+
+        // 기대값이 다르면 삭제되지 않아야 함
+        let deleted = db
+            .delete_if("locks".into(), "lock1".into(), "owner-b".into())
+            .await
+            .unwrap();
+        assert!(!deleted);
+        assert_eq!(
+            db.get_value("locks", "lock1").await.unwrap().text(),
+            "owner-a"
+        );
+
+        // 기대값이 같으면 삭제
+        let deleted = db
+            .delete_if("locks".into(), "lock1".into(), "owner-a".into())
+            .await
+            .unwrap();
+        assert!(deleted);
+        assert!(db.get_value("locks", "lock1").await.is_err());
+
+        // 이미 없는 키는 false
+        let deleted = db
+            .delete_if("locks".into(), "lock1".into(), "owner-a".into())
+            .await
+            .unwrap();
+        assert!(!deleted);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_get_value_from_source() {
+        let base_path = test_base_path("get_value_from_source");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("docs", CreateTableOptions::default())
+            .await
+            .unwrap();
+        db.put_value("docs".into(), "doc1".into(), "v1".into())
+            .await
+            .unwrap();
+
+        let get = |source| {
+            let db = &db;
+            async move {
+                db.get_value_from("docs", "doc1", source)
+                    .await
+                    .map(|response| response.value)
+                    .map_err(|error| error.error_code)
+            }
+        };
+
+        // 아직 memtable에만 있음
+        assert_eq!(get(ReadSource::Default).await.unwrap(), b"v1");
+        assert_eq!(get(ReadSource::MemtableOnly).await.unwrap(), b"v1");
+        assert!(matches!(
+            get(ReadSource::DiskOnly).await,
+            Err(errors::ErrorCodes::ValueNotFound)
+        ));
+
+        // flush 후에는 disk에만 있음
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+
+        assert_eq!(get(ReadSource::Default).await.unwrap(), b"v1");
+        assert_eq!(get(ReadSource::DiskOnly).await.unwrap(), b"v1");
+        assert!(matches!(
+            get(ReadSource::MemtableOnly).await,
+            Err(errors::ErrorCodes::ValueNotFound)
+        ));
+
+        // disk only ignores newer memtable writes and deletes
+        db.put_value("docs".into(), "doc1".into(), "v2".into())
+            .await
+            .unwrap();
+        assert_eq!(get(ReadSource::Default).await.unwrap(), b"v2");
+        assert_eq!(get(ReadSource::MemtableOnly).await.unwrap(), b"v2");
+        assert_eq!(get(ReadSource::DiskOnly).await.unwrap(), b"v1");
+
+        db.delete_value("docs".into(), "doc1".into()).await.unwrap();
+        assert!(get(ReadSource::Default).await.is_err());
+        assert!(get(ReadSource::MemtableOnly).await.is_err());
+        assert_eq!(get(ReadSource::DiskOnly).await.unwrap(), b"v1");
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    // put/delete (including write-through), flush, truncate and reads at the same time.
+    // debug builds also panic on any lock order violation (lock::LockLevel)
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_writes_flush_and_truncate_do_not_deadlock() {
+        let base_path = test_base_path("concurrent_lock_order");
+        let mut db = DBEngine::initialize(base_path.clone()).await.unwrap();
+        db.large_value_threshold = Some(1000);
+        let db = std::sync::Arc::new(db);
+
+        for table in ["items", "logs"] {
+            db.create_table(table, CreateTableOptions::default())
+                .await
+                .unwrap();
+        }
+
+        let mut tasks = vec![];
+        for writer in 0..4 {
+            let db = db.clone();
+            tasks.push(tokio::spawn(async move {
+                for i in 0..100 {
+                    let table = if i % 2 == 0 { "items" } else { "logs" };
+                    let key = format!("{}-{}", writer, i % 10);
+                    let value = if i % 7 == 0 {
+                        "v".repeat(2000)
+                    } else {
+                        "v".into()
+                    };
+
+                    db.put_value(table.into(), key.clone(), value)
+                        .await
+                        .unwrap();
+                    if i % 3 == 0 {
+                        db.delete_value(table.into(), key.clone()).await.unwrap();
+                    }
+                    let _ = db.get_value(table, &key).await;
+                }
+            }));
+        }
+        tasks.push(tokio::spawn({
+            let db = db.clone();
+            async move {
+                for _ in 0..20 {
+                    // AlreadyInProgress while the previous flush runs
+                    let _ = db.trigger_memtable_flush().await;
+                    db.truncate_table("logs").await.unwrap();
+                    db.scan("items", ScanOptions::default()).await.unwrap();
+                    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                }
+            }
+        }));
+
+        tokio::time::timeout(std::time::Duration::from_secs(30), async {
+            for task in tasks {
+                task.await.unwrap();
+            }
+        })
+        .await
+        .expect("deadlock between writes, flush and truncate");
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_size_histogram() {
+        let base_path = test_base_path("size_histogram");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("sizes", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        // keys: 4 bytes, values: 10 x 8 bytes (flushed) + 10 x 100 bytes (memtable)
+        for i in 0..10 {
+            db.put_value("sizes".into(), format!("a{:03}", i), "v".repeat(8))
+                .await
+                .unwrap();
+        }
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+        for i in 0..10 {
+            db.put_value("sizes".into(), format!("b{:03}", i), "v".repeat(100))
+                .await
+                .unwrap();
+        }
+
+        let histogram = db.size_histogram("sizes", 1.0).await.unwrap();
+        assert_eq!(histogram.total_records, 20);
+        assert_eq!(histogram.key_sizes.count, 20);
+        assert_eq!((histogram.key_sizes.min, histogram.key_sizes.max), (4, 4));
+        assert_eq!(histogram.value_sizes.min, 8);
+        assert_eq!(histogram.value_sizes.p50, 8);
+        assert_eq!(histogram.value_sizes.p90, 100);
+
+        let bucket_count = |le| {
+            histogram
+                .value_sizes
+                .buckets
+                .iter()
+                .find(|bucket| bucket.le == le)
+                .unwrap()
+                .count
+        };
+        assert_eq!(bucket_count(8), 10);
+        assert_eq!(bucket_count(64), 0);
+        assert_eq!(bucket_count(128), 10);
+
+        let sampled = db.size_histogram("sizes", 0.5).await.unwrap();
+        assert_eq!(sampled.total_records, 20);
+        assert!(sampled.key_sizes.count < 20);
+
+        // a deleted disk record is not counted
+        db.delete_value("sizes".into(), "a000".into())
+            .await
+            .unwrap();
+        let histogram = db.size_histogram("sizes", 1.0).await.unwrap();
+        assert_eq!(histogram.total_records, 19);
+        assert_eq!(histogram.value_sizes.count, 19);
+
+        assert!(db.size_histogram("missing", 1.0).await.is_err());
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_get_if_version_gt() {
+        let base_path = test_base_path("get_if_version_gt");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("docs", CreateTableOptions::default())
+            .await
+            .unwrap();
+        db.put_value("docs".into(), "doc1".into(), "v1".into())
+            .await
+            .unwrap();
+
+        let first = db.get_value("docs", "doc1").await.unwrap();
+        assert!(first.version > 0);
+
+        // 변경이 없으면 None
+        let not_modified = db
+            .get_if_version_gt("docs", "doc1", first.version)
+            .await
+            .unwrap();
+        assert!(not_modified.is_none());
+
+        // 쓰기마다 버전 증가
+        db.put_value("docs".into(), "doc1".into(), "v2".into())
+            .await
+            .unwrap();
+
+        let modified = db
+            .get_if_version_gt("docs", "doc1", first.version)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(modified.value, b"v2");
+        assert!(modified.version > first.version);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_find_by_index() {
+        let base_path = test_base_path("find_by_index");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        let options = CreateTableOptions {
+            secondary_indexes: vec![SecondaryIndexInfo {
+                name: "by_city".into(),
+                json_path: "address.city".into(),
+            }],
+            ..Default::default()
+        };
+        db.create_table("users", options).await.unwrap();
+
+        db.put_value(
+            "users".into(),
+            "u1".into(),
+            r#"{"address": {"city": "seoul"}}"#.into(),
+        )
+        .await
+        .unwrap();
+        db.put_value(
+            "users".into(),
+            "u2".into(),
+            r#"{"address": {"city": "busan"}}"#.into(),
+        )
+        .await
+        .unwrap();
+        db.put_value("users".into(), "u3".into(), "not json".into())
+            .await
+            .unwrap();
+
+        let response = db.find_by_index("users", "by_city", "seoul").await.unwrap();
+        let keys: Vec<_> = response
+            .items
+            .iter()
+            .map(|item| item.key.as_str())
+            .collect();
+        assert_eq!(keys, vec!["u1"]);
+
+        // 값이 바뀌면 이전 필드 값으로는 찾을 수 없음
+        db.put_value(
+            "users".into(),
+            "u1".into(),
+            r#"{"address": {"city": "busan"}}"#.into(),
+        )
+        .await
+        .unwrap();
+        assert!(
+            db.find_by_index("users", "by_city", "seoul")
+                .await
+                .unwrap()
+                .items
+                .is_empty()
+        );
+        assert_eq!(
+            db.find_by_index("users", "by_city", "busan")
+                .await
+                .unwrap()
+                .items
+                .len(),
+            2
+        );
+
+        // 없는 인덱스
+        let error = db
+            .find_by_index("users", "by_name", "alice")
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::SecondaryIndexNotFound
+        ));
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_write_to_missing_table_skips_wal() {
+        let base_path = test_base_path("missing_table");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        let last_record_id =
+            || async { u64::from(db.wal_manager.wal_state.lock().await.last_record_id) };
+        let before = last_record_id().await;
+
+        let error = db
+            .put_value("missing".into(), "key1".into(), "value".into())
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::TableNotFound
+        ));
+
+        let error = db
+            .delete_value("missing".into(), "key1".into())
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::TableNotFound
+        ));
+
+        // WAL 레코드가 추가되지 않아야 함
+        assert_eq!(last_record_id().await, before);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_mode() {
+        let base_path = test_base_path("read_only");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+        db.put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .unwrap();
+
+        db.set_read_only(true);
+
+        // 쓰기는 모두 거부
+        let is_read_only_error = |result: errors::Result<()>| {
+            matches!(
+                result.err().map(|e| e.error_code),
+                Some(errors::ErrorCodes::EngineReadOnly)
+            )
+        };
+        assert!(is_read_only_error(
+            db.put_value("items".into(), "item1".into(), "v2".into())
+                .await
+        ));
+        assert!(is_read_only_error(
+            db.delete_value("items".into(), "item1".into()).await
+        ));
+        assert!(is_read_only_error(
+            db.create_table("others", CreateTableOptions::default())
+                .await
+        ));
+        assert!(is_read_only_error(db.delete_table("items").await));
+
+        // 읽기는 가능
+        assert_eq!(db.get_value("items", "item1").await.unwrap().value, b"v1");
+
+        db.set_read_only(false);
+        db.put_value("items".into(), "item1".into(), "v2".into())
+            .await
+            .unwrap();
+        assert_eq!(db.get_value("items", "item1").await.unwrap().value, b"v2");
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_storage_degraded_mode() {
+        let base_path = test_base_path("storage_degraded");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+        db.put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .unwrap();
+
+        // 디스크 full로 인한 쓰기 실패를 흉내냄
+        let io_error = std::io::Error::from_raw_os_error(libc::ENOSPC);
+        let write_result: errors::Result<()> = Err(errors::Errors::new(errors::io_error_code(
+            &io_error,
+            errors::ErrorCodes::FileWriteError,
+        )));
+        assert!(db.check_storage(write_result).is_err());
+
+        assert!(db.is_storage_degraded());
+        assert!(!db.is_healthy());
+        assert!(db.get_metrics().await.storage_degraded);
+
+        // 이후 쓰기는 바로 거부
+        assert!(matches!(
+            db.put_value("items".into(), "item1".into(), "v2".into())
+                .await
+                .err()
+                .map(|e| e.error_code),
+            Some(errors::ErrorCodes::StorageUnavailable)
+        ));
+
+        // 읽기는 가능
+        assert_eq!(db.get_value("items", "item1").await.unwrap().value, b"v1");
+
+        // 공간 확보 후 read-only 해제로 복구
+        db.set_read_only(false);
+        assert!(db.is_healthy());
+        db.put_value("items".into(), "item1".into(), "v2".into())
+            .await
+            .unwrap();
+        assert_eq!(db.get_value("items", "item1").await.unwrap().value, b"v2");
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_write_limit_reject_policy() {
+        let base_path = test_base_path("write_limit");
+        let mut db = DBEngine::initialize(base_path.clone()).await.unwrap();
+        db.write_limit_policy = WriteLimitPolicy::Reject;
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        // 모든 permit을 점유하면 쓰기가 거부됨
+        let write_limiter = db.write_limiter.clone();
+        let permits = write_limiter
+            .acquire_many(*MAX_CONCURRENT_WRITES as u32)
+            .await
+            .unwrap();
+        assert_eq!(db.get_metrics().await.available_write_permits, 0);
+
+        let error = db
+            .put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::TooManyWrites
+        ));
+
+        drop(permits);
+        db.put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .unwrap();
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_memtable_flush_end_to_end() {
+        let base_path = test_base_path("flush_end_to_end");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+        db.put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .unwrap();
+
+        db.trigger_memtable_flush().await.unwrap();
+
+        wait_for_flush(&db).await;
+
+        // memtable은 비워지고 disk에서 읽힘
+        assert!(matches!(
+            db.memtable_manager
+                .get_value("items", "item1")
+                .await
+                .unwrap(),
+            MemtableGetValueResult::NotFound
+        ));
+        assert_eq!(db.get_value("items", "item1").await.unwrap().value, b"v1");
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_flush_syncs_segment_and_index_files() {
+        let base_path = test_base_path("flush_fsync");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+        assert!(*FSYNC_ON_FLUSH);
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+        db.put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .unwrap();
+
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+
+        // segment 1개 + primary index 파일/메타데이터 + bloom filter
+        assert!(db.get_metrics().await.flush_synced_files >= 4);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_create_table() {
+        let base_path = test_base_path("concurrent_create");
+        let db = std::sync::Arc::new(DBEngine::initialize(base_path.clone()).await.unwrap());
+
+        let creates: Vec<_> = (0..32)
+            .map(|i| {
+                let db = db.clone();
+                tokio::spawn(async move {
+                    let options = CreateTableOptions {
+                        description: Some(format!("create {}", i)),
+                        ..Default::default()
+                    };
+                    db.create_table("items", options).await.map(|()| i)
+                })
+            })
+            .collect();
+
+        let mut winners = vec![];
+        for create in creates {
+            match create.await.unwrap() {
+                Ok(i) => winners.push(i),
+                Err(error) => assert!(
+                    matches!(error.error_code, errors::ErrorCodes::TableAlreadyExists),
+                    "{:?}",
+                    error
+                ),
+            }
+        }
+        assert_eq!(winners.len(), 1);
+
+        // the table info is the winner's, not a mix of concurrent writes
+        let table_info = db.get_table("items").await.unwrap();
+        assert_eq!(
+            table_info.description,
+            Some(format!("create {}", winners[0]))
+        );
+        assert_eq!(db.list_tables().await.unwrap().tables.len(), 1);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_flush_and_delete_table_concurrently() {
+        let base_path = test_base_path("flush_delete_race");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        for table in ["items", "others"] {
+            db.create_table(table, CreateTableOptions::default())
+                .await
+                .unwrap();
+
+            for i in 0..500 {
+                db.put_value(table.into(), format!("key{}", i), format!("value{}", i))
+                    .await
+                    .unwrap();
+            }
+        }
+
+        // flush가 진행되는 동안 테이블 삭제
+        let (flush_result, delete_result) =
+            tokio::join!(db.trigger_memtable_flush(), db.delete_table("items"));
+        flush_result.unwrap();
+        delete_result.unwrap();
+
+        wait_for_flush(&db).await;
+
+        // 삭제된 테이블은 flush로 되살아나지 않음
+        assert!(db.get_table("items").await.is_err());
+        assert!(!base_path.join(TABLES_DIRECTORY).join("items").exists());
+
+        // 다른 테이블의 데이터는 정상적으로 flush됨
+        for i in 0..500 {
+            assert_eq!(
+                db.get_value("others", &format!("key{}", i))
+                    .await
+                    .unwrap()
+                    .text(),
+                format!("value{}", i)
+            );
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_large_value_write_through() {
+        let base_path = test_base_path("large_value_write_through");
+        let mut db = DBEngine::initialize(base_path.clone()).await.unwrap();
+        db.large_value_threshold = Some(1024);
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        let large_value = "x".repeat(4096);
+
+        // 작은 값 위에 큰 값을 덮어쓰면 memtable에는 marker만 남음
+        db.put_value("items".into(), "item1".into(), "small".into())
+            .await
+            .unwrap();
+        db.put_value("items".into(), "item1".into(), large_value.clone())
+            .await
+            .unwrap();
+        assert!(matches!(
+            db.memtable_manager
+                .get_value("items", "item1")
+                .await
+                .unwrap(),
+            MemtableGetValueResult::OnDisk { .. }
+        ));
+        assert_eq!(
+            db.get_value("items", "item1").await.unwrap().text(),
+            large_value
+        );
+
+        // 큰 값 위에 작은 값을 쓰면 다시 memtable에서 읽힘
+        db.put_value("items".into(), "item2".into(), large_value.clone())
+            .await
+            .unwrap();
+        db.put_value("items".into(), "item2".into(), "small".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_value("items", "item2").await.unwrap().value,
+            b"small"
+        );
+
+        // flush 이후에도 최신 값 유지
+        db.trigger_memtable_flush().await.unwrap();
+
+        wait_for_flush(&db).await;
+
+        assert_eq!(
+            db.get_value("items", "item1").await.unwrap().text(),
+            large_value
+        );
+        assert_eq!(
+            db.get_value("items", "item2").await.unwrap().value,
+            b"small"
+        );
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_get_many() {
+        let base_path = test_base_path("get_many");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", Default::default()).await.unwrap();
+        for key in ["a", "b", "c"] {
+            db.put_value("items".into(), key.into(), format!("value-{}", key))
+                .await
+                .unwrap();
+        }
+        db.delete_value("items".into(), "b".into()).await.unwrap();
+
+        let keys: Vec<String> = ["c", "missing", "a", "b"].map(String::from).into();
+        let values: Vec<Option<String>> = db
+            .get_many("items", &keys)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|result| result.map(|result| result.text().into_owned()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![Some("value-c".into()), None, Some("value-a".into()), None]
+        );
+
+        let error = db.get_many("missing_table", &keys).await.unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::TableNotFound
+        ));
+
+        let too_many_keys = vec!["a".to_string(); MULTI_GET_MAX_KEYS + 1];
+        let error = db.get_many("items", &too_many_keys).await.unwrap_err();
+        assert!(matches!(error.error_code, errors::ErrorCodes::TooManyKeys));
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_put_if_changed() {
+        let base_path = test_base_path("put_if_changed");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", Default::default()).await.unwrap();
+
+        assert!(
+            db.put_if_changed("items".into(), "key1".into(), "value1".into())
+                .await
+                .unwrap()
+        );
+        let last_record_id = db.last_record_id().await;
+        let wal_offset = db
+            .wal_manager
+            .wal_state
+            .lock()
+            .await
+            .last_segment_file_offset;
+
+        // 같은 값은 WAL에 기록하지 않음
+        for _ in 0..3 {
+            assert!(
+                !db.put_if_changed("items".into(), "key1".into(), "value1".into())
+                    .await
+                    .unwrap()
+            );
+        }
+        assert_eq!(db.last_record_id().await, last_record_id);
+        assert_eq!(
+            db.wal_manager
+                .wal_state
+                .lock()
+                .await
+                .last_segment_file_offset,
+            wal_offset
+        );
+
+        assert!(
+            db.put_if_changed("items".into(), "key1".into(), "value2".into())
+                .await
+                .unwrap()
+        );
+        assert_eq!(db.last_record_id().await, last_record_id + 1);
+        assert_eq!(
+            db.get_value("items", "key1").await.unwrap().value,
+            b"value2"
+        );
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_append_only_table() {
+        let base_path = test_base_path("append_only_table");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        let options = CreateTableOptions {
+            append_only: true,
+            ..Default::default()
+        };
+        db.create_table("audit", options).await.unwrap();
+        assert!(db.get_table("audit").await.unwrap().append_only);
+
+        db.put_value("audit".into(), "event1".into(), "created".into())
+            .await
+            .unwrap();
+
+        // 기존 키 덮어쓰기, 삭제는 거부
+        let error = db
+            .put_value("audit".into(), "event1".into(), "changed".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::TableIsAppendOnly
+        ));
+
+        let error = db
+            .delete_value("audit".into(), "event1".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::TableIsAppendOnly
+        ));
+
+        // 새 키는 허용
+        db.put_value("audit".into(), "event2".into(), "updated".into())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            db.get_value("audit", "event1").await.unwrap().text(),
+            "created"
+        );
+        assert_eq!(
+            db.get_value("audit", "event2").await.unwrap().text(),
+            "updated"
+        );
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_write_batch() {
+        let base_path = test_base_path("write_batch");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+        db.create_table("foo", CreateTableOptions::default())
+            .await
+            .unwrap();
+        db.put_value("foo".into(), "old".into(), "value".into())
+            .await
+            .unwrap();
+
+        let write = |key: &str, value: Option<&str>| BatchWrite {
+            table: "foo".into(),
+            key: key.into(),
+            value: value.map(|value| value.into()),
+            value_type: ValueType::Raw,
+        };
+        let results = db
+            .write_batch(vec![
+                write("a", Some("1")),
+                write("", Some("empty")),
+                write("b", Some("2")),
+                write("old", None),
+            ])
+            .await
+            .unwrap();
+
+        // 검증에 실패한 쓰기만 건너뛰고, 나머지는 연속된 record ID로 기록
+        assert!(results[1].version.is_none() && results[1].error.is_some());
+        let versions: Vec<u64> = [0, 2, 3]
+            .iter()
+            .map(|&index| results[index].version.unwrap())
+            .collect();
+        assert_eq!(versions[1], versions[0] + 1);
+        assert_eq!(versions[2], versions[1] + 1);
+
+        assert_eq!(db.get_value("foo", "a").await.unwrap().value, b"1");
+        assert_eq!(db.get_value("foo", "b").await.unwrap().value, b"2");
+        let error = db.get_value("foo", "old").await.unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::ValueNotFound
+        ));
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_write_batch_rejects_key_both_put_and_deleted() {
+        let base_path = test_base_path("write_batch_conflict");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+        let options = CreateTableOptions {
+            key_normalization: TableKeyNormalization::Lowercase,
+            ..Default::default()
+        };
+        db.create_table("users", options).await.unwrap();
+
+        let write = |key: &str, value: Option<&str>| BatchWrite {
+            table: "users".into(),
+            key: key.into(),
+            value: value.map(|value| value.into()),
+            value_type: ValueType::Raw,
+        };
+
+        // 정규화 후 같은 키
+        let error = db
+            .write_batch(vec![write("A", Some("1")), write("a", None)])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::BatchKeyConflict
+        ));
+        assert!(db.get_value("users", "a").await.is_err());
+
+        db.write_batch(vec![write("A", Some("1")), write("b", None)])
+            .await
+            .unwrap();
+        assert_eq!(db.get_value("users", "a").await.unwrap().value, b"1");
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_get_prior_versions() {
+        let base_path = test_base_path("prior_versions");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        let options = CreateTableOptions {
+            keep_versions: 2,
+            ..Default::default()
+        };
+        db.create_table("docs", options).await.unwrap();
+
+        for value in ["v1", "v2", "v3"] {
+            db.put_value("docs".into(), "doc1".into(), value.into())
+                .await
+                .unwrap();
+            flush_and_wait(&db).await;
+        }
+
+        let value_of = |previous| {
+            let db = &db;
+            async move {
+                db.get_version("docs", "doc1", previous)
+                    .await
+                    .map(|response| response.value)
+            }
+        };
+        assert_eq!(value_of(0).await.unwrap(), b"v3");
+        assert_eq!(value_of(1).await.unwrap(), b"v2");
+        assert_eq!(value_of(2).await.unwrap(), b"v1");
+        assert!(value_of(3).await.is_err());
+
+        // 아직 flush되지 않은 쓰기가 있으면 disk의 값이 첫 번째 이전 버전
+        db.put_value("docs".into(), "doc1".into(), "v4".into())
+            .await
+            .unwrap();
+        assert_eq!(value_of(0).await.unwrap(), b"v4");
+        assert_eq!(value_of(1).await.unwrap(), b"v3");
+        assert_eq!(value_of(2).await.unwrap(), b"v2");
+
+        // 삭제된 키도 이전 버전은 읽힘. keep_versions를 넘은 버전은 history에서 빠짐
+        db.delete_value("docs".into(), "doc1".into()).await.unwrap();
+        flush_and_wait(&db).await;
+        assert!(value_of(0).await.is_err());
+        assert_eq!(value_of(1).await.unwrap(), b"v3");
+        assert_eq!(value_of(2).await.unwrap(), b"v2");
+
+        // reindex는 삭제된 record로 history를 다시 만듦
+        db.reindex_table("docs").await.unwrap();
+        assert_eq!(value_of(1).await.unwrap(), b"v3");
+        assert_eq!(value_of(2).await.unwrap(), b"v2");
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_case_insensitive_keys() {
+        let base_path = test_base_path("case_insensitive_keys");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        let options = CreateTableOptions {
+            key_normalization: TableKeyNormalization::Lowercase,
+            ..Default::default()
+        };
+        db.create_table("users", options).await.unwrap();
+        db.create_table("plain", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        db.put_value("users".into(), "Foo@Example.com".into(), "1".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_value("users", "foo@example.com")
+                .await
+                .unwrap()
+                .text(),
+            "1"
+        );
+
+        // the differently cased put overwrites the same key
+        db.put_value("users".into(), "FOO@EXAMPLE.COM".into(), "2".into())
+            .await
+            .unwrap();
+        db.put_value("users".into(), "Bar".into(), "3".into())
+            .await
+            .unwrap();
+
+        // B-tree lookups after a flush
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+        let response = db
+            .get_value_from("users", "fOO@example.COM", ReadSource::DiskOnly)
+            .await
+            .unwrap();
+        assert_eq!(response.value, b"2");
+
+        let scan = db
+            .scan(
+                "users",
+                ScanOptions {
+                    after: Some("BAR".into()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let keys: Vec<_> = scan.items.iter().map(|item| item.key.as_str()).collect();
+        assert_eq!(keys, ["foo@example.com"]);
+
+        db.delete_value("users".into(), "BAR".into()).await.unwrap();
+        let error = db.get_value("users", "bar").await.unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::ValueNotFound
+        ));
+
+        // other tables stay case-sensitive
+        db.put_value("plain".into(), "Foo".into(), "1".into())
+            .await
+            .unwrap();
+        assert!(db.get_value("plain", "foo").await.is_err());
+
+        // the option survives a restart
+        db.shutdown().await.unwrap();
+        drop(db);
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+        assert_eq!(
+            db.get_value("users", "Foo@example.com")
+                .await
+                .unwrap()
+                .text(),
+            "2"
+        );
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_write_amplification() {
+        let base_path = test_base_path("write_amplification");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        // 100 keys of 6 bytes and values of 100 bytes, each written twice
+        for _ in 0..2 {
+            for i in 0..100 {
+                db.put_value("items".into(), format!("key{:03}", i), "v".repeat(100))
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let stats = db.get_metrics().await.write_amplification["items"];
+        assert_eq!(stats.bytes.user_bytes, 200 * 106);
+        assert!(stats.bytes.wal_bytes > stats.bytes.user_bytes);
+        assert_eq!(stats.bytes.flush_bytes, 0);
+
+        // the flush writes the latest value of each key once
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+
+        let stats = db.get_metrics().await.write_amplification["items"];
+        assert!(stats.bytes.flush_bytes > 100 * 106);
+        assert!(stats.bytes.flush_bytes < 200 * 106);
+        assert_eq!(stats.bytes.rewrite_bytes, 0);
+
+        let expected = (stats.bytes.wal_bytes + stats.bytes.flush_bytes) as f64
+            / stats.bytes.user_bytes as f64;
+        assert_eq!(stats.write_amplification, Some(expected));
+        assert!(expected > 1.5, "{}", expected);
+
+        db.delete_table("items").await.unwrap();
+        assert!(
+            !db.get_metrics()
+                .await
+                .write_amplification
+                .contains_key("items")
+        );
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_written_at_survives_flush() {
+        let base_path = test_base_path("written_at_flush");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        let before = crate::system::unix_millis_now();
+        db.put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .unwrap();
+        let after = crate::system::unix_millis_now();
+
+        let written_at = db.get_value("items", "item1").await.unwrap().written_at;
+        assert!(before <= written_at && written_at <= after);
+
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+
+        // disk에서 읽어도 같은 시각
+        assert!(matches!(
+            db.memtable_manager
+                .get_value("items", "item1")
+                .await
+                .unwrap(),
+            MemtableGetValueResult::NotFound
+        ));
+        assert_eq!(
+            db.get_value("items", "item1").await.unwrap().written_at,
+            written_at
+        );
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_typed_values() {
+        let base_path = test_base_path("typed_values");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        db.put_typed_value("items".into(), "count".into(), "42".into(), ValueType::Int)
+            .await
+            .unwrap();
+        db.put_typed_value(
+            "items".into(),
+            "doc".into(),
+            r#"{"name": "barus"}"#.into(),
+            ValueType::Json,
+        )
+        .await
+        .unwrap();
+        db.put_value("items".into(), "text".into(), "hello".into())
+            .await
+            .unwrap();
+        db.put_bytes("items".into(), "blob".into(), &[0, 255, 16, b'a'])
+            .await
+            .unwrap();
+
+        let assert_types = async |db: &DBEngine| {
+            for (key, value_type) in [
+                ("count", ValueType::Int),
+                ("doc", ValueType::Json),
+                ("text", ValueType::Raw),
+                ("blob", ValueType::Binary),
+            ] {
+                assert_eq!(
+                    db.get_value("items", key).await.unwrap().value_type,
+                    value_type
+                );
+            }
+
+            let blob = db.get_value("items", "blob").await.unwrap();
+            assert_eq!(blob.value, vec![0, 255, 16, b'a']);
+            let text = db.get_value("items", "text").await.unwrap();
+            assert_eq!(text.value, b"hello");
+        };
+
+        assert_types(&db).await;
+
+        // 타입은 disk에도 기록됨
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+
+        assert_types(&db).await;
+        let scan = db.scan("items", ScanOptions::default()).await.unwrap();
+        assert_eq!(scan.items[0].key, "blob");
+        assert_eq!(scan.items[0].value_type, ValueType::Binary);
+        assert_eq!(scan.items[1].key, "count");
+        assert_eq!(scan.items[1].value_type, ValueType::Int);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_value_type_mismatch() {
+        let base_path = test_base_path("value_type_mismatch");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+        let last_record_id = db.last_record_id().await;
+
+        for (value, value_type) in [("forty-two", ValueType::Int), ("{", ValueType::Json)] {
+            let error = db
+                .put_typed_value("items".into(), "key1".into(), value.into(), value_type)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                error.error_code,
+                errors::ErrorCodes::ValueTypeMismatch
+            ));
+        }
+
+        // 잘못된 값은 WAL에 기록되지 않음
+        assert_eq!(db.last_record_id().await, last_record_id);
+
+        // append keeps the type, so it cannot break the value
+        db.put_typed_value("items".into(), "count".into(), "4".into(), ValueType::Int)
+            .await
+            .unwrap();
+        db.append_value("items".into(), "count".into(), "2".into())
+            .await
+            .unwrap();
+        let error = db
+            .append_value("items".into(), "count".into(), "x".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::ValueTypeMismatch
+        ));
+
+        let current = db.get_value("items", "count").await.unwrap();
+        assert_eq!(current.value, b"42");
+        assert_eq!(current.value_type, ValueType::Int);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_move_wal_checkpoint_backward_replays_records() {
+        let base_path = test_base_path("move_wal_checkpoint");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        db.put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .unwrap();
+        let target = db.wal_checkpoint().await;
+
+        for key in ["item2", "item3"] {
+            db.put_value("items".into(), key.into(), "v".into())
+                .await
+                .unwrap();
+        }
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+
+        let checkpoint = db.wal_checkpoint().await;
+        assert_eq!(
+            checkpoint.last_checkpoint_record_id,
+            checkpoint.last_record_id
+        );
+
+        assert_eq!(checkpoint.last_flushed_record_id, checkpoint.last_record_id);
+
+        // 플러시된 기록 이후로는 이동 불가
+        db.put_value("items".into(), "item4".into(), "v".into())
+            .await
+            .unwrap();
+        let unflushed = db.wal_checkpoint().await;
+        let error = db
+            .move_wal_checkpoint(unflushed.last_segment_id, unflushed.last_record_id)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::WALCheckpointOutOfRange
+        ));
+
+        db.move_wal_checkpoint(target.last_segment_id, target.last_record_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            db.wal_checkpoint().await.last_checkpoint_record_id,
+            target.last_record_id
+        );
+
+        // crash without a shutdown: shutdown would flush the memtable and move the checkpoint to the end
+        db.flush_wal().await.unwrap();
+        drop(db);
+
+        // 재시작하면 checkpoint 이후의 기록만 다시 적용됨
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+        for (key, replayed) in [
+            ("item1", false),
+            ("item2", true),
+            ("item3", true),
+            ("item4", true),
+        ] {
+            let result = db.memtable_manager.get_value("items", key).await.unwrap();
+            assert_eq!(
+                matches!(result, MemtableGetValueResult::Found { .. }),
+                replayed,
+                "{}",
+                key
+            );
+            assert!(db.get_value("items", key).await.is_ok());
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_move_wal_checkpoint_rejects_segments_after_last_flush() {
+        let base_path = test_base_path("move_wal_checkpoint_segment");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+        db.put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .unwrap();
+        flush_and_wait(&db).await;
+        let flushed = db.wal_checkpoint().await;
+
+        // 플러시되지 않은 기록이 있는 segment 이후의 segment로는 이동 불가
+        db.rotate_wal().await.unwrap();
+        db.put_value("items".into(), "item2".into(), "v2".into())
+            .await
+            .unwrap();
+        db.rotate_wal().await.unwrap();
+
+        let checkpoint = db.wal_checkpoint().await;
+        assert!(checkpoint.last_segment_id > flushed.last_flushed_segment_id);
+        let error = db
+            .move_wal_checkpoint(checkpoint.last_segment_id, flushed.last_flushed_record_id)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::WALCheckpointOutOfRange
+        ));
+
+        db.move_wal_checkpoint(
+            flushed.last_flushed_segment_id,
+            flushed.last_flushed_record_id,
+        )
+        .await
+        .unwrap();
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_shutdown_refuses_writes_after_accepted_ones() {
+        let base_path = test_base_path("shutdown_writes");
+        let db = std::sync::Arc::new(DBEngine::initialize(base_path.clone()).await.unwrap());
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        let writers: Vec<_> = (0..200)
+            .map(|i| {
+                let db = db.clone();
+                tokio::spawn(async move {
+                    let key = format!("key{}", i);
+                    let result = db
+                        .put_value("items".into(), key.clone(), "value".into())
+                        .await;
+                    (key, result)
+                })
+            })
+            .collect();
+
+        tokio::task::yield_now().await;
+        db.shutdown().await.unwrap();
+
+        let mut accepted = vec![];
+        for writer in writers {
+            let (key, result) = writer.await.unwrap();
+            match result {
+                Ok(()) => accepted.push(key),
+                Err(error) => assert!(
+                    matches!(error.error_code, errors::ErrorCodes::EngineReadOnly),
+                    "{:?}",
+                    error
+                ),
+            }
+        }
+
+        let error = db
+            .put_value("items".into(), "late".into(), "value".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::EngineReadOnly
+        ));
+        drop(db);
+
+        // everything acknowledged was flushed, so nothing is left to replay
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+        for key in &accepted {
+            assert!(matches!(
+                db.memtable_manager.get_value("items", key).await.unwrap(),
+                MemtableGetValueResult::NotFound
+            ));
+            assert_eq!(db.get_value("items", key).await.unwrap().value, b"value");
+        }
+        assert!(db.get_value("items", "late").await.is_err());
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_startup_integrity_with_corrupt_index() {
+        let base_path = test_base_path("startup_integrity");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+        db.put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .unwrap();
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+
+        db.shutdown().await.unwrap();
+        drop(db);
+
+        // 인덱스 파일 손상
+        let index_file = base_path
+            .join(crate::config::TABLES_DIRECTORY)
+            .join("items")
+            .join(crate::config::TABLES_INDEX_DIRECTORY)
+            .join("index.btree");
+        std::fs::write(&index_file, b"garbage").unwrap();
+
+        let error = DBEngine::initialize_with_startup_integrity(
+            base_path.clone(),
+            StartupIntegrity::Strict,
+        )
+        .await
+        .err()
+        .unwrap();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::IntegrityCheckFailed
+        ));
+        assert!(error.to_string().contains("table 'items'"), "{}", error);
+        // strict mode leaves the files as they were
+        assert_eq!(std::fs::read(&index_file).unwrap(), b"garbage");
+
+        // repair mode recreates the index, and reindex restores it from the segments
+        let db = DBEngine::initialize_with_startup_integrity(
+            base_path.clone(),
+            StartupIntegrity::Repair,
+        )
+        .await
+        .unwrap();
+        assert_eq!(db.reindex_table("items").await.unwrap(), 1);
+        assert_eq!(db.get_value("items", "item1").await.unwrap().value, b"v1");
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_scan_stream_matches_scan() {
+        let base_path = test_base_path("scan_stream");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        for key in ["item1", "item2", "item3"] {
+            db.put_value("items".into(), key.into(), "old".into())
+                .await
+                .unwrap();
+        }
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+
+        // memtable entries shadow the flushed records
+        db.put_value("items".into(), "item2".into(), "new".into())
+            .await
+            .unwrap();
+        db.delete_value("items".into(), "item3".into())
+            .await
+            .unwrap();
+        db.put_value("items".into(), "item4".into(), "new".into())
+            .await
+            .unwrap();
+
+        let mut streamed: Vec<ScanResponseItem> = db
+            .scan_stream("items")
+            .await
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .await
+            .unwrap();
+        streamed.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let scanned = db
+            .scan("items", ScanOptions::default())
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(streamed, scanned);
+        assert_eq!(
+            streamed
+                .iter()
+                .map(|item| (item.key.as_str(), std::str::from_utf8(&item.value).unwrap()))
+                .collect::<Vec<_>>(),
+            vec![("item1", "old"), ("item2", "new"), ("item4", "new")]
+        );
+
+        assert!(matches!(
+            db.scan_stream("missing").await.unwrap_err().error_code,
+            errors::ErrorCodes::TableNotFound
+        ));
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_scan_modified_since() {
+        let base_path = test_base_path("scan_modified_since");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        for key in ["item1", "item2", "item3"] {
+            db.put_value("items".into(), key.into(), "old".into())
+                .await
+                .unwrap();
+        }
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+
+        let since = crate::system::unix_millis_now();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+        // disk 위에 memtable 변경분
+        db.put_value("items".into(), "item1".into(), "new".into())
+            .await
+            .unwrap();
+        db.delete_value("items".into(), "item2".into())
+            .await
+            .unwrap();
+        db.put_value("items".into(), "item4".into(), "new".into())
+            .await
+            .unwrap();
+
+        let all = db.scan("items", ScanOptions::default()).await.unwrap();
+        let all: Vec<_> = all
+            .items
+            .iter()
+            .map(|item| (item.key.as_str(), std::str::from_utf8(&item.value).unwrap()))
+            .collect();
+        assert_eq!(
+            all,
+            vec![("item1", "new"), ("item3", "old"), ("item4", "new")]
+        );
+
+        let modified = db
+            .scan(
+                "items",
+                ScanOptions {
+                    modified_since: Some(since),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let modified: Vec<_> = modified
+            .items
+            .iter()
+            .map(|item| item.key.as_str())
+            .collect();
+        assert_eq!(modified, vec!["item1", "item4"]);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_replayed_writes_keep_their_write_time() {
+        let base_path = test_base_path("replayed_writes_keep_their_write_time");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+        db.put_value("items".into(), "item1".into(), "value".into())
+            .await
+            .unwrap();
+        let written_at = db.get_value("items", "item1").await.unwrap().written_at;
+
+        // crash without a shutdown, so the write is replayed from the WAL
+        db.flush_wal().await.unwrap();
+        drop(db);
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+        assert_eq!(
+            db.get_value("items", "item1").await.unwrap().written_at,
+            written_at
+        );
+
+        let modified = db
+            .scan(
+                "items",
+                ScanOptions {
+                    modified_since: Some(written_at),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(modified.items.is_empty());
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_scan_merges_pages_of_memtable_and_disk() {
+        let base_path = test_base_path("scan_merges_pages");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        // more keys than a page on both sides: even keys on disk, odd keys in the memtable
+        let key_count = SCAN_PAGE_KEYS * 3;
+        let mut expected = std::collections::BTreeMap::new();
+        for i in (0..key_count).step_by(2) {
+            db.put_value("items".into(), format!("key{:05}", i), "disk".into())
+                .await
+                .unwrap();
+            expected.insert(format!("key{:05}", i), "disk");
+        }
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+
+        for i in 0..key_count {
+            let key = format!("key{:05}", i);
+            if i % 7 == 0 {
+                db.delete_value("items".into(), key.clone()).await.unwrap();
+                expected.remove(&key);
+            } else if i % 2 == 1 || i % 10 == 0 {
+                db.put_value("items".into(), key.clone(), "memtable".into())
+                    .await
+                    .unwrap();
+                expected.insert(key, "memtable");
+            }
+        }
+
+        let scanned = db.scan("items", ScanOptions::default()).await.unwrap();
+        assert!(!scanned.truncated);
+        assert_eq!(
+            scanned
+                .items
+                .iter()
+                .map(|item| (item.key.clone(), std::str::from_utf8(&item.value).unwrap()))
+                .collect::<Vec<_>>(),
+            expected.into_iter().collect::<Vec<_>>()
+        );
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_scan_truncated_resumes_from_cursor() {
+        let base_path = test_base_path("scan_truncated_resumes_from_cursor");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        // 절반은 disk, 절반은 memtable
+        for i in 0..10 {
+            db.put_value("items".into(), format!("item{}", i), "0123456789".into())
+                .await
+                .unwrap();
+        }
+        db.trigger_memtable_flush().await.unwrap();
+        wait_for_flush(&db).await;
+        for i in 10..20 {
+            db.put_value("items".into(), format!("item{}", i), "0123456789".into())
+                .await
+                .unwrap();
+        }
+        db.delete_value("items".into(), "item3".into())
+            .await
+            .unwrap();
+
+        let expected = db.scan("items", ScanOptions::default()).await.unwrap();
+        assert!(!expected.truncated);
+        assert_eq!(expected.next_cursor, None);
+        assert_eq!(expected.items.len(), 19);
+
+        // 15~16 bytes per item: every page stops after 4 items
+        let mut items = vec![];
+        let mut after = None;
+        let mut pages = 0;
+        loop {
+            let page = db
+                .scan(
+                    "items",
+                    ScanOptions {
+                        after: after.clone(),
+                        max_bytes: Some(60),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+            pages += 1;
+
+            assert!(page.items.len() <= 4);
+            items.extend(page.items);
+
+            if !page.truncated {
+                assert_eq!(page.next_cursor, None);
+                break;
+            }
+            after = Some(page.next_cursor.unwrap());
+        }
+        assert_eq!(items, expected.items);
+        assert_eq!(pages, 5);
+
+        // an expired duration still moves past one key per call
+        let page = db
+            .scan(
+                "items",
+                ScanOptions {
+                    max_duration: Some(std::time::Duration::ZERO),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(page.truncated);
+        assert_eq!(page.items, expected.items[..1]);
+        assert_eq!(page.next_cursor.as_deref(), Some("item0"));
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_changes() {
+        let base_path = test_base_path("subscribe_changes");
+        let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+        db.create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        // 구독 이전의 쓰기는 받지 않음
+        db.put_value("items".into(), "before".into(), "v".into())
+            .await
+            .unwrap();
+
+        let mut changes = db.subscribe_changes();
+
+        db.put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .unwrap();
+        let version = db.get_value("items", "item1").await.unwrap().version;
+        db.delete_value("items".into(), "item1".into())
+            .await
+            .unwrap();
+
+        let put = changes.recv().await.unwrap();
+        assert_eq!(put.table, "items");
+        assert_eq!(put.key, "item1");
+        assert_eq!(put.op, ChangeOp::Put);
+        assert_eq!(put.record_id, version);
+        assert_eq!(put.value.as_deref(), Some(b"v1".as_slice()));
+
+        let delete = changes.recv().await.unwrap();
+        assert_eq!(delete.key, "item1");
+        assert_eq!(delete.op, ChangeOp::Delete);
+        assert!(delete.record_id > put.record_id);
+
+        // 실패한 쓰기는 발행되지 않음
+        assert!(
+            db.put_value("missing".into(), "item1".into(), "v".into())
+                .await
+                .is_err()
+        );
+        assert!(changes.try_recv().is_err());
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_apply_replicated_records() {
+        let leader_path = test_base_path("replication_leader");
+        let follower_path = test_base_path("replication_follower");
+        let leader = DBEngine::initialize(leader_path.clone()).await.unwrap();
+        let follower = DBEngine::initialize(follower_path.clone()).await.unwrap();
+        follower.set_replica();
+
+        leader
+            .create_table("items", CreateTableOptions::default())
+            .await
+            .unwrap();
+        leader
+            .put_value("items".into(), "item1".into(), "v1".into())
+            .await
+            .unwrap();
+        leader
+            .put_value("items".into(), "item2".into(), "v2".into())
+            .await
+            .unwrap();
+        leader
+            .delete_value("items".into(), "item2".into())
+            .await
+            .unwrap();
+
+        // follower는 마지막으로 적용한 record 이후부터 이어받음
+        for event in leader
+            .changes_after(follower.last_record_id().await)
+            .await
+            .unwrap()
+        {
+            assert!(
+                follower
+                    .apply_replicated_record(event.into())
+                    .await
+                    .unwrap()
+            );
+        }
+        assert_eq!(
+            follower.last_record_id().await,
+            leader.last_record_id().await
+        );
+
+        // 중복 적용은 무시됨
+        for event in leader.changes_after(0).await.unwrap() {
+            assert!(
+                !follower
+                    .apply_replicated_record(event.into())
+                    .await
+                    .unwrap()
+            );
+        }
+
+        assert_eq!(
+            follower.get_value("items", "item1").await.unwrap().text(),
+            "v1"
+        );
+        assert!(follower.get_value("items", "item2").await.is_err());
+
+        // 사용자 쓰기는 거부
+        let error = follower
+            .put_value("items".into(), "item3".into(), "v3".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::EngineReadOnly
+        ));
+
+        // leader보다 앞선 위치에서는 이어받을 수 없음
+        let error = leader
+            .changes_after(leader.last_record_id().await + 10)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            errors::ErrorCodes::WALRecordsUnavailable
+        ));
+
+        let _ = std::fs::remove_dir_all(&leader_path);
+        let _ = std::fs::remove_dir_all(&follower_path);
     }
 }
@@ -0,0 +1,171 @@
+use crate::checksum::crc32;
+
+// Bloom filter of the primary keys of a table.
+// Stored next to the index files and consulted before the B-tree lookup.
+// A negative answer means the key was never flushed to disk.
+pub const BLOOM_FILTER_FILE_NAME: &str = "bloom";
+
+const BITS_PER_KEY: u64 = 10; // ~1% false positive rate per block
+const HASH_COUNT: u64 = 7;
+const INITIAL_CAPACITY: u64 = 1024;
+
+#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+struct BloomFilterBlock {
+    bits: Vec<u64>,
+    capacity: u64,
+    key_count: u64,
+}
+
+impl BloomFilterBlock {
+    fn new(capacity: u64) -> Self {
+        let bit_count = capacity * BITS_PER_KEY;
+
+        Self {
+            bits: vec![0; bit_count.div_ceil(64) as usize],
+            capacity,
+            key_count: 0,
+        }
+    }
+
+    fn bit_count(&self) -> u64 {
+        self.bits.len() as u64 * 64
+    }
+
+    // double hashing: h1 + i * h2
+    fn bit_indexes(&self, key: &str) -> impl Iterator<Item = u64> {
+        let hash = fnv1a_64(key.as_bytes());
+        let h1 = hash;
+        let h2 = hash.rotate_left(32) | 1;
+        let bit_count = self.bit_count();
+
+        (0..HASH_COUNT).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % bit_count)
+    }
+
+    fn insert(&mut self, key: &str) {
+        for bit in self.bit_indexes(key).collect::<Vec<_>>() {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+        self.key_count += 1;
+    }
+
+    fn may_contain(&self, key: &str) -> bool {
+        self.bit_indexes(key)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+}
+
+// Scalable bloom filter.
+// When the last block reaches its capacity, a block with twice the capacity is appended,
+// so the false positive rate stays bounded as the table grows.
+#[derive(Debug, Clone, Default, bincode::Encode, bincode::Decode)]
+pub struct BloomFilter {
+    blocks: Vec<BloomFilterBlock>,
+}
+
+impl BloomFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: &str) {
+        // 이미 들어있는 키(update)는 다시 넣지 않음
+        if self.may_contain(key) {
+            return;
+        }
+
+        let needs_block = match self.blocks.last() {
+            Some(block) => block.key_count >= block.capacity,
+            None => true,
+        };
+
+        if needs_block {
+            let capacity = self
+                .blocks
+                .last()
+                .map(|block| block.capacity * 2)
+                .unwrap_or(INITIAL_CAPACITY);
+            self.blocks.push(BloomFilterBlock::new(capacity));
+        }
+
+        if let Some(block) = self.blocks.last_mut() {
+            block.insert(key);
+        }
+    }
+
+    pub fn may_contain(&self, key: &str) -> bool {
+        self.blocks.iter().any(|block| block.may_contain(key))
+    }
+
+    // [crc32 (4)][bincode]
+    pub fn encode(&self) -> Vec<u8> {
+        let encoded = bincode::encode_to_vec(self, bincode::config::standard()).unwrap_or_default();
+
+        let mut bytes = Vec::with_capacity(encoded.len() + 4);
+        bytes.extend_from_slice(&crc32(&encoded).to_be_bytes());
+        bytes.extend_from_slice(&encoded);
+        bytes
+    }
+
+    // None if the bytes are truncated or corrupted
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 4 {
+            return None;
+        }
+
+        let (checksum, encoded) = bytes.split_at(4);
+        if crc32(encoded).to_be_bytes() != checksum {
+            return None;
+        }
+
+        bincode::decode_from_slice(encoded, bincode::config::standard())
+            .ok()
+            .map(|(filter, _)| filter)
+    }
+}
+
+// hash must stay stable across builds since filters are persisted
+pub(crate) fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BloomFilter;
+
+    #[test]
+    fn test_bloom_filter() {
+        let mut filter = BloomFilter::new();
+
+        // 여러 블록으로 늘어나도 false negative는 없어야 함
+        for i in 0..10000 {
+            filter.insert(&format!("key{}", i));
+        }
+        for i in 0..10000 {
+            assert!(filter.may_contain(&format!("key{}", i)));
+        }
+
+        let false_positives = (0..10000)
+            .filter(|i| filter.may_contain(&format!("absent{}", i)))
+            .count();
+        assert!(
+            false_positives < 500,
+            "false positives: {}",
+            false_positives
+        );
+
+        let decoded = BloomFilter::decode(&filter.encode()).unwrap();
+        assert!(decoded.may_contain("key42"));
+
+        let mut corrupted = filter.encode();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xFF;
+        assert!(BloomFilter::decode(&corrupted).is_none());
+    }
+}
@@ -1,4 +1,9 @@
-use std::{collections::HashMap, io::SeekFrom, path::PathBuf, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    io::SeekFrom,
+    path::PathBuf,
+    sync::Arc,
+};
 
 use async_recursion::async_recursion;
 use tokio::{
@@ -8,9 +13,11 @@ use tokio::{
 };
 
 use crate::{
+    checksum::crc32,
     config::{TABLES_DIRECTORY, TABLES_INDEX_DIRECTORY},
     disktable::segment::position::TableRecordPosition,
     errors::{self, ErrorCodes},
+    os::sync_file,
 };
 
 /// 인덱스 세그먼트 파일의 최대 크기 (1GB)
@@ -19,6 +26,21 @@ const INDEX_SEGMENT_SIZE: u64 = 1024 * 1024 * 1024;
 /// BTree 노드의 고정 크기 (8KB)
 const NODE_SIZE: usize = 8192;
 
+/// 노드 블록 헤더 크기 (크기 4바이트 + CRC32 4바이트)
+const NODE_HEADER_SIZE: usize = 8;
+
+/// 노드 블록에 들어갈 수 있는 최대 인코딩 크기
+const NODE_MAX_DATA_SIZE: usize = NODE_SIZE - NODE_HEADER_SIZE;
+
+/// 크기 헤더의 최상위 비트. 체크섬이 있는 노드임을 표시 (체크섬 도입 이전에 쓰인 노드와 구분)
+const NODE_CHECKSUM_FLAG: u32 = 1 << 31;
+
+/// 인덱스 파일 포맷 버전. 1: 리프 노드가 다음 리프를 가리킴 (next), 2: 메타데이터에 free list
+pub const BTREE_FORMAT_VERSION: u32 = 2;
+
+/// 기본(primary) 인덱스 파일 이름
+pub const PRIMARY_INDEX_FILE_NAME: &str = "index";
+
 /// BTree 노드의 타입
 #[derive(Debug, Clone, Copy, PartialEq, Eq, bincode::Encode, bincode::Decode)]
 pub enum BTreeNodeType {
@@ -56,6 +78,43 @@ pub struct BTreeNode {
     // 내부 노드의 경우
     pub internal_entries: Vec<BTreeInternalEntry>,
     pub leftmost_child: Option<BTreeNodePosition>,
+    // 리프 노드의 경우 키 순서상 다음 리프 (범위 스캔용)
+    pub next: Option<BTreeNodePosition>,
+}
+
+/// 버전 0 포맷의 노드 (next 없음). 마이그레이션에서만 읽는다
+#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+struct BTreeNodeV0 {
+    node_type: BTreeNodeType,
+    parent: Option<BTreeNodePosition>,
+    leaf_entries: Vec<BTreeLeafEntry>,
+    internal_entries: Vec<BTreeInternalEntry>,
+    leftmost_child: Option<BTreeNodePosition>,
+}
+
+impl From<BTreeNodeV0> for BTreeNode {
+    fn from(node: BTreeNodeV0) -> Self {
+        Self {
+            node_type: node.node_type,
+            parent: node.parent,
+            leaf_entries: node.leaf_entries,
+            internal_entries: node.internal_entries,
+            leftmost_child: node.leftmost_child,
+            next: None,
+        }
+    }
+}
+
+/// 노드 데이터 디코딩 (포맷 버전에 따라)
+fn decode_node(buffer: &[u8], version: u32) -> Result<BTreeNode, bincode::error::DecodeError> {
+    if version == 0 {
+        let (node, _): (BTreeNodeV0, usize) =
+            bincode::decode_from_slice(buffer, bincode::config::standard())?;
+        return Ok(node.into());
+    }
+
+    let (node, _) = bincode::decode_from_slice(buffer, bincode::config::standard())?;
+    Ok(node)
 }
 
 impl BTreeNode {
@@ -66,6 +125,7 @@ impl BTreeNode {
             leaf_entries: Vec::new(),
             internal_entries: Vec::new(),
             leftmost_child: None,
+            next: None,
         }
     }
 
@@ -76,6 +136,7 @@ impl BTreeNode {
             leaf_entries: Vec::new(),
             internal_entries: Vec::new(),
             leftmost_child: None,
+            next: None,
         }
     }
 
@@ -83,12 +144,73 @@ impl BTreeNode {
         self.node_type == BTreeNodeType::Leaf
     }
 
+    /// 엔트리 수가 order / 2 - 1 미만이면 형제와 합치거나 빌려올 대상 (루트 제외)
+    /// 분할 직후의 노드는 (order - 1) / 2개 안팎이므로 분할과 합치기가 번갈아 일어나지 않는다
+    pub fn is_underfull(&self, order: u16) -> bool {
+        let entry_count = match self.node_type {
+            BTreeNodeType::Leaf => self.leaf_entries.len(),
+            BTreeNodeType::Internal => self.internal_entries.len(),
+        };
+
+        entry_count < (order / 2).saturating_sub(1) as usize
+    }
+
+    /// index번째 자식 (0: leftmost_child)
+    fn child_at(&self, index: usize) -> Option<BTreeNodePosition> {
+        match index {
+            0 => self.leftmost_child,
+            _ => self
+                .internal_entries
+                .get(index - 1)
+                .map(|entry| entry.child_position),
+        }
+    }
+
+    /// 엔트리 수가 order에 도달했거나, 인코딩 크기가 노드 블록을 넘으면 분할 대상
+    /// 긴 키는 order보다 적은 엔트리로도 블록을 채우므로 크기도 함께 확인한다
     pub fn is_full(&self, order: u16) -> bool {
-        match self.node_type {
-            BTreeNodeType::Leaf => self.leaf_entries.len() as u16 >= order - 1,
-            BTreeNodeType::Internal => self.internal_entries.len() as u16 >= order - 1,
+        let entry_count = match self.node_type {
+            BTreeNodeType::Leaf => self.leaf_entries.len(),
+            BTreeNodeType::Internal => self.internal_entries.len(),
+        };
+
+        entry_count as u16 >= order - 1 || self.encoded_size() > NODE_MAX_DATA_SIZE
+    }
+
+    /// 노드 블록에 기록될 인코딩 크기 (헤더 제외)
+    pub fn encoded_size(&self) -> usize {
+        encoded_size(self)
+    }
+}
+
+fn encoded_size<T: bincode::Encode>(value: &T) -> usize {
+    let mut writer = bincode::enc::write::SizeWriter::default();
+
+    match bincode::encode_into_writer(value, &mut writer, bincode::config::standard()) {
+        Ok(()) => writer.bytes_written,
+        Err(_) => usize::MAX,
+    }
+}
+
+/// 분할 지점 계산. 키 길이가 제각각이어도 양쪽 노드가 블록에 들어가도록 엔트리 수가 아닌 바이트 기준으로 나눈다.
+/// 반환값은 [1, len - 1] 범위
+fn size_balanced_split_index<T: bincode::Encode>(entries: &[T]) -> usize {
+    if entries.len() < 2 {
+        return entries.len();
+    }
+
+    let sizes: Vec<usize> = entries.iter().map(encoded_size).collect();
+    let half = sizes.iter().sum::<usize>() / 2;
+
+    let mut accumulated = 0;
+    for (index, size) in sizes.iter().enumerate() {
+        accumulated += size;
+        if accumulated >= half {
+            return (index + 1).clamp(1, entries.len() - 1);
         }
     }
+
+    entries.len() - 1
 }
 
 /// BTree 인덱스 메타데이터
@@ -97,6 +219,9 @@ pub struct BTreeMetadata {
     pub root_position: Option<BTreeNodePosition>,
     pub order: u16,       // BTree의 차수
     pub next_offset: u64, // 다음 노드를 쓸 위치
+    pub version: u32,     // 포맷 버전 (BTREE_FORMAT_VERSION)
+    // 트리에서 빠진 노드 블록. write_node가 next_offset보다 먼저 다시 쓴다
+    pub free_offsets: Vec<u64>,
 }
 
 impl Default for BTreeMetadata {
@@ -105,6 +230,49 @@ impl Default for BTreeMetadata {
             root_position: None,
             order: 64, // 기본 차수
             next_offset: 0,
+            version: BTREE_FORMAT_VERSION,
+            free_offsets: Vec::new(),
+        }
+    }
+}
+
+/// 버전 필드가 생기기 전의 메타데이터
+#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+struct BTreeMetadataV0 {
+    root_position: Option<BTreeNodePosition>,
+    order: u16,
+    next_offset: u64,
+}
+
+impl From<BTreeMetadataV0> for BTreeMetadata {
+    fn from(metadata: BTreeMetadataV0) -> Self {
+        Self {
+            root_position: metadata.root_position,
+            order: metadata.order,
+            next_offset: metadata.next_offset,
+            version: 0,
+            free_offsets: Vec::new(),
+        }
+    }
+}
+
+/// free list가 생기기 전의 메타데이터 (버전 1)
+#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+struct BTreeMetadataV1 {
+    root_position: Option<BTreeNodePosition>,
+    order: u16,
+    next_offset: u64,
+    version: u32,
+}
+
+impl From<BTreeMetadataV1> for BTreeMetadata {
+    fn from(metadata: BTreeMetadataV1) -> Self {
+        Self {
+            root_position: metadata.root_position,
+            order: metadata.order,
+            next_offset: metadata.next_offset,
+            version: metadata.version,
+            free_offsets: Vec::new(),
         }
     }
 }
@@ -114,17 +282,27 @@ impl Default for BTreeMetadata {
 pub struct BTreeIndex {
     base_path: PathBuf,
     table_name: String,
+    file_name: String, // 인덱스 파일 이름 ({file_name}.btree, {file_name}.metadata)
     metadata: Arc<Mutex<BTreeMetadata>>,
     file_locks: Arc<RwLock<HashMap<u32, Arc<Mutex<File>>>>>,
+    // 조회는 read, 삽입/삭제는 write. 삭제로 비운 블록이 재사용되므로 조회 도중 노드가 바뀌지 않게 한다
+    tree_lock: Arc<RwLock<()>>,
 }
 
 impl BTreeIndex {
     pub fn new(base_path: PathBuf, table_name: String) -> Self {
+        Self::with_file_name(base_path, table_name, PRIMARY_INDEX_FILE_NAME.to_string())
+    }
+
+    /// 파일 이름을 지정해서 생성 (보조 인덱스용)
+    pub fn with_file_name(base_path: PathBuf, table_name: String, file_name: String) -> Self {
         Self {
             base_path,
             table_name,
+            file_name,
             metadata: Arc::new(Mutex::new(BTreeMetadata::default())),
             file_locks: Arc::new(RwLock::new(HashMap::new())),
+            tree_lock: Arc::new(RwLock::new(())),
         }
     }
 
@@ -137,9 +315,9 @@ impl BTreeIndex {
             .join(TABLES_INDEX_DIRECTORY);
 
         if segment_number == 0 {
-            base.join("index.btree")
+            base.join(format!("{}.btree", self.file_name))
         } else {
-            base.join(format!("index.btree.{}", segment_number))
+            base.join(format!("{}.btree.{}", self.file_name, segment_number))
         }
     }
 
@@ -203,73 +381,157 @@ impl BTreeIndex {
             .join(TABLES_DIRECTORY)
             .join(&self.table_name)
             .join(TABLES_INDEX_DIRECTORY)
-            .join("index.metadata")
+            .join(format!("{}.metadata", self.file_name))
     }
 
-    /// 인덱스 초기화 (파일 열기 또는 생성)
-    pub async fn initialize(&self) -> errors::Result<()> {
+    /// 메타데이터 파일 읽기 (없으면 None)
+    async fn read_metadata(&self) -> errors::Result<Option<BTreeMetadata>> {
         let metadata_path = self.metadata_file_path();
 
-        // 메타데이터 파일 읽기 또는 생성
-        if metadata_path.exists() {
-            let metadata_bytes = tokio::fs::read(&metadata_path).await.map_err(|e| {
-                errors::Errors::new(ErrorCodes::FileReadError)
-                    .with_message(format!("Failed to read metadata file: {}", e))
-            })?;
+        if !metadata_path.exists() {
+            return Ok(None);
+        }
+
+        let metadata_bytes = tokio::fs::read(&metadata_path).await.map_err(|e| {
+            errors::Errors::new(ErrorCodes::FileReadError)
+                .with_message(format!("Failed to read metadata file: {}", e))
+        })?;
+
+        // 이전 버전 메타데이터는 뒤쪽 필드가 없어서 현재 포맷으로 디코딩되지 않음
+        let config = bincode::config::standard();
+        let metadata = if let Ok((metadata, _)) =
+            bincode::decode_from_slice::<BTreeMetadata, _>(&metadata_bytes, config)
+        {
+            metadata
+        } else if let Ok((metadata, _)) =
+            bincode::decode_from_slice::<BTreeMetadataV1, _>(&metadata_bytes, config)
+        {
+            metadata.into()
+        } else {
+            bincode::decode_from_slice::<BTreeMetadataV0, _>(&metadata_bytes, config)
+                .map_err(|e| {
+                    errors::Errors::new(ErrorCodes::FileReadError)
+                        .with_message(format!("Failed to decode metadata: {}", e))
+                })?
+                .0
+                .into()
+        };
 
-            let metadata: BTreeMetadata =
-                bincode::decode_from_slice(&metadata_bytes, bincode::config::standard())
-                    .map_err(|e| {
-                        errors::Errors::new(ErrorCodes::FileReadError)
-                            .with_message(format!("Failed to decode metadata: {}", e))
-                    })?
-                    .0;
+        Ok(Some(metadata))
+    }
 
+    /// 인덱스 초기화 (파일 열기 또는 생성)
+    /// 파일이 없거나 손상되어 빈 인덱스를 새로 만들었으면 true
+    pub async fn initialize(&self) -> errors::Result<bool> {
+        // 메타데이터 파일 읽기 또는 생성
+        if let Some(metadata) = self.read_metadata().await? {
             // 인덱스 파일 유효성 검증
-            let is_valid = self.validate_index_files(&metadata).await;
+            match self.validate_index_files(&metadata).await {
+                Ok(()) => {
+                    let version = metadata.version;
+                    let mut meta_guard = self.metadata.lock().await;
+                    *meta_guard = metadata;
+                    drop(meta_guard);
+
+                    if version < BTREE_FORMAT_VERSION
+                        && let Err(error) = self.migrate(version).await
+                    {
+                        log::warn!(
+                            "Failed to migrate index '{}' of table '{}' ({}). Reinitializing index",
+                            self.file_name,
+                            self.table_name,
+                            error
+                        );
+                        *self.metadata.lock().await = BTreeMetadata::default();
+                        self.file_locks.write().await.clear();
+                        self.cleanup_index_files().await?;
+                        self.save_metadata().await?;
+                        return Ok(true);
+                    }
 
-            if is_valid {
-                let mut meta_guard = self.metadata.lock().await;
-                *meta_guard = metadata;
-            } else {
-                // 손상된 인덱스 파일 정리 및 재생성
-                log::warn!(
-                    "Index files are corrupted. Reinitializing index for table '{}'",
-                    self.table_name
-                );
-                self.cleanup_index_files().await?;
-                self.save_metadata().await?;
+                    Ok(false)
+                }
+                Err(reason) => {
+                    // 손상된 인덱스 파일 정리 및 재생성
+                    log::warn!(
+                        "Index files are corrupted ({}). Reinitializing index for table '{}'",
+                        reason,
+                        self.table_name
+                    );
+                    self.cleanup_index_files().await?;
+                    self.save_metadata().await?;
+                    Ok(true)
+                }
             }
         } else {
             // 새로운 메타데이터 생성
             self.save_metadata().await?;
+            Ok(true)
+        }
+    }
+
+    /// 파일을 바꾸지 않고 유효성만 검사 (BARUS_STARTUP_INTEGRITY=strict)
+    pub async fn check(&self) -> errors::Result<()> {
+        let metadata = self.read_metadata().await.map_err(|error| {
+            errors::Errors::new(ErrorCodes::IntegrityCheckFailed).with_message(format!(
+                "Index '{}' of table '{}' is corrupted: {}",
+                self.file_name, self.table_name, error
+            ))
+        })?;
+
+        if let Some(metadata) = metadata
+            && let Err(reason) = self.validate_index_files(&metadata).await
+        {
+            return Err(
+                errors::Errors::new(ErrorCodes::IntegrityCheckFailed).with_message(format!(
+                    "Index '{}' of table '{}' is corrupted: {}",
+                    self.file_name, self.table_name, reason
+                )),
+            );
         }
 
         Ok(())
     }
 
     /// 인덱스 파일 유효성 검증
-    async fn validate_index_files(&self, metadata: &BTreeMetadata) -> bool {
+    async fn validate_index_files(&self, metadata: &BTreeMetadata) -> Result<(), String> {
         // next_offset이 0이면 빈 인덱스 (아직 아무것도 안 씀)
         if metadata.next_offset == 0 {
             // root_position이 있으면 모순
             if metadata.root_position.is_some() {
-                log::warn!("Metadata inconsistency: root_position exists but next_offset is 0");
-                return false;
+                return Err(
+                    "Metadata inconsistency: root_position exists but next_offset is 0".to_string(),
+                );
             }
-            return true;
+            return Ok(());
         }
 
         // root_position이 없으면 빈 인덱스로 간주
         let Some(root_pos) = metadata.root_position else {
             // next_offset이 0이 아닌데 root가 없으면 모순
-            log::warn!(
+            return Err(format!(
                 "Metadata inconsistency: next_offset is {} but no root_position",
                 metadata.next_offset
-            );
-            return false;
+            ));
         };
 
+        // free list에 잘못된 블록이 있으면 살아있는 노드를 덮어쓰게 됨
+        let mut free_offsets = HashSet::new();
+        for &offset in &metadata.free_offsets {
+            if offset % NODE_SIZE as u64 != 0 || offset >= metadata.next_offset {
+                return Err(format!(
+                    "Invalid free offset {} (next_offset is {})",
+                    offset, metadata.next_offset
+                ));
+            }
+            if offset == root_pos.offset || !free_offsets.insert(offset) {
+                return Err(format!(
+                    "Free offset {} is the root or listed twice",
+                    offset
+                ));
+            }
+        }
+
         // next_offset으로 마지막 세그먼트 확인
         let (last_segment, _) = self.offset_to_segment(metadata.next_offset);
 
@@ -277,8 +539,7 @@ impl BTreeIndex {
         for seg_num in 0..=last_segment {
             let path = self.index_file_path(seg_num);
             if !path.exists() {
-                log::warn!("Missing index segment file: {}", path.display());
-                return false;
+                return Err(format!("Missing index segment file: {}", path.display()));
             }
         }
 
@@ -293,87 +554,88 @@ impl BTreeIndex {
                 let file_size = match file.metadata().await {
                     Ok(meta) => meta.len(),
                     Err(e) => {
-                        log::warn!("Failed to get file metadata: {}", e);
-                        return false;
+                        return Err(format!("Failed to get file metadata: {}", e));
                     }
                 };
 
                 // 최소한 헤더(4바이트) + 일부 데이터가 있어야 함
                 if segment_offset + 4 > file_size {
-                    log::warn!(
+                    return Err(format!(
                         "Index file too small: offset {} + 4 > file size {}",
-                        segment_offset,
-                        file_size
-                    );
-                    return false;
+                        segment_offset, file_size
+                    ));
                 }
 
                 // 노드 크기 헤더 읽기
                 if let Err(e) = file.seek(SeekFrom::Start(segment_offset)).await {
-                    log::warn!("Failed to seek: {}", e);
-                    return false;
+                    return Err(format!("Failed to seek: {}", e));
                 }
 
-                let node_size = match file.read_u32().await {
+                let size_header = match file.read_u32().await {
                     Ok(size) => size,
                     Err(e) => {
-                        log::warn!("Failed to read node size: {}", e);
-                        return false;
+                        return Err(format!("Failed to read node size: {}", e));
                     }
                 };
 
+                let node_size = size_header & !NODE_CHECKSUM_FLAG;
+                let (header_size, expected_checksum) = if size_header & NODE_CHECKSUM_FLAG != 0 {
+                    match file.read_u32().await {
+                        Ok(checksum) => (NODE_HEADER_SIZE as u64, Some(checksum)),
+                        Err(e) => {
+                            return Err(format!("Failed to read node checksum: {}", e));
+                        }
+                    }
+                } else {
+                    (4, None)
+                };
+
                 // 노드 크기가 비정상적으로 크거나 파일 크기를 초과하는지 확인
                 if node_size > 10_000_000 {
                     // 10MB 이상은 비정상
-                    log::warn!(
-                        "Node size {} is suspiciously large (>10MB). Index is corrupted.",
+                    return Err(format!(
+                        "Node size {} is suspiciously large (>10MB)",
                         node_size
-                    );
-                    return false;
+                    ));
                 }
 
                 if node_size == 0 {
-                    log::warn!("Node size is 0. Index is corrupted.");
-                    return false;
+                    return Err("Node size is 0".to_string());
                 }
 
-                if segment_offset + 4 + node_size as u64 > file_size {
-                    log::warn!(
-                        "Node size {} exceeds file bounds: offset {} + 4 + {} > file size {}. Index is corrupted.",
-                        node_size,
-                        segment_offset,
-                        node_size,
-                        file_size
-                    );
-                    return false;
+                if segment_offset + header_size + node_size as u64 > file_size {
+                    return Err(format!(
+                        "Node size {} exceeds file bounds: offset {} + {} + {} > file size {}",
+                        node_size, segment_offset, header_size, node_size, file_size
+                    ));
                 }
 
                 // 실제로 데이터를 읽어서 디코딩 시도
                 let mut buffer = vec![0u8; node_size as usize];
                 if let Err(e) = file.read_exact(&mut buffer).await {
-                    log::warn!("Failed to read node data: {}", e);
-                    return false;
+                    return Err(format!("Failed to read node data: {}", e));
                 }
 
-                // 디코딩 시도
-                let decode_result: Result<(BTreeNode, usize), _> =
-                    bincode::decode_from_slice(&buffer, bincode::config::standard());
+                if let Some(expected_checksum) = expected_checksum
+                    && crc32(&buffer) != expected_checksum
+                {
+                    return Err("Root node checksum mismatch".to_string());
+                }
 
-                match decode_result {
+                // 디코딩 시도
+                match decode_node(&buffer, metadata.version) {
                     Ok(_) => {
                         log::debug!("Index validation passed for table '{}'", self.table_name);
-                        true
-                    }
-                    Err(e) => {
-                        log::warn!("Failed to decode node: {}", e);
-                        false
+                        Ok(())
                     }
+                    Err(e) => Err(format!("Failed to decode node: {}", e)),
                 }
             }
-            Err(e) => {
-                log::warn!("Failed to open index file {}: {}", path.display(), e);
-                false
-            }
+            Err(e) => Err(format!(
+                "Failed to open index file {}: {}",
+                path.display(),
+                e
+            )),
         }
     }
 
@@ -389,7 +651,9 @@ impl BTreeIndex {
             return Ok(());
         }
 
-        // 인덱스 디렉토리 내 모든 index.btree* 파일 삭제
+        // 인덱스 디렉토리 내 모든 {file_name}.btree* 파일 삭제
+        let btree_file_prefix = format!("{}.btree", self.file_name);
+
         let mut entries = tokio::fs::read_dir(&index_dir).await.map_err(|e| {
             errors::Errors::new(ErrorCodes::FileReadError)
                 .with_message(format!("Failed to read index directory: {}", e))
@@ -401,7 +665,7 @@ impl BTreeIndex {
         })? {
             let path = entry.path();
             if let Some(file_name) = path.file_name().and_then(|n| n.to_str())
-                && file_name.starts_with("index.btree")
+                && file_name.starts_with(&btree_file_prefix)
             {
                 tokio::fs::remove_file(&path).await.map_err(|e| {
                     errors::Errors::new(ErrorCodes::FileWriteError).with_message(format!(
@@ -450,8 +714,106 @@ impl BTreeIndex {
         Ok(())
     }
 
+    /// 이전 버전 인덱스를 현재 포맷으로 올린다.
+    /// 버전 0은 노드를 제자리에서 다시 쓰고 리프의 next를 키 순서대로 연결한다. 이후 버전은 메타데이터만 다시 쓴다
+    async fn migrate(&self, version: u32) -> errors::Result<()> {
+        let root_position = self.metadata.lock().await.root_position;
+
+        if version == 0
+            && let Some(root_position) = root_position
+        {
+            // 모두 읽은 뒤에 쓴다 (쓰는 도중에 버전 0 노드를 새 포맷으로 읽지 않도록)
+            let mut nodes = vec![];
+            self.collect_v0_nodes(root_position, &mut nodes).await?;
+
+            let mut previous_leaf: Option<usize> = None;
+            for index in 0..nodes.len() {
+                if nodes[index].1.is_leaf() {
+                    if let Some(previous) = previous_leaf {
+                        nodes[previous].1.next = Some(nodes[index].0);
+                    }
+                    previous_leaf = Some(index);
+                }
+            }
+
+            // 커진 노드가 블록에 들어가는지 먼저 확인
+            for (_, node) in &nodes {
+                self.encode_node_block(node)?;
+            }
+            for (position, node) in &nodes {
+                self.update_node(*position, node).await?;
+            }
+            self.sync().await?;
+        }
+
+        self.metadata.lock().await.version = BTREE_FORMAT_VERSION;
+        self.save_metadata().await?;
+
+        log::info!(
+            "Migrated index '{}' of table '{}' to format version {}",
+            self.file_name,
+            self.table_name,
+            BTREE_FORMAT_VERSION
+        );
+
+        Ok(())
+    }
+
+    /// 버전 0 노드를 키 순서(전위 순회)로 수집
+    #[async_recursion]
+    async fn collect_v0_nodes(
+        &self,
+        node_pos: BTreeNodePosition,
+        nodes: &mut Vec<(BTreeNodePosition, BTreeNode)>,
+    ) -> errors::Result<()> {
+        let node = self.read_node_with_version(node_pos, 0).await?;
+
+        let children: Vec<BTreeNodePosition> = node
+            .leftmost_child
+            .into_iter()
+            .chain(
+                node.internal_entries
+                    .iter()
+                    .map(|entry| entry.child_position),
+            )
+            .collect();
+        nodes.push((node_pos, node));
+
+        for child in children {
+            self.collect_v0_nodes(child, nodes).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 인덱스 파일과 메타데이터 파일을 sync_data. sync한 파일 수를 반환
+    pub async fn sync(&self) -> errors::Result<usize> {
+        let files: Vec<_> = self.file_locks.read().await.values().cloned().collect();
+
+        for file in &files {
+            file.lock().await.sync_data().await.map_err(|e| {
+                errors::Errors::new(errors::io_error_code(&e, ErrorCodes::FileWriteError))
+                    .with_message(format!("Failed to sync index file: {}", e))
+            })?;
+        }
+
+        sync_file(&self.metadata_file_path()).await?;
+
+        Ok(files.len() + 1)
+    }
+
     /// 노드 읽기
     async fn read_node(&self, position: BTreeNodePosition) -> errors::Result<BTreeNode> {
+        self.read_node_with_version(position, BTREE_FORMAT_VERSION)
+            .await
+    }
+
+    /// 지정한 포맷 버전으로 노드 읽기
+    async fn read_node_with_version(
+        &self,
+        position: BTreeNodePosition,
+        version: u32,
+    ) -> errors::Result<BTreeNode> {
         // 논리적 오프셋을 세그먼트 정보로 변환
         let (segment_number, segment_offset) = self.offset_to_segment(position.offset);
 
@@ -492,7 +854,7 @@ impl BTreeIndex {
             })?;
 
         // 노드 크기 읽기
-        let node_size = file.read_u32().await.map_err(|e| {
+        let size_header = file.read_u32().await.map_err(|e| {
             log::error!(
                 "[BTree:{}] Failed to read size header at offset {}: {}",
                 self.table_name,
@@ -505,6 +867,22 @@ impl BTreeIndex {
             ))
         })?;
 
+        let has_checksum = size_header & NODE_CHECKSUM_FLAG != 0;
+        let node_size = size_header & !NODE_CHECKSUM_FLAG;
+
+        // 체크섬 읽기 (체크섬 도입 이전 노드는 크기 헤더만 있음)
+        let (header_size, expected_checksum) = if has_checksum {
+            let checksum = file.read_u32().await.map_err(|e| {
+                errors::Errors::new(ErrorCodes::FileReadError).with_message(format!(
+                    "Failed to read node checksum at offset {}: {}",
+                    segment_offset, e
+                ))
+            })?;
+            (NODE_HEADER_SIZE, Some(checksum))
+        } else {
+            (4, None)
+        };
+
         // 노드 크기 검증
         if node_size == 0 {
             log::error!(
@@ -520,7 +898,7 @@ impl BTreeIndex {
             )));
         }
 
-        let max_data_size = NODE_SIZE - 4;
+        let max_data_size = NODE_SIZE - header_size;
         if node_size > max_data_size as u32 {
             log::error!(
                 "[BTree:{}] Node size {} exceeds maximum {} at offset {}. Segment: {}, Logical: {}",
@@ -539,19 +917,20 @@ impl BTreeIndex {
             );
         }
 
-        if segment_offset + 4 + node_size as u64 > file_size {
+        if segment_offset + header_size as u64 + node_size as u64 > file_size {
             log::error!(
-                "[BTree:{}] Node size error: offset {} + 4 + {} > file size {}. Segment: {}, Logical: {}",
+                "[BTree:{}] Node size error: offset {} + {} + {} > file size {}. Segment: {}, Logical: {}",
                 self.table_name,
                 segment_offset,
+                header_size,
                 node_size,
                 file_size,
                 segment_number,
                 position.offset
             );
             return Err(errors::Errors::new(ErrorCodes::FileReadError).with_message(format!(
-                "Node size {} exceeds file bounds: offset {} + 4 + {} > file size {}. Index may be corrupted.",
-                node_size, segment_offset, node_size, file_size
+                "Node size {} exceeds file bounds: offset {} + {} + {} > file size {}. Index may be corrupted.",
+                node_size, segment_offset, header_size, node_size, file_size
             )));
         }
 
@@ -571,32 +950,54 @@ impl BTreeIndex {
             ))
         })?;
 
-        // 디코딩
-        let node: BTreeNode = bincode::decode_from_slice(&buffer, bincode::config::standard())
-            .map_err(|e| {
+        // 체크섬 검증 (torn write 등으로 인한 손상 감지)
+        if let Some(expected_checksum) = expected_checksum {
+            let actual_checksum = crc32(&buffer);
+
+            if actual_checksum != expected_checksum {
                 log::error!(
-                    "[BTree:{}] Decode failed at offset {}: {}. Buffer size: {}, Header bytes: {:02X?}",
-                    self.table_name, position.offset, e, buffer.len(),
-                    &buffer[..buffer.len().min(32)]
+                    "[BTree:{}] Checksum mismatch at offset {}: expected {:08X}, actual {:08X}. Segment: {}, Logical: {}",
+                    self.table_name,
+                    segment_offset,
+                    expected_checksum,
+                    actual_checksum,
+                    segment_number,
+                    position.offset
                 );
-                errors::Errors::new(ErrorCodes::FileReadError)
-                    .with_message(format!("Failed to decode node: {}", e))
-            })?
-            .0;
+                return Err(errors::Errors::new(ErrorCodes::FileReadError).with_message(format!(
+                    "Node checksum mismatch at offset {}: expected {:08X}, actual {:08X}. Index is corrupted, run reindex to rebuild it.",
+                    position.offset, expected_checksum, actual_checksum
+                )));
+            }
+        }
+
+        // 디코딩
+        let node = decode_node(&buffer, version).map_err(|e| {
+            log::error!(
+                "[BTree:{}] Decode failed at offset {}: {}. Buffer size: {}, Header bytes: {:02X?}",
+                self.table_name,
+                position.offset,
+                e,
+                buffer.len(),
+                &buffer[..buffer.len().min(32)]
+            );
+            errors::Errors::new(ErrorCodes::FileReadError)
+                .with_message(format!("Failed to decode node: {}", e))
+        })?;
 
         Ok(node)
     }
 
-    /// 노드 쓰기 (고정 크기 블록 사용)
-    async fn write_node(&self, node: &BTreeNode) -> errors::Result<BTreeNodePosition> {
-        // 1. 노드 인코딩 (락 없이)
+    /// 노드를 고정 크기 블록으로 인코딩
+    /// [크기 | 체크섬 플래그 (4)][CRC32 (4)][데이터][0 패딩]
+    fn encode_node_block(&self, node: &BTreeNode) -> errors::Result<Vec<u8>> {
         let encoded = bincode::encode_to_vec(node, bincode::config::standard()).map_err(|e| {
             errors::Errors::new(ErrorCodes::FileWriteError)
                 .with_message(format!("Failed to encode node: {}", e))
         })?;
 
         // 고정 크기 블록 검증
-        let max_data_size = NODE_SIZE - 4;
+        let max_data_size = NODE_MAX_DATA_SIZE;
         if encoded.len() > max_data_size {
             log::error!(
                 "[BTree:{}] Node too large: {} > {} (type={:?}, entries={})",
@@ -615,13 +1016,38 @@ impl BTreeIndex {
             );
         }
 
+        let size_header = encoded.len() as u32 | NODE_CHECKSUM_FLAG;
+
+        let mut block = Vec::with_capacity(NODE_SIZE);
+        block.extend_from_slice(&size_header.to_be_bytes());
+        block.extend_from_slice(&crc32(&encoded).to_be_bytes());
+        block.extend_from_slice(&encoded);
+
+        // 나머지 공간을 0으로 패딩 (고정 크기 유지)
+        block.resize(NODE_SIZE, 0);
+
+        Ok(block)
+    }
+
+    /// 노드 쓰기 (고정 크기 블록 사용)
+    async fn write_node(&self, node: &BTreeNode) -> errors::Result<BTreeNodePosition> {
+        // 1. 노드 인코딩 (락 없이)
+        let block = self.encode_node_block(node)?;
+
         // 2. 오프셋 예약 (락을 잡고 즉시 증가시켜서 다른 스레드가 같은 offset을 받지 못하게 함)
         let (logical_offset, segment_number, segment_offset) = {
             let mut meta_guard = self.metadata.lock().await;
-            let logical_offset = meta_guard.next_offset;
 
-            // 오프셋 즉시 증가 (예약)
-            meta_guard.next_offset += NODE_SIZE as u64;
+            // 비워진 블록이 있으면 먼저 재사용
+            let logical_offset = match meta_guard.free_offsets.pop() {
+                Some(offset) => offset,
+                None => {
+                    let offset = meta_guard.next_offset;
+                    // 오프셋 즉시 증가 (예약)
+                    meta_guard.next_offset += NODE_SIZE as u64;
+                    offset
+                }
+            };
 
             // 세그먼트 정보 계산
             let (seg_num, seg_off) = self.offset_to_segment(logical_offset);
@@ -637,10 +1063,6 @@ impl BTreeIndex {
         };
 
         // 3. 파일 I/O 수행 (락으로 보호하여 seek/write가 원자적으로 실행되도록)
-        let node_size = encoded.len() as u32;
-        let size_bytes = node_size.to_be_bytes();
-
-        // 해당 세그먼트 파일 열기
         let file_handle = self.get_segment_file(segment_number).await?;
         let mut file = file_handle.lock().await;
 
@@ -651,34 +1073,28 @@ impl BTreeIndex {
                     .with_message(format!("Failed to seek to write position: {}", e))
             })?;
 
-        // 크기 쓰기
-        file.write_all(&size_bytes).await.map_err(|e| {
-            errors::Errors::new(ErrorCodes::FileWriteError)
-                .with_message(format!("Failed to write node size: {}", e))
-        })?;
-
-        // 노드 데이터 쓰기
-        file.write_all(&encoded).await.map_err(|e| {
+        file.write_all(&block).await.map_err(|e| {
             errors::Errors::new(ErrorCodes::FileWriteError)
                 .with_message(format!("Failed to write node data: {}", e))
         })?;
 
-        // 나머지 공간을 0으로 패딩 (고정 크기 유지)
-        let padding_size = max_data_size - encoded.len();
-        if padding_size > 0 {
-            let padding = vec![0u8; padding_size];
-            file.write_all(&padding).await.map_err(|e| {
-                errors::Errors::new(ErrorCodes::FileWriteError)
-                    .with_message(format!("Failed to write padding: {}", e))
-            })?;
-        }
-
         // 메타데이터 저장 (next_offset은 이미 증가되어 있음)
         self.save_metadata().await?;
 
         Ok(position)
     }
 
+    /// 트리에서 빠진 노드 블록을 free list에 넣는다
+    async fn free_node(&self, position: BTreeNodePosition) -> errors::Result<()> {
+        self.metadata
+            .lock()
+            .await
+            .free_offsets
+            .push(position.offset);
+
+        self.save_metadata().await
+    }
+
     /// 노드 업데이트 (기존 위치에 in-place 덮어쓰기)
     async fn update_node(
         &self,
@@ -689,34 +1105,7 @@ impl BTreeIndex {
         let (segment_number, segment_offset) = self.offset_to_segment(position.offset);
 
         // 노드 인코딩
-        let encoded = bincode::encode_to_vec(node, bincode::config::standard()).map_err(|e| {
-            errors::Errors::new(ErrorCodes::FileWriteError)
-                .with_message(format!("Failed to encode node: {}", e))
-        })?;
-
-        // 고정 크기 블록 체크
-        let max_data_size = NODE_SIZE - 4;
-        if encoded.len() > max_data_size {
-            log::error!(
-                "[BTree:{}] Update: Node too large: {} > {} at offset={}",
-                self.table_name,
-                encoded.len(),
-                max_data_size,
-                position.offset
-            );
-            return Err(
-                errors::Errors::new(ErrorCodes::FileWriteError).with_message(format!(
-                    "Node size {} exceeds maximum block size {}",
-                    encoded.len(),
-                    max_data_size
-                )),
-            );
-        }
-
-        let node_size = encoded.len() as u32;
-        let size_bytes = node_size.to_be_bytes();
-
-        // 해당 세그먼트 파일 열기
+        let block = self.encode_node_block(node)?;
 
         // 파일 I/O를 락으로 보호
         let file_handle = self.get_segment_file(segment_number).await?;
@@ -729,33 +1118,18 @@ impl BTreeIndex {
                     .with_message(format!("Failed to seek to update position: {}", e))
             })?;
 
-        // 크기 쓰기
-        file.write_all(&size_bytes).await.map_err(|e| {
-            errors::Errors::new(ErrorCodes::FileWriteError)
-                .with_message(format!("Failed to write node size: {}", e))
-        })?;
-
-        // 노드 데이터 쓰기
-        file.write_all(&encoded).await.map_err(|e| {
+        file.write_all(&block).await.map_err(|e| {
             errors::Errors::new(ErrorCodes::FileWriteError)
                 .with_message(format!("Failed to write node data: {}", e))
         })?;
 
-        // 나머지 공간을 0으로 패딩
-        let padding_size = max_data_size - encoded.len();
-        if padding_size > 0 {
-            let padding = vec![0u8; padding_size];
-            file.write_all(&padding).await.map_err(|e| {
-                errors::Errors::new(ErrorCodes::FileWriteError)
-                    .with_message(format!("Failed to write padding: {}", e))
-            })?;
-        }
-
         Ok(())
     }
 
     /// 키를 기반으로 레코드 위치 찾기
     pub async fn find(&self, key: &str) -> errors::Result<Option<TableRecordPosition>> {
+        let _tree_guard = self.tree_lock.read().await;
+
         let meta_guard = self.metadata.lock().await;
         let root_pos = match meta_guard.root_position {
             Some(pos) => pos,
@@ -812,8 +1186,161 @@ impl BTreeIndex {
         }
     }
 
+    /// [start, end) 범위의 엔트리를 키 순서로 최대 limit개 반환 (end가 None이면 상한 없음).
+    /// start가 들어갈 리프까지 내려간 뒤 next를 따라 리프를 순회한다
+    pub async fn range(
+        &self,
+        start: &str,
+        end: Option<&str>,
+        limit: usize,
+    ) -> errors::Result<Vec<(String, TableRecordPosition)>> {
+        let _tree_guard = self.tree_lock.read().await;
+
+        let meta_guard = self.metadata.lock().await;
+        let Some(mut node_pos) = meta_guard.root_position else {
+            return Ok(vec![]);
+        };
+        drop(meta_guard);
+
+        let mut entries = vec![];
+        if limit == 0 || end.is_some_and(|end| start >= end) {
+            return Ok(entries);
+        }
+
+        // 1. start가 속한 리프 찾기
+        let mut node = self.read_node(node_pos).await?;
+        while !node.is_leaf() {
+            let Some(mut child_pos) = node.leftmost_child else {
+                return Err(
+                    errors::Errors::new(ErrorCodes::FileReadError).with_message(format!(
+                        "Internal node at offset {} has no leftmost_child. Index may be corrupted.",
+                        node_pos.offset
+                    )),
+                );
+            };
+
+            for entry in &node.internal_entries {
+                if start < entry.key.as_str() {
+                    break;
+                }
+                child_pos = entry.child_position;
+            }
+
+            node_pos = child_pos;
+            node = self.read_node(node_pos).await?;
+        }
+
+        // 2. 리프 체인을 따라가며 수집
+        loop {
+            for entry in &node.leaf_entries {
+                if entry.key.as_str() < start {
+                    continue;
+                }
+                if end.is_some_and(|end| entry.key.as_str() >= end) {
+                    return Ok(entries);
+                }
+
+                entries.push((entry.key.clone(), entry.position.clone()));
+                if entries.len() >= limit {
+                    return Ok(entries);
+                }
+            }
+
+            match node.next {
+                Some(next_pos) => node = self.read_node(next_pos).await?,
+                None => return Ok(entries),
+            }
+        }
+    }
+
+    /// 접두사가 일치하는 모든 엔트리 찾기 (키 순서)
+    pub async fn find_prefix(
+        &self,
+        prefix: &str,
+    ) -> errors::Result<Vec<(String, TableRecordPosition)>> {
+        let _tree_guard = self.tree_lock.read().await;
+
+        let meta_guard = self.metadata.lock().await;
+        let root_pos = match meta_guard.root_position {
+            Some(pos) => pos,
+            None => {
+                return Ok(vec![]);
+            }
+        };
+        drop(meta_guard);
+
+        let mut entries = vec![];
+        self.find_prefix_in_node(root_pos, prefix, &mut entries)
+            .await?;
+
+        Ok(entries)
+    }
+
+    /// 특정 노드에서 접두사 검색 (재귀적)
+    #[async_recursion]
+    async fn find_prefix_in_node(
+        &self,
+        node_pos: BTreeNodePosition,
+        prefix: &str,
+        entries: &mut Vec<(String, TableRecordPosition)>,
+    ) -> errors::Result<()> {
+        let node = self.read_node(node_pos).await?;
+
+        match node.node_type {
+            BTreeNodeType::Leaf => {
+                for entry in &node.leaf_entries {
+                    if entry.key.starts_with(prefix) {
+                        entries.push((entry.key.clone(), entry.position.clone()));
+                    }
+                }
+                Ok(())
+            }
+            BTreeNodeType::Internal => {
+                let Some(leftmost_child) = node.leftmost_child else {
+                    return Err(errors::Errors::new(ErrorCodes::FileReadError)
+                        .with_message(format!(
+                            "Internal node at offset {} has no leftmost_child. Index may be corrupted.",
+                            node_pos.offset
+                        )));
+                };
+
+                // 각 자식은 [lower, upper) 범위의 키를 가짐
+                let mut children = vec![(None, leftmost_child)];
+                for entry in &node.internal_entries {
+                    children.push((Some(entry.key.as_str()), entry.child_position));
+                }
+
+                for (i, (lower, child_pos)) in children.iter().enumerate() {
+                    let upper = children.get(i + 1).and_then(|(key, _)| *key);
+
+                    // 상한이 접두사보다 작거나 같으면 접두사로 시작하는 키가 없음
+                    if let Some(upper) = upper
+                        && upper <= prefix
+                    {
+                        continue;
+                    }
+
+                    // 하한이 접두사보다 크면서 접두사로 시작하지 않으면 이후 자식도 모두 범위 밖
+                    if let Some(lower) = lower
+                        && *lower > prefix
+                        && !lower.starts_with(prefix)
+                    {
+                        break;
+                    }
+
+                    self.find_prefix_in_node(*child_pos, prefix, entries)
+                        .await?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+
     /// 키-값 삽입
     pub async fn insert(&self, key: String, position: TableRecordPosition) -> errors::Result<()> {
+        let _tree_guard = self.tree_lock.write().await;
+
         let meta_guard = self.metadata.lock().await;
 
         // 루트가 없으면 새로운 리프 노드 생성
@@ -885,11 +1412,18 @@ impl BTreeIndex {
 
         match node.node_type {
             BTreeNodeType::Leaf => {
-                // 리프 노드에 삽입
-                let insert_pos = node
+                // 리프 노드에 삽입. 이미 있는 키는 위치만 교체 (중복 엔트리를 만들지 않음)
+                let insert_pos = match node
                     .leaf_entries
                     .binary_search_by(|entry| entry.key.as_str().cmp(&key))
-                    .unwrap_or_else(|pos| pos);
+                {
+                    Ok(existing_pos) => {
+                        node.leaf_entries[existing_pos].position = position;
+                        self.update_node(node_pos, &node).await?;
+                        return Ok(None);
+                    }
+                    Err(pos) => pos,
+                };
 
                 node.leaf_entries.insert(
                     insert_pos,
@@ -970,19 +1504,50 @@ impl BTreeIndex {
         mut node: BTreeNode,
         _order: u16,
     ) -> errors::Result<Option<(String, BTreeNodePosition)>> {
-        let mid = node.leaf_entries.len() / 2;
+        let mid = size_balanced_split_index(&node.leaf_entries);
         let split_key = node.leaf_entries[mid].key.clone();
 
         let mut new_node = BTreeNode::new_leaf();
         new_node.leaf_entries = node.leaf_entries.split_off(mid);
         new_node.parent = node.parent;
+        new_node.next = node.next;
+
+        // 디스크에 쓰기 전에 양쪽 노드가 블록에 들어가는지 확인
+        self.ensure_split_fits(&node, &new_node)?;
 
+        // 새 노드를 먼저 써야 기존 노드가 가리킬 위치가 정해진다
         let new_node_pos = self.write_node(&new_node).await?;
+        node.next = Some(new_node_pos);
         self.update_node(node_pos, &node).await?;
 
         Ok(Some((split_key, new_node_pos)))
     }
 
+    /// 분할된 두 노드가 모두 블록 크기 안에 들어가는지 확인
+    fn ensure_split_fits(&self, node: &BTreeNode, new_node: &BTreeNode) -> errors::Result<()> {
+        for split_node in [node, new_node] {
+            let size = split_node.encoded_size();
+
+            if size > NODE_MAX_DATA_SIZE {
+                log::error!(
+                    "[BTree:{}] Split node still too large: {} > {} (type={:?})",
+                    self.table_name,
+                    size,
+                    NODE_MAX_DATA_SIZE,
+                    split_node.node_type
+                );
+                return Err(
+                    errors::Errors::new(ErrorCodes::FileWriteError).with_message(format!(
+                        "Split node size {} exceeds maximum block size {}",
+                        size, NODE_MAX_DATA_SIZE
+                    )),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     /// 내부 노드 분할
     async fn split_internal_node(
         &self,
@@ -990,7 +1555,10 @@ impl BTreeIndex {
         mut node: BTreeNode,
         _order: u16,
     ) -> errors::Result<Option<(String, BTreeNodePosition)>> {
-        let mid = node.internal_entries.len() / 2;
+        // mid 엔트리는 부모로 올라가므로 양쪽에 최소 하나씩 남도록 한다
+        let mid = size_balanced_split_index(&node.internal_entries)
+            .min(node.internal_entries.len().saturating_sub(2))
+            .max(1);
         let split_key = node.internal_entries[mid].key.clone();
 
         let mut new_node = BTreeNode::new_internal();
@@ -1013,6 +1581,8 @@ impl BTreeIndex {
         // node.leftmost_child는 이미 설정되어 있으므로 그대로 유지
         // (변경하지 않음)
 
+        self.ensure_split_fits(&node, &new_node)?;
+
         let new_node_pos = self.write_node(&new_node).await?;
 
         // new_node로 이동한 자식 노드들의 parent 포인터 갱신
@@ -1035,26 +1605,48 @@ impl BTreeIndex {
         Ok(Some((split_key, new_node_pos)))
     }
 
-    /// 키 삭제
+    /// 키 삭제. 엔트리가 부족해진 노드는 형제와 합치거나 형제에게서 빌리고, 빠진 노드 블록은 free list로 간다
     pub async fn delete(&self, key: &str) -> errors::Result<()> {
+        let _tree_guard = self.tree_lock.write().await;
+
         let meta_guard = self.metadata.lock().await;
         let root_pos = match meta_guard.root_position {
             Some(pos) => pos,
             None => return Ok(()), // 빈 트리
         };
+        let order = meta_guard.order;
         drop(meta_guard);
 
-        self.delete_from_node(root_pos, key).await?;
+        if !self.delete_from_node(root_pos, key, order).await? {
+            return Ok(());
+        }
+
+        // 자식이 하나만 남은 내부 루트는 그 자식으로 교체 (트리 높이 감소)
+        let root = self.read_node(root_pos).await?;
+        if !root.is_leaf()
+            && root.internal_entries.is_empty()
+            && let Some(child_pos) = root.leftmost_child
+        {
+            let mut child = self.read_node(child_pos).await?;
+            child.parent = None;
+            self.update_node(child_pos, &child).await?;
+
+            self.metadata.lock().await.root_position = Some(child_pos);
+            self.save_metadata().await?;
+
+            self.free_node(root_pos).await?;
+        }
 
         Ok(())
     }
 
-    /// 노드에서 삭제 (재귀적)
+    /// 노드에서 삭제 (재귀적). 삭제했으면 true
     #[async_recursion]
     async fn delete_from_node(
         &self,
         node_pos: BTreeNodePosition,
         key: &str,
+        order: u16,
     ) -> errors::Result<bool> {
         let mut node = self.read_node(node_pos).await?;
 
@@ -1071,29 +1663,202 @@ impl BTreeIndex {
             }
             BTreeNodeType::Internal => {
                 // 내부 노드는 반드시 leftmost_child를 가져야 함
-                if node.leftmost_child.is_none() {
+                let Some(mut child_pos) = node.leftmost_child else {
                     return Err(errors::Errors::new(ErrorCodes::FileReadError)
                         .with_message(format!(
                             "Internal node at offset {} has no leftmost_child. Index may be corrupted.",
                             node_pos.offset
                         )));
-                }
+                };
 
                 // 적절한 자식 노드 찾기
-                let mut child_pos = node.leftmost_child.unwrap();
-
-                for entry in &node.internal_entries {
+                let mut child_index = 0;
+                for (i, entry) in node.internal_entries.iter().enumerate() {
                     if key < entry.key.as_str() {
                         break;
                     }
                     child_pos = entry.child_position;
+                    child_index = i + 1;
+                }
+
+                if !self.delete_from_node(child_pos, key, order).await? {
+                    return Ok(false);
                 }
 
-                self.delete_from_node(child_pos, key).await
+                let child = self.read_node(child_pos).await?;
+                if child.is_underfull(order)
+                    && self.rebalance_child(&mut node, child_index, order).await?
+                {
+                    self.update_node(node_pos, &node).await?;
+                }
+
+                Ok(true)
             }
         }
     }
 
+    /// 엔트리가 부족한 자식을 옆 형제와 합치고, 합친 노드가 가득 차면 형제의 엔트리 하나를 빌려온다.
+    /// 부모(node)의 구분 키가 바뀌었으면 true (부모는 호출한 쪽이 기록)
+    async fn rebalance_child(
+        &self,
+        parent: &mut BTreeNode,
+        child_index: usize,
+        order: u16,
+    ) -> errors::Result<bool> {
+        // 형제가 없으면 (자식 하나뿐인 루트) 루트 교체에서 처리
+        if parent.internal_entries.is_empty() {
+            return Ok(false);
+        }
+
+        let (left_index, right_index) = if child_index > 0 {
+            (child_index - 1, child_index)
+        } else {
+            (0, 1)
+        };
+        let (Some(left_pos), Some(right_pos)) =
+            (parent.child_at(left_index), parent.child_at(right_index))
+        else {
+            return Ok(false);
+        };
+
+        let mut left = self.read_node(left_pos).await?;
+        let mut right = self.read_node(right_pos).await?;
+        let separator_index = right_index - 1;
+        let separator = parent.internal_entries[separator_index].key.clone();
+
+        let missing_child = || {
+            errors::Errors::new(ErrorCodes::FileReadError).with_message(format!(
+                "Internal node at offset {} has no leftmost_child. Index may be corrupted.",
+                right_pos.offset
+            ))
+        };
+
+        // 1. 합치기: 오른쪽 노드를 왼쪽에 붙이고 블록을 반환
+        let mut merged = left.clone();
+        let mut moved_children = vec![];
+        if merged.is_leaf() {
+            merged
+                .leaf_entries
+                .extend(right.leaf_entries.iter().cloned());
+            merged.next = right.next;
+        } else {
+            let right_leftmost = right.leftmost_child.ok_or_else(missing_child)?;
+            merged.internal_entries.push(BTreeInternalEntry {
+                key: separator.clone(),
+                child_position: right_leftmost,
+            });
+            merged
+                .internal_entries
+                .extend(right.internal_entries.iter().cloned());
+
+            moved_children.push(right_leftmost);
+            moved_children.extend(right.internal_entries.iter().map(|e| e.child_position));
+        }
+
+        if !merged.is_full(order) {
+            for child_pos in moved_children {
+                self.set_parent(child_pos, left_pos).await?;
+            }
+
+            self.update_node(left_pos, &merged).await?;
+            parent.internal_entries.remove(separator_index);
+            self.free_node(right_pos).await?;
+
+            return Ok(true);
+        }
+
+        // 2. 빌리기: 부족한 쪽이 왼쪽이면 오른쪽의 첫 엔트리, 오른쪽이면 왼쪽의 마지막 엔트리
+        let borrow_from_right = child_index == left_index;
+        let mut moved_child = None;
+        let new_separator = if left.is_leaf() {
+            if borrow_from_right {
+                if right.leaf_entries.len() < 2 {
+                    return Ok(false);
+                }
+                left.leaf_entries.push(right.leaf_entries.remove(0));
+            } else {
+                if left.leaf_entries.len() < 2 {
+                    return Ok(false);
+                }
+                let Some(last) = left.leaf_entries.pop() else {
+                    return Ok(false);
+                };
+                right.leaf_entries.insert(0, last);
+            }
+
+            right.leaf_entries[0].key.clone()
+        } else if borrow_from_right {
+            if right.internal_entries.len() < 2 {
+                return Ok(false);
+            }
+            let first = right.internal_entries.remove(0);
+            let child_pos = right
+                .leftmost_child
+                .replace(first.child_position)
+                .ok_or_else(missing_child)?;
+            left.internal_entries.push(BTreeInternalEntry {
+                key: separator,
+                child_position: child_pos,
+            });
+            moved_child = Some((child_pos, left_pos));
+
+            first.key
+        } else {
+            if left.internal_entries.len() < 2 {
+                return Ok(false);
+            }
+            let Some(last) = left.internal_entries.pop() else {
+                return Ok(false);
+            };
+            let child_pos = right
+                .leftmost_child
+                .replace(last.child_position)
+                .ok_or_else(missing_child)?;
+            right.internal_entries.insert(
+                0,
+                BTreeInternalEntry {
+                    key: separator,
+                    child_position: child_pos,
+                },
+            );
+            moved_child = Some((last.child_position, right_pos));
+
+            last.key
+        };
+
+        // 긴 키 때문에 블록을 넘으면 그대로 둔다
+        let old_separator = std::mem::replace(
+            &mut parent.internal_entries[separator_index].key,
+            new_separator,
+        );
+        if [&left, &right, &*parent]
+            .iter()
+            .any(|node| node.encoded_size() > NODE_MAX_DATA_SIZE)
+        {
+            parent.internal_entries[separator_index].key = old_separator;
+            return Ok(false);
+        }
+
+        if let Some((child_pos, parent_pos)) = moved_child {
+            self.set_parent(child_pos, parent_pos).await?;
+        }
+        self.update_node(left_pos, &left).await?;
+        self.update_node(right_pos, &right).await?;
+
+        Ok(true)
+    }
+
+    /// 노드의 parent 포인터 갱신
+    async fn set_parent(
+        &self,
+        node_pos: BTreeNodePosition,
+        parent_pos: BTreeNodePosition,
+    ) -> errors::Result<()> {
+        let mut node = self.read_node(node_pos).await?;
+        node.parent = Some(parent_pos);
+        self.update_node(node_pos, &node).await
+    }
+
     /// 키 업데이트 (삭제 후 삽입)
     pub async fn update(&self, key: String, position: TableRecordPosition) -> errors::Result<()> {
         self.delete(&key).await?;
@@ -1101,3 +1866,422 @@ impl BTreeIndex {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BTREE_FORMAT_VERSION, BTreeIndex, BTreeMetadataV0, BTreeNodeV0, NODE_CHECKSUM_FLAG,
+        NODE_HEADER_SIZE, NODE_SIZE,
+    };
+    use crate::{
+        checksum::crc32,
+        config::{TABLES_DIRECTORY, TABLES_INDEX_DIRECTORY},
+        disktable::segment::{position::TableRecordPosition, segment_id::TableSegmentID},
+        errors::ErrorCodes,
+    };
+
+    #[tokio::test]
+    async fn test_read_node_detects_checksum_mismatch() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_btree_checksum_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+        let index_directory = base_path
+            .join(TABLES_DIRECTORY)
+            .join("items")
+            .join(TABLES_INDEX_DIRECTORY);
+        std::fs::create_dir_all(&index_directory).unwrap();
+
+        let index = BTreeIndex::new(base_path.clone(), "items".to_string());
+        index.initialize().await.unwrap();
+
+        for i in 0..10 {
+            let position = TableRecordPosition {
+                segment_id: TableSegmentID::new(1),
+                offset: i * 100,
+            };
+            index.insert(format!("key{}", i), position).await.unwrap();
+        }
+        assert!(index.find("key3").await.unwrap().is_some());
+
+        // 루트 노드 데이터의 한 바이트를 변조 (torn write 재현)
+        let root_offset = index.metadata.lock().await.root_position.unwrap().offset;
+        let index_file_path = index.index_file_path(0);
+        let mut bytes = std::fs::read(&index_file_path).unwrap();
+        bytes[root_offset as usize + NODE_HEADER_SIZE + 4] ^= 0xFF;
+        std::fs::write(&index_file_path, &bytes).unwrap();
+
+        let error = index.find("key3").await.unwrap_err();
+        assert!(matches!(error.error_code, ErrorCodes::FileReadError));
+        assert!(error.message.unwrap().contains("checksum mismatch"));
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_insert_long_keys_splits_by_node_size() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_btree_long_keys_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+        let index_directory = base_path
+            .join(TABLES_DIRECTORY)
+            .join("items")
+            .join(TABLES_INDEX_DIRECTORY);
+        std::fs::create_dir_all(&index_directory).unwrap();
+
+        let index = BTreeIndex::new(base_path.clone(), "items".to_string());
+        index.initialize().await.unwrap();
+
+        // 1000바이트 키 63개는 8KB 노드에 들어가지 않음
+        let long_key = |i: u32| format!("{:04}{}", i, "k".repeat(996));
+
+        for i in 0..300 {
+            let position = TableRecordPosition {
+                segment_id: TableSegmentID::new(1),
+                offset: i,
+            };
+            index.insert(long_key(i), position).await.unwrap();
+        }
+
+        for i in 0..300 {
+            let position = index.find(&long_key(i)).await.unwrap().unwrap();
+            assert_eq!(position.offset, i);
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_insert_mixed_key_sizes_splits_by_bytes() {
+        let base_path = std::env::temp_dir().join(format!(
+            "barus_test_btree_mixed_keys_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base_path);
+        let index_directory = base_path
+            .join(TABLES_DIRECTORY)
+            .join("items")
+            .join(TABLES_INDEX_DIRECTORY);
+        std::fs::create_dir_all(&index_directory).unwrap();
+
+        let index = BTreeIndex::new(base_path.clone(), "items".to_string());
+        index.initialize().await.unwrap();
+
+        // 작은 키 54개 뒤에 긴 키 8개가 몰려 있으면, 엔트리 수 기준 중앙 분할 시 오른쪽 노드가 8KB를 넘음
+        let mut keys: Vec<String> = (0..54).map(|i| format!("a{:04}", i)).collect();
+        keys.extend((0..8).map(|i| format!("z{:04}{}", i, "k".repeat(995))));
+
+        for (i, key) in keys.iter().enumerate() {
+            let position = TableRecordPosition {
+                segment_id: TableSegmentID::new(1),
+                offset: i as u32,
+            };
+            index.insert(key.clone(), position).await.unwrap();
+        }
+
+        for (i, key) in keys.iter().enumerate() {
+            let position = index.find(key).await.unwrap().unwrap();
+            assert_eq!(position.offset, i as u32);
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_range_walks_leaf_chain() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_btree_range_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+        std::fs::create_dir_all(
+            base_path
+                .join(TABLES_DIRECTORY)
+                .join("items")
+                .join(TABLES_INDEX_DIRECTORY),
+        )
+        .unwrap();
+
+        let index = BTreeIndex::new(base_path.clone(), "items".to_string());
+        index.initialize().await.unwrap();
+
+        // 역순으로 넣어서 여러 리프로 분할되게 함
+        for i in (0..500u32).rev() {
+            let position = TableRecordPosition {
+                segment_id: TableSegmentID::new(1),
+                offset: i,
+            };
+            index
+                .insert(format!("key{:04}", i), position)
+                .await
+                .unwrap();
+        }
+
+        let entries = index.range("key0100", Some("key0300"), 1000).await.unwrap();
+        assert_eq!(entries.len(), 200);
+        assert!(
+            entries
+                .iter()
+                .enumerate()
+                .all(|(i, (key, position))| *key == format!("key{:04}", i + 100)
+                    && position.offset == i as u32 + 100)
+        );
+
+        let entries = index.range("key0250x", Some("z"), 3).await.unwrap();
+        let keys: Vec<_> = entries.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["key0251", "key0252", "key0253"]);
+
+        // 상한 없음
+        let entries = index.range("key0497", None, 10).await.unwrap();
+        let keys: Vec<_> = entries.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["key0497", "key0498", "key0499"]);
+
+        assert!(
+            index
+                .range("key0300", Some("key0100"), 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_insert_existing_key_replaces_position() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_btree_reinsert_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+        std::fs::create_dir_all(
+            base_path
+                .join(TABLES_DIRECTORY)
+                .join("items")
+                .join(TABLES_INDEX_DIRECTORY),
+        )
+        .unwrap();
+
+        let index = BTreeIndex::new(base_path.clone(), "items".to_string());
+        index.initialize().await.unwrap();
+
+        // 여러 리프로 분할된 뒤 같은 키를 다시 삽입
+        for i in 0..300u32 {
+            let position = TableRecordPosition {
+                segment_id: TableSegmentID::new(1),
+                offset: i,
+            };
+            index
+                .insert(format!("key{:04}", i), position)
+                .await
+                .unwrap();
+        }
+        let new_position = TableRecordPosition {
+            segment_id: TableSegmentID::new(2),
+            offset: 7,
+        };
+        index
+            .insert("key0150".to_string(), new_position.clone())
+            .await
+            .unwrap();
+
+        let found = index.find("key0150").await.unwrap().unwrap();
+        assert_eq!(found.segment_id, new_position.segment_id);
+        assert_eq!(found.offset, new_position.offset);
+
+        let entries = index.range("key0149", Some("key0152"), 10).await.unwrap();
+        let keys: Vec<_> = entries.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["key0149", "key0150", "key0151"]);
+        assert_eq!(entries[1].1.segment_id, new_position.segment_id);
+        assert_eq!(entries[1].1.offset, new_position.offset);
+
+        let entries = index.range("", None, 1000).await.unwrap();
+        assert_eq!(entries.len(), 300);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_v0_index_links_leaves() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_btree_migrate_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+        std::fs::create_dir_all(
+            base_path
+                .join(TABLES_DIRECTORY)
+                .join("items")
+                .join(TABLES_INDEX_DIRECTORY),
+        )
+        .unwrap();
+
+        let index = BTreeIndex::new(base_path.clone(), "items".to_string());
+        index.initialize().await.unwrap();
+        for i in 0..300u32 {
+            let position = TableRecordPosition {
+                segment_id: TableSegmentID::new(1),
+                offset: i,
+            };
+            index
+                .insert(format!("key{:04}", i), position)
+                .await
+                .unwrap();
+        }
+
+        // 모든 노드와 메타데이터를 버전 0 포맷으로 다시 씀
+        let metadata = index.metadata.lock().await.clone();
+        let index_file_path = index.index_file_path(0);
+        let mut bytes = std::fs::read(&index_file_path).unwrap();
+        for offset in (0..metadata.next_offset).step_by(NODE_SIZE) {
+            let node = index
+                .read_node(super::BTreeNodePosition { offset })
+                .await
+                .unwrap();
+            let encoded = bincode::encode_to_vec(
+                BTreeNodeV0 {
+                    node_type: node.node_type,
+                    parent: node.parent,
+                    leaf_entries: node.leaf_entries,
+                    internal_entries: node.internal_entries,
+                    leftmost_child: node.leftmost_child,
+                },
+                bincode::config::standard(),
+            )
+            .unwrap();
+
+            let mut block = vec![];
+            block.extend_from_slice(&(encoded.len() as u32 | NODE_CHECKSUM_FLAG).to_be_bytes());
+            block.extend_from_slice(&crc32(&encoded).to_be_bytes());
+            block.extend_from_slice(&encoded);
+            block.resize(NODE_SIZE, 0);
+            bytes[offset as usize..offset as usize + NODE_SIZE].copy_from_slice(&block);
+        }
+        std::fs::write(&index_file_path, &bytes).unwrap();
+        std::fs::write(
+            index.metadata_file_path(),
+            bincode::encode_to_vec(
+                BTreeMetadataV0 {
+                    root_position: metadata.root_position,
+                    order: metadata.order,
+                    next_offset: metadata.next_offset,
+                },
+                bincode::config::standard(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        drop(index);
+
+        let index = BTreeIndex::new(base_path.clone(), "items".to_string());
+        index.initialize().await.unwrap();
+        assert_eq!(index.metadata.lock().await.version, BTREE_FORMAT_VERSION);
+
+        let entries = index.range("key0000", Some("key9999"), 1000).await.unwrap();
+        assert_eq!(entries.len(), 300);
+        assert_eq!(index.find("key0123").await.unwrap().unwrap().offset, 123);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_delete_all_keys_reuses_blocks() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_btree_delete_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+        std::fs::create_dir_all(
+            base_path
+                .join(TABLES_DIRECTORY)
+                .join("items")
+                .join(TABLES_INDEX_DIRECTORY),
+        )
+        .unwrap();
+
+        let index = BTreeIndex::new(base_path.clone(), "items".to_string());
+        index.initialize().await.unwrap();
+        // 작은 차수로 여러 단계의 내부 노드를 만든다
+        index.metadata.lock().await.order = 8;
+
+        let position = |i: u32| TableRecordPosition {
+            segment_id: TableSegmentID::new(1),
+            offset: i,
+        };
+        let key = |i: u32| format!("key{:04}", (i * 37) % 500);
+
+        let mut next_offset = 0;
+        for round in 0..3 {
+            for i in 0..500 {
+                index.insert(key(i), position(i)).await.unwrap();
+            }
+            assert_eq!(index.range("", Some("z"), 1000).await.unwrap().len(), 500);
+
+            if round == 0 {
+                next_offset = index.metadata.lock().await.next_offset;
+            }
+            // 비운 블록을 다시 쓰므로 파일이 커지지 않음
+            assert!(index.metadata.lock().await.next_offset <= next_offset);
+            let file_size = std::fs::metadata(index.index_file_path(0)).unwrap().len();
+            assert!(file_size <= next_offset);
+
+            // 절반을 지운 뒤에도 나머지는 모두 찾고 순서대로 읽힘
+            for i in (0..500).step_by(2) {
+                index.delete(&key(i)).await.unwrap();
+            }
+            for i in 0..500 {
+                assert_eq!(index.find(&key(i)).await.unwrap().is_some(), i % 2 == 1);
+            }
+            let entries = index.range("", Some("z"), 1000).await.unwrap();
+            assert_eq!(entries.len(), 250);
+            assert!(entries.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+            for i in (1..500).step_by(2) {
+                index.delete(&key(i)).await.unwrap();
+            }
+            assert!(index.range("", Some("z"), 1000).await.unwrap().is_empty());
+
+            // 루트 리프 하나만 남음
+            let metadata = index.metadata.lock().await.clone();
+            assert_eq!(
+                metadata.free_offsets.len() as u64 + 1,
+                metadata.next_offset / NODE_SIZE as u64
+            );
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_rejects_invalid_free_list() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_btree_free_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+        std::fs::create_dir_all(
+            base_path
+                .join(TABLES_DIRECTORY)
+                .join("items")
+                .join(TABLES_INDEX_DIRECTORY),
+        )
+        .unwrap();
+
+        let index = BTreeIndex::new(base_path.clone(), "items".to_string());
+        index.initialize().await.unwrap();
+        let position = TableRecordPosition {
+            segment_id: TableSegmentID::new(1),
+            offset: 0,
+        };
+        index.insert("key".to_string(), position).await.unwrap();
+
+        // 루트 블록이 free list에 있으면 다음 write_node가 루트를 덮어씀
+        let root_offset = {
+            let mut metadata = index.metadata.lock().await;
+            let root_offset = metadata.root_position.unwrap().offset;
+            metadata.free_offsets.push(root_offset);
+            root_offset
+        };
+        index.save_metadata().await.unwrap();
+        drop(index);
+
+        let index = BTreeIndex::new(base_path.clone(), "items".to_string());
+        assert!(index.check().await.is_err());
+        index.initialize().await.unwrap();
+        // 손상으로 보고 인덱스를 다시 만듦
+        let metadata = index.metadata.lock().await.clone();
+        assert!(!metadata.free_offsets.contains(&root_offset));
+        assert!(metadata.root_position.is_none());
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+}
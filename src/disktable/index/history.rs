@@ -0,0 +1,38 @@
+// Prior versions of keys in tables with keep_versions > 0.
+// An overwritten or deleted record stays in its segment with the Deleted flag,
+// and this index keeps its position under "{key}\0{version}" so it can still be read.
+// The version is zero-padded, so the entries of a key are ordered oldest first.
+
+const HISTORY_KEY_SEPARATOR: char = '\0';
+
+// Index file name of the history index ({file_name}.btree, {file_name}.metadata)
+pub const HISTORY_INDEX_FILE_NAME: &str = "history";
+
+// Prefix shared by all prior versions of the key
+pub fn history_key_prefix(key: &str) -> String {
+    format!("{}{}", key, HISTORY_KEY_SEPARATOR)
+}
+
+pub fn make_history_key(key: &str, version: u64) -> String {
+    format!("{}{:020}", history_key_prefix(key), version)
+}
+
+// Returns the (primary key, version) parts of a history index key
+pub fn parse_history_key(history_key: &str) -> Option<(&str, u64)> {
+    let (key, version) = history_key.rsplit_once(HISTORY_KEY_SEPARATOR)?;
+    Some((key, version.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{history_key_prefix, make_history_key, parse_history_key};
+
+    #[test]
+    fn test_history_key() {
+        let key = make_history_key("user:1", 42);
+
+        assert!(key.starts_with(&history_key_prefix("user:1")));
+        assert!(key < make_history_key("user:1", 100));
+        assert_eq!(parse_history_key(&key), Some(("user:1", 42)));
+    }
+}
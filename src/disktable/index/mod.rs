@@ -1,19 +1,64 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
 use tokio::sync::Mutex;
 
 use crate::{
-    config::TABLES_DIRECTORY,
-    disktable::segment::position::TableRecordPosition,
+    config::{TABLES_DIRECTORY, TABLES_INDEX_DIRECTORY},
+    disktable::{
+        segment::{
+            ScanSegmentFileResult, TableSegmentManager, position::TableRecordPosition,
+            record::RecordStateFlags,
+        },
+        table::SecondaryIndexInfo,
+    },
     errors::{self, ErrorCodes},
+    os::sync_file,
 };
 
+pub mod bloom;
 pub mod btree;
+pub mod history;
+pub mod secondary;
+
+// append order of a scanned record
+fn record_order(item: &ScanSegmentFileResult) -> (u64, u32) {
+    (item.position.segment_id.0, item.position.offset)
+}
+
+#[derive(Debug, Default)]
+struct BloomFilterCounters {
+    checks: AtomicU64,
+    negatives: AtomicU64,
+    false_positives: AtomicU64,
+}
+
+// bloom filter lookups since startup
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BloomFilterStats {
+    pub checks: u64,
+    pub negatives: u64,
+    pub false_positives: u64,
+    // false positives / keys absent from the index
+    pub false_positive_rate: f64,
+}
 
 #[derive(Debug, Clone)]
 pub struct IndexManager {
     base_path: PathBuf,
     indices: Arc<Mutex<HashMap<String, Arc<btree::BTreeIndex>>>>,
+    // None: the table has an index built before bloom filters existed. rebuilt by reindex
+    bloom_filters: Arc<Mutex<HashMap<String, Option<bloom::BloomFilter>>>>,
+    bloom_filter_counters: Arc<BloomFilterCounters>,
+    // tables whose primary index was created empty because its files were missing or corrupt.
+    // taken by open_index, which tells the caller to rebuild it from the segment files
+    recreated_indexes: Arc<Mutex<HashSet<String>>>,
 }
 
 impl IndexManager {
@@ -21,9 +66,19 @@ impl IndexManager {
         Self {
             base_path,
             indices: Arc::new(Mutex::new(HashMap::new())),
+            bloom_filters: Arc::new(Mutex::new(HashMap::new())),
+            bloom_filter_counters: Arc::new(BloomFilterCounters::default()),
+            recreated_indexes: Arc::new(Mutex::new(HashSet::new())),
         }
     }
 
+    fn index_directory(&self, table_name: &str) -> PathBuf {
+        self.base_path
+            .join(TABLES_DIRECTORY)
+            .join(table_name)
+            .join(TABLES_INDEX_DIRECTORY)
+    }
+
     // delete index file and remove from in-memory map
     pub async fn delete_index(&self, table_name: &str) -> errors::Result<()> {
         // 1. remove all file
@@ -45,10 +100,159 @@ impl IndexManager {
         // 2. remove from in-memory map
         let mut indices = self.indices.lock().await;
         indices.remove(table_name);
+        let secondary_prefix = format!("{}/", table_name);
+        indices.retain(|name, _| !name.starts_with(&secondary_prefix));
+        drop(indices);
+
+        self.bloom_filters.lock().await.remove(table_name);
+        self.recreated_indexes.lock().await.remove(table_name);
 
         Ok(())
     }
 
+    // drop the index and rebuild it from all Alive records in segment files.
+    // returns the number of indexed records
+    pub async fn rebuild(
+        &self,
+        table_name: &str,
+        segment_manager: &TableSegmentManager,
+        secondary_indexes: &[SecondaryIndexInfo],
+        keep_versions: u32,
+    ) -> errors::Result<usize> {
+        // 1. drop existing index files
+        self.delete_index(table_name).await?;
+
+        // 2. scan all segment files and keep the most recent alive record per key.
+        //    a crash can leave several Alive copies of a key; the latest append (segment_id, offset) wins.
+        let mut latest_records: HashMap<String, ScanSegmentFileResult> = HashMap::new();
+        // deleted records are prior versions of their key (keep_versions > 0)
+        let mut deleted_records: HashMap<String, Vec<ScanSegmentFileResult>> = HashMap::new();
+
+        for segment_file in segment_manager.list_segment_files(table_name).await? {
+            let scan_items = segment_manager
+                .scan_segment_file(table_name, &segment_file.file_name)
+                .await?;
+
+            for item in scan_items {
+                if !matches!(item.state_flags, RecordStateFlags::Alive) {
+                    if keep_versions > 0 && matches!(item.state_flags, RecordStateFlags::Deleted) {
+                        deleted_records
+                            .entry(item.payload.key.clone())
+                            .or_default()
+                            .push(item);
+                    }
+                    continue;
+                }
+
+                let stale = match latest_records.get(&item.payload.key) {
+                    Some(existing) if record_order(existing) > record_order(&item) => item,
+                    _ => match latest_records.insert(item.payload.key.clone(), item) {
+                        Some(previous) => previous,
+                        None => continue,
+                    },
+                };
+
+                // 3. tombstone older duplicates
+                log::warn!(
+                    "Duplicate alive record for key '{}' in table '{}'. Marking older copy as deleted",
+                    stale.payload.key,
+                    table_name
+                );
+                segment_manager
+                    .mark_deleted_record(table_name, stale.position)
+                    .await?;
+            }
+        }
+
+        // 4. re-insert alive records
+        let indexed_count = latest_records.len();
+
+        for item in latest_records.values() {
+            let key = &item.payload.key;
+            self.add_record(table_name, key, &item.position).await?;
+
+            for index in secondary_indexes {
+                if let Some(field_value) =
+                    secondary::extract_json_field(&item.payload.value, &index.json_path)
+                {
+                    self.add_secondary_record(
+                        table_name,
+                        &index.name,
+                        &field_value,
+                        key,
+                        &item.position,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        // 5. re-insert the newest keep_versions prior versions of each key
+        for (key, mut records) in deleted_records {
+            let current_version = latest_records.get(&key).map(|item| item.payload.version);
+            records.retain(|item| {
+                current_version.is_none_or(|current| item.payload.version < current)
+            });
+            records.sort_by_key(|item| std::cmp::Reverse(item.payload.version));
+            records.dedup_by_key(|item| item.payload.version);
+
+            for item in records.iter().take(keep_versions as usize) {
+                self.add_history_record(table_name, &key, item.payload.version, &item.position)
+                    .await?;
+            }
+        }
+
+        self.save_bloom_filter(table_name).await?;
+        self.recreated_indexes.lock().await.remove(table_name);
+
+        log::info!(
+            "Index rebuilt for table '{}': {} records",
+            table_name,
+            indexed_count
+        );
+
+        Ok(indexed_count)
+    }
+
+    // check the primary and secondary index files of a table without opening or repairing them
+    pub async fn check_indexes(
+        &self,
+        table_name: &str,
+        secondary_indexes: &[SecondaryIndexInfo],
+    ) -> errors::Result<()> {
+        btree::BTreeIndex::new(self.base_path.clone(), table_name.to_string())
+            .check()
+            .await?;
+
+        btree::BTreeIndex::with_file_name(
+            self.base_path.clone(),
+            table_name.to_string(),
+            history::HISTORY_INDEX_FILE_NAME.to_string(),
+        )
+        .check()
+        .await?;
+
+        for secondary_index in secondary_indexes {
+            btree::BTreeIndex::with_file_name(
+                self.base_path.clone(),
+                table_name.to_string(),
+                secondary::secondary_index_file_name(&secondary_index.name),
+            )
+            .check()
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    // open the primary index of the table. true if it was created empty since the last call or rebuild
+    // (files missing or corrupt), so its records have to be re-inserted from the segment files
+    pub async fn open_index(&self, table_name: &str) -> errors::Result<bool> {
+        self.get_or_create_index(table_name).await?;
+
+        Ok(self.recreated_indexes.lock().await.remove(table_name))
+    }
+
     /// 테이블의 인덱스 가져오기 또는 생성
     async fn get_or_create_index(
         &self,
@@ -65,23 +269,246 @@ impl IndexManager {
             self.base_path.clone(),
             table_name.to_string(),
         ));
-        index.initialize().await?;
+        if index.initialize().await? {
+            self.recreated_indexes
+                .lock()
+                .await
+                .insert(table_name.to_string());
+        }
 
         indices.insert(table_name.to_string(), index.clone());
 
         Ok(index)
     }
 
+    /// 테이블의 보조 인덱스 가져오기 또는 생성
+    async fn get_or_create_secondary_index(
+        &self,
+        table_name: &str,
+        index_name: &str,
+    ) -> errors::Result<Arc<btree::BTreeIndex>> {
+        self.get_or_create_index_file(
+            table_name,
+            format!("{}/{}", table_name, index_name),
+            secondary::secondary_index_file_name(index_name),
+        )
+        .await
+    }
+
+    /// 테이블의 history 인덱스 가져오기 또는 생성
+    async fn get_or_create_history_index(
+        &self,
+        table_name: &str,
+    ) -> errors::Result<Arc<btree::BTreeIndex>> {
+        // 보조 인덱스 이름에는 '#'이 들어갈 수 없음
+        self.get_or_create_index_file(
+            table_name,
+            format!("{}/#history", table_name),
+            history::HISTORY_INDEX_FILE_NAME.to_string(),
+        )
+        .await
+    }
+
+    async fn get_or_create_index_file(
+        &self,
+        table_name: &str,
+        map_key: String,
+        file_name: String,
+    ) -> errors::Result<Arc<btree::BTreeIndex>> {
+        let mut indices = self.indices.lock().await;
+
+        if let Some(index) = indices.get(&map_key) {
+            return Ok(index.clone());
+        }
+
+        let index = Arc::new(btree::BTreeIndex::with_file_name(
+            self.base_path.clone(),
+            table_name.to_string(),
+            file_name,
+        ));
+        index.initialize().await?;
+
+        indices.insert(map_key, index.clone());
+
+        Ok(index)
+    }
+
+    /// 테이블의 bloom filter 로드
+    /// 파일이 없는데 primary 인덱스가 이미 있으면 filter 도입 이전 테이블이므로 사용하지 않음 (None)
+    pub async fn load_bloom_filter(&self, table_name: &str) -> errors::Result<()> {
+        let mut bloom_filters = self.bloom_filters.lock().await;
+
+        if !bloom_filters.contains_key(table_name) {
+            let filter = self.read_bloom_filter(table_name).await?;
+            bloom_filters.insert(table_name.to_string(), filter);
+        }
+
+        Ok(())
+    }
+
+    async fn read_bloom_filter(
+        &self,
+        table_name: &str,
+    ) -> errors::Result<Option<bloom::BloomFilter>> {
+        let index_directory = self.index_directory(table_name);
+        let filter_path = index_directory.join(bloom::BLOOM_FILTER_FILE_NAME);
+
+        match tokio::fs::read(&filter_path).await {
+            Ok(bytes) => {
+                let filter = bloom::BloomFilter::decode(&bytes);
+                if filter.is_none() {
+                    log::warn!(
+                        "Bloom filter of table '{}' is corrupted. Disabled until reindex",
+                        table_name
+                    );
+                }
+                Ok(filter)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let primary_index_exists = index_directory
+                    .join(format!("{}.metadata", btree::PRIMARY_INDEX_FILE_NAME))
+                    .exists();
+
+                if primary_index_exists {
+                    log::warn!(
+                        "Bloom filter of table '{}' not found. Disabled until reindex",
+                        table_name
+                    );
+                    Ok(None)
+                } else {
+                    Ok(Some(bloom::BloomFilter::new()))
+                }
+            }
+            Err(e) => Err(errors::Errors::new(ErrorCodes::FileReadError)
+                .with_message(format!("Failed to read bloom filter file: {}", e))),
+        }
+    }
+
+    /// primary/보조 인덱스 파일과 bloom filter 파일을 sync_data. sync한 파일 수를 반환
+    pub async fn sync_table(&self, table_name: &str) -> errors::Result<usize> {
+        let secondary_prefix = format!("{}/", table_name);
+        let indices: Vec<_> = self
+            .indices
+            .lock()
+            .await
+            .iter()
+            .filter(|(name, _)| *name == table_name || name.starts_with(&secondary_prefix))
+            .map(|(_, index)| index.clone())
+            .collect();
+
+        let mut synced_files = 0;
+        for index in indices {
+            synced_files += index.sync().await?;
+        }
+
+        let filter_path = self
+            .index_directory(table_name)
+            .join(bloom::BLOOM_FILTER_FILE_NAME);
+        if filter_path.exists() {
+            sync_file(&filter_path).await?;
+            synced_files += 1;
+        }
+
+        Ok(synced_files)
+    }
+
+    /// bloom filter를 파일에 저장 (flush 후 호출)
+    pub async fn save_bloom_filter(&self, table_name: &str) -> errors::Result<()> {
+        self.load_bloom_filter(table_name).await?;
+
+        let bloom_filters = self.bloom_filters.lock().await;
+        let Some(Some(filter)) = bloom_filters.get(table_name) else {
+            return Ok(());
+        };
+
+        let index_directory = self.index_directory(table_name);
+        tokio::fs::create_dir_all(&index_directory)
+            .await
+            .map_err(|e| {
+                errors::Errors::new(ErrorCodes::FileWriteError)
+                    .with_message(format!("Failed to create index directory: {}", e))
+            })?;
+
+        // 임시 파일에 쓰고 rename해서 중간에 죽어도 이전 filter가 남도록 함
+        let filter_path = index_directory.join(bloom::BLOOM_FILTER_FILE_NAME);
+        let temp_path = filter_path.with_extension("tmp");
+
+        tokio::fs::write(&temp_path, filter.encode())
+            .await
+            .map_err(|e| {
+                errors::Errors::new(ErrorCodes::FileWriteError)
+                    .with_message(format!("Failed to write bloom filter file: {}", e))
+            })?;
+        tokio::fs::rename(&temp_path, &filter_path)
+            .await
+            .map_err(|e| {
+                errors::Errors::new(ErrorCodes::FileWriteError)
+                    .with_message(format!("Failed to replace bloom filter file: {}", e))
+            })?;
+
+        Ok(())
+    }
+
+    pub fn bloom_filter_stats(&self) -> BloomFilterStats {
+        let checks = self.bloom_filter_counters.checks.load(Ordering::Relaxed);
+        let negatives = self.bloom_filter_counters.negatives.load(Ordering::Relaxed);
+        let false_positives = self
+            .bloom_filter_counters
+            .false_positives
+            .load(Ordering::Relaxed);
+
+        let absent_count = negatives + false_positives;
+        let false_positive_rate = if absent_count == 0 {
+            0.0
+        } else {
+            false_positives as f64 / absent_count as f64
+        };
+
+        BloomFilterStats {
+            checks,
+            negatives,
+            false_positives,
+            false_positive_rate,
+        }
+    }
+
     pub async fn add_record(
         &self,
         table_name: &str,
         key: &str,
         position: &TableRecordPosition,
     ) -> errors::Result<()> {
+        self.load_bloom_filter(table_name).await?;
+        if let Some(Some(filter)) = self.bloom_filters.lock().await.get_mut(table_name) {
+            filter.insert(key);
+        }
+
         let index = self.get_or_create_index(table_name).await?;
         index.insert(key.to_string(), position.clone()).await
     }
 
+    // all (key, position) entries of the primary index, in key order
+    pub async fn list_records(
+        &self,
+        table_name: &str,
+    ) -> errors::Result<Vec<(String, TableRecordPosition)>> {
+        let index = self.get_or_create_index(table_name).await?;
+        index.find_prefix("").await
+    }
+
+    // up to limit (key, position) entries of the primary index with keys in [start, end), in key order.
+    // end None: no upper bound
+    pub async fn range_records(
+        &self,
+        table_name: &str,
+        start: &str,
+        end: Option<&str>,
+        limit: usize,
+    ) -> errors::Result<Vec<(String, TableRecordPosition)>> {
+        let index = self.get_or_create_index(table_name).await?;
+        index.range(start, end, limit).await
+    }
+
     pub async fn delete_record(&self, table_name: &str, key: &str) -> errors::Result<()> {
         let index = self.get_or_create_index(table_name).await?;
         index.delete(key).await
@@ -102,7 +529,145 @@ impl IndexManager {
         table_name: &str,
         key: &str,
     ) -> errors::Result<Option<TableRecordPosition>> {
+        // 1. bloom filter가 없다고 하면 B-tree를 읽지 않음
+        self.load_bloom_filter(table_name).await?;
+        let may_contain = match self.bloom_filters.lock().await.get(table_name) {
+            Some(Some(filter)) => Some(filter.may_contain(key)),
+            _ => None,
+        };
+
+        if may_contain.is_some() {
+            self.bloom_filter_counters
+                .checks
+                .fetch_add(1, Ordering::Relaxed);
+        }
+
+        if may_contain == Some(false) {
+            self.bloom_filter_counters
+                .negatives
+                .fetch_add(1, Ordering::Relaxed);
+            return Ok(None);
+        }
+
+        // 2. B-tree lookup
         let index = self.get_or_create_index(table_name).await?;
-        index.find(key).await
+        let position = index.find(key).await?;
+
+        if may_contain == Some(true) && position.is_none() {
+            self.bloom_filter_counters
+                .false_positives
+                .fetch_add(1, Ordering::Relaxed);
+        }
+
+        Ok(position)
+    }
+
+    pub async fn add_secondary_record(
+        &self,
+        table_name: &str,
+        index_name: &str,
+        field_value: &str,
+        key: &str,
+        position: &TableRecordPosition,
+    ) -> errors::Result<()> {
+        let index = self
+            .get_or_create_secondary_index(table_name, index_name)
+            .await?;
+        index
+            .insert(
+                secondary::make_secondary_key(field_value, key),
+                position.clone(),
+            )
+            .await
+    }
+
+    pub async fn delete_secondary_record(
+        &self,
+        table_name: &str,
+        index_name: &str,
+        field_value: &str,
+        key: &str,
+    ) -> errors::Result<()> {
+        let index = self
+            .get_or_create_secondary_index(table_name, index_name)
+            .await?;
+        index
+            .delete(&secondary::make_secondary_key(field_value, key))
+            .await
+    }
+
+    // primary keys whose indexed field equals field_value
+    pub async fn find_secondary_keys(
+        &self,
+        table_name: &str,
+        index_name: &str,
+        field_value: &str,
+    ) -> errors::Result<Vec<String>> {
+        let index = self
+            .get_or_create_secondary_index(table_name, index_name)
+            .await?;
+        let entries = index
+            .find_prefix(&secondary::secondary_key_prefix(field_value))
+            .await?;
+
+        Ok(entries
+            .iter()
+            .filter_map(|(secondary_key, _)| secondary::primary_key_of(secondary_key))
+            .map(|key| key.to_string())
+            .collect())
+    }
+
+    pub async fn add_history_record(
+        &self,
+        table_name: &str,
+        key: &str,
+        version: u64,
+        position: &TableRecordPosition,
+    ) -> errors::Result<()> {
+        let index = self.get_or_create_history_index(table_name).await?;
+        index
+            .insert(history::make_history_key(key, version), position.clone())
+            .await
+    }
+
+    pub async fn delete_history_record(
+        &self,
+        table_name: &str,
+        key: &str,
+        version: u64,
+    ) -> errors::Result<()> {
+        let index = self.get_or_create_history_index(table_name).await?;
+        index.delete(&history::make_history_key(key, version)).await
+    }
+
+    pub async fn find_history_record(
+        &self,
+        table_name: &str,
+        key: &str,
+        version: u64,
+    ) -> errors::Result<Option<TableRecordPosition>> {
+        let index = self.get_or_create_history_index(table_name).await?;
+        index.find(&history::make_history_key(key, version)).await
+    }
+
+    // (version, position) of the prior versions of the key, newest first
+    pub async fn list_history_records(
+        &self,
+        table_name: &str,
+        key: &str,
+    ) -> errors::Result<Vec<(u64, TableRecordPosition)>> {
+        let index = self.get_or_create_history_index(table_name).await?;
+        let entries = index.find_prefix(&history::history_key_prefix(key)).await?;
+
+        let mut records: Vec<_> = entries
+            .into_iter()
+            .filter_map(|(history_key, position)| {
+                let (history_of, version) = history::parse_history_key(&history_key)?;
+                (history_of == key).then_some((version, position))
+            })
+            .collect();
+        records.sort_by_key(|(version, _)| std::cmp::Reverse(*version));
+
+        Ok(records)
     }
 }
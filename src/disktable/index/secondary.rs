@@ -0,0 +1,90 @@
+// Secondary index on a JSON field of the value.
+// Entries are stored in a separate BTreeIndex keyed by "{field_value}\0{primary_key}",
+// so every primary key with the same field value shares the "{field_value}\0" prefix.
+
+use crate::config::KEY_BYTES_MAX_SIZE;
+
+const SECONDARY_INDEX_KEY_SEPARATOR: char = '\0';
+
+// Index file name for the given secondary index ({file_name}.btree, {file_name}.metadata)
+pub fn secondary_index_file_name(index_name: &str) -> String {
+    format!("secondary.{}", index_name)
+}
+
+// Extracts the field at a dot-separated path (ex: "user.name") from a JSON value.
+// Returns None for non-JSON values, missing fields, and non-scalar fields.
+pub fn extract_json_field(value: &[u8], json_path: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_slice(value).ok()?;
+
+    let mut current = &json;
+    for field in json_path.split('.') {
+        current = current.get(field)?;
+    }
+
+    let field_value = match current {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::Bool(b) => b.to_string(),
+        _ => return None,
+    };
+
+    // too long to be stored as an index key
+    if field_value.len() > KEY_BYTES_MAX_SIZE || field_value.contains(SECONDARY_INDEX_KEY_SEPARATOR)
+    {
+        return None;
+    }
+
+    Some(field_value)
+}
+
+// Prefix shared by all entries with the given field value
+pub fn secondary_key_prefix(field_value: &str) -> String {
+    format!("{}{}", field_value, SECONDARY_INDEX_KEY_SEPARATOR)
+}
+
+pub fn make_secondary_key(field_value: &str, primary_key: &str) -> String {
+    format!("{}{}", secondary_key_prefix(field_value), primary_key)
+}
+
+// Returns the primary key part of a secondary index key
+pub fn primary_key_of(secondary_key: &str) -> Option<&str> {
+    secondary_key
+        .split_once(SECONDARY_INDEX_KEY_SEPARATOR)
+        .map(|(_, primary_key)| primary_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_json_field, make_secondary_key, primary_key_of};
+
+    #[test]
+    fn test_extract_json_field() {
+        let value = br#"{"user": {"name": "alice", "age": 30}, "active": true}"#;
+
+        assert_eq!(
+            extract_json_field(value, "user.name"),
+            Some("alice".to_string())
+        );
+        assert_eq!(
+            extract_json_field(value, "user.age"),
+            Some("30".to_string())
+        );
+        assert_eq!(
+            extract_json_field(value, "active"),
+            Some("true".to_string())
+        );
+
+        // missing field, non-scalar field, non-JSON value
+        assert_eq!(extract_json_field(value, "user.email"), None);
+        assert_eq!(extract_json_field(value, "user"), None);
+        assert_eq!(extract_json_field(b"plain text", "user.name"), None);
+    }
+
+    #[test]
+    fn test_secondary_key() {
+        let key = make_secondary_key("alice", "user:1");
+
+        assert!(key.starts_with("alice\0"));
+        assert_eq!(primary_key_of(&key), Some("user:1"));
+    }
+}
@@ -1,18 +1,49 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+};
 
-use tokio::sync::Mutex;
+use tokio::{io::AsyncWriteExt, sync::Mutex};
+use tokio_stream::{Stream, StreamExt};
 
 use crate::{
-    config::{TABLES_DIRECTORY, TABLES_INDEX_DIRECTORY, TABLES_SEGMENT_DIRECTORY},
-    disktable::{segment::record::TableSegmentPayload, table::TableInfo},
+    amplification::{SegmentWriteKind, WriteAmplification},
+    config::{
+        COMPACTION_MAX_BYTES_PER_SEC, FSYNC_ON_FLUSH, READ_PROMOTION_DEAD_RATIO,
+        READ_PROMOTION_MAX_PER_SECOND, RECLAIM_DEAD_SEGMENTS, STARTUP_INTEGRITY, StartupIntegrity,
+        TABLES_DIRECTORY, TABLES_INDEX_DIRECTORY, TABLES_SEGMENT_DIRECTORY,
+    },
+    disktable::{
+        index::{BloomFilterStats, secondary::extract_json_field},
+        promotion::ReadPromotion,
+        segment::{
+            ScanSegmentFileResult,
+            position::TableRecordPosition,
+            record::{RecordLayout, RecordStateFlags, TableSegmentPayload},
+            segment_id::TableSegmentID,
+        },
+        table::{CreateTableOptions, SecondaryIndexInfo, TableInfo, TableKeyNormalization},
+        throttle::{CompactionStats, CompactionThrottle},
+    },
     errors::{self, ErrorCodes},
+    lock::{LockLevel, TableLock, ordered},
     memtable::MemtableMap,
-    wal::{SharedWALState, state::WALStateWriteHandles},
+    system::unix_millis_now,
+    value_type::ValueType,
+    wal::{
+        SharedWALState,
+        state::{WALPosition, WALStateWriteHandles},
+    },
 };
 
 pub mod index;
+pub mod promotion;
 pub mod segment;
 pub mod table;
+pub mod throttle;
 
 #[derive(Debug)]
 pub struct DiskTableManager {
@@ -20,6 +51,35 @@ pub struct DiskTableManager {
     base_path: std::path::PathBuf,
     index_manager: index::IndexManager,
     segment_manager: segment::TableSegmentManager,
+    table_locks: TableLock,
+    // set once a value was written through. flush then checks disk versions before overwriting
+    write_through_used: AtomicBool,
+    // sync segment/index files before the WAL checkpoint moves (BARUS_FSYNC_ON_FLUSH)
+    fsync_on_flush: bool,
+    flush_synced_files: AtomicU64,
+    // strict: initialize fails on corrupt index files instead of recreating them (BARUS_STARTUP_INTEGRITY)
+    startup_integrity: StartupIntegrity,
+    // bytes written per table (write amplification)
+    write_stats: Arc<WriteAmplification>,
+    // tables that normalize keys, so reads don't have to load the table info file
+    key_normalizations: std::sync::RwLock<HashMap<String, TableKeyNormalization>>,
+    // table info files loaded by initialize or written by create_table, so writes don't read them
+    table_infos: std::sync::RwLock<HashMap<String, TableInfo>>,
+    // None: reads never promote records (BARUS_READ_PROMOTION_DEAD_RATIO unset)
+    read_promotion: Option<ReadPromotion>,
+    // paces the segment writes of vacuum_table
+    compaction_throttle: CompactionThrottle,
+    // flushes remove segment files whose records are all deleted (BARUS_RECLAIM_DEAD_SEGMENTS)
+    reclaim_dead_segments: bool,
+    // tables whose primary index was opened (and rebuilt if it had to be recreated) by ensure_index
+    opened_indexes: std::sync::RwLock<HashSet<String>>,
+    index_open_lock: Mutex<()>,
+    // write_memtable fails this many more times (flush failure tests)
+    #[cfg(test)]
+    pub(crate) injected_flush_failures: std::sync::atomic::AtomicU32,
+    // the injected failures are a full disk instead of a write error
+    #[cfg(test)]
+    pub(crate) injected_flush_storage_full: std::sync::atomic::AtomicBool,
 }
 
 impl DiskTableManager {
@@ -28,9 +88,47 @@ impl DiskTableManager {
             base_path: base_path.clone(),
             index_manager: index::IndexManager::new(base_path.clone()),
             segment_manager: segment::TableSegmentManager::new(base_path),
+            table_locks: TableLock::new(),
+            write_through_used: AtomicBool::new(false),
+            fsync_on_flush: *FSYNC_ON_FLUSH,
+            flush_synced_files: AtomicU64::new(0),
+            startup_integrity: *STARTUP_INTEGRITY,
+            write_stats: Arc::new(WriteAmplification::default()),
+            key_normalizations: std::sync::RwLock::new(HashMap::new()),
+            table_infos: std::sync::RwLock::new(HashMap::new()),
+            read_promotion: READ_PROMOTION_DEAD_RATIO
+                .map(|dead_ratio| ReadPromotion::new(dead_ratio, *READ_PROMOTION_MAX_PER_SECOND)),
+            compaction_throttle: CompactionThrottle::new(*COMPACTION_MAX_BYTES_PER_SEC),
+            reclaim_dead_segments: *RECLAIM_DEAD_SEGMENTS,
+            opened_indexes: std::sync::RwLock::new(HashSet::new()),
+            index_open_lock: Mutex::new(()),
+            #[cfg(test)]
+            injected_flush_failures: std::sync::atomic::AtomicU32::new(0),
+            #[cfg(test)]
+            injected_flush_storage_full: std::sync::atomic::AtomicBool::new(false),
         }
     }
 
+    pub fn with_startup_integrity(mut self, startup_integrity: StartupIntegrity) -> Self {
+        self.startup_integrity = startup_integrity;
+        self
+    }
+
+    pub fn with_write_stats(mut self, write_stats: Arc<WriteAmplification>) -> Self {
+        self.write_stats = write_stats;
+        self
+    }
+
+    pub fn with_read_promotion(mut self, dead_ratio: f64, max_per_second: u32) -> Self {
+        self.read_promotion = Some(ReadPromotion::new(dead_ratio, max_per_second));
+        self
+    }
+
+    pub fn with_compaction_throttle(mut self, bytes_per_second: Option<u64>) -> Self {
+        self.compaction_throttle = CompactionThrottle::new(bytes_per_second);
+        self
+    }
+
     pub async fn initialize(&self) -> errors::Result<()> {
         // 1. Initialize Table Directory
         let tables_path = self.base_path.join(TABLES_DIRECTORY);
@@ -46,7 +144,31 @@ impl DiskTableManager {
 
         // 2. Set Table Names
         let table_names = self.list_tables().await?;
-        self.segment_manager.set_table_names(table_names).await?;
+
+        for table_name in &table_names {
+            let table_info = self.load_table(table_name).await?;
+            self.set_key_normalization(table_name, table_info.key_normalization);
+            self.table_infos
+                .write()
+                .unwrap()
+                .insert(table_name.clone(), table_info.clone());
+
+            // indexes are opened lazily and recreated if corrupt. strict mode checks them all up front instead
+            if self.startup_integrity == StartupIntegrity::Strict {
+                self.index_manager
+                    .check_indexes(table_name, &table_info.secondary_indexes)
+                    .await?;
+            }
+        }
+
+        self.segment_manager
+            .set_table_names(table_names.clone())
+            .await?;
+
+        // 3. Load Bloom Filters
+        for table_name in &table_names {
+            self.index_manager.load_bloom_filter(table_name).await?;
+        }
 
         Ok(())
     }
@@ -76,14 +198,28 @@ impl DiskTableManager {
     }
 
     pub async fn get_table(&self, table: &str) -> errors::Result<TableInfo> {
+        if let Some(table_info) = self.table_infos.read().unwrap().get(table) {
+            return Ok(table_info.clone());
+        }
+
+        self.load_table(table).await
+    }
+
+    // reads the table info file
+    async fn load_table(&self, table: &str) -> errors::Result<TableInfo> {
         let table_path = self
             .base_path
             .join(TABLES_DIRECTORY)
             .join(format!("{}.json", table));
 
         let table_info_bytes = tokio::fs::read(table_path).await.map_err(|e| {
-            errors::Errors::new(errors::ErrorCodes::TableNotFound)
-                .with_message(format!("Failed to read table file: {}", e))
+            if e.kind() == std::io::ErrorKind::NotFound {
+                errors::Errors::new(errors::ErrorCodes::TableNotFound)
+                    .with_message(format!("Table '{}' not found", table))
+            } else {
+                errors::Errors::new(errors::io_error_code(&e, ErrorCodes::TableGetFailed))
+                    .with_message(format!("Failed to read table file: {}", e))
+            }
         })?;
 
         let table_info = serde_json::from_slice(&table_info_bytes).map_err(|e| {
@@ -94,20 +230,112 @@ impl DiskTableManager {
         Ok(table_info)
     }
 
-    pub async fn create_table(&self, table: &str) -> errors::Result<()> {
-        // 1. Create table info file
+    // key normalization of the table. None for unknown tables
+    pub fn key_normalization(&self, table: &str) -> TableKeyNormalization {
+        self.key_normalizations
+            .read()
+            .unwrap()
+            .get(table)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn set_key_normalization(&self, table: &str, key_normalization: TableKeyNormalization) {
+        let mut key_normalizations = self.key_normalizations.write().unwrap();
+
+        match key_normalization {
+            TableKeyNormalization::None => key_normalizations.remove(table),
+            _ => key_normalizations.insert(table.to_string(), key_normalization),
+        };
+    }
+
+    // check the table info file exists
+    pub async fn table_exists(&self, table: &str) -> errors::Result<bool> {
         let table_info_path = self
             .base_path
             .join(TABLES_DIRECTORY)
             .join(format!("{}.json", table));
 
-        if table_info_path.exists() {
-            return Err(errors::Errors::new(errors::ErrorCodes::TableAlreadyExists)
-                .with_message(format!("Table '{}' already exists", table)));
+        tokio::fs::try_exists(&table_info_path).await.map_err(|e| {
+            errors::Errors::new(errors::ErrorCodes::TableGetFailed)
+                .with_message(format!("Failed to check table file: {}", e))
+        })
+    }
+
+    // Opens the primary index of the table on its first access.
+    // An index that had to be created empty (files missing or corrupt) is rebuilt from the segment files
+    async fn ensure_index(&self, table_name: &str) -> errors::Result<()> {
+        if self.opened_indexes.read().unwrap().contains(table_name) {
+            return Ok(());
+        }
+
+        // other tables wait for a rebuild too, so an index is never read half rebuilt
+        let _open_guard = self.index_open_lock.lock().await;
+        if self.opened_indexes.read().unwrap().contains(table_name) {
+            return Ok(());
+        }
+
+        let table_info = match self.get_table(table_name).await {
+            Ok(table_info) => table_info,
+            // left to the caller to report
+            Err(error) if matches!(error.error_code, ErrorCodes::TableNotFound) => return Ok(()),
+            Err(error) => return Err(error),
+        };
+
+        if self.index_manager.open_index(table_name).await?
+            && !self
+                .segment_manager
+                .list_segment_files(table_name)
+                .await?
+                .is_empty()
+        {
+            log::warn!(
+                table = table_name;
+                "Index of table '{}' is missing or was corrupt. Rebuilding it from the segment files",
+                table_name
+            );
+            self.index_manager
+                .rebuild(
+                    table_name,
+                    &self.segment_manager,
+                    &table_info.secondary_indexes,
+                    table_info.keep_versions,
+                )
+                .await?;
         }
 
+        self.opened_indexes
+            .write()
+            .unwrap()
+            .insert(table_name.to_string());
+
+        Ok(())
+    }
+
+    pub async fn create_table(
+        &self,
+        table: &str,
+        options: &CreateTableOptions,
+    ) -> errors::Result<()> {
+        // concurrent creates (and deletes) of the same name take turns
+        let _table_guard = self.table_locks.write(table).await;
+
+        // 1. Create table info file
+        let table_info_path = self
+            .base_path
+            .join(TABLES_DIRECTORY)
+            .join(format!("{}.json", table));
+
         let table_info = table::TableInfo {
             name: table.to_string(),
+            secondary_indexes: options.secondary_indexes.clone(),
+            append_only: options.append_only,
+            created_at: Some(unix_millis_now()),
+            description: options.description.clone(),
+            compression: options.compression,
+            index_type: options.index_type,
+            key_normalization: options.key_normalization,
+            keep_versions: options.keep_versions,
         };
 
         let table_info_json = serde_json::to_string_pretty(&table_info).map_err(|e| {
@@ -115,10 +343,26 @@ impl DiskTableManager {
                 .with_message(format!("Failed to serialize table info to JSON: {}", e))
         })?;
 
-        tokio::fs::write(&table_info_path, table_info_json)
+        // create_new: fails if the file exists, even if it was created outside this process
+        let mut table_info_file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&table_info_path)
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::AlreadyExists {
+                    errors::Errors::new(ErrorCodes::TableAlreadyExists)
+                        .with_message(format!("Table '{}' already exists", table))
+                } else {
+                    errors::Errors::new(errors::io_error_code(&e, ErrorCodes::TableCreationError))
+                        .with_message(format!("Failed to create table info file: {}", e))
+                }
+            })?;
+        table_info_file
+            .write_all(table_info_json.as_bytes())
             .await
             .map_err(|e| {
-                errors::Errors::new(errors::ErrorCodes::TableCreationError)
+                errors::Errors::new(errors::io_error_code(&e, ErrorCodes::TableCreationError))
                     .with_message(format!("Failed to write table info to file: {}", e))
             })?;
 
@@ -128,7 +372,7 @@ impl DiskTableManager {
             tokio::fs::create_dir_all(&table_segment_directory)
                 .await
                 .map_err(|e| {
-                    errors::Errors::new(errors::ErrorCodes::TableCreationError)
+                    errors::Errors::new(errors::io_error_code(&e, ErrorCodes::TableCreationError))
                         .with_message(format!("Failed to create table segment directory: {}", e))
                 })?;
         }
@@ -139,7 +383,7 @@ impl DiskTableManager {
             tokio::fs::create_dir_all(&table_segment_directory)
                 .await
                 .map_err(|e| {
-                    errors::Errors::new(errors::ErrorCodes::TableCreationError)
+                    errors::Errors::new(errors::io_error_code(&e, ErrorCodes::TableCreationError))
                         .with_message(format!("Failed to create table segment directory: {}", e))
                 })?;
         }
@@ -163,13 +407,31 @@ impl DiskTableManager {
         // 5. Create First Segment File
         self.segment_manager.initialize_table(table).await?;
 
+        // 6. Open the empty index. ensure_index rebuilds only indexes recreated later.
+        //    the bloom filter is loaded first, an index without one is taken for a table older than bloom filters
+        self.index_manager.load_bloom_filter(table).await?;
+        self.index_manager.open_index(table).await?;
+        self.opened_indexes
+            .write()
+            .unwrap()
+            .insert(table.to_string());
+
+        self.set_key_normalization(table, options.key_normalization);
+        self.table_infos
+            .write()
+            .unwrap()
+            .insert(table.to_string(), table_info);
+
         Ok(())
     }
 
     // delete all table datas.
     // No error occurs if db file not exists
     pub async fn delete_table(&self, table: &str) -> errors::Result<()> {
+        let _table_guard = self.table_locks.write(table).await;
+
         // 1. Table info file 삭제
+        self.table_infos.write().unwrap().remove(table);
         let table_info_path = self
             .base_path
             .join(TABLES_DIRECTORY)
@@ -195,11 +457,20 @@ impl DiskTableManager {
                 })?;
         }
 
+        // 3. 메모리에 남은 인덱스/bloom filter 정리
+        self.index_manager.delete_index(table).await?;
+        self.opened_indexes.write().unwrap().remove(table);
+        self.segment_manager.discard_table_segments(table).await;
+        self.set_key_normalization(table, TableKeyNormalization::None);
+        self.write_stats.remove_table(table);
+
         Ok(())
     }
 
     // truncate table data
     pub async fn truncate_table(&self, table_name: &str) -> errors::Result<()> {
+        let _table_guard = self.table_locks.write(table_name).await;
+
         // 1. truncate segment files
         self.segment_manager.truncate_table(table_name).await?;
 
@@ -214,38 +485,209 @@ impl DiskTableManager {
         table_name: &str,
         key: &str,
     ) -> errors::Result<DisktableGetResult> {
+        self.ensure_index(table_name).await?;
+
         // 1. find record position from index
         let Some(position) = self.index_manager.find_record(table_name, key).await? else {
             return Ok(DisktableGetResult::NotFound);
         };
 
         // 2. read record from segment
-        let (flag, record) = self
+        let (flag, record) = match self.segment_manager.find_record(table_name, position).await {
+            Ok(found) => found,
+            // the segment only held deleted records and was removed after the index was read
+            Err(error) if matches!(error.error_code, ErrorCodes::TableSegmentReclaimed) => {
+                return Ok(DisktableGetResult::Deleted);
+            }
+            Err(error) => return Err(error),
+        };
+
+        if flag.is_deleted() {
+            return Ok(DisktableGetResult::Deleted);
+        }
+
+        Ok(DisktableGetResult::Found {
+            value: record.value,
+            value_type: record.value_type,
+            version: record.version,
+            written_at: record.written_at,
+        })
+    }
+
+    // get_value for client reads. A record found in an older, mostly-dead segment
+    // may be promoted to the current one (BARUS_READ_PROMOTION_DEAD_RATIO).
+    // promote: false when the engine takes no writes (read-only, degraded, shutting down, replica)
+    pub async fn read_value(
+        &self,
+        table_name: &str,
+        key: &str,
+        promote: bool,
+    ) -> errors::Result<DisktableGetResult> {
+        self.ensure_index(table_name).await?;
+
+        let Some(position) = self.index_manager.find_record(table_name, key).await? else {
+            return Ok(DisktableGetResult::NotFound);
+        };
+
+        let Some(record) = self.read_record(table_name, position.clone()).await? else {
+            return Ok(DisktableGetResult::Deleted);
+        };
+
+        if let Some(read_promotion) = self.read_promotion.as_ref().filter(|_| promote) {
+            // the read already succeeded. a failed promotion leaves the record where it was
+            if let Err(error) = self
+                .promote_record(read_promotion, table_name, key, position)
+                .await
+            {
+                log::warn!(
+                    "Failed to promote key '{}' of table '{}': {}",
+                    key,
+                    table_name,
+                    error
+                );
+            }
+        }
+
+        Ok(DisktableGetResult::Found {
+            value: record.value,
+            value_type: record.value_type,
+            version: record.version,
+            written_at: record.written_at,
+        })
+    }
+
+    // Re-append the record at `position` to the current segment if its segment is dead enough.
+    // Returns whether it was moved
+    async fn promote_record(
+        &self,
+        read_promotion: &ReadPromotion,
+        table_name: &str,
+        key: &str,
+        position: TableRecordPosition,
+    ) -> errors::Result<bool> {
+        let current_segment_id = self.segment_manager.current_segment_id(table_name).await;
+        if current_segment_id.is_none_or(|segment_id| segment_id.0 == position.segment_id.0) {
+            return Ok(false);
+        }
+
+        let dead_ratio = self
             .segment_manager
-            .find_record(table_name, position)
+            .segment_dead_ratio(table_name, &position.segment_id);
+        if dead_ratio.is_none_or(|dead_ratio| dead_ratio < read_promotion.dead_ratio) {
+            return Ok(false);
+        }
+
+        if !read_promotion.try_acquire() {
+            return Ok(false);
+        }
+
+        let _table_guard = self.table_locks.write(table_name).await;
+
+        // a write may have moved the key meanwhile
+        let indexed_position = self.index_manager.find_record(table_name, key).await?;
+        if indexed_position.is_none_or(|indexed| {
+            indexed.segment_id.0 != position.segment_id.0 || indexed.offset != position.offset
+        }) {
+            return Ok(false);
+        }
+
+        let Some(record) = self.read_record(table_name, position).await? else {
+            return Ok(false);
+        };
+
+        let secondary_indexes = self.get_table(table_name).await?.secondary_indexes;
+
+        let record_size = self
+            .write_entry(
+                table_name,
+                key,
+                Some((&record.value, record.value_type)),
+                record.version,
+                record.written_at,
+                &secondary_indexes,
+                0,
+            )
             .await?;
+        self.write_stats
+            .add_segment_bytes(table_name, SegmentWriteKind::Rewrite, record_size);
+        read_promotion.add_promoted();
+
+        Ok(true)
+    }
+
+    pub fn compaction_stats(&self) -> CompactionStats {
+        self.compaction_throttle.stats()
+    }
+
+    // records moved by read promotion since startup
+    pub fn read_promotions(&self) -> u64 {
+        self.read_promotion
+            .as_ref()
+            .map(ReadPromotion::promoted)
+            .unwrap_or(0)
+    }
+
+    // up to limit indexed keys after `after` (from the first if None) with their record positions, ordered by key.
+    // only the keys are loaded; read the records with read_record.
+    pub async fn list_indexed_records(
+        &self,
+        table_name: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> errors::Result<Vec<(String, TableRecordPosition)>> {
+        self.ensure_index(table_name).await?;
+
+        // the smallest key greater than `after`
+        let start = after
+            .map(|after| format!("{}\0", after))
+            .unwrap_or_default();
+
+        self.index_manager
+            .range_records(table_name, &start, None, limit)
+            .await
+    }
+
+    // the record at `position`. None if it was deleted since the position was looked up
+    pub async fn read_record(
+        &self,
+        table_name: &str,
+        position: TableRecordPosition,
+    ) -> errors::Result<Option<TableSegmentPayload>> {
+        let (flag, record) = match self.segment_manager.find_record(table_name, position).await {
+            Ok(found) => found,
+            Err(error) if matches!(error.error_code, ErrorCodes::TableSegmentReclaimed) => {
+                return Ok(None);
+            }
+            Err(error) => return Err(error),
+        };
 
         if flag.is_deleted() {
-            return Ok(DisktableGetResult::Deleted);
+            return Ok(None);
         }
 
-        Ok(DisktableGetResult::Found(record.value))
+        Ok(Some(record))
     }
 
     pub async fn insert_value(
         &self,
         table_name: &str,
         key: &str,
-        value: &str,
-    ) -> errors::Result<()> {
+        value: &[u8],
+        value_type: ValueType,
+        version: u64,
+        written_at: u64,
+    ) -> errors::Result<(TableRecordPosition, u32)> {
         // insert new data
-        let position = self
+        let (position, record_size) = self
             .segment_manager
             .append_record(
                 table_name,
                 TableSegmentPayload {
                     key: key.to_owned(),
                     value: value.to_owned(),
+                    version,
+                    written_at,
+                    value_type,
                 },
             )
             .await?;
@@ -254,103 +696,2074 @@ impl DiskTableManager {
             .add_record(table_name, key, &position)
             .await?;
 
-        Ok(())
+        Ok((position, record_size))
     }
 
-    pub async fn delete_value(&self, table_name: &str, key: &str) -> errors::Result<()> {
-        let old_position = self.index_manager.find_record(table_name, key).await?;
+    // remove secondary index entries of the value currently stored on disk
+    async fn delete_secondary_entries(
+        &self,
+        table_name: &str,
+        key: &str,
+        secondary_indexes: &[SecondaryIndexInfo],
+    ) -> errors::Result<()> {
+        let DisktableGetResult::Found { value, .. } = self.get_value(table_name, key).await? else {
+            return Ok(());
+        };
 
-        if let Some(old_position) = old_position {
-            self.segment_manager
-                .mark_deleted_record(table_name, old_position)
-                .await?;
+        for index in secondary_indexes {
+            if let Some(field_value) = extract_json_field(&value, &index.json_path) {
+                self.index_manager
+                    .delete_secondary_record(table_name, &index.name, &field_value, key)
+                    .await?;
+            }
         }
 
         Ok(())
     }
 
-    pub async fn write_memtable(
+    async fn insert_secondary_entries(
         &self,
-        memtable: MemtableMap,
-        wal_state: SharedWALState,
-        wal_state_write_handles: Arc<Mutex<WALStateWriteHandles>>,
+        table_name: &str,
+        key: &str,
+        value: &[u8],
+        position: &TableRecordPosition,
+        secondary_indexes: &[SecondaryIndexInfo],
     ) -> errors::Result<()> {
-        log::info!("Memtable Flush Started...");
-        let start_time = std::time::Instant::now();
+        for index in secondary_indexes {
+            if let Some(field_value) = extract_json_field(value, &index.json_path) {
+                self.index_manager
+                    .add_secondary_record(table_name, &index.name, &field_value, key, position)
+                    .await?;
+            }
+        }
 
-        let memtable = memtable.read().await;
+        Ok(())
+    }
 
-        // 1. write memtable to disk
-        for (table_name, memtable_lock) in memtable.iter() {
-            let memtable = memtable_lock.read().await;
-            let entry_count = memtable.kv_map.len();
+    // rebuild primary and secondary indexes from segment files
+    pub async fn rebuild_index(&self, table_name: &str) -> errors::Result<usize> {
+        let _table_guard = self.table_locks.write(table_name).await;
 
-            log::trace!("Flushing table '{}': {} entries", table_name, entry_count);
-            let mut processed = 0;
-            let report_interval = (entry_count / 10).max(1000); // 10% 또는 최소 1000개마다 리포트
+        let table_info = self.get_table(table_name).await?;
 
-            for (key, memtable_entry) in memtable.kv_map.iter() {
-                match &memtable_entry.value {
-                    // Insert/Update Process
-                    Some(value) => {
-                        // delete old data if exists
-                        self.delete_value(table_name.as_str(), key.as_str()).await?;
+        self.index_manager
+            .rebuild(
+                table_name,
+                &self.segment_manager,
+                &table_info.secondary_indexes,
+                table_info.keep_versions,
+            )
+            .await
+    }
 
-                        // insert new data
-                        self.insert_value(table_name.as_str(), key.as_str(), value.as_str())
-                            .await?;
-                    }
-                    // Delete Process
-                    None => {
-                        self.delete_value(table_name.as_str(), key.as_str()).await?;
-                    }
-                };
+    // all alive records on disk, ordered by key.
+    // segments and offsets are scanned in append order, so the latest copy of a key wins.
+    pub async fn scan_table(&self, table_name: &str) -> errors::Result<Vec<TableSegmentPayload>> {
+        let mut records = std::collections::BTreeMap::new();
 
-                processed += 1;
-                if processed % report_interval == 0 {
-                    log::trace!(
-                        "Table '{}': {}/{} entries processed ({:.1}%)",
-                        table_name,
-                        processed,
-                        entry_count,
-                        (processed as f64 / entry_count as f64) * 100.0
-                    );
+        for segment_file in self.segment_manager.list_segment_files(table_name).await? {
+            let scan_items = self
+                .segment_manager
+                .scan_segment_file(table_name, &segment_file.file_name)
+                .await?;
+
+            for item in scan_items {
+                if matches!(item.state_flags, segment::record::RecordStateFlags::Alive) {
+                    records.insert(item.payload.key.clone(), item.payload);
                 }
             }
+        }
 
-            log::trace!("Table '{}': flushed {} entries", table_name, entry_count);
+        Ok(records.into_values().collect())
+    }
+
+    // names of the segment files of a table, in append order
+    pub async fn list_segment_files(&self, table_name: &str) -> errors::Result<Vec<String>> {
+        Ok(self
+            .segment_manager
+            .list_segment_files(table_name)
+            .await?
+            .into_iter()
+            .map(|segment_file| segment_file.file_name)
+            .collect())
+    }
+
+    // Alive records of one segment file that are the latest copy of their key (the one the index points to).
+    // Lets a scan hold a single segment in memory instead of the whole table.
+    pub async fn scan_segment_live_records(
+        &self,
+        table_name: &str,
+        segment_file_name: &str,
+    ) -> errors::Result<Vec<TableSegmentPayload>> {
+        self.ensure_index(table_name).await?;
+
+        let scan_items = self
+            .segment_manager
+            .scan_segment_file(table_name, segment_file_name)
+            .await?;
 
-            drop(memtable);
+        let mut records = vec![];
+        for item in scan_items {
+            if !matches!(item.state_flags, segment::record::RecordStateFlags::Alive) {
+                continue;
+            }
+
+            let indexed_position = self
+                .index_manager
+                .find_record(table_name, &item.payload.key)
+                .await?;
 
-            // 1.3. destroy memtable. now, we can find data in disk
-            let mut memtable = memtable_lock.write().await;
-            memtable.kv_map.clear();
+            if indexed_position.is_some_and(|position| {
+                position.segment_id.0 == item.position.segment_id.0
+                    && position.offset == item.position.offset
+            }) {
+                records.push(item.payload);
+            }
         }
 
-        // 2. move WAL checkpoint
+        Ok(records)
+    }
+
+    // (key, position, state) of every primary index entry, ordered by key (ex: data placement audits).
+    // The index entries are listed up front; the state of each record is read as the stream is polled.
+    // Writes made meanwhile may or may not be reflected.
+    pub async fn key_locations(
+        &self,
+        table_name: &str,
+    ) -> errors::Result<
+        impl Stream<Item = errors::Result<(String, TableRecordPosition, RecordStateFlags)>> + '_,
+    > {
+        self.ensure_index(table_name).await?;
+
+        self.get_table(table_name).await?;
+
+        let index_entries = self.index_manager.list_records(table_name).await?;
+        let table_name = table_name.to_string();
+
+        Ok(
+            tokio_stream::iter(index_entries).then(move |(key, position)| {
+                let table_name = table_name.clone();
+                async move {
+                    let state = match self
+                        .segment_manager
+                        .find_record(&table_name, position.clone())
+                        .await
+                    {
+                        Ok((state, _)) => state,
+                        // the entry points at no record
+                        Err(error)
+                            if matches!(error.error_code, ErrorCodes::TableRecordInvalid) =>
+                        {
+                            RecordStateFlags::Unknown
+                        }
+                        Err(error) => return Err(error),
+                    };
+                    Ok((key, position, state))
+                }
+            }),
+        )
+    }
+
+    // The record at a position (from the key map or a scan), whatever its state.
+    // RecordPositionInvalid unless the position is the start of a record in an existing segment
+    pub async fn record_at(
+        &self,
+        table_name: &str,
+        position: TableRecordPosition,
+    ) -> errors::Result<(RecordStateFlags, TableSegmentPayload)> {
+        self.ensure_index(table_name).await?;
+
+        self.get_table(table_name).await?;
+
+        let invalid_position = || {
+            errors::Errors::new(ErrorCodes::RecordPositionInvalid).with_message(format!(
+                "No record starts at offset {} of segment {} in table '{}'",
+                position.offset,
+                position.segment_id.file_name(),
+                table_name
+            ))
+        };
+
+        let segment_files = self.list_segment_files(table_name).await?;
+        if !segment_files.contains(&position.segment_id.file_name()) {
+            return Err(invalid_position());
+        }
+
+        // a reclaimed segment scans empty
+        let scan_items = self
+            .segment_manager
+            .scan_segment(table_name, &position.segment_id)
+            .await?;
+        if !scan_items
+            .iter()
+            .any(|item| item.position.offset == position.offset)
+        {
+            return Err(invalid_position());
+        }
+
+        match self
+            .segment_manager
+            .find_record(table_name, position.clone())
+            .await
         {
-            let mut wal_state = wal_state.lock().await;
+            Err(error) if matches!(error.error_code, ErrorCodes::TableSegmentReclaimed) => {
+                Err(invalid_position())
+            }
+            result => result,
+        }
+    }
 
-            wal_state.last_checkpoint_record_id = wal_state.last_record_id.to_owned();
-            wal_state.last_checkpoint_segment_id = wal_state.last_segment_id.clone();
-            let mut write_handle = wal_state_write_handles.lock().await;
+    // Rewrite the live records stored in an older payload layout in the current one (barus migrate).
+    // The new copies are appended and indexed like a flush writes them, the old ones are marked deleted.
+    // A dry run only counts them.
+    pub async fn migrate_table(
+        &self,
+        table_name: &str,
+        dry_run: bool,
+    ) -> errors::Result<MigrateTableReport> {
+        self.ensure_index(table_name).await?;
 
-            if let Some(ref mut file) = write_handle.state_file {
-                wal_state.save(file).await?;
-            } else {
-                return Err(errors::Errors::new(ErrorCodes::WALStateFileHandleNotFound));
+        let _table_guard = self.table_locks.write(table_name).await;
+
+        let secondary_indexes = self.get_table(table_name).await?.secondary_indexes;
+
+        let segment_files = self.list_segment_files(table_name).await?;
+        let mut report = MigrateTableReport {
+            segments: segment_files.len(),
+            ..Default::default()
+        };
+
+        for (segment_index, segment_file) in segment_files.iter().enumerate() {
+            let scan_items = self
+                .segment_manager
+                .scan_segment_file(table_name, segment_file)
+                .await?;
+
+            for item in scan_items {
+                if !matches!(item.state_flags, RecordStateFlags::Alive) {
+                    continue;
+                }
+
+                // stale copies of a key are left alone, like scan_segment_live_records does
+                let indexed_position = self
+                    .index_manager
+                    .find_record(table_name, &item.payload.key)
+                    .await?;
+                if !indexed_position.is_some_and(|position| {
+                    position.segment_id.0 == item.position.segment_id.0
+                        && position.offset == item.position.offset
+                }) {
+                    continue;
+                }
+
+                report.live_records += 1;
+                if item.layout == RecordLayout::Current {
+                    continue;
+                }
+                *report.outdated_records.entry(item.layout).or_default() += 1;
+
+                if dry_run {
+                    continue;
+                }
+
+                let record = item.payload;
+                let record_size = self
+                    .write_entry(
+                        table_name,
+                        &record.key,
+                        Some((&record.value, record.value_type)),
+                        record.version,
+                        record.written_at,
+                        &secondary_indexes,
+                        0,
+                    )
+                    .await?;
+                self.write_stats.add_segment_bytes(
+                    table_name,
+                    SegmentWriteKind::Rewrite,
+                    record_size,
+                );
+                report.rewritten_records += 1;
             }
+
+            log::info!(
+                table = table_name;
+                "Table '{}': segment {}/{} checked, {} outdated records",
+                table_name,
+                segment_index + 1,
+                segment_files.len(),
+                report.outdated_count()
+            );
         }
 
-        let elapsed = start_time.elapsed();
-        log::info!("Memtable Flush Completed Successfully in {:?}", elapsed);
+        if report.rewritten_records > 0 {
+            self.index_manager.save_bloom_filter(table_name).await?;
 
-        Ok(())
+            if self.fsync_on_flush {
+                self.segment_manager.sync_table(table_name).await?;
+                self.index_manager.sync_table(table_name).await?;
+            }
+        }
+
+        Ok(report)
     }
-}
 
-pub enum DisktableGetResult {
-    Found(String),
-    NotFound,
-    Deleted,
+    // Check that the primary index and the segment files agree:
+    // every index entry must point to an Alive record with the same key,
+    // and every Alive record must be the one its key is indexed at.
+    pub async fn verify_table(&self, table_name: &str) -> errors::Result<VerifyTableReport> {
+        self.ensure_index(table_name).await?;
+
+        // no flush or write-through changes the table meanwhile
+        let _table_guard = self.table_locks.write(table_name).await;
+
+        self.get_table(table_name).await?;
+
+        let mut report = VerifyTableReport::default();
+
+        // 1. index -> segment
+        let index_entries = self.index_manager.list_records(table_name).await?;
+        report.indexed_records = index_entries.len();
+
+        let mut indexed_positions = std::collections::HashSet::with_capacity(index_entries.len());
+        for (key, position) in index_entries {
+            let record_position = RecordPositionInfo::from(&position);
+            indexed_positions.insert(record_position.clone());
+
+            let problem = match self.segment_manager.find_record(table_name, position).await {
+                Ok((flag, _)) if !matches!(flag, segment::record::RecordStateFlags::Alive) => {
+                    format!("record is not alive ({:?})", flag)
+                }
+                Ok((_, record)) if record.key != key => {
+                    format!("record key is '{}'", record.key)
+                }
+                Ok(_) => continue,
+                Err(error) => format!("record is unreadable: {}", error),
+            };
+
+            report.mismatches.push(IndexMismatch {
+                key,
+                position: record_position,
+                problem,
+            });
+        }
+
+        // 2. segment -> index
+        for segment_file in self.segment_manager.list_segment_files(table_name).await? {
+            let scan_items = self
+                .segment_manager
+                .scan_segment_file(table_name, &segment_file.file_name)
+                .await?;
+
+            for item in scan_items {
+                if !matches!(item.state_flags, segment::record::RecordStateFlags::Alive) {
+                    continue;
+                }
+                report.alive_records += 1;
+
+                let position = RecordPositionInfo::from(&item.position);
+                if !indexed_positions.contains(&position) {
+                    report.orphaned_records.push(OrphanedRecord {
+                        key: item.payload.key,
+                        position,
+                    });
+                }
+            }
+        }
+
+        report.consistent = report.mismatches.is_empty() && report.orphaned_records.is_empty();
+
+        if !report.consistent {
+            log::warn!(
+                "Table '{}' index and segments disagree: {} mismatches, {} orphaned records",
+                table_name,
+                report.mismatches.len(),
+                report.orphaned_records.len()
+            );
+        }
+
+        Ok(report)
+    }
+
+    // open segment file handles per table
+    pub fn open_segment_counts(&self) -> std::collections::BTreeMap<String, usize> {
+        self.segment_manager.open_segment_counts()
+    }
+
+    pub fn bloom_filter_stats(&self) -> BloomFilterStats {
+        self.index_manager.bloom_filter_stats()
+    }
+
+    // files synced by memtable flushes since startup
+    pub fn flush_synced_files(&self) -> u64 {
+        self.flush_synced_files.load(Ordering::Relaxed)
+    }
+
+    // primary keys on disk whose indexed field equals field_value.
+    // entries can be stale until the next flush, so callers must re-check the value.
+    pub async fn find_by_index(
+        &self,
+        table_name: &str,
+        index_name: &str,
+        field_value: &str,
+    ) -> errors::Result<Vec<String>> {
+        self.ensure_index(table_name).await?;
+
+        self.index_manager
+            .find_secondary_keys(table_name, index_name, field_value)
+            .await
+    }
+
+    pub async fn delete_value(&self, table_name: &str, key: &str) -> errors::Result<()> {
+        self.ensure_index(table_name).await?;
+
+        self.mark_deleted(table_name, key).await?;
+
+        Ok(())
+    }
+
+    // Marks the record the key is indexed at deleted and returns its position
+    async fn mark_deleted(
+        &self,
+        table_name: &str,
+        key: &str,
+    ) -> errors::Result<Option<TableRecordPosition>> {
+        let old_position = self.index_manager.find_record(table_name, key).await?;
+
+        if let Some(old_position) = &old_position {
+            self.segment_manager
+                .mark_deleted_record(table_name, old_position.clone())
+                .await?;
+        }
+
+        Ok(old_position)
+    }
+
+    // Insert/Update (Some) or Delete (None) one key with its index entries.
+    // With keep_versions > 0, the replaced record is kept as a prior version of the key
+    // (relocations of the same version pass 0)
+    #[allow(clippy::too_many_arguments)]
+    async fn write_entry(
+        &self,
+        table_name: &str,
+        key: &str,
+        value: Option<(&[u8], ValueType)>,
+        version: u64,
+        written_at: u64,
+        secondary_indexes: &[SecondaryIndexInfo],
+        keep_versions: u32,
+    ) -> errors::Result<u32> {
+        // delete old data if exists
+        self.delete_secondary_entries(table_name, key, secondary_indexes)
+            .await?;
+        let old_position = self.mark_deleted(table_name, key).await?;
+
+        if keep_versions > 0
+            && let Some(old_position) = old_position
+        {
+            self.keep_prior_version(table_name, key, old_position, version, keep_versions)
+                .await?;
+        }
+
+        // insert new data
+        let mut record_size = 0;
+        if let Some((value, value_type)) = value {
+            let (position, size) = self
+                .insert_value(table_name, key, value, value_type, version, written_at)
+                .await?;
+            self.insert_secondary_entries(table_name, key, value, &position, secondary_indexes)
+                .await?;
+            record_size = size;
+        }
+
+        // appended record size in bytes (0 for deletes)
+        Ok(record_size)
+    }
+
+    // Indexes the replaced record as a prior version of the key and drops the versions
+    // past keep_versions from the history index. Their records are reclaimed like other deleted ones
+    async fn keep_prior_version(
+        &self,
+        table_name: &str,
+        key: &str,
+        old_position: TableRecordPosition,
+        version: u64,
+        keep_versions: u32,
+    ) -> errors::Result<()> {
+        let old_record = match self
+            .segment_manager
+            .find_record(table_name, old_position.clone())
+            .await
+        {
+            Ok((_, record)) => record,
+            Err(error) if matches!(error.error_code, ErrorCodes::TableSegmentReclaimed) => {
+                return Ok(());
+            }
+            Err(error) => return Err(error),
+        };
+        if old_record.version >= version {
+            return Ok(());
+        }
+
+        self.index_manager
+            .add_history_record(table_name, key, old_record.version, &old_position)
+            .await?;
+
+        let history = self
+            .index_manager
+            .list_history_records(table_name, key)
+            .await?;
+        for (expired_version, _) in history.iter().skip(keep_versions as usize) {
+            self.index_manager
+                .delete_history_record(table_name, key, *expired_version)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // Prior versions of the key on disk, newest first (keep_versions tables).
+    // The value the key is indexed at now is not included
+    pub async fn value_versions(
+        &self,
+        table_name: &str,
+        key: &str,
+    ) -> errors::Result<Vec<TableSegmentPayload>> {
+        self.ensure_index(table_name).await?;
+
+        let history = self
+            .index_manager
+            .list_history_records(table_name, key)
+            .await?;
+
+        let mut versions = Vec::with_capacity(history.len());
+        for (_, position) in history {
+            let (_, record) = self
+                .segment_manager
+                .find_record(table_name, position)
+                .await?;
+            versions.push(record);
+        }
+
+        Ok(versions)
+    }
+
+    // whether the record is a prior version of its key that the history index still points to
+    async fn is_retained_version(
+        &self,
+        table_name: &str,
+        item: &ScanSegmentFileResult,
+    ) -> errors::Result<bool> {
+        if !matches!(item.state_flags, RecordStateFlags::Deleted) {
+            return Ok(false);
+        }
+
+        let position = self
+            .index_manager
+            .find_history_record(table_name, &item.payload.key, item.payload.version)
+            .await?;
+
+        Ok(position.is_some_and(|position| {
+            position.segment_id.0 == item.position.segment_id.0
+                && position.offset == item.position.offset
+        }))
+    }
+
+    // Removes the index entries pointing into segments whose records are all deleted and returns them.
+    // Their files can be removed once the index changes are synced
+    async fn unindex_dead_segments(
+        &self,
+        table_name: &str,
+        keep_versions: u32,
+    ) -> errors::Result<Vec<TableSegmentID>> {
+        let mut dead_segments = vec![];
+
+        for segment_id in self.segment_manager.take_deleted_segments(table_name).await {
+            // only segments without alive records are read
+            if self
+                .segment_manager
+                .live_records(table_name, &segment_id)
+                .await?
+                > 0
+            {
+                continue;
+            }
+
+            let scan_items = self
+                .segment_manager
+                .scan_segment(table_name, &segment_id)
+                .await?;
+
+            if scan_items.is_empty()
+                || scan_items
+                    .iter()
+                    .any(|item| !matches!(item.state_flags, RecordStateFlags::Deleted))
+            {
+                continue;
+            }
+
+            // prior versions still readable through the history index keep the segment
+            if keep_versions > 0 {
+                let mut retained = false;
+                for item in &scan_items {
+                    if self.is_retained_version(table_name, item).await? {
+                        retained = true;
+                        break;
+                    }
+                }
+                if retained {
+                    continue;
+                }
+            }
+
+            self.unindex_segment(table_name, &segment_id, &scan_items)
+                .await?;
+            dead_segments.push(segment_id);
+        }
+
+        Ok(dead_segments)
+    }
+
+    // Removes the primary index entries pointing into the segment.
+    // A deleted key stays indexed at its tombstoned record until it is written again
+    async fn unindex_segment(
+        &self,
+        table_name: &str,
+        segment_id: &TableSegmentID,
+        scan_items: &[ScanSegmentFileResult],
+    ) -> errors::Result<()> {
+        for item in scan_items {
+            let indexed_position = self
+                .index_manager
+                .find_record(table_name, &item.payload.key)
+                .await?;
+
+            if indexed_position.is_some_and(|position| position.segment_id.0 == segment_id.0) {
+                self.index_manager
+                    .delete_record(table_name, &item.payload.key)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Compacts the table's older segments (barus vacuum): a segment whose share of dead records
+    // (deleted, or a stale copy of a key) is at least min_dead_ratio has its live records
+    // re-appended to the current segment and is removed. The current segment is left as is.
+    pub async fn vacuum_table(
+        &self,
+        table_name: &str,
+        min_dead_ratio: f64,
+    ) -> errors::Result<VacuumTableReport> {
+        self.ensure_index(table_name).await?;
+
+        let table_info = self.get_table(table_name).await?;
+        let secondary_indexes = table_info.secondary_indexes;
+
+        let segment_files = self.segment_manager.list_segment_files(table_name).await?;
+        let mut report = VacuumTableReport {
+            segments: segment_files.len(),
+            ..Default::default()
+        };
+
+        // the table lock is taken per segment, so flushes can run between two segments
+        for segment_file in segment_files {
+            let _table_guard = self.table_locks.write(table_name).await;
+
+            let segment_id = segment_file.segment_id;
+            let current_segment_id = self.segment_manager.current_segment_id(table_name).await;
+            if current_segment_id
+                .as_ref()
+                .is_none_or(|current| current.0 == segment_id.0)
+            {
+                continue;
+            }
+
+            let scan_items = self
+                .segment_manager
+                .scan_segment(table_name, &segment_id)
+                .await?;
+
+            let mut live_records = vec![];
+            let mut retained_versions = vec![];
+            for item in &scan_items {
+                if !matches!(item.state_flags, RecordStateFlags::Alive) {
+                    if table_info.keep_versions > 0
+                        && self.is_retained_version(table_name, item).await?
+                    {
+                        retained_versions.push(&item.payload);
+                    }
+                    continue;
+                }
+
+                let indexed_position = self
+                    .index_manager
+                    .find_record(table_name, &item.payload.key)
+                    .await?;
+                if indexed_position.is_some_and(|position| {
+                    position.segment_id.0 == item.position.segment_id.0
+                        && position.offset == item.position.offset
+                }) {
+                    live_records.push(&item.payload);
+                }
+            }
+
+            let dead_ratio = match scan_items.len() {
+                0 => 1.0,
+                total => 1.0 - (live_records.len() + retained_versions.len()) as f64 / total as f64,
+            };
+            if dead_ratio < min_dead_ratio {
+                continue;
+            }
+
+            for record in live_records {
+                let record_size = self
+                    .write_entry(
+                        table_name,
+                        &record.key,
+                        Some((&record.value, record.value_type)),
+                        record.version,
+                        record.written_at,
+                        &secondary_indexes,
+                        0,
+                    )
+                    .await?;
+                self.write_stats.add_segment_bytes(
+                    table_name,
+                    SegmentWriteKind::Rewrite,
+                    record_size,
+                );
+                self.compaction_throttle.consume(record_size as u64).await;
+                report.relocated_records += 1;
+            }
+
+            // prior versions are re-appended as deleted records and re-indexed in the history index
+            for record in retained_versions {
+                let (position, record_size) = self
+                    .segment_manager
+                    .append_record(table_name, record.clone())
+                    .await?;
+                self.segment_manager
+                    .mark_deleted_record(table_name, position.clone())
+                    .await?;
+                self.index_manager
+                    .add_history_record(table_name, &record.key, record.version, &position)
+                    .await?;
+                self.write_stats.add_segment_bytes(
+                    table_name,
+                    SegmentWriteKind::Rewrite,
+                    record_size,
+                );
+                self.compaction_throttle.consume(record_size as u64).await;
+                report.relocated_records += 1;
+            }
+
+            self.unindex_segment(table_name, &segment_id, &scan_items)
+                .await?;
+
+            // the relocated copies and index changes are always synced before the old file goes away,
+            // whatever BARUS_FSYNC_ON_FLUSH says: the old file may hold the only durable copy
+            self.index_manager.save_bloom_filter(table_name).await?;
+            self.segment_manager.sync_table(table_name).await?;
+            self.index_manager.sync_table(table_name).await?;
+
+            self.segment_manager
+                .delete_segment(table_name, &segment_id)
+                .await?;
+            report.reclaimed_bytes += segment_file.file_size as u64;
+            report.removed_segments += 1;
+        }
+
+        if report.removed_segments == 0 {
+            return Ok(report);
+        }
+
+        log::info!(
+            table = table_name;
+            "Table '{}': vacuumed {} segments, {} records relocated, {} bytes reclaimed",
+            table_name,
+            report.removed_segments,
+            report.relocated_records,
+            report.reclaimed_bytes
+        );
+
+        Ok(report)
+    }
+
+    // version of the alive record on disk
+    async fn disk_version(&self, table_name: &str, key: &str) -> errors::Result<Option<u64>> {
+        match self.get_value(table_name, key).await? {
+            DisktableGetResult::Found { version, .. } => Ok(Some(version)),
+            _ => Ok(None),
+        }
+    }
+
+    // Write a value straight to a segment, bypassing the memtable (large values).
+    // The caller logs it to the WAL first and leaves an on-disk marker in the memtable.
+    pub async fn write_value(
+        &self,
+        table_name: &str,
+        key: &str,
+        value: &[u8],
+        value_type: ValueType,
+        version: u64,
+        written_at: u64,
+    ) -> errors::Result<()> {
+        self.ensure_index(table_name).await?;
+
+        let _table_guard = self.table_locks.write(table_name).await;
+
+        let table_info = self.get_table(table_name).await?;
+        let secondary_indexes = table_info.secondary_indexes;
+
+        self.write_through_used.store(true, Ordering::Relaxed);
+
+        let record_size = self
+            .write_entry(
+                table_name,
+                key,
+                Some((value, value_type)),
+                version,
+                written_at,
+                &secondary_indexes,
+                table_info.keep_versions,
+            )
+            .await?;
+        self.write_stats
+            .add_segment_bytes(table_name, SegmentWriteKind::Flush, record_size);
+        self.index_manager.save_bloom_filter(table_name).await?;
+
+        Ok(())
+    }
+
+    pub async fn write_memtable(
+        &self,
+        memtable: MemtableMap,
+        wal_state: SharedWALState,
+        flushed_up_to: WALPosition,
+        wal_state_write_handles: Arc<Mutex<WALStateWriteHandles>>,
+    ) -> errors::Result<()> {
+        #[cfg(test)]
+        if self
+            .injected_flush_failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                count.checked_sub(1)
+            })
+            .is_ok()
+        {
+            let error_code = if self.injected_flush_storage_full.load(Ordering::SeqCst) {
+                ErrorCodes::StorageUnavailable
+            } else {
+                ErrorCodes::TableSegmentFileWriteError
+            };
+            return Err(
+                errors::Errors::new(error_code).with_message("injected flush failure".to_string())
+            );
+        }
+
+        let start_time = std::time::Instant::now();
+
+        // the flushing memtables are not swapped while their flush is pending, so a snapshot of the table list is enough.
+        // not holding the map lock during the flush keeps truncate (which write-locks it) from waiting for all tables.
+        let tables: Vec<_> = ordered(LockLevel::FlushingMemtableMap, memtable.read())
+            .await
+            .iter()
+            .map(|(table_name, memtable_lock)| (table_name.clone(), memtable_lock.clone()))
+            .collect();
+        log::info!(tables = tables.len() as u64; "Memtable Flush Started...");
+
+        // 1. write memtable to disk
+        for (table_name, memtable_lock) in &tables {
+            // delete/truncate of this table waits until the flush of the table is done
+            let _table_guard = self.table_locks.write(table_name).await;
+
+            let mut entry_count = 0;
+            for shard in memtable_lock.shards() {
+                entry_count += ordered(LockLevel::Memtable, shard.read())
+                    .await
+                    .kv_map
+                    .len();
+            }
+
+            let (secondary_indexes, keep_versions) = match self.get_table(table_name).await {
+                Ok(table_info) => (table_info.secondary_indexes, table_info.keep_versions),
+                Err(error) if matches!(error.error_code, ErrorCodes::TableNotFound) => {
+                    // dropped while the flush was waiting for the lock
+                    log::warn!(
+                        table = table_name.as_str();
+                        "Table '{}' no longer exists. Skipping flush",
+                        table_name
+                    );
+
+                    memtable_lock.clear().await;
+                    continue;
+                }
+                Err(_) => (vec![], 0),
+            };
+
+            self.ensure_index(table_name).await?;
+
+            log::trace!("Flushing table '{}': {} entries", table_name, entry_count);
+            let mut processed = 0;
+            let report_interval = (entry_count / 10).max(1000); // 10% 또는 최소 1000개마다 리포트
+
+            for shard in memtable_lock.shards() {
+                let memtable = ordered(LockLevel::Memtable, shard.read()).await;
+                for (key, memtable_entry) in memtable.kv_map.iter() {
+                    // skip values already written through to disk.
+                    // a newer value may also have been written through while this entry waited for the flush
+                    let skip = memtable_entry.on_disk
+                        || (self.write_through_used.load(Ordering::Relaxed)
+                            && self.disk_version(table_name, key).await?
+                                > Some(memtable_entry.version));
+
+                    if !skip {
+                        let record_size = self
+                            .write_entry(
+                                table_name,
+                                key,
+                                memtable_entry
+                                    .value
+                                    .as_deref()
+                                    .map(|value| (value, memtable_entry.value_type)),
+                                memtable_entry.version,
+                                memtable_entry.written_at,
+                                &secondary_indexes,
+                                keep_versions,
+                            )
+                            .await?;
+                        self.write_stats.add_segment_bytes(
+                            table_name,
+                            SegmentWriteKind::Flush,
+                            record_size,
+                        );
+                    }
+
+                    processed += 1;
+                    if processed % report_interval == 0 {
+                        log::trace!(
+                            "Table '{}': {}/{} entries processed ({:.1}%)",
+                            table_name,
+                            processed,
+                            entry_count,
+                            (processed as f64 / entry_count as f64) * 100.0
+                        );
+                    }
+                }
+            }
+
+            self.index_manager.save_bloom_filter(table_name).await?;
+
+            let dead_segments = if self.reclaim_dead_segments {
+                self.unindex_dead_segments(table_name, keep_versions)
+                    .await?
+            } else {
+                vec![]
+            };
+
+            // 1.2. make the flushed data durable before the checkpoint skips its WAL records
+            if self.fsync_on_flush {
+                let synced_files = self.segment_manager.sync_table(table_name).await?
+                    + self.index_manager.sync_table(table_name).await?;
+
+                self.flush_synced_files
+                    .fetch_add(synced_files as u64, Ordering::Relaxed);
+            }
+
+            log::trace!("Table '{}': flushed {} entries", table_name, entry_count);
+
+            // 1.3. remove the segment files left with only deleted records
+            for segment_id in &dead_segments {
+                self.segment_manager
+                    .delete_segment(table_name, segment_id)
+                    .await?;
+            }
+            if !dead_segments.is_empty() {
+                log::info!(
+                    table = table_name.as_str();
+                    "Table '{}': removed {} dead segment files",
+                    table_name,
+                    dead_segments.len()
+                );
+            }
+
+            // 1.4. destroy memtable. now, we can find data in disk
+            memtable_lock.clear().await;
+        }
+
+        // 2. move WAL checkpoint
+        {
+            let mut wal_state = ordered(LockLevel::WALState, wal_state.lock()).await;
+
+            // records logged after the swap are only in the active memtable, and still have to be replayed
+            wal_state.last_checkpoint_record_id = flushed_up_to.record_id;
+            wal_state.last_checkpoint_segment_id = flushed_up_to.segment_id.clone();
+            wal_state.last_flushed_record_id = flushed_up_to.record_id;
+            wal_state.last_flushed_segment_id = flushed_up_to.segment_id;
+            let mut write_handle =
+                ordered(LockLevel::WALStateFile, wal_state_write_handles.lock()).await;
+
+            if let Some(ref mut file) = write_handle.state_file {
+                wal_state.save(file).await?;
+            } else {
+                return Err(errors::Errors::new(ErrorCodes::WALStateFileHandleNotFound));
+            }
+        }
+
+        let elapsed = start_time.elapsed();
+        log::info!(
+            elapsed_ms = elapsed.as_millis() as u64;
+            "Memtable Flush Completed Successfully in {:?}",
+            elapsed
+        );
+
+        Ok(())
+    }
+}
+
+// Result of migrate_table
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct MigrateTableReport {
+    pub segments: usize,
+    pub live_records: usize,
+    // live records in an older payload layout, per layout
+    pub outdated_records: std::collections::BTreeMap<RecordLayout, usize>,
+    // outdated records rewritten in the current layout. 0 on a dry run
+    pub rewritten_records: usize,
+}
+
+impl MigrateTableReport {
+    pub fn outdated_count(&self) -> usize {
+        self.outdated_records.values().sum()
+    }
+}
+
+// Result of vacuum_table
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct VacuumTableReport {
+    pub segments: usize,
+    pub removed_segments: usize,
+    // live records moved from the removed segments to the current one
+    pub relocated_records: usize,
+    // size of the removed segment files
+    pub reclaimed_bytes: u64,
+}
+
+// Result of verify_table
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct VerifyTableReport {
+    pub consistent: bool,
+    pub indexed_records: usize,
+    pub alive_records: usize,
+    // index entries that do not point to an Alive record of the same key
+    pub mismatches: Vec<IndexMismatch>,
+    // Alive records no index entry points to (missing from the index, or a stale copy of the key)
+    pub orphaned_records: Vec<OrphanedRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize)]
+pub struct RecordPositionInfo {
+    pub segment_id: u64,
+    pub offset: u32,
+}
+
+impl From<&TableRecordPosition> for RecordPositionInfo {
+    fn from(position: &TableRecordPosition) -> Self {
+        Self {
+            segment_id: position.segment_id.0,
+            offset: position.offset,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IndexMismatch {
+    pub key: String,
+    pub position: RecordPositionInfo,
+    pub problem: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OrphanedRecord {
+    pub key: String,
+    pub position: RecordPositionInfo,
+}
+
+pub enum DisktableGetResult {
+    Found {
+        value: Vec<u8>,
+        value_type: ValueType,
+        version: u64,
+        written_at: u64,
+    },
+    NotFound,
+    Deleted,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use tokio::sync::Mutex;
+    use tokio_stream::StreamExt;
+
+    use super::{
+        DiskTableManager, DisktableGetResult, RecordStateFlags,
+        table::{CreateTableOptions, SecondaryIndexInfo},
+    };
+    use crate::{
+        config::{
+            DISKTABLE_PAGE_SIZE, TABLES_DIRECTORY, TABLES_INDEX_DIRECTORY, TABLES_SEGMENT_DIRECTORY,
+        },
+        disktable::segment::{
+            encode::TableRecordBincodeCodec,
+            position::TableRecordPosition,
+            record::{
+                LegacyTableSegmentPayload, RecordLayout, TableSegmentPayload,
+                TimestampedTableSegmentPayload, VersionedTableSegmentPayload,
+            },
+            segment_id::TableSegmentID,
+        },
+        errors::ErrorCodes,
+        memtable::shard::ShardedMemtable,
+        value_type::ValueType,
+        wal::{
+            segment_id::WALSegmentID,
+            state::{WALGlobalState, WALPosition, WALStateWriteHandles},
+        },
+    };
+
+    // flush one table's writes (None: delete) through write_memtable
+    async fn flush(manager: &DiskTableManager, table_name: &str, writes: &[(&str, Option<&str>)]) {
+        let memtable = ShardedMemtable::new(1);
+        for (i, (key, value)) in writes.iter().enumerate() {
+            let mut shard = memtable.shard(key).write().await;
+            match value {
+                Some(value) => shard.put(
+                    key.to_string(),
+                    value.as_bytes().to_vec(),
+                    ValueType::Raw,
+                    i as u64,
+                    0,
+                ),
+                None => shard.delete(key, i as u64, 0),
+            };
+        }
+
+        let memtable_map = Arc::new(tokio::sync::RwLock::new(HashMap::from([(
+            table_name.to_string(),
+            Arc::new(memtable),
+        )])));
+        let state_file = tokio::fs::File::create(manager.base_path.join("wal_state.json"))
+            .await
+            .unwrap();
+
+        manager
+            .write_memtable(
+                memtable_map,
+                Arc::new(Mutex::new(WALGlobalState::default())),
+                WALPosition::default(),
+                Arc::new(Mutex::new(WALStateWriteHandles {
+                    state_file: Some(state_file),
+                })),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_flush_moves_checkpoint_to_the_swap_position() {
+        let base_path = std::env::temp_dir().join(format!(
+            "barus_test_flush_checkpoint_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+
+        // 스왑 이후에 기록된 레코드(6..10)는 활성 memtable에만 있음
+        let wal_state = Arc::new(Mutex::new(WALGlobalState {
+            last_record_id: 10.into(),
+            last_segment_id: WALSegmentID::new(3),
+            ..Default::default()
+        }));
+        let state_file = tokio::fs::File::create(base_path.join("wal_state.json"))
+            .await
+            .unwrap();
+        manager
+            .write_memtable(
+                Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+                wal_state.clone(),
+                WALPosition {
+                    segment_id: WALSegmentID::new(2),
+                    record_id: 5.into(),
+                },
+                Arc::new(Mutex::new(WALStateWriteHandles {
+                    state_file: Some(state_file),
+                })),
+            )
+            .await
+            .unwrap();
+
+        let wal_state = wal_state.lock().await;
+        assert_eq!(u64::from(wal_state.last_checkpoint_record_id), 5);
+        assert_eq!(wal_state.last_checkpoint_segment_id, WALSegmentID::new(2));
+        assert_eq!(u64::from(wal_state.last_flushed_record_id), 5);
+        assert_eq!(wal_state.last_flushed_segment_id, WALSegmentID::new(2));
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_table_info_is_cached() {
+        let base_path = std::env::temp_dir().join(format!(
+            "barus_test_table_info_cache_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        manager
+            .create_table(
+                "items",
+                &CreateTableOptions {
+                    append_only: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // served without reading the table file
+        let table_path = base_path.join(TABLES_DIRECTORY).join("items.json");
+        let table_file = std::fs::read(&table_path).unwrap();
+        std::fs::remove_file(&table_path).unwrap();
+        assert!(manager.get_table("items").await.unwrap().append_only);
+        std::fs::write(&table_path, table_file).unwrap();
+
+        manager.delete_table("items").await.unwrap();
+        let error = manager.get_table("items").await.unwrap_err();
+        assert!(matches!(error.error_code, ErrorCodes::TableNotFound));
+
+        // a table file that can't be read is not reported as a missing table
+        std::fs::create_dir_all(base_path.join(TABLES_DIRECTORY).join("broken.json")).unwrap();
+        let error = manager.get_table("broken").await.unwrap_err();
+        assert!(matches!(error.error_code, ErrorCodes::TableGetFailed));
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_verify_table_detects_index_segment_drift() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_verify_table_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        manager
+            .create_table("items", &CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        let mut positions = vec![];
+        for (i, key) in ["item1", "item2", "item3", "item4"].iter().enumerate() {
+            let (position, _) = manager
+                .insert_value("items", key, b"value", ValueType::Raw, i as u64 + 1, 0)
+                .await
+                .unwrap();
+            positions.push(position);
+        }
+
+        let report = manager.verify_table("items").await.unwrap();
+        assert!(report.consistent);
+        assert_eq!(report.indexed_records, 4);
+        assert_eq!(report.alive_records, 4);
+
+        // 인덱스와 세그먼트를 의도적으로 어긋나게 만듦
+        // item1 -> item2의 레코드, item3 레코드는 삭제 표시, item4는 인덱스에서 제거
+        manager
+            .index_manager
+            .update_record("items", "item1", &positions[1])
+            .await
+            .unwrap();
+        manager
+            .segment_manager
+            .mark_deleted_record("items", positions[2].clone())
+            .await
+            .unwrap();
+        manager
+            .index_manager
+            .delete_record("items", "item4")
+            .await
+            .unwrap();
+
+        let report = manager.verify_table("items").await.unwrap();
+        assert!(!report.consistent);
+        assert_eq!(report.indexed_records, 3);
+        assert_eq!(report.alive_records, 3);
+
+        let mismatches: Vec<(&str, &str)> = report
+            .mismatches
+            .iter()
+            .map(|mismatch| (mismatch.key.as_str(), mismatch.problem.as_str()))
+            .collect();
+        assert_eq!(
+            mismatches,
+            vec![
+                ("item1", "record key is 'item2'"),
+                ("item3", "record is not alive (Deleted)"),
+            ]
+        );
+
+        let orphaned: Vec<&str> = report
+            .orphaned_records
+            .iter()
+            .map(|record| record.key.as_str())
+            .collect();
+        assert_eq!(orphaned, vec!["item1", "item4"]);
+
+        // reindex로 복구
+        manager.rebuild_index("items").await.unwrap();
+        assert!(manager.verify_table("items").await.unwrap().consistent);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_index_prefers_most_recent_duplicate() {
+        let base_path = std::env::temp_dir().join(format!(
+            "barus_test_rebuild_duplicates_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        manager
+            .create_table("items", &CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        // crash 상황 재현: 같은 키의 Alive 레코드가 두 개 남아있음
+        let (old_position, _) = manager
+            .segment_manager
+            .append_record(
+                "items",
+                TableSegmentPayload {
+                    key: "item1".into(),
+                    value: "old".into(),
+                    version: 1,
+                    written_at: 0,
+                    value_type: ValueType::Raw,
+                },
+            )
+            .await
+            .unwrap();
+        manager
+            .segment_manager
+            .append_record(
+                "items",
+                TableSegmentPayload {
+                    key: "item1".into(),
+                    value: "new".into(),
+                    version: 2,
+                    written_at: 0,
+                    value_type: ValueType::Raw,
+                },
+            )
+            .await
+            .unwrap();
+
+        let indexed_count = manager.rebuild_index("items").await.unwrap();
+        assert_eq!(indexed_count, 1);
+
+        // 가장 최근 레코드가 인덱싱됨
+        match manager.get_value("items", "item1").await.unwrap() {
+            DisktableGetResult::Found { value, version, .. } => {
+                assert_eq!(value, b"new");
+                assert_eq!(version, 2);
+            }
+            _ => panic!("item1 should be found"),
+        }
+
+        // 이전 레코드는 삭제 표시됨
+        let (flag, record) = manager
+            .segment_manager
+            .find_record("items", old_position)
+            .await
+            .unwrap();
+        assert!(flag.is_deleted());
+        assert_eq!(record.value, b"old");
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_missing_index_is_rebuilt_on_first_access() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_missing_index_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        let options = CreateTableOptions {
+            secondary_indexes: vec![SecondaryIndexInfo {
+                name: "by_user".into(),
+                json_path: "user".into(),
+            }],
+            ..Default::default()
+        };
+        manager.create_table("items", &options).await.unwrap();
+        flush(
+            &manager,
+            "items",
+            &[
+                ("item1", Some(r#"{"user":"alice"}"#)),
+                ("item2", Some(r#"{"user":"bob"}"#)),
+                ("item3", Some(r#"{"user":"alice"}"#)),
+            ],
+        )
+        .await;
+        flush(&manager, "items", &[("item3", None)]).await;
+        drop(manager);
+
+        // crash 등으로 인덱스 파일이 모두 사라짐
+        std::fs::remove_dir_all(
+            base_path
+                .join(TABLES_DIRECTORY)
+                .join("items")
+                .join(TABLES_INDEX_DIRECTORY),
+        )
+        .unwrap();
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+
+        assert!(matches!(
+            manager.get_value("items", "item1").await.unwrap(),
+            DisktableGetResult::Found { .. }
+        ));
+        assert!(matches!(
+            manager.get_value("items", "item3").await.unwrap(),
+            DisktableGetResult::NotFound
+        ));
+        assert_eq!(
+            manager
+                .find_by_index("items", "by_user", "alice")
+                .await
+                .unwrap(),
+            vec!["item1".to_string()]
+        );
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_key_locations_match_segment_records() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_key_locations_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        manager
+            .create_table("items", &CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        for (version, key) in ["item3", "item1", "item2"].iter().enumerate() {
+            manager
+                .write_value("items", key, b"v1", ValueType::Raw, version as u64 + 1, 0)
+                .await
+                .unwrap();
+        }
+        // 덮어쓰면 새 레코드로 이동
+        manager
+            .write_value("items", "item2", b"v2", ValueType::Raw, 4, 0)
+            .await
+            .unwrap();
+
+        let locations: Vec<_> = manager
+            .key_locations("items")
+            .await
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .await
+            .unwrap();
+
+        let keys: Vec<_> = locations.iter().map(|(key, _, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["item1", "item2", "item3"]);
+
+        for (key, position, state) in locations {
+            let (flag, record) = manager
+                .segment_manager
+                .find_record("items", position)
+                .await
+                .unwrap();
+            assert_eq!(flag, state);
+            assert_eq!(state, RecordStateFlags::Alive);
+            assert_eq!(record.key, key);
+            if key == "item2" {
+                assert_eq!(record.value, b"v2");
+            }
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_record_at_position() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_record_at_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        manager
+            .create_table("items", &CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        manager
+            .write_value("items", "item1", b"v1", ValueType::Raw, 1, 0)
+            .await
+            .unwrap();
+        let position = manager
+            .index_manager
+            .find_record("items", "item1")
+            .await
+            .unwrap()
+            .unwrap();
+        manager
+            .write_value("items", "item1", b"v2", ValueType::Raw, 2, 0)
+            .await
+            .unwrap();
+
+        // 덮어쓴 이전 레코드도 위치로 읽힘
+        let (state, record) = manager.record_at("items", position.clone()).await.unwrap();
+        assert_eq!(state, RecordStateFlags::Deleted);
+        assert_eq!(record.key, "item1");
+        assert_eq!(record.value, b"v1");
+
+        // 레코드 중간, 없는 세그먼트
+        for invalid in [
+            TableRecordPosition {
+                offset: position.offset + 1,
+                ..position.clone()
+            },
+            TableRecordPosition {
+                segment_id: TableSegmentID::new(999),
+                offset: position.offset,
+            },
+        ] {
+            let error = manager.record_at("items", invalid).await.unwrap_err();
+            assert!(matches!(
+                error.error_code,
+                ErrorCodes::RecordPositionInvalid
+            ));
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_table_rewrites_outdated_layouts() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_migrate_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        manager
+            .create_table("items", &CreateTableOptions::default())
+            .await
+            .unwrap();
+        drop(manager);
+
+        // a segment written by older versions: one record per payload layout
+        let config = TableRecordBincodeCodec::CONFIG;
+        let mut page = vec![];
+        for payload in [
+            bincode::encode_to_vec(
+                LegacyTableSegmentPayload {
+                    key: "legacy".into(),
+                    value: "v1".into(),
+                },
+                config,
+            ),
+            bincode::encode_to_vec(
+                VersionedTableSegmentPayload {
+                    key: "versioned".into(),
+                    value: "v2".into(),
+                    version: 2,
+                },
+                config,
+            ),
+            bincode::encode_to_vec(
+                TimestampedTableSegmentPayload {
+                    key: "timestamped".into(),
+                    value: "v3".into(),
+                    version: 3,
+                    written_at: 1700000000000,
+                },
+                config,
+            ),
+            bincode::encode_to_vec(
+                TableSegmentPayload {
+                    key: "current".into(),
+                    value: "{}".into(),
+                    version: 4,
+                    written_at: 1700000000000,
+                    value_type: ValueType::Json,
+                },
+                config,
+            ),
+        ] {
+            let payload = payload.unwrap();
+            page.push(RecordStateFlags::Alive as u8);
+            page.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+            page.extend_from_slice(&payload);
+        }
+        page.resize(DISKTABLE_PAGE_SIZE as usize, 0);
+
+        let segment_directory = base_path
+            .join(TABLES_DIRECTORY)
+            .join("items")
+            .join(TABLES_SEGMENT_DIRECTORY);
+        std::fs::create_dir_all(&segment_directory).unwrap();
+        std::fs::write(segment_directory.join("0000000000000001.seg"), &page).unwrap();
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        assert_eq!(manager.rebuild_index("items").await.unwrap(), 4);
+
+        // dry run only counts
+        let report = manager.migrate_table("items", true).await.unwrap();
+        assert_eq!(report.live_records, 4);
+        assert_eq!(report.outdated_count(), 3);
+        assert_eq!(
+            report.outdated_records.keys().copied().collect::<Vec<_>>(),
+            vec![
+                RecordLayout::Legacy,
+                RecordLayout::Versioned,
+                RecordLayout::Timestamped
+            ]
+        );
+        assert_eq!(report.rewritten_records, 0);
+        assert_eq!(
+            manager
+                .migrate_table("items", true)
+                .await
+                .unwrap()
+                .outdated_count(),
+            3
+        );
+
+        assert_eq!(manager.write_stats.table("items").rewrite_bytes, 0);
+        let report = manager.migrate_table("items", false).await.unwrap();
+        assert_eq!(report.rewritten_records, 3);
+
+        // three records with a 9 byte header, their keys and values and the current payload fields
+        let rewrite_bytes = manager.write_stats.table("items").rewrite_bytes;
+        assert!(rewrite_bytes > 3 * (9 + 12), "{}", rewrite_bytes);
+
+        let report = manager.migrate_table("items", true).await.unwrap();
+        assert_eq!(report.live_records, 4);
+        assert_eq!(report.outdated_count(), 0);
+
+        // values and versions are kept
+        for (key, expected_value, expected_version, expected_written_at) in [
+            ("legacy", "v1", 0, 0),
+            ("versioned", "v2", 2, 0),
+            ("timestamped", "v3", 3, 1700000000000),
+            ("current", "{}", 4, 1700000000000),
+        ] {
+            match manager.get_value("items", key).await.unwrap() {
+                DisktableGetResult::Found {
+                    value,
+                    version,
+                    written_at,
+                    ..
+                } => {
+                    assert_eq!(value, expected_value.as_bytes());
+                    assert_eq!(version, expected_version);
+                    assert_eq!(written_at, expected_written_at);
+                }
+                _ => panic!("{} should be found", key),
+            }
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_read_promotes_record_from_dead_segment() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_read_promotion_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        manager
+            .create_table("items", &CreateTableOptions::default())
+            .await
+            .unwrap();
+        drop(manager);
+
+        // an older segment where 2 of 3 records are deleted, and an empty current segment
+        let config = TableRecordBincodeCodec::CONFIG;
+        let mut page = vec![];
+        for (key, state) in [
+            ("hot", RecordStateFlags::Alive),
+            ("gone1", RecordStateFlags::Deleted),
+            ("gone2", RecordStateFlags::Deleted),
+        ] {
+            let payload = bincode::encode_to_vec(
+                TableSegmentPayload {
+                    key: key.into(),
+                    value: "value".into(),
+                    version: 1,
+                    written_at: 1700000000000,
+                    value_type: ValueType::Raw,
+                },
+                config,
+            )
+            .unwrap();
+            page.push(state as u8);
+            page.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+            page.extend_from_slice(&payload);
+        }
+        page.resize(DISKTABLE_PAGE_SIZE as usize, 0);
+
+        let segment_directory = base_path
+            .join(TABLES_DIRECTORY)
+            .join("items")
+            .join(TABLES_SEGMENT_DIRECTORY);
+        std::fs::write(segment_directory.join("0000000000000001.seg"), &page).unwrap();
+        std::fs::write(
+            segment_directory.join("0000000000000002.seg"),
+            vec![0u8; DISKTABLE_PAGE_SIZE as usize],
+        )
+        .unwrap();
+
+        let manager = DiskTableManager::new(base_path.clone()).with_read_promotion(0.5, 10);
+        manager.initialize().await.unwrap();
+        assert_eq!(manager.rebuild_index("items").await.unwrap(), 1);
+
+        let old_position = manager
+            .index_manager
+            .find_record("items", "hot")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(old_position.segment_id.0, 1);
+
+        // the segment predates startup and was not counted yet. reads don't scan it
+        manager.read_value("items", "hot", true).await.unwrap();
+        assert_eq!(manager.read_promotions(), 0);
+        manager
+            .segment_manager
+            .live_records("items", &old_position.segment_id)
+            .await
+            .unwrap();
+
+        // an engine that takes no writes doesn't promote
+        manager.read_value("items", "hot", false).await.unwrap();
+        assert_eq!(manager.read_promotions(), 0);
+
+        match manager.read_value("items", "hot", true).await.unwrap() {
+            DisktableGetResult::Found { value, version, .. } => {
+                assert_eq!(value, b"value");
+                assert_eq!(version, 1);
+            }
+            _ => panic!("hot should be found"),
+        }
+        assert_eq!(manager.read_promotions(), 1);
+
+        // the index points to the copy in the current segment, the old one is deleted
+        let new_position = manager
+            .index_manager
+            .find_record("items", "hot")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(new_position.segment_id.0, 2);
+        let (state, record) = manager
+            .segment_manager
+            .find_record("items", new_position)
+            .await
+            .unwrap();
+        assert!(matches!(state, RecordStateFlags::Alive));
+        assert_eq!(record.written_at, 1700000000000);
+        let (state, _) = manager
+            .segment_manager
+            .find_record("items", old_position)
+            .await
+            .unwrap();
+        assert!(matches!(state, RecordStateFlags::Deleted));
+
+        // a record in the current segment stays where it is
+        manager.read_value("items", "hot", true).await.unwrap();
+        assert_eq!(manager.read_promotions(), 1);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_flush_removes_dead_segment_files() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_dead_segments_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        manager
+            .create_table("items", &CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        // segment 1: key0..key2, then appends move to segment 2
+        flush(
+            &manager,
+            "items",
+            &[
+                ("key0", Some("v0")),
+                ("key1", Some("v1")),
+                ("key2", Some("v2")),
+            ],
+        )
+        .await;
+        manager.segment_manager.fill_current_segment("items").await;
+        flush(&manager, "items", &[("key3", Some("v3"))]).await;
+
+        let segment_directory = base_path
+            .join(TABLES_DIRECTORY)
+            .join("items")
+            .join(TABLES_SEGMENT_DIRECTORY);
+        let first_segment = segment_directory.join("0000000000000001.seg");
+        let second_segment = segment_directory.join("0000000000000002.seg");
+
+        // a live record keeps the segment
+        flush(&manager, "items", &[("key0", None)]).await;
+        assert!(first_segment.exists());
+
+        flush(&manager, "items", &[("key1", None), ("key2", None)]).await;
+        assert!(!first_segment.exists());
+        assert!(second_segment.exists());
+
+        // no index entry points into the removed segment
+        let indexed = manager
+            .list_indexed_records("items", None, usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            indexed
+                .iter()
+                .map(|(key, position)| (key.as_str(), position.segment_id.0))
+                .collect::<Vec<_>>(),
+            vec![("key3", 2)]
+        );
+        for key in ["key0", "key1", "key2"] {
+            assert!(matches!(
+                manager.get_value("items", key).await.unwrap(),
+                DisktableGetResult::NotFound
+            ));
+        }
+        assert_eq!(manager.scan_table("items").await.unwrap().len(), 1);
+        assert!(manager.verify_table("items").await.unwrap().consistent);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_vacuum_relocates_live_records() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_vacuum_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        manager
+            .create_table("items", &CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        // segment 1: 3 of 4 records overwritten later in segment 2
+        flush(
+            &manager,
+            "items",
+            &[
+                ("a", Some("a1")),
+                ("b", Some("b1")),
+                ("c", Some("c1")),
+                ("d", Some("d1")),
+            ],
+        )
+        .await;
+        manager.segment_manager.fill_current_segment("items").await;
+        flush(
+            &manager,
+            "items",
+            &[("b", Some("b2")), ("c", Some("c2")), ("d", Some("d2"))],
+        )
+        .await;
+
+        let first_segment = base_path
+            .join(TABLES_DIRECTORY)
+            .join("items")
+            .join(TABLES_SEGMENT_DIRECTORY)
+            .join("0000000000000001.seg");
+        assert!(first_segment.exists());
+
+        let report = manager.vacuum_table("items", 0.9).await.unwrap();
+        assert_eq!(report.segments, 2);
+        assert_eq!(report.removed_segments, 0);
+        assert!(first_segment.exists());
+
+        let report = manager.vacuum_table("items", 0.5).await.unwrap();
+        assert_eq!(report.removed_segments, 1);
+        assert_eq!(report.relocated_records, 1);
+        assert_eq!(report.reclaimed_bytes, DISKTABLE_PAGE_SIZE as u64);
+        assert!(!first_segment.exists());
+
+        for (key, expected_value) in [("a", "a1"), ("b", "b2"), ("c", "c2"), ("d", "d2")] {
+            match manager.get_value("items", key).await.unwrap() {
+                DisktableGetResult::Found { value, .. } => {
+                    assert_eq!(value, expected_value.as_bytes())
+                }
+                _ => panic!("{} should be found", key),
+            }
+        }
+        let indexed = manager
+            .list_indexed_records("items", None, usize::MAX)
+            .await
+            .unwrap();
+        assert!(
+            indexed
+                .iter()
+                .all(|(_, position)| position.segment_id.0 == 2)
+        );
+        assert!(manager.verify_table("items").await.unwrap().consistent);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_vacuum_respects_compaction_throttle() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_vacuum_throttle_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let bytes_per_second = 10_000;
+        let manager = DiskTableManager::new(base_path.clone())
+            .with_compaction_throttle(Some(bytes_per_second));
+        manager.initialize().await.unwrap();
+        manager
+            .create_table("items", &CreateTableOptions::default())
+            .await
+            .unwrap();
+
+        // segment 1: 40 records of ~500 bytes, all relocated by the vacuum
+        let value = "v".repeat(500);
+        let keys: Vec<_> = (0..40).map(|i| format!("key{:02}", i)).collect();
+        let writes: Vec<_> = keys
+            .iter()
+            .map(|key| (key.as_str(), Some(value.as_str())))
+            .collect();
+        flush(&manager, "items", &writes).await;
+        manager.segment_manager.fill_current_segment("items").await;
+        flush(&manager, "items", &[("other", Some("v"))]).await;
+
+        let started_at = std::time::Instant::now();
+        let report = manager.vacuum_table("items", 0.0).await.unwrap();
+        let elapsed = started_at.elapsed();
+        assert_eq!(report.relocated_records, 40);
+
+        // one second of bytes passes right away, the rest at the cap
+        let stats = manager.compaction_stats();
+        assert_eq!(stats.max_bytes_per_second, Some(bytes_per_second));
+        assert!(stats.written_bytes > 2 * bytes_per_second);
+        let expected = std::time::Duration::from_secs_f64(
+            (stats.written_bytes - bytes_per_second) as f64 / bytes_per_second as f64,
+        );
+        assert!(
+            elapsed >= expected.mul_f64(0.9),
+            "vacuum of {} bytes took {:?}",
+            stats.written_bytes,
+            elapsed
+        );
+        assert!(stats.bytes_per_second > 0);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_segment_live_record_counts() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_live_records_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        manager
+            .create_table("items", &CreateTableOptions::default())
+            .await
+            .unwrap();
+        let segment_id = TableSegmentID::new(1);
+
+        flush(
+            &manager,
+            "items",
+            &[("a", Some("1")), ("b", Some("2")), ("c", Some("3"))],
+        )
+        .await;
+        assert_eq!(
+            manager
+                .segment_manager
+                .live_records("items", &segment_id)
+                .await
+                .unwrap(),
+            3
+        );
+
+        // an overwrite moves the key within the segment, a delete leaves a tombstone
+        flush(&manager, "items", &[("a", Some("10")), ("b", None)]).await;
+        assert_eq!(
+            manager
+                .segment_manager
+                .live_records("items", &segment_id)
+                .await
+                .unwrap(),
+            2
+        );
+        drop(manager);
+
+        // counted again from the segment after a restart
+        let manager = DiskTableManager::new(base_path.clone());
+        manager.initialize().await.unwrap();
+        assert_eq!(
+            manager
+                .segment_manager
+                .live_records("items", &segment_id)
+                .await
+                .unwrap(),
+            2
+        );
+
+        flush(&manager, "items", &[("a", None), ("c", None)]).await;
+        assert_eq!(
+            manager
+                .segment_manager
+                .live_records("items", &segment_id)
+                .await
+                .unwrap(),
+            0
+        );
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
 }
@@ -0,0 +1,52 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+// Read-triggered promotion settings (BARUS_READ_PROMOTION_DEAD_RATIO) and its rate limit.
+// Promotions are counted in one second windows: past max_per_second, reads don't promote until the next window
+#[derive(Debug)]
+pub struct ReadPromotion {
+    pub(crate) dead_ratio: f64,
+    max_per_second: u32,
+    // (window start, checks in the window)
+    window: std::sync::Mutex<(Instant, u32)>,
+    promoted: AtomicU64,
+}
+
+impl ReadPromotion {
+    pub fn new(dead_ratio: f64, max_per_second: u32) -> Self {
+        Self {
+            dead_ratio,
+            max_per_second: max_per_second.max(1),
+            window: std::sync::Mutex::new((Instant::now(), 0)),
+            promoted: AtomicU64::new(0),
+        }
+    }
+
+    // whether a read may promote its record now
+    pub fn try_acquire(&self) -> bool {
+        let mut window = self.window.lock().unwrap();
+
+        let now = Instant::now();
+        if now.duration_since(window.0) >= Duration::from_secs(1) {
+            *window = (now, 0);
+        }
+
+        if window.1 >= self.max_per_second {
+            return false;
+        }
+
+        window.1 += 1;
+        true
+    }
+
+    pub fn add_promoted(&self) {
+        self.promoted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // records promoted since startup
+    pub fn promoted(&self) -> u64 {
+        self.promoted.load(Ordering::Relaxed)
+    }
+}
@@ -2,7 +2,13 @@ use std::fmt::Debug;
 
 use bincode::config::{Configuration, Fixint, LittleEndian, NoLimit};
 
-use crate::{disktable::segment::record::TableSegmentPayload, errors};
+use crate::{
+    disktable::segment::record::{
+        LegacyTableSegmentPayload, RecordLayout, TableSegmentPayload,
+        TimestampedTableSegmentPayload, VersionedTableSegmentPayload,
+    },
+    errors,
+};
 
 pub trait TableRecordCodec: Debug {
     fn encode(&self, record: &TableSegmentPayload) -> errors::Result<Vec<u8>>;
@@ -11,17 +17,33 @@ pub trait TableRecordCodec: Debug {
         record: &TableSegmentPayload,
         buf: &mut [u8],
     ) -> errors::Result<usize>;
-    fn decode(&self, data: &[u8]) -> errors::Result<TableSegmentPayload>;
+    fn decode(&self, data: &[u8]) -> errors::Result<TableSegmentPayload> {
+        self.decode_with_layout(data).map(|(record, _)| record)
+    }
+    // also reports which payload layout the record was written in
+    fn decode_with_layout(
+        &self,
+        data: &[u8],
+    ) -> errors::Result<(TableSegmentPayload, RecordLayout)>;
 }
 
 #[derive(Debug)]
 pub struct TableRecordBincodeCodec;
 
 impl TableRecordBincodeCodec {
-    const CONFIG: Configuration<LittleEndian, Fixint, NoLimit> = bincode::config::standard()
-        .with_fixed_int_encoding()
-        .with_little_endian()
-        .with_no_limit();
+    pub(crate) const CONFIG: Configuration<LittleEndian, Fixint, NoLimit> =
+        bincode::config::standard()
+            .with_fixed_int_encoding()
+            .with_little_endian()
+            .with_no_limit();
+
+    // Some only if the whole record is consumed by the layout
+    fn decode_exact<T: bincode::Decode<()>>(data: &[u8]) -> Option<T> {
+        match bincode::decode_from_slice::<T, _>(data, Self::CONFIG) {
+            Ok((decoded, len)) if len == data.len() => Some(decoded),
+            _ => None,
+        }
+    }
 }
 
 impl TableRecordCodec for TableRecordBincodeCodec {
@@ -43,14 +65,113 @@ impl TableRecordCodec for TableRecordBincodeCodec {
         })
     }
 
-    fn decode(&self, data: &[u8]) -> errors::Result<TableSegmentPayload> {
+    fn decode_with_layout(
+        &self,
+        data: &[u8],
+    ) -> errors::Result<(TableSegmentPayload, RecordLayout)> {
         // bincode 2.x uses decode_from_slice with config
-        let (decoded, _len): (TableSegmentPayload, usize) =
-            bincode::decode_from_slice(data, Self::CONFIG).map_err(|e| {
-                errors::Errors::new(errors::ErrorCodes::TableRecordDecodeError)
-                    .with_message(e.to_string())
-            })?;
+        match bincode::decode_from_slice::<TableSegmentPayload, _>(data, Self::CONFIG) {
+            Ok((decoded, len)) if len == data.len() => Ok((decoded, RecordLayout::Current)),
+            current_result => {
+                // Records written before value types (or write timestamps, versioning) have fewer trailing fields.
+                if let Some(decoded) = Self::decode_exact::<TimestampedTableSegmentPayload>(data) {
+                    return Ok((decoded.into(), RecordLayout::Timestamped));
+                }
+                if let Some(decoded) = Self::decode_exact::<VersionedTableSegmentPayload>(data) {
+                    return Ok((decoded.into(), RecordLayout::Versioned));
+                }
+                if let Some(decoded) = Self::decode_exact::<LegacyTableSegmentPayload>(data) {
+                    return Ok((decoded.into(), RecordLayout::Legacy));
+                }
+
+                let message = match current_result {
+                    Ok((_, len)) => format!("Unexpected record length: {} of {}", len, data.len()),
+                    Err(e) => e.to_string(),
+                };
+
+                Err(
+                    errors::Errors::new(errors::ErrorCodes::TableRecordDecodeError)
+                        .with_message(message),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TableRecordBincodeCodec, TableRecordCodec};
+    use crate::{
+        disktable::segment::record::{
+            LegacyTableSegmentPayload, TableSegmentPayload, TimestampedTableSegmentPayload,
+            VersionedTableSegmentPayload,
+        },
+        value_type::ValueType,
+    };
+
+    #[test]
+    fn test_decode_legacy_payload() {
+        let legacy = LegacyTableSegmentPayload {
+            key: "key".to_string(),
+            value: b"value".to_vec(),
+        };
+        let bytes = bincode::encode_to_vec(&legacy, TableRecordBincodeCodec::CONFIG).unwrap();
+
+        let decoded = TableRecordBincodeCodec.decode(&bytes).unwrap();
+        assert_eq!(decoded.key, "key");
+        assert_eq!(decoded.value, b"value");
+        assert_eq!(decoded.version, 0);
+        assert_eq!(decoded.written_at, 0);
+    }
+
+    #[test]
+    fn test_decode_versioned_payload() {
+        let versioned = VersionedTableSegmentPayload {
+            key: "key".to_string(),
+            value: b"value".to_vec(),
+            version: 42,
+        };
+        let bytes = bincode::encode_to_vec(&versioned, TableRecordBincodeCodec::CONFIG).unwrap();
+
+        let decoded = TableRecordBincodeCodec.decode(&bytes).unwrap();
+        assert_eq!(decoded.key, "key");
+        assert_eq!(decoded.value, b"value");
+        assert_eq!(decoded.version, 42);
+        assert_eq!(decoded.written_at, 0);
+    }
+
+    #[test]
+    fn test_decode_timestamped_payload() {
+        let timestamped = TimestampedTableSegmentPayload {
+            key: "key".to_string(),
+            value: b"value".to_vec(),
+            version: 42,
+            written_at: 1700000000000,
+        };
+        let bytes = bincode::encode_to_vec(&timestamped, TableRecordBincodeCodec::CONFIG).unwrap();
+
+        let decoded = TableRecordBincodeCodec.decode(&bytes).unwrap();
+        assert_eq!(decoded.value, b"value");
+        assert_eq!(decoded.written_at, 1700000000000);
+        assert_eq!(decoded.value_type, ValueType::Raw);
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let payload = TableSegmentPayload {
+            key: "key".to_string(),
+            value: b"value".to_vec(),
+            version: 42,
+            written_at: 1700000000000,
+            value_type: ValueType::Json,
+        };
+        let bytes = TableRecordBincodeCodec.encode(&payload).unwrap();
 
-        Ok(decoded)
+        let decoded = TableRecordBincodeCodec.decode(&bytes).unwrap();
+        assert_eq!(decoded.key, "key");
+        assert_eq!(decoded.value, b"value");
+        assert_eq!(decoded.version, 42);
+        assert_eq!(decoded.written_at, 1700000000000);
+        assert_eq!(decoded.value_type, ValueType::Json);
     }
 }
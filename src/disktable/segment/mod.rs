@@ -1,4 +1,10 @@
-use std::{collections::HashMap, fmt::Debug, io::SeekFrom, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Debug,
+    io::SeekFrom,
+    path::PathBuf,
+    sync::Arc,
+};
 
 use tokio::{
     fs::{File, OpenOptions},
@@ -7,14 +13,18 @@ use tokio::{
 };
 
 use crate::{
+    checksum::crc32,
     config::{
-        DISKTABLE_PAGE_SIZE, DISKTABLE_SEGMENT_SIZE, TABLE_SEGMENT_RECORD_HEADER_SIZE,
-        TABLES_DIRECTORY, TABLES_SEGMENT_DIRECTORY,
+        DISKTABLE_PAGE_SIZE, DISKTABLE_SEGMENT_SIZE, MAX_OPEN_SEGMENTS_PER_TABLE,
+        REQUIRE_RECORD_CHECKSUM, TABLE_SEGMENT_LEGACY_RECORD_HEADER_SIZE,
+        TABLE_SEGMENT_RECORD_HEADER_SIZE, TABLES_DIRECTORY, TABLES_SEGMENT_DIRECTORY,
+        VALUE_BYTES_MAX_SIZE,
     },
     disktable::segment::{
         encode::{TableRecordBincodeCodec, TableRecordCodec},
+        pool::{SegmentHandle, SegmentHandlePool},
         position::TableRecordPosition,
-        record::{RecordStateFlags, TableSegmentPayload},
+        record::{RECORD_CHECKSUM_FLAG, RecordLayout, RecordStateFlags, TableSegmentPayload},
         segment_id::TableSegmentID,
         state::TableSegmentState,
     },
@@ -23,6 +33,7 @@ use crate::{
 };
 
 pub mod encode;
+pub mod pool;
 pub mod position;
 pub mod record;
 pub mod segment_id;
@@ -34,6 +45,19 @@ pub struct TableSegmentManager {
     base_path: PathBuf,
     tables_map: Arc<Mutex<HashMap<String, TableSegmentState>>>,
     file_rw_lock: Arc<Mutex<HashMap<String, Arc<RwLock<()>>>>>,
+    // segments written since the last sync_table, per table
+    dirty_segments: Arc<Mutex<HashMap<String, Vec<TableSegmentID>>>>,
+    // open segment files, reused by appends and point reads (BARUS_MAX_OPEN_SEGMENTS_PER_TABLE)
+    handle_pool: SegmentHandlePool,
+    // segments with records marked deleted since the last take_deleted_segments, per table
+    deleted_segments: Arc<Mutex<HashMap<String, HashSet<u64>>>>,
+    // segment files removed by delete_segment, per table. reads that looked up a position before get TableSegmentReclaimed
+    reclaimed_segments: Arc<Mutex<HashMap<String, HashSet<u64>>>>,
+    // record counts per segment (table -> segment ID -> counts). Kept up to date by appends and deletes;
+    // a segment that existed before startup is counted by a scan the first time it is needed (live_records)
+    record_counts: Arc<std::sync::Mutex<HashMap<String, HashMap<u64, SegmentRecordCounts>>>>,
+    // find_record rejects records without a checksum (BARUS_REQUIRE_RECORD_CHECKSUM)
+    require_record_checksum: bool,
 }
 
 impl TableSegmentManager {
@@ -42,10 +66,26 @@ impl TableSegmentManager {
             base_path,
             tables_map: Arc::new(Mutex::new(HashMap::new())),
             file_rw_lock: Arc::new(Mutex::new(HashMap::new())),
+            dirty_segments: Arc::new(Mutex::new(HashMap::new())),
+            handle_pool: SegmentHandlePool::new(*MAX_OPEN_SEGMENTS_PER_TABLE),
+            deleted_segments: Arc::new(Mutex::new(HashMap::new())),
+            reclaimed_segments: Arc::new(Mutex::new(HashMap::new())),
+            record_counts: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            require_record_checksum: *REQUIRE_RECORD_CHECKSUM,
             codec: Box::new(TableRecordBincodeCodec {}),
         }
     }
 
+    pub fn with_max_open_segments(mut self, max_open_per_table: usize) -> Self {
+        self.handle_pool = SegmentHandlePool::new(max_open_per_table);
+        self
+    }
+
+    pub fn with_require_record_checksum(mut self, require_record_checksum: bool) -> Self {
+        self.require_record_checksum = require_record_checksum;
+        self
+    }
+
     // Table Initialization
     pub async fn initialize_table(&self, table_name: &str) -> errors::Result<()> {
         let mut tables_map = self.tables_map.lock().await;
@@ -99,6 +139,8 @@ impl TableSegmentManager {
         let mut tables_map = self.tables_map.lock().await;
         let _ = tables_map.remove(table_name);
 
+        self.discard_table_segments(table_name).await;
+
         Ok(())
     }
 
@@ -113,6 +155,7 @@ impl TableSegmentManager {
             .join(TABLES_SEGMENT_DIRECTORY);
 
         // 1. 모든 세그먼트 파일 읽기 (파일만 필터링해서 파일명 반환)
+        // files whose name is not a segment ID are skipped, so they are never read as segments
         let mut segment_files: Vec<_> = std::fs::read_dir(&table_directory)
             .map_err(|e| {
                 errors::Errors::new(errors::ErrorCodes::WALSegmentFileOpenError)
@@ -127,12 +170,26 @@ impl TableSegmentManager {
                             .and_then(|name| name.to_str().map(|s| s.to_string()))
                             .unwrap_or_default();
 
+                        let segment_id = match TableSegmentID::try_from(file_name.as_str()) {
+                            Ok(segment_id) => segment_id,
+                            Err(error) => {
+                                log::warn!(
+                                    "Skipping '{}' in the segment directory of table '{}': {}",
+                                    file_name,
+                                    table_name,
+                                    error
+                                );
+                                return None;
+                            }
+                        };
+
                         let Ok(file_size) = e.metadata().map(|meta| meta.len() as u32) else {
                             return None;
                         };
 
                         Some(ListSegmentFileResultItem {
                             file_name,
+                            segment_id,
                             file_size,
                         })
                     } else {
@@ -142,9 +199,8 @@ impl TableSegmentManager {
             })
             .collect();
 
-        // 2. 파일명 기준 정렬
-        segment_files
-            .sort_by(|file1, file2| file1.file_name.as_str().cmp(file2.file_name.as_str()));
+        // 2. 세그먼트 ID 기준 정렬
+        segment_files.sort_by_key(|file| file.segment_id.0);
 
         Ok(segment_files)
     }
@@ -154,6 +210,14 @@ impl TableSegmentManager {
         table_name: &str,
         segment_file_name: &str,
     ) -> errors::Result<Vec<ScanSegmentFileResult>> {
+        // record positions point at this ID, so a name that doesn't parse must not become segment 0
+        let segment_id = TableSegmentID::try_from(segment_file_name).map_err(|_| {
+            errors::Errors::new(errors::ErrorCodes::TableSegmentIDParseError).with_message(format!(
+                "'{}' is not a segment file name",
+                segment_file_name
+            ))
+        })?;
+
         let file_path = self
             .base_path
             .join(TABLES_DIRECTORY)
@@ -161,6 +225,13 @@ impl TableSegmentManager {
             .join(TABLES_SEGMENT_DIRECTORY)
             .join(segment_file_name);
 
+        // a listed segment may be removed before it is opened (delete_segment). the open file stays readable
+        let segment_file_lock = self.lock_segment_file(table_name, &segment_id).await;
+        let read_lock = segment_file_lock.read().await;
+        if self.is_reclaimed(table_name, &segment_id).await {
+            return Ok(vec![]);
+        }
+
         let mut file = File::open(&file_path).await.map_err(|e| {
             errors::Errors::new(errors::ErrorCodes::FileOpenError).with_message(format!(
                 "Failed to open file '{}': {}",
@@ -168,6 +239,8 @@ impl TableSegmentManager {
                 e
             ))
         })?;
+        drop(read_lock);
+
         let metadata = file.metadata().await.map_err(|e| {
             errors::Errors::new(errors::ErrorCodes::FileMetadataError).with_message(format!(
                 "Failed to get metadata for file '{}': {}",
@@ -200,7 +273,8 @@ impl TableSegmentManager {
                 let real_offset = page_start_offset + page_offset as u32;
 
                 // read from header byte
-                let flag_header = page_buffer[page_offset].into();
+                let state_byte = page_buffer[page_offset];
+                let flag_header = state_byte.into();
                 page_offset += 1;
 
                 // process header byte
@@ -217,27 +291,60 @@ impl TableSegmentManager {
                     }
                 }
 
-                let size_header_bytes = [
-                    page_buffer[page_offset],
-                    page_buffer[page_offset + 1],
-                    page_buffer[page_offset + 2],
-                    page_buffer[page_offset + 3],
-                ];
-                let size_header = u32::from_be_bytes(size_header_bytes);
+                // a record never spans pages: a header or size running past it is a torn write or bit rot
+                let header_end = page_offset - 1 + record_header_size(state_byte) as usize;
+                if header_end > DISKTABLE_PAGE_SIZE as usize {
+                    return Err(corrupt_record(
+                        &segment_id,
+                        real_offset,
+                        "header runs past the page",
+                    ));
+                }
+
+                let size_header = u32::from_be_bytes(
+                    page_buffer[page_offset..page_offset + 4]
+                        .try_into()
+                        .unwrap(),
+                );
                 page_offset += 4;
 
-                let payload = &page_buffer[page_offset..page_offset + size_header as usize];
-                page_offset += size_header as usize;
+                let checksum = if state_byte & RECORD_CHECKSUM_FLAG != 0 {
+                    let checksum = u32::from_be_bytes(
+                        page_buffer[page_offset..page_offset + 4]
+                            .try_into()
+                            .unwrap(),
+                    );
+                    page_offset += 4;
+                    Some(checksum)
+                } else {
+                    None
+                };
+
+                let payload_end = page_offset as u64 + size_header as u64;
+                if payload_end > DISKTABLE_PAGE_SIZE as u64 {
+                    return Err(corrupt_record(
+                        &segment_id,
+                        real_offset,
+                        &format!("size {} doesn't fit in the page", size_header),
+                    ));
+                }
 
-                let record = self.codec.decode(payload)?;
+                let payload = &page_buffer[page_offset..payload_end as usize];
+                page_offset = payload_end as usize;
+
+                verify_checksum(&segment_id, real_offset, payload, checksum)?;
+                let (record, layout) = self.codec.decode_with_layout(payload).map_err(|error| {
+                    corrupt_record(&segment_id, real_offset, &error.to_string())
+                })?;
 
                 scan_items.push(ScanSegmentFileResult {
                     state_flags: flag_header,
                     position: TableRecordPosition {
-                        segment_id: TableSegmentID::try_from(segment_file_name).unwrap_or_default(),
+                        segment_id: segment_id.clone(),
                         offset: real_offset,
                     },
                     payload: record,
+                    layout,
                 });
             }
         }
@@ -250,8 +357,7 @@ impl TableSegmentManager {
             let segment_files = self.list_segment_files(&table_name).await?;
 
             let last_segment_id = match segment_files.last() {
-                Some(file) => TableSegmentID::try_from(file.file_name.as_str())
-                    .unwrap_or(TableSegmentID::new(0)),
+                Some(file) => file.segment_id.clone(),
                 None => TableSegmentID::new(0),
             };
 
@@ -274,19 +380,28 @@ impl TableSegmentManager {
         Ok(())
     }
 
+    // "<ID>.seg", or "<ID>" if the segment was created before the extension was added
+    fn segment_file_path(&self, table_name: &str, segment_id: &TableSegmentID) -> PathBuf {
+        let segment_directory = self
+            .base_path
+            .join(TABLES_DIRECTORY)
+            .join(table_name)
+            .join(TABLES_SEGMENT_DIRECTORY);
+
+        let legacy_file_path = segment_directory.join(segment_id.legacy_file_name());
+        if legacy_file_path.exists() {
+            return legacy_file_path;
+        }
+
+        segment_directory.join(segment_id.file_name())
+    }
+
     pub async fn describe_segment_file(
         &self,
         table_name: &str,
         segment_id: &TableSegmentID,
     ) -> errors::Result<TableSegmentState> {
-        let file_name: String = segment_id.into();
-
-        let file_path = self
-            .base_path
-            .join(TABLES_DIRECTORY)
-            .join(table_name)
-            .join(TABLES_SEGMENT_DIRECTORY)
-            .join(file_name);
+        let file_path = self.segment_file_path(table_name, segment_id);
 
         let mut file = File::open(&file_path).await.map_err(|e| {
             errors::Errors::new(errors::ErrorCodes::FileOpenError).with_message(format!(
@@ -324,21 +439,17 @@ impl TableSegmentManager {
                 })?;
 
             // read from header byte
-            let flag_header = file
-                .read_u8()
-                .await
-                .map_err(|e| {
-                    errors::Errors::new(errors::ErrorCodes::FileReadError).with_message(format!(
-                        "Failed to read header byte at offset {} in file '{}': {}",
-                        flag_header_offset,
-                        file_path.display(),
-                        e
-                    ))
-                })?
-                .into();
+            let state_byte = file.read_u8().await.map_err(|e| {
+                errors::Errors::new(errors::ErrorCodes::FileReadError).with_message(format!(
+                    "Failed to read header byte at offset {} in file '{}': {}",
+                    flag_header_offset,
+                    file_path.display(),
+                    e
+                ))
+            })?;
 
             // process header byte
-            match flag_header {
+            match RecordStateFlags::from(state_byte) {
                 RecordStateFlags::Nothing => {
                     // end of data
                     break;
@@ -360,7 +471,17 @@ impl TableSegmentManager {
                 ))
             })?;
 
-            offset += TABLE_SEGMENT_RECORD_HEADER_SIZE + size_header;
+            // u64: a corrupt size must not wrap the offset around
+            let record_end =
+                offset as u64 + record_header_size(state_byte) as u64 + size_header as u64;
+            if record_end > file_size as u64 {
+                return Err(corrupt_record(
+                    segment_id,
+                    offset,
+                    &format!("size {} runs past the end of the segment", size_header),
+                ));
+            }
+            offset = record_end as u32;
         }
 
         Ok(TableSegmentState {
@@ -371,32 +492,89 @@ impl TableSegmentManager {
         })
     }
 
+    // Pooled handle of the segment file, opened if it is not open (or was evicted).
+    // Reads share it; writes take turns on its writer (SegmentHandle)
     pub async fn get_segment_file(
         &self,
         table_name: &str,
         segment_id: &TableSegmentID,
-    ) -> errors::Result<File> {
-        // 2. get segment file
-        let segment_filename: String = segment_id.into();
+    ) -> errors::Result<Arc<SegmentHandle>> {
+        if let Some(handle) = self.handle_pool.get(table_name, segment_id) {
+            return Ok(handle);
+        }
 
-        let new_segment_file_path = self
-            .base_path
-            .join(TABLES_DIRECTORY)
-            .join(table_name)
-            .join(TABLES_SEGMENT_DIRECTORY)
-            .join(segment_filename);
+        let segment_file_path = self.segment_file_path(table_name, segment_id);
 
+        let open_error = |err: std::io::Error| {
+            errors::Errors::new(errors::ErrorCodes::TableSegmentFileOpenError)
+                .with_message(err.to_string())
+        };
         let file = OpenOptions::new()
             .write(true)
             .read(true)
-            .open(new_segment_file_path)
+            .open(segment_file_path)
             .await
-            .map_err(|err| {
-                errors::Errors::new(errors::ErrorCodes::TableSegmentFileOpenError)
-                    .with_message(err.to_string())
-            })?;
+            .map_err(open_error)?;
+        let handle = SegmentHandle::new(file.into_std().await).map_err(open_error)?;
 
-        Ok(file)
+        Ok(self.handle_pool.insert(table_name, segment_id, handle))
+    }
+
+    // the next append starts a new segment (tests without writing a whole segment)
+    #[cfg(test)]
+    pub(crate) async fn fill_current_segment(&self, table_name: &str) {
+        let mut tables_map = self.tables_map.lock().await;
+        let state = tables_map.get_mut(table_name).unwrap();
+        state.segment_file_size = DISKTABLE_SEGMENT_SIZE;
+        state.current_page_offset = DISKTABLE_SEGMENT_SIZE;
+    }
+
+    // segment appends currently go to. None if the table has no segment yet
+    pub async fn current_segment_id(&self, table_name: &str) -> Option<TableSegmentID> {
+        let tables_map = self.tables_map.lock().await;
+
+        tables_map
+            .get(table_name)
+            .map(|state| state.last_segment_id.clone())
+            .filter(|segment_id| segment_id.0 > 0)
+    }
+
+    // scan_segment_file by segment ID
+    pub async fn scan_segment(
+        &self,
+        table_name: &str,
+        segment_id: &TableSegmentID,
+    ) -> errors::Result<Vec<ScanSegmentFileResult>> {
+        let file_path = self.segment_file_path(table_name, segment_id);
+        let file_name = file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        self.scan_segment_file(table_name, &file_name).await
+    }
+
+    // Share of the segment's records that are not alive (0 for an empty segment), from the record counts.
+    // None if the segment was not counted yet: it is not scanned here, so reads can call this.
+    // A segment with deletes since startup is counted by the next flush (unindex_dead_segments)
+    pub fn segment_dead_ratio(&self, table_name: &str, segment_id: &TableSegmentID) -> Option<f64> {
+        let counts = *self
+            .record_counts
+            .lock()
+            .unwrap()
+            .get(table_name)?
+            .get(&segment_id.0)?;
+
+        if counts.total == 0 {
+            return Some(0.0);
+        }
+
+        Some(1.0 - counts.live as f64 / counts.total as f64)
+    }
+
+    // open segment file handles per table
+    pub fn open_segment_counts(&self) -> BTreeMap<String, usize> {
+        self.handle_pool.open_counts()
     }
 
     // new segment file (DISKTABLE_PAGE_SIZE start)
@@ -412,7 +590,18 @@ impl TableSegmentManager {
         table_state.current_page_offset = 0;
         table_state.segment_file_size = size;
 
-        let segment_filename: String = (&table_state.last_segment_id).into();
+        let segment_filename = table_state.last_segment_id.file_name();
+
+        // a new segment has no records. the count is known from the start
+        self.record_counts
+            .lock()
+            .unwrap()
+            .entry(table_name.to_owned())
+            .or_default()
+            .insert(
+                table_state.last_segment_id.0,
+                SegmentRecordCounts::default(),
+            );
 
         let new_segment_file_path = self
             .base_path
@@ -428,8 +617,11 @@ impl TableSegmentManager {
             .open(new_segment_file_path)
             .await
             .map_err(|err| {
-                errors::Errors::new(errors::ErrorCodes::TableSegmentFileCreateError)
-                    .with_message(err.to_string())
+                errors::Errors::new(errors::io_error_code(
+                    &err,
+                    errors::ErrorCodes::TableSegmentFileCreateError,
+                ))
+                .with_message(err.to_string())
             })?;
 
         file_resize_and_set_zero(&mut file, size).await?;
@@ -443,19 +635,19 @@ impl TableSegmentManager {
         table_name: &str,
         table_state: &mut TableSegmentState,
         size: u32,
-    ) -> errors::Result<File> {
-        let mut file = self
+    ) -> errors::Result<()> {
+        let handle = self
             .get_segment_file(table_name, &table_state.last_segment_id)
             .await?;
 
         // 3. Expand segment file
-        file_resize_and_set_zero(&mut file, size).await?;
+        file_resize_and_set_zero(&mut *handle.lock_writer().await, size).await?;
 
         table_state.current_page_offset = table_state.segment_file_size;
         table_state.current_page_index += 1;
         table_state.segment_file_size += size;
 
-        Ok(file)
+        Ok(())
     }
 
     // Provides protection for segment areas that have already been created
@@ -477,29 +669,46 @@ impl TableSegmentManager {
         }
     }
 
-    // Appends a record to the segment file.
+    // Appends a record to the segment file. Returns its position and size in bytes (header included).
     pub async fn append_record(
         &self,
         table_name: &str,
         record: TableSegmentPayload,
-    ) -> errors::Result<TableRecordPosition> {
+    ) -> errors::Result<(TableRecordPosition, u32)> {
+        // 0. Size check. API layer validates too, but internal callers (WAL replay etc.) may not
+        if record.value.len() > VALUE_BYTES_MAX_SIZE {
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::ValueSizeTooLarge).with_message(format!(
+                    "Value size {} exceeds maximum {}",
+                    record.value.len(),
+                    VALUE_BYTES_MAX_SIZE
+                )),
+            );
+        }
+
         // 1. Payload Prepare
         let encoded_bytes = self.codec.encode(&record)?;
 
-        let state_byte = RecordStateFlags::Alive;
+        let state_byte = RecordStateFlags::Alive as u8 | RECORD_CHECKSUM_FLAG;
         let record_size = encoded_bytes.len() as u32;
-        let record_size_bytes = record_size.to_be_bytes();
-        assert!(record_size_bytes.len() == 4);
 
-        let header: [u8; 5] = [
-            state_byte as u8,
-            record_size_bytes[0],
-            record_size_bytes[1],
-            record_size_bytes[2],
-            record_size_bytes[3],
-        ];
+        // state, size, CRC32 of the payload
+        let mut header = [0u8; TABLE_SEGMENT_RECORD_HEADER_SIZE as usize];
+        header[0] = state_byte;
+        header[1..5].copy_from_slice(&record_size.to_be_bytes());
+        header[5..9].copy_from_slice(&crc32(&encoded_bytes).to_be_bytes());
 
         let total_bytes = header.len() as u32 + encoded_bytes.len() as u32;
+
+        // a record never spans pages (scan_segment_file reads page by page)
+        if total_bytes > DISKTABLE_PAGE_SIZE {
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::ValueSizeTooLarge).with_message(format!(
+                    "Record size {} exceeds page size {}",
+                    total_bytes, DISKTABLE_PAGE_SIZE
+                )),
+            );
+        }
         let mut write_buffer = Vec::with_capacity(total_bytes as usize);
         write_buffer.extend_from_slice(&header);
         write_buffer.extend_from_slice(&encoded_bytes);
@@ -525,10 +734,10 @@ impl TableSegmentManager {
         }
 
         // 4. If there is enough space, write the data immediately.
-        // TODO: managing file handler pool for I/O performance
-        let mut file = self
+        let handle = self
             .get_segment_file(table_name, &table.last_segment_id)
             .await?;
+        let mut file = handle.lock_writer().await;
 
         file.seek(SeekFrom::Start(table.current_page_offset as u64))
             .await
@@ -537,9 +746,21 @@ impl TableSegmentManager {
                     .with_message(format!("Failed to seek file: {}", e))
             })?;
         file.write_all(&write_buffer).await.map_err(|e| {
-            errors::Errors::new(errors::ErrorCodes::TableSegmentFileWriteError)
-                .with_message(format!("Failed to write data: {}", e))
+            errors::Errors::new(errors::io_error_code(
+                &e,
+                errors::ErrorCodes::TableSegmentFileWriteError,
+            ))
+            .with_message(format!("Failed to write data: {}", e))
+        })?;
+        // wait for the write here, so its error is not reported to the next user of the pooled handle
+        file.flush().await.map_err(|e| {
+            errors::Errors::new(errors::io_error_code(
+                &e,
+                errors::ErrorCodes::TableSegmentFileWriteError,
+            ))
+            .with_message(format!("Failed to write data: {}", e))
         })?;
+        drop(file);
 
         let position = TableRecordPosition {
             segment_id: table.last_segment_id.clone(),
@@ -548,7 +769,10 @@ impl TableSegmentManager {
 
         table.current_page_offset += total_bytes;
 
-        Ok(position)
+        self.mark_dirty(table_name, &position.segment_id).await;
+        self.add_record_counts(table_name, &position.segment_id, 1, 1);
+
+        Ok((position, total_bytes))
     }
 
     // Finds a record in the segment file.
@@ -562,36 +786,84 @@ impl TableSegmentManager {
             .await;
         let read_lock = segment_file_lock.read().await;
 
-        let mut file = self
+        if self.is_reclaimed(table_name, &position.segment_id).await {
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::TableSegmentReclaimed).with_message(
+                    format!(
+                        "Segment {} of table '{}' was removed",
+                        position.segment_id.file_name(),
+                        table_name
+                    ),
+                ),
+            );
+        }
+
+        let handle = self
             .get_segment_file(table_name, &position.segment_id)
             .await?;
 
-        file.seek(SeekFrom::Start(position.offset as u64))
+        // state byte and size header
+        let header = handle
+            .read_exact_at(position.offset as u64, 5)
             .await
             .map_err(|e| {
-                errors::Errors::new(errors::ErrorCodes::FileSeekError)
-                    .with_message(format!("Failed to seek file: {}", e))
+                errors::Errors::new(errors::ErrorCodes::FileReadError)
+                    .with_message(format!("Failed to read record header: {}", e))
             })?;
-
-        let flag_byte = file.read_u8().await.map_err(|e| {
-            errors::Errors::new(errors::ErrorCodes::FileReadError)
-                .with_message(format!("Failed to read flag byte: {}", e))
-        })?;
+        let flag_byte = header[0];
         let flag = RecordStateFlags::from(flag_byte);
 
-        let size_header = file.read_u32().await.map_err(|e| {
-            errors::Errors::new(errors::ErrorCodes::FileReadError)
-                .with_message(format!("Failed to read size header: {}", e))
-        })?;
+        // a stale or corrupt index entry can point anywhere: reject what can't be the start of a record
+        let invalid_record = |reason: String| {
+            errors::Errors::new(errors::ErrorCodes::TableRecordInvalid).with_message(format!(
+                "No valid record at segment {} offset {} of table '{}' ({}). The index may be stale, run reindex to rebuild it",
+                position.segment_id.file_name(),
+                position.offset,
+                table_name,
+                reason
+            ))
+        };
 
-        let mut buffer = vec![0; size_header as usize];
-        file.read_exact(&mut buffer).await.map_err(|e| {
-            errors::Errors::new(errors::ErrorCodes::FileReadError)
-                .with_message(format!("Failed to read data: {}", e))
-        })?;
+        if !matches!(flag, RecordStateFlags::Alive | RecordStateFlags::Deleted) {
+            return Err(invalid_record(format!("state byte {:#04X}", flag_byte)));
+        }
+
+        let size_header = u32::from_be_bytes(header[1..5].try_into().unwrap());
+
+        // a record never spans pages
+        let page_offset = position.offset % DISKTABLE_PAGE_SIZE;
+        let record_end =
+            page_offset as u64 + record_header_size(flag_byte) as u64 + size_header as u64;
+        if size_header == 0 || record_end > DISKTABLE_PAGE_SIZE as u64 {
+            return Err(invalid_record(format!(
+                "size {} doesn't fit in the page",
+                size_header
+            )));
+        }
+
+        if self.require_record_checksum && flag_byte & RECORD_CHECKSUM_FLAG == 0 {
+            return Err(invalid_record("no checksum".to_string()));
+        }
+
+        // checksum (if any) and payload
+        let checksum_size = record_header_size(flag_byte) - 5;
+        let mut buffer = handle
+            .read_exact_at(
+                position.offset as u64 + 5,
+                (checksum_size + size_header) as usize,
+            )
+            .await
+            .map_err(|e| {
+                errors::Errors::new(errors::ErrorCodes::FileReadError)
+                    .with_message(format!("Failed to read data: {}", e))
+            })?;
+        let checksum = (checksum_size > 0)
+            .then(|| u32::from_be_bytes(buffer[..checksum_size as usize].try_into().unwrap()));
+        buffer.drain(..checksum_size as usize);
 
         drop(read_lock);
 
+        verify_checksum(&position.segment_id, position.offset, &buffer, checksum)?;
         let record = self.codec.decode(&buffer)?;
 
         Ok((flag, record))
@@ -608,9 +880,10 @@ impl TableSegmentManager {
             .await;
         let _read_lock = segment_file_lock.read().await;
 
-        let mut file = self
+        let handle = self
             .get_segment_file(table_name, &position.segment_id)
             .await?;
+        let mut file = handle.lock_writer().await;
 
         file.seek(SeekFrom::Start(position.offset as u64))
             .await
@@ -619,20 +892,263 @@ impl TableSegmentManager {
                     .with_message(format!("Failed to seek file: {}", e))
             })?;
 
-        let delete_flag = RecordStateFlags::Deleted as u8;
+        // keep the checksum flag, it tells readers the header size
+        let state_byte = file.read_u8().await.map_err(|e| {
+            errors::Errors::new(errors::ErrorCodes::FileReadError)
+                .with_message(format!("Failed to read flag byte: {}", e))
+        })?;
+        let delete_flag = RecordStateFlags::Deleted as u8 | (state_byte & RECORD_CHECKSUM_FLAG);
+        let was_alive = matches!(RecordStateFlags::from(state_byte), RecordStateFlags::Alive);
 
+        file.seek(SeekFrom::Start(position.offset as u64))
+            .await
+            .map_err(|e| {
+                errors::Errors::new(errors::ErrorCodes::FileSeekError)
+                    .with_message(format!("Failed to seek file: {}", e))
+            })?;
         file.write_u8(delete_flag).await.map_err(|e| {
             errors::Errors::new(errors::ErrorCodes::FileWriteError)
                 .with_message(format!("Failed to write delete flag: {}", e))
         })?;
+        file.flush().await.map_err(|e| {
+            errors::Errors::new(errors::ErrorCodes::FileWriteError)
+                .with_message(format!("Failed to write delete flag: {}", e))
+        })?;
+
+        drop(file);
+
+        self.mark_dirty(table_name, &position.segment_id).await;
+        if was_alive {
+            self.add_record_counts(table_name, &position.segment_id, -1, 0);
+        }
+        self.deleted_segments
+            .lock()
+            .await
+            .entry(table_name.to_owned())
+            .or_default()
+            .insert(position.segment_id.0);
 
         Ok(())
     }
+
+    // Segments that had records marked deleted since the last call, except the current one
+    // (it stays a candidate until appends move to a new segment)
+    pub async fn take_deleted_segments(&self, table_name: &str) -> Vec<TableSegmentID> {
+        let current_segment_id = self.current_segment_id(table_name).await;
+
+        let mut deleted_segments = self.deleted_segments.lock().await;
+        let Some(segment_ids) = deleted_segments.get_mut(table_name) else {
+            return vec![];
+        };
+
+        let mut taken: Vec<_> = segment_ids
+            .iter()
+            .copied()
+            .filter(|segment_id| current_segment_id.as_ref().map(|id| id.0) != Some(*segment_id))
+            .collect();
+        segment_ids.retain(|segment_id| !taken.contains(segment_id));
+        taken.sort();
+
+        taken.into_iter().map(TableSegmentID::new).collect()
+    }
+
+    // Removes a segment file. The caller makes sure no index entry points into it
+    pub async fn delete_segment(
+        &self,
+        table_name: &str,
+        segment_id: &TableSegmentID,
+    ) -> errors::Result<()> {
+        let segment_file_lock = self.lock_segment_file(table_name, segment_id).await;
+        let _write_lock = segment_file_lock.write().await;
+
+        let file_path = self.segment_file_path(table_name, segment_id);
+        tokio::fs::remove_file(&file_path).await.map_err(|e| {
+            errors::Errors::new(errors::ErrorCodes::FileDeleteError).with_message(format!(
+                "Failed to delete segment file '{}': {}",
+                file_path.display(),
+                e
+            ))
+        })?;
+
+        self.handle_pool.close_segment(table_name, segment_id);
+        if let Some(segment_ids) = self.dirty_segments.lock().await.get_mut(table_name) {
+            segment_ids.retain(|dirty_segment_id| dirty_segment_id.0 != segment_id.0);
+        }
+        if let Some(segment_ids) = self.deleted_segments.lock().await.get_mut(table_name) {
+            segment_ids.remove(&segment_id.0);
+        }
+        if let Some(segments) = self.record_counts.lock().unwrap().get_mut(table_name) {
+            segments.remove(&segment_id.0);
+        }
+        self.reclaimed_segments
+            .lock()
+            .await
+            .entry(table_name.to_owned())
+            .or_default()
+            .insert(segment_id.0);
+
+        Ok(())
+    }
+
+    // Alive records in the segment. Counted by a scan if the segment was not written since startup,
+    // so call it under the table lock (no appends or deletes meanwhile)
+    pub async fn live_records(
+        &self,
+        table_name: &str,
+        segment_id: &TableSegmentID,
+    ) -> errors::Result<u64> {
+        if let Some(counts) = self
+            .record_counts
+            .lock()
+            .unwrap()
+            .get(table_name)
+            .and_then(|segments| segments.get(&segment_id.0))
+        {
+            return Ok(counts.live);
+        }
+
+        let scan_items = self.scan_segment(table_name, segment_id).await?;
+        let counts = SegmentRecordCounts {
+            live: scan_items
+                .iter()
+                .filter(|item| matches!(item.state_flags, RecordStateFlags::Alive))
+                .count() as u64,
+            total: scan_items.len() as u64,
+        };
+
+        self.record_counts
+            .lock()
+            .unwrap()
+            .entry(table_name.to_owned())
+            .or_default()
+            .insert(segment_id.0, counts);
+
+        Ok(counts.live)
+    }
+
+    // adjusts known counts. segments not counted yet are left to live_records
+    fn add_record_counts(
+        &self,
+        table_name: &str,
+        segment_id: &TableSegmentID,
+        live_delta: i64,
+        appended: u64,
+    ) {
+        if let Some(counts) = self
+            .record_counts
+            .lock()
+            .unwrap()
+            .get_mut(table_name)
+            .and_then(|segments| segments.get_mut(&segment_id.0))
+        {
+            counts.live = counts.live.saturating_add_signed(live_delta);
+            counts.total += appended;
+        }
+    }
+
+    async fn is_reclaimed(&self, table_name: &str, segment_id: &TableSegmentID) -> bool {
+        self.reclaimed_segments
+            .lock()
+            .await
+            .get(table_name)
+            .is_some_and(|segment_ids| segment_ids.contains(&segment_id.0))
+    }
+
+    async fn mark_dirty(&self, table_name: &str, segment_id: &TableSegmentID) {
+        let mut dirty_segments = self.dirty_segments.lock().await;
+        let segment_ids = dirty_segments.entry(table_name.to_owned()).or_default();
+
+        if !segment_ids.contains(segment_id) {
+            segment_ids.push(segment_id.clone());
+        }
+    }
+
+    // the segment files were deleted (ex: table dropped). Forgets their dirty state and open handles
+    pub async fn discard_table_segments(&self, table_name: &str) {
+        self.dirty_segments.lock().await.remove(table_name);
+        self.deleted_segments.lock().await.remove(table_name);
+        // segment IDs start over
+        self.reclaimed_segments.lock().await.remove(table_name);
+        self.record_counts.lock().unwrap().remove(table_name);
+        self.handle_pool.close_table(table_name);
+    }
+
+    // sync_data the segment files written since the last call. Returns the number of synced files
+    pub async fn sync_table(&self, table_name: &str) -> errors::Result<usize> {
+        let Some(segment_ids) = self.dirty_segments.lock().await.remove(table_name) else {
+            return Ok(0);
+        };
+
+        for (i, segment_id) in segment_ids.iter().enumerate() {
+            let result = match self.get_segment_file(table_name, segment_id).await {
+                Ok(handle) => handle.lock_writer().await.sync_data().await.map_err(|e| {
+                    errors::Errors::new(errors::io_error_code(
+                        &e,
+                        errors::ErrorCodes::TableSegmentFileWriteError,
+                    ))
+                    .with_message(format!("Failed to sync segment file: {}", e))
+                }),
+                Err(error) => Err(error),
+            };
+
+            if let Err(error) = result {
+                // keep the rest for the next flush
+                for segment_id in &segment_ids[i..] {
+                    self.mark_dirty(table_name, segment_id).await;
+                }
+                return Err(error);
+            }
+        }
+
+        Ok(segment_ids.len())
+    }
+}
+
+// header size of the record starting with this state byte
+fn record_header_size(state_byte: u8) -> u32 {
+    if state_byte & RECORD_CHECKSUM_FLAG != 0 {
+        TABLE_SEGMENT_RECORD_HEADER_SIZE
+    } else {
+        TABLE_SEGMENT_LEGACY_RECORD_HEADER_SIZE
+    }
+}
+
+// torn write or bit rot. None: written before checksums existed
+fn verify_checksum(
+    segment_id: &TableSegmentID,
+    offset: u32,
+    payload: &[u8],
+    checksum: Option<u32>,
+) -> errors::Result<()> {
+    match checksum {
+        Some(checksum) if crc32(payload) != checksum => {
+            Err(corrupt_record(segment_id, offset, "checksum mismatch"))
+        }
+        _ => Ok(()),
+    }
+}
+
+// a record whose bytes can't be read back (TableRecordDecodeError)
+fn corrupt_record(segment_id: &TableSegmentID, offset: u32, reason: &str) -> errors::Errors {
+    errors::Errors::new(errors::ErrorCodes::TableRecordDecodeError).with_message(format!(
+        "Corrupt record at segment {} offset {}: {}",
+        segment_id.file_name(),
+        offset,
+        reason
+    ))
+}
+
+// records of a segment: all appended ones, and those still alive
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SegmentRecordCounts {
+    pub live: u64,
+    pub total: u64,
 }
 
 #[derive(Debug)]
 pub struct ListSegmentFileResultItem {
     pub file_name: String,
+    pub segment_id: TableSegmentID,
     pub file_size: u32,
 }
 
@@ -641,4 +1157,464 @@ pub struct ScanSegmentFileResult {
     pub state_flags: RecordStateFlags,
     pub position: TableRecordPosition,
     pub payload: TableSegmentPayload,
+    pub layout: RecordLayout,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TableSegmentManager;
+    use crate::{
+        config::{
+            DISKTABLE_SEGMENT_SIZE, TABLE_SEGMENT_RECORD_HEADER_SIZE, TABLES_DIRECTORY,
+            TABLES_SEGMENT_DIRECTORY, VALUE_BYTES_MAX_SIZE,
+        },
+        disktable::segment::record::TableSegmentPayload,
+        errors::ErrorCodes,
+        value_type::ValueType,
+    };
+
+    #[tokio::test]
+    async fn test_append_record_rejects_oversized_value() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_segment_size_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+        std::fs::create_dir_all(
+            base_path
+                .join(TABLES_DIRECTORY)
+                .join("items")
+                .join(TABLES_SEGMENT_DIRECTORY),
+        )
+        .unwrap();
+
+        let manager = TableSegmentManager::new(base_path.clone());
+        manager.initialize_table("items").await.unwrap();
+
+        let error = manager
+            .append_record(
+                "items",
+                TableSegmentPayload {
+                    key: "key".to_string(),
+                    value: vec![b'v'; VALUE_BYTES_MAX_SIZE + 1],
+                    version: 1,
+                    written_at: 0,
+                    value_type: ValueType::Raw,
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error.error_code, ErrorCodes::ValueSizeTooLarge));
+
+        // 최대 크기는 허용
+        manager
+            .append_record(
+                "items",
+                TableSegmentPayload {
+                    key: "key".to_string(),
+                    value: vec![b'v'; VALUE_BYTES_MAX_SIZE],
+                    version: 2,
+                    written_at: 0,
+                    value_type: ValueType::Raw,
+                },
+            )
+            .await
+            .unwrap();
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_reads_reopen_evicted_segment_files() {
+        let base_path = std::env::temp_dir().join(format!(
+            "barus_test_segment_handle_pool_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base_path);
+        std::fs::create_dir_all(
+            base_path
+                .join(TABLES_DIRECTORY)
+                .join("items")
+                .join(TABLES_SEGMENT_DIRECTORY),
+        )
+        .unwrap();
+
+        let manager = TableSegmentManager::new(base_path.clone()).with_max_open_segments(2);
+        manager.initialize_table("items").await.unwrap();
+
+        // one record per segment
+        let mut positions = vec![];
+        for i in 0..5 {
+            let (position, _) = manager
+                .append_record(
+                    "items",
+                    TableSegmentPayload {
+                        key: format!("key{}", i),
+                        value: format!("value{}", i).into_bytes(),
+                        version: i + 1,
+                        written_at: 0,
+                        value_type: ValueType::Raw,
+                    },
+                )
+                .await
+                .unwrap();
+            positions.push(position);
+
+            let mut tables_map = manager.tables_map.lock().await;
+            let state = tables_map.get_mut("items").unwrap();
+            state.segment_file_size = DISKTABLE_SEGMENT_SIZE;
+            state.current_page_offset = DISKTABLE_SEGMENT_SIZE;
+        }
+        assert_eq!(positions.last().unwrap().segment_id.0, 5);
+        assert_eq!(manager.open_segment_counts()["items"], 2);
+
+        // every segment is read again after its handle was evicted
+        for _ in 0..2 {
+            for (i, position) in positions.iter().enumerate() {
+                let (_, record) = manager
+                    .find_record("items", position.clone())
+                    .await
+                    .unwrap();
+                assert_eq!(record.key, format!("key{}", i));
+            }
+        }
+        assert_eq!(manager.open_segment_counts()["items"], 2);
+
+        // reads of the same segment share its handle
+        let (first, second, third) = tokio::join!(
+            manager.find_record("items", positions[4].clone()),
+            manager.find_record("items", positions[4].clone()),
+            manager.find_record("items", positions[3].clone()),
+        );
+        assert_eq!(first.unwrap().1.key, "key4");
+        assert_eq!(second.unwrap().1.key, "key4");
+        assert_eq!(third.unwrap().1.key, "key3");
+
+        manager.discard_table_segments("items").await;
+        assert!(manager.open_segment_counts().is_empty());
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_record_fails_checksum() {
+        let base_path = std::env::temp_dir().join(format!(
+            "barus_test_segment_checksum_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base_path);
+        let segment_directory = base_path
+            .join(TABLES_DIRECTORY)
+            .join("items")
+            .join(TABLES_SEGMENT_DIRECTORY);
+        std::fs::create_dir_all(&segment_directory).unwrap();
+
+        let manager = TableSegmentManager::new(base_path.clone());
+        manager.initialize_table("items").await.unwrap();
+
+        let mut positions = vec![];
+        for key in ["key1", "key2"] {
+            let (position, _) = manager
+                .append_record(
+                    "items",
+                    TableSegmentPayload {
+                        key: key.to_string(),
+                        value: b"value".to_vec(),
+                        version: 1,
+                        written_at: 0,
+                        value_type: ValueType::Raw,
+                    },
+                )
+                .await
+                .unwrap();
+            positions.push(position);
+        }
+
+        // the delete mark keeps the checksum readable
+        manager
+            .mark_deleted_record("items", positions[0].clone())
+            .await
+            .unwrap();
+        let (state, record) = manager
+            .find_record("items", positions[0].clone())
+            .await
+            .unwrap();
+        assert!(state.is_deleted());
+        assert_eq!(record.key, "key1");
+
+        // flip the last payload byte of the second record
+        let file_name = positions[1].segment_id.file_name();
+        let file_path = segment_directory.join(&file_name);
+        let mut bytes = std::fs::read(&file_path).unwrap();
+        let end = positions[1].offset as usize
+            + TABLE_SEGMENT_RECORD_HEADER_SIZE as usize
+            + u32::from_be_bytes(
+                bytes[positions[1].offset as usize + 1..positions[1].offset as usize + 5]
+                    .try_into()
+                    .unwrap(),
+            ) as usize;
+        bytes[end - 1] ^= 0xFF;
+        std::fs::write(&file_path, &bytes).unwrap();
+        manager.discard_table_segments("items").await;
+
+        let error = manager
+            .find_record("items", positions[1].clone())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            ErrorCodes::TableRecordDecodeError
+        ));
+        let message = error.message.unwrap();
+        assert!(message.contains(&file_name), "{}", message);
+        assert!(
+            message.contains(&positions[1].offset.to_string()),
+            "{}",
+            message
+        );
+
+        let error = manager
+            .scan_segment_file("items", &file_name)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            ErrorCodes::TableRecordDecodeError
+        ));
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_size_header_fails_scan() {
+        let base_path = std::env::temp_dir().join(format!(
+            "barus_test_segment_size_header_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base_path);
+        let segment_directory = base_path
+            .join(TABLES_DIRECTORY)
+            .join("items")
+            .join(TABLES_SEGMENT_DIRECTORY);
+        std::fs::create_dir_all(&segment_directory).unwrap();
+
+        let manager = TableSegmentManager::new(base_path.clone());
+        manager.initialize_table("items").await.unwrap();
+
+        let (position, _) = manager
+            .append_record(
+                "items",
+                TableSegmentPayload {
+                    key: "key1".to_string(),
+                    value: b"value".to_vec(),
+                    version: 1,
+                    written_at: 0,
+                    value_type: ValueType::Raw,
+                },
+            )
+            .await
+            .unwrap();
+
+        // a size that runs past the page (and wraps a u32 offset)
+        let file_name = position.segment_id.file_name();
+        let file_path = segment_directory.join(&file_name);
+        let mut bytes = std::fs::read(&file_path).unwrap();
+        let size_header = position.offset as usize + 1;
+        bytes[size_header..size_header + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        std::fs::write(&file_path, &bytes).unwrap();
+        manager.discard_table_segments("items").await;
+
+        let error = manager
+            .scan_segment_file("items", &file_name)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            ErrorCodes::TableRecordDecodeError
+        ));
+        let message = error.message.unwrap();
+        assert!(message.contains(&file_name), "{}", message);
+
+        let error = manager
+            .describe_segment_file("items", &position.segment_id)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            ErrorCodes::TableRecordDecodeError
+        ));
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_segment_file_name() {
+        let base_path = std::env::temp_dir().join(format!(
+            "barus_test_segment_legacy_name_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base_path);
+        let segment_directory = base_path
+            .join(TABLES_DIRECTORY)
+            .join("items")
+            .join(TABLES_SEGMENT_DIRECTORY);
+        std::fs::create_dir_all(&segment_directory).unwrap();
+
+        let payload = |key: &str, version: u64| TableSegmentPayload {
+            key: key.to_string(),
+            value: b"value".to_vec(),
+            version,
+            written_at: 0,
+            value_type: ValueType::Raw,
+        };
+
+        let manager = TableSegmentManager::new(base_path.clone());
+        manager.initialize_table("items").await.unwrap();
+        manager
+            .append_record("items", payload("key1", 1))
+            .await
+            .unwrap();
+
+        let segment_files = manager.list_segment_files("items").await.unwrap();
+        assert_eq!(segment_files.len(), 1);
+        assert_eq!(segment_files[0].file_name, "0000000000000001.seg");
+
+        // 확장자가 없던 시절에 만들어진 세그먼트 파일
+        std::fs::rename(
+            segment_directory.join("0000000000000001.seg"),
+            segment_directory.join("0000000000000001"),
+        )
+        .unwrap();
+
+        let manager = TableSegmentManager::new(base_path.clone());
+        manager.set_table_names(vec!["items".into()]).await.unwrap();
+        manager
+            .append_record("items", payload("key2", 2))
+            .await
+            .unwrap();
+
+        let segment_files = manager.list_segment_files("items").await.unwrap();
+        assert_eq!(segment_files.len(), 1);
+        assert_eq!(segment_files[0].file_name, "0000000000000001");
+
+        let records = manager
+            .scan_segment_file("items", "0000000000000001")
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].payload.key, "key2");
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_skip_unparsable_segment_file_names() {
+        let base_path = std::env::temp_dir().join(format!(
+            "barus_test_segment_garbage_name_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base_path);
+        let segment_directory = base_path
+            .join(TABLES_DIRECTORY)
+            .join("items")
+            .join(TABLES_SEGMENT_DIRECTORY);
+        std::fs::create_dir_all(&segment_directory).unwrap();
+
+        let manager = TableSegmentManager::new(base_path.clone());
+        manager.initialize_table("items").await.unwrap();
+        manager
+            .append_record(
+                "items",
+                TableSegmentPayload {
+                    key: "key1".to_string(),
+                    value: b"value".to_vec(),
+                    version: 1,
+                    written_at: 0,
+                    value_type: ValueType::Raw,
+                },
+            )
+            .await
+            .unwrap();
+
+        // 세그먼트가 아닌 파일 (ex: 편집기 임시 파일)
+        std::fs::write(segment_directory.join("garbage.swp"), b"garbage").unwrap();
+        std::fs::write(segment_directory.join("FFFFFFFFFFFFFFFF.bak"), b"garbage").unwrap();
+
+        let segment_files = manager.list_segment_files("items").await.unwrap();
+        assert_eq!(segment_files.len(), 1);
+        assert_eq!(segment_files[0].segment_id.0, 1);
+
+        let error = manager
+            .scan_segment_file("items", "garbage.swp")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            ErrorCodes::TableSegmentIDParseError
+        ));
+
+        // 마지막 세그먼트를 제대로 찾아서 이어 씀
+        let manager = TableSegmentManager::new(base_path.clone());
+        manager.set_table_names(vec!["items".into()]).await.unwrap();
+        let records = manager
+            .scan_segment_file("items", &segment_files[0].file_name)
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].position.segment_id.0, 1);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_find_record_rejects_misaligned_offset() {
+        let base_path = std::env::temp_dir().join(format!(
+            "barus_test_segment_bad_offset_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base_path);
+        std::fs::create_dir_all(
+            base_path
+                .join(TABLES_DIRECTORY)
+                .join("items")
+                .join(TABLES_SEGMENT_DIRECTORY),
+        )
+        .unwrap();
+
+        let manager = TableSegmentManager::new(base_path.clone());
+        manager.initialize_table("items").await.unwrap();
+
+        let mut positions = vec![];
+        for i in 0..2 {
+            let (position, _) = manager
+                .append_record(
+                    "items",
+                    TableSegmentPayload {
+                        key: format!("key{}", i),
+                        value: b"value".to_vec(),
+                        version: i,
+                        written_at: 0,
+                        value_type: ValueType::Raw,
+                    },
+                )
+                .await
+                .unwrap();
+            positions.push(position);
+        }
+
+        let (_, record) = manager
+            .find_record("items", positions[1].clone())
+            .await
+            .unwrap();
+        assert_eq!(record.key, "key1");
+
+        // 레코드 중간, 데이터 뒤의 빈 공간
+        for offset in [positions[0].offset + 3, positions[1].offset + 100] {
+            let mut position = positions[0].clone();
+            position.offset = offset;
+
+            let error = manager.find_record("items", position).await.unwrap_err();
+            assert!(matches!(error.error_code, ErrorCodes::TableRecordInvalid));
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
 }
@@ -0,0 +1,147 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    os::unix::fs::FileExt,
+    sync::Arc,
+};
+
+use tokio::{
+    fs::File,
+    sync::{Mutex, MutexGuard},
+};
+
+use crate::disktable::segment::segment_id::TableSegmentID;
+
+// An open segment file. Reads are positional (read_at) and share the file, so they don't wait on each other.
+// Writes (appends, delete marks, growing the file, sync) take turns on `writer`, a clone of the same file
+#[derive(Debug)]
+pub struct SegmentHandle {
+    file: Arc<std::fs::File>,
+    writer: Mutex<File>,
+}
+
+impl SegmentHandle {
+    pub fn new(file: std::fs::File) -> std::io::Result<Self> {
+        let writer = File::from_std(file.try_clone()?);
+
+        Ok(Self {
+            file: Arc::new(file),
+            writer: Mutex::new(writer),
+        })
+    }
+
+    // `len` bytes at `offset`. Doesn't move the writer's file position
+    pub async fn read_exact_at(&self, offset: u64, len: usize) -> std::io::Result<Vec<u8>> {
+        let file = self.file.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut buffer = vec![0; len];
+            file.read_exact_at(&mut buffer, offset)?;
+            Ok(buffer)
+        })
+        .await
+        .map_err(std::io::Error::other)?
+    }
+
+    pub async fn lock_writer(&self) -> MutexGuard<'_, File> {
+        self.writer.lock().await
+    }
+}
+
+// Open segment file handles, at most max_open_per_table per table.
+// Evicting a handle only drops the pool's reference: an operation still using it keeps the file open
+// until it is done, and the next access opens the file again.
+#[derive(Debug)]
+pub struct SegmentHandlePool {
+    max_open_per_table: usize,
+    tables: std::sync::Mutex<HashMap<String, TableHandles>>,
+}
+
+#[derive(Debug, Default)]
+struct TableHandles {
+    // segment ID -> (handle, last use)
+    handles: HashMap<u64, (Arc<SegmentHandle>, u64)>,
+    clock: u64,
+}
+
+impl TableHandles {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+impl SegmentHandlePool {
+    pub fn new(max_open_per_table: usize) -> Self {
+        Self {
+            max_open_per_table: max_open_per_table.max(1),
+            tables: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, table_name: &str, segment_id: &TableSegmentID) -> Option<Arc<SegmentHandle>> {
+        let mut tables = self.tables.lock().unwrap();
+        let table = tables.get_mut(table_name)?;
+
+        let now = table.tick();
+        let (handle, last_used) = table.handles.get_mut(&segment_id.0)?;
+        *last_used = now;
+
+        Some(handle.clone())
+    }
+
+    // Adds a newly opened file, closing the least recently used handles over the limit.
+    // If another task opened the same segment meanwhile, its handle is kept and returned
+    pub fn insert(
+        &self,
+        table_name: &str,
+        segment_id: &TableSegmentID,
+        handle: SegmentHandle,
+    ) -> Arc<SegmentHandle> {
+        let mut tables = self.tables.lock().unwrap();
+        let table = tables.entry(table_name.to_owned()).or_default();
+
+        let now = table.tick();
+        let (handle, last_used) = table
+            .handles
+            .entry(segment_id.0)
+            .or_insert_with(|| (Arc::new(handle), now));
+        *last_used = now;
+        let handle = handle.clone();
+
+        while table.handles.len() > self.max_open_per_table {
+            let Some(least_recently_used) = table
+                .handles
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(segment_id, _)| *segment_id)
+            else {
+                break;
+            };
+            table.handles.remove(&least_recently_used);
+        }
+
+        handle
+    }
+
+    // the table's segment files were deleted or replaced
+    pub fn close_table(&self, table_name: &str) {
+        self.tables.lock().unwrap().remove(table_name);
+    }
+
+    pub fn close_segment(&self, table_name: &str, segment_id: &TableSegmentID) {
+        if let Some(table) = self.tables.lock().unwrap().get_mut(table_name) {
+            table.handles.remove(&segment_id.0);
+        }
+    }
+
+    // number of open handles per table
+    pub fn open_counts(&self) -> BTreeMap<String, usize> {
+        self.tables
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(table_name, table)| (table_name.clone(), table.handles.len()))
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+}
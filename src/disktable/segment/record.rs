@@ -1,12 +1,92 @@
+use crate::value_type::ValueType;
+
 // Contents stored in table segments
 #[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
 pub struct TableSegmentPayload {
     pub key: String,
-    pub value: String,
+    pub value: Vec<u8>,
+    pub version: u64,          // record ID of the write that produced this value
+    pub written_at: u64, // unix timestamp (ms) of the write. 0 for records written before it was recorded
+    pub value_type: ValueType, // Raw for records written before value types existed
+}
+
+// Payload layouts of segment records, oldest first.
+// Older layouts are still decoded, `barus migrate` rewrites them in the current one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordLayout {
+    Legacy,      // key, value
+    Versioned,   // + version
+    Timestamped, // + written_at
+    Current,     // + value_type
+}
+
+// Payload layout written before value types existed.
+#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+pub struct TimestampedTableSegmentPayload {
+    pub key: String,
+    pub value: Vec<u8>,
+    pub version: u64,
+    pub written_at: u64,
+}
+
+impl From<TimestampedTableSegmentPayload> for TableSegmentPayload {
+    fn from(timestamped: TimestampedTableSegmentPayload) -> Self {
+        Self {
+            key: timestamped.key,
+            value: timestamped.value,
+            version: timestamped.version,
+            written_at: timestamped.written_at,
+            value_type: ValueType::Raw,
+        }
+    }
+}
+
+// Payload layout written before write timestamps existed.
+#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+pub struct VersionedTableSegmentPayload {
+    pub key: String,
+    pub value: Vec<u8>,
+    pub version: u64,
+}
+
+impl From<VersionedTableSegmentPayload> for TableSegmentPayload {
+    fn from(versioned: VersionedTableSegmentPayload) -> Self {
+        Self {
+            key: versioned.key,
+            value: versioned.value,
+            version: versioned.version,
+            written_at: 0,
+            value_type: ValueType::Raw,
+        }
+    }
+}
+
+// Payload layout written before record versions existed.
+#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+pub struct LegacyTableSegmentPayload {
+    pub key: String,
+    pub value: Vec<u8>,
 }
 
+impl From<LegacyTableSegmentPayload> for TableSegmentPayload {
+    fn from(legacy: LegacyTableSegmentPayload) -> Self {
+        Self {
+            key: legacy.key,
+            value: legacy.value,
+            version: 0,
+            written_at: 0,
+            value_type: ValueType::Raw,
+        }
+    }
+}
+
+// Set in the state byte of records whose size header is followed by a CRC32 of the payload.
+// Records written before checksums existed don't have it and are read without verification
+pub const RECORD_CHECKSUM_FLAG: u8 = 0x80;
+
 // Determines the validity of records within a segment.
-// It is written to the first byte of each Record in the file.
+// It is written to the first byte of each Record in the file (with RECORD_CHECKSUM_FLAG).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, bincode::Encode, bincode::Decode)]
 #[repr(u8)]
 pub enum RecordStateFlags {
@@ -18,7 +98,7 @@ pub enum RecordStateFlags {
 
 impl From<u8> for RecordStateFlags {
     fn from(value: u8) -> Self {
-        match value {
+        match value & !RECORD_CHECKSUM_FLAG {
             0 => RecordStateFlags::Nothing,
             1 => RecordStateFlags::Alive,
             2 => RecordStateFlags::Deleted,
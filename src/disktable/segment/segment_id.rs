@@ -1,6 +1,7 @@
-use crate::errors;
+use crate::{config::TABLES_SEGMENT_FILE_EXTENSION, errors};
 
 // 16 length hex ID (ex 0000000D000000EA)
+// Segment files are named "<ID>.seg" (older ones just "<ID>")
 #[derive(
     Debug,
     Clone,
@@ -29,6 +30,15 @@ impl TableSegmentID {
     pub fn increment(&mut self) {
         self.0 = self.0.saturating_add(1);
     }
+
+    pub fn file_name(&self) -> String {
+        format!("{:016X}.{}", self.0, TABLES_SEGMENT_FILE_EXTENSION)
+    }
+
+    // name of segment files created before the extension was added
+    pub fn legacy_file_name(&self) -> String {
+        self.into()
+    }
 }
 
 impl From<TableSegmentID> for u64 {
@@ -46,7 +56,13 @@ impl From<&TableSegmentID> for String {
 impl TryFrom<&str> for TableSegmentID {
     type Error = errors::Errors;
 
+    // accepts both the ID and a segment file name
     fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value
+            .strip_suffix(TABLES_SEGMENT_FILE_EXTENSION)
+            .and_then(|value| value.strip_suffix('.'))
+            .unwrap_or(value);
+
         if value.len() != 16 {
             return Err(
                 errors::Errors::new(errors::ErrorCodes::TableSegmentIDParseError)
@@ -62,3 +78,29 @@ impl TryFrom<&str> for TableSegmentID {
         Ok(TableSegmentID(id))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::TableSegmentID;
+
+    #[test]
+    fn test_parse_segment_file_names() {
+        let segment_id = TableSegmentID::new(0xEA);
+
+        assert_eq!(segment_id.file_name(), "00000000000000EA.seg");
+        assert_eq!(segment_id.legacy_file_name(), "00000000000000EA");
+
+        assert_eq!(
+            TableSegmentID::try_from("00000000000000EA.seg").unwrap(),
+            segment_id
+        );
+        assert_eq!(
+            TableSegmentID::try_from("00000000000000EA").unwrap(),
+            segment_id
+        );
+
+        assert!(TableSegmentID::try_from("00000000000000EA.tmp").is_err());
+        assert!(TableSegmentID::try_from("00000000000000EAseg").is_err());
+        assert!(TableSegmentID::try_from(".seg").is_err());
+    }
+}
@@ -1,4 +1,118 @@
+use std::borrow::Cow;
+
+// Stored as tables/{name}.json.
+// New fields must have a serde default so that older table files still load.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TableInfo {
     pub name: String,
+    #[serde(default)]
+    pub secondary_indexes: Vec<SecondaryIndexInfo>,
+    // existing keys can't be overwritten or deleted (ex: audit logs)
+    #[serde(default)]
+    pub append_only: bool,
+    #[serde(default)]
+    pub created_at: Option<u64>, // unix timestamp (ms), None for tables created before it was recorded
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub compression: TableCompression,
+    #[serde(default)]
+    pub index_type: TableIndexType,
+    #[serde(default)]
+    pub key_normalization: TableKeyNormalization,
+    // prior versions of a key kept readable after it is overwritten or deleted. 0 keeps none
+    #[serde(default)]
+    pub keep_versions: u32,
+}
+
+// Secondary index on a JSON field of the value
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SecondaryIndexInfo {
+    pub name: String,
+    pub json_path: String, // dot-separated path (ex: "user.name")
+}
+
+// Compression of the values in segment files
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableCompression {
+    #[default]
+    None,
+}
+
+// Structure of the primary index
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableIndexType {
+    #[default]
+    BTree,
+}
+
+// How keys are compared. Keys are normalized before they are locked, logged and indexed,
+// so every layer below the engine only ever sees the normalized form
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableKeyNormalization {
+    #[default]
+    None,
+    // case-insensitive keys (ex: emails). Stored and returned in lowercase
+    Lowercase,
+}
+
+impl TableKeyNormalization {
+    pub fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        match self {
+            TableKeyNormalization::None => Cow::Borrowed(key),
+            TableKeyNormalization::Lowercase if key.chars().any(|c| c.to_lowercase().ne([c])) => {
+                Cow::Owned(key.to_lowercase())
+            }
+            TableKeyNormalization::Lowercase => Cow::Borrowed(key),
+        }
+    }
+}
+
+// Options chosen at table creation
+#[derive(Debug, Clone, Default)]
+pub struct CreateTableOptions {
+    pub secondary_indexes: Vec<SecondaryIndexInfo>,
+    pub append_only: bool,
+    pub description: Option<String>,
+    pub compression: TableCompression,
+    pub index_type: TableIndexType,
+    pub key_normalization: TableKeyNormalization,
+    pub keep_versions: u32,
+}
+
+impl From<TableInfo> for CreateTableOptions {
+    fn from(table_info: TableInfo) -> Self {
+        Self {
+            secondary_indexes: table_info.secondary_indexes,
+            append_only: table_info.append_only,
+            description: table_info.description,
+            compression: table_info.compression,
+            index_type: table_info.index_type,
+            key_normalization: table_info.key_normalization,
+            keep_versions: table_info.keep_versions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TableCompression, TableIndexType, TableInfo, TableKeyNormalization};
+
+    #[test]
+    fn test_load_legacy_table_info() {
+        let table_info: TableInfo = serde_json::from_str(r#"{"name":"users"}"#).unwrap();
+
+        assert_eq!(table_info.name, "users");
+        assert!(table_info.secondary_indexes.is_empty());
+        assert!(!table_info.append_only);
+        assert_eq!(table_info.created_at, None);
+        assert_eq!(table_info.description, None);
+        assert_eq!(table_info.compression, TableCompression::None);
+        assert_eq!(table_info.index_type, TableIndexType::BTree);
+        assert_eq!(table_info.key_normalization, TableKeyNormalization::None);
+        assert_eq!(table_info.keep_versions, 0);
+    }
 }
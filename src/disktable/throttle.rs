@@ -0,0 +1,120 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+// vacuum segment writes, reported in /metrics
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CompactionStats {
+    pub bytes_per_second: u64, // last full second, 0 when no vacuum is writing
+    pub max_bytes_per_second: Option<u64>,
+    pub written_bytes: u64, // since startup
+}
+
+// Vacuum write throughput cap (BARUS_COMPACTION_MAX_BYTES_PER_SEC), a token bucket holding up to one second of bytes.
+// A write larger than the tokens left borrows ahead, and the writer sleeps until the debt is paid off
+#[derive(Debug)]
+pub struct CompactionThrottle {
+    bytes_per_second: Option<u64>, // None: unlimited
+    // (tokens, last refill)
+    bucket: std::sync::Mutex<(f64, Instant)>,
+    // (window start, bytes written in the window, bytes per second of the last full window)
+    window: std::sync::Mutex<(Instant, u64, u64)>,
+    written: AtomicU64,
+}
+
+impl CompactionThrottle {
+    pub fn new(bytes_per_second: Option<u64>) -> Self {
+        let bytes_per_second = bytes_per_second.filter(|rate| *rate > 0);
+        let now = Instant::now();
+
+        Self {
+            bytes_per_second,
+            bucket: std::sync::Mutex::new((bytes_per_second.unwrap_or_default() as f64, now)),
+            window: std::sync::Mutex::new((now, 0, 0)),
+            written: AtomicU64::new(0),
+        }
+    }
+
+    // Count bytes written by a vacuum, and wait if they go over the cap
+    pub async fn consume(&self, bytes: u64) {
+        self.written.fetch_add(bytes, Ordering::Relaxed);
+        self.add_to_window(bytes);
+
+        let Some(rate) = self.bytes_per_second else {
+            return;
+        };
+
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+
+            let now = Instant::now();
+            let refill = now.duration_since(bucket.1).as_secs_f64() * rate as f64;
+            bucket.0 = (bucket.0 + refill).min(rate as f64) - bytes as f64;
+            bucket.1 = now;
+
+            (bucket.0 < 0.0).then(|| Duration::from_secs_f64(-bucket.0 / rate as f64))
+        };
+
+        if let Some(wait) = wait {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn add_to_window(&self, bytes: u64) {
+        let mut window = self.window.lock().unwrap();
+
+        let elapsed = window.0.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            let rate = (window.1 as f64 / elapsed.as_secs_f64()) as u64;
+            *window = (Instant::now(), 0, rate);
+        }
+        window.1 += bytes;
+    }
+
+    pub fn stats(&self) -> CompactionStats {
+        // a window left untouched for a second means no vacuum is writing
+        let bytes_per_second = {
+            let window = self.window.lock().unwrap();
+            if window.0.elapsed() >= Duration::from_secs(2) {
+                0
+            } else {
+                window.2
+            }
+        };
+
+        CompactionStats {
+            bytes_per_second,
+            max_bytes_per_second: self.bytes_per_second,
+            written_bytes: self.written.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::CompactionThrottle;
+
+    #[tokio::test]
+    async fn test_throttle_paces_writes() {
+        let throttle = CompactionThrottle::new(Some(1000));
+
+        // 1초 분량까지는 바로 통과, 이후에는 초과분만큼 대기
+        let started_at = Instant::now();
+        throttle.consume(1000).await;
+        assert!(started_at.elapsed() < Duration::from_millis(100));
+
+        throttle.consume(500).await;
+        assert!(started_at.elapsed() >= Duration::from_millis(450));
+        assert_eq!(throttle.stats().written_bytes, 1500);
+
+        // 제한 없음
+        let unlimited = CompactionThrottle::new(None);
+        let started_at = Instant::now();
+        unlimited.consume(u32::MAX as u64).await;
+        assert!(started_at.elapsed() < Duration::from_millis(100));
+        assert_eq!(unlimited.stats().max_bytes_per_second, None);
+    }
+}
@@ -21,6 +21,16 @@ impl Errors {
     }
 }
 
+// StorageUnavailable for a full disk or a read-only filesystem (retrying won't help), `error_code` otherwise
+pub fn io_error_code(error: &std::io::Error, error_code: ErrorCodes) -> ErrorCodes {
+    match error.kind() {
+        std::io::ErrorKind::StorageFull | std::io::ErrorKind::ReadOnlyFilesystem => {
+            ErrorCodes::StorageUnavailable
+        }
+        _ => error_code,
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum ErrorCodes {
     // WAL related errors
@@ -33,14 +43,18 @@ pub enum ErrorCodes {
     WALStateEncodeError,
     WALStateWriteError,
     WALSegmentIDParseError,
+    WALCheckpointOutOfRange,
     WALSegmentFileOpenError,
     WALSegmentFileDeleteError,
+    WALRecordsUnavailable,
 
     // Table related errors
     TableSegmentIDParseError,
     TableSegmentFileCreateError,
     TableSegmentFileOpenError,
     TableSegmentFileWriteError,
+    TableSegmentReclaimed,
+    TableRecordInvalid,
     TableRecordDecodeError,
     TableRecordEncodeError,
     TableCreationError,
@@ -64,12 +78,29 @@ pub enum ErrorCodes {
     KeySizeTooLarge,
     ValueSizeTooLarge,
     MemtableFlushAlreadyInProgress,
+    SecondaryIndexIsInvalid,
+    SecondaryIndexNotFound,
+    EngineReadOnly,
+    TooManyWrites,
+    TableIsAppendOnly,
+    TooManyKeys,
+    BatchKeyConflict,
+    StorageUnavailable,
+    RecordPositionInvalid,
+
+    // Server Errors
+    ServerBindError,
+    ServerError,
 
     // Internal Errors
     TableListFailed,
     TableGetFailed,
     WALStateFileHandleNotFound,
     UnknownTableRecordHeaderFlag,
+    MemtableFlushUnavailable,
+    InvalidValueType,
+    ValueTypeMismatch,
+    IntegrityCheckFailed,
 }
 
 impl std::fmt::Display for ErrorCodes {
@@ -84,11 +115,16 @@ impl std::fmt::Display for ErrorCodes {
             ErrorCodes::WALStateEncodeError => write!(f, "WAL State Encode Error"),
             ErrorCodes::WALStateWriteError => write!(f, "WAL State Write Error"),
             ErrorCodes::WALSegmentIDParseError => write!(f, "WAL Segment ID Parse Error"),
+            ErrorCodes::WALCheckpointOutOfRange => write!(f, "WAL Checkpoint Out Of Range"),
             ErrorCodes::WALSegmentFileOpenError => write!(f, "WAL Segment File Open Error"),
             ErrorCodes::WALSegmentFileDeleteError => write!(f, "WAL Segment File Delete Error"),
+            ErrorCodes::WALRecordsUnavailable => write!(f, "WAL Records Unavailable"),
             ErrorCodes::TableSegmentIDParseError => write!(f, "Table Segment ID Parse Error"),
             ErrorCodes::TableSegmentFileCreateError => write!(f, "Table Segment File Create Error"),
             ErrorCodes::TableSegmentFileWriteError => write!(f, "Table Segment File Write Error"),
+            ErrorCodes::TableSegmentReclaimed => write!(f, "Table Segment Reclaimed"),
+            ErrorCodes::TableRecordInvalid => write!(f, "Table Record Invalid"),
+            ErrorCodes::RecordPositionInvalid => write!(f, "Record Position Invalid"),
             ErrorCodes::TableNotFound => write!(f, "Table Not Found"),
             ErrorCodes::ValueNotFound => write!(f, "Value Not Found"),
             ErrorCodes::TableAlreadyExists => write!(f, "Table Already Exists"),
@@ -101,6 +137,9 @@ impl std::fmt::Display for ErrorCodes {
             ErrorCodes::KeySizeTooLarge => write!(f, "Key Size Too Large"),
             ErrorCodes::KeyIsEmpty => write!(f, "Key Is Empty"),
             ErrorCodes::ValueSizeTooLarge => write!(f, "Value Size Too Large"),
+            ErrorCodes::InvalidValueType => write!(f, "Invalid Value Type"),
+            ErrorCodes::ValueTypeMismatch => write!(f, "Value Type Mismatch"),
+            ErrorCodes::IntegrityCheckFailed => write!(f, "Integrity Check Failed"),
             ErrorCodes::FileOpenError => write!(f, "File Open Error"),
             ErrorCodes::FileMetadataError => write!(f, "File Metadata Error"),
             ErrorCodes::FileSeekError => write!(f, "File Seek Error"),
@@ -110,6 +149,7 @@ impl std::fmt::Display for ErrorCodes {
             ErrorCodes::MemtableFlushAlreadyInProgress => {
                 write!(f, "Memtable Flush Already In Progress")
             }
+            ErrorCodes::MemtableFlushUnavailable => write!(f, "Memtable Flush Unavailable"),
             ErrorCodes::TableSegmentFileOpenError => write!(f, "Table Segment File Open Error"),
             ErrorCodes::WALStateFileHandleNotFound => write!(f, "WAL State File Handle Not Found"),
             ErrorCodes::TableRecordDecodeError => write!(f, "Table Record Decode Error"),
@@ -117,6 +157,16 @@ impl std::fmt::Display for ErrorCodes {
             ErrorCodes::UnknownTableRecordHeaderFlag => {
                 write!(f, "Unknown Table Record Header Flag")
             }
+            ErrorCodes::SecondaryIndexIsInvalid => write!(f, "Secondary Index Is Invalid"),
+            ErrorCodes::SecondaryIndexNotFound => write!(f, "Secondary Index Not Found"),
+            ErrorCodes::EngineReadOnly => write!(f, "Engine Read Only"),
+            ErrorCodes::TooManyWrites => write!(f, "Too Many Writes"),
+            ErrorCodes::TableIsAppendOnly => write!(f, "Table Is Append Only"),
+            ErrorCodes::TooManyKeys => write!(f, "Too Many Keys"),
+            ErrorCodes::BatchKeyConflict => write!(f, "Batch Key Conflict"),
+            ErrorCodes::StorageUnavailable => write!(f, "Storage Unavailable"),
+            ErrorCodes::ServerBindError => write!(f, "Server Bind Error"),
+            ErrorCodes::ServerError => write!(f, "Server Error"),
         }
     }
 }
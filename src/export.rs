@@ -0,0 +1,229 @@
+use tokio_stream::{Stream, StreamExt};
+
+use crate::{db::ScanResponseItem, errors, value_type::value_to_text};
+
+pub const CSV_HEADER: &str = "key,value,value_type,version,written_at";
+
+// Output format of a table export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    // one JSON object per line
+    #[default]
+    Jsonl,
+    // a single JSON array
+    Json,
+    // RFC 4180 with a header row
+    Csv,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "application/x-ndjson",
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    fn header(&self) -> String {
+        match self {
+            ExportFormat::Jsonl => String::new(),
+            ExportFormat::Json => "[".to_string(),
+            ExportFormat::Csv => format!("{}\r\n", CSV_HEADER),
+        }
+    }
+
+    fn footer(&self) -> String {
+        match self {
+            ExportFormat::Jsonl | ExportFormat::Csv => String::new(),
+            ExportFormat::Json => "\n]\n".to_string(),
+        }
+    }
+
+    fn record(&self, item: &ScanResponseItem, first: bool) -> String {
+        let value = value_to_text(&item.value, item.value_type);
+        let json = || {
+            serde_json::json!({
+                "key": item.key,
+                "value": value,
+                "value_type": item.value_type,
+                "version": item.version,
+                "written_at": item.written_at,
+            })
+        };
+
+        match self {
+            ExportFormat::Jsonl => format!("{}\n", json()),
+            ExportFormat::Json if first => format!("\n{}", json()),
+            ExportFormat::Json => format!(",\n{}", json()),
+            ExportFormat::Csv => format!(
+                "{},{},{},{},{}\r\n",
+                csv_field(&item.key),
+                csv_field(&value),
+                item.value_type.as_str(),
+                item.version,
+                item.written_at
+            ),
+        }
+    }
+
+    // Encodes records as they arrive, so nothing but the current record is buffered
+    pub fn encode_stream(
+        self,
+        records: impl Stream<Item = errors::Result<ScanResponseItem>>,
+    ) -> impl Stream<Item = errors::Result<String>> {
+        let mut first = true;
+        let records = records.map(move |record| {
+            let line = self.record(&record?, first);
+            first = false;
+            Ok(line)
+        });
+
+        tokio_stream::once(Ok(self.header()))
+            .chain(records)
+            .chain(tokio_stream::once(Ok(self.footer())))
+            .filter(|chunk| chunk.as_ref().map_or(true, |chunk| !chunk.is_empty()))
+    }
+}
+
+// Quote the field if it contains a separator, quote or line break. Quotes are doubled
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_stream::StreamExt;
+
+    use super::{CSV_HEADER, ExportFormat};
+    use crate::{db::ScanResponseItem, value_type::ValueType};
+
+    fn items() -> Vec<ScanResponseItem> {
+        [
+            ("plain", "value", ValueType::Raw),
+            ("comma,key", "a, b", ValueType::Raw),
+            ("quote", r#"say "hi""#, ValueType::Raw),
+            ("newline", "line1\nline2\r\nline3", ValueType::Raw),
+            ("doc", r#"{"a": "b,c"}"#, ValueType::Json),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (key, value, value_type))| ScanResponseItem {
+            key: key.into(),
+            value: value.into(),
+            value_type,
+            version: i as u64 + 1,
+            written_at: 1700000000000,
+        })
+        .collect()
+    }
+
+    async fn export(format: ExportFormat, items: Vec<ScanResponseItem>) -> String {
+        format
+            .encode_stream(tokio_stream::iter(items.into_iter().map(Ok)))
+            .collect::<Result<Vec<String>, _>>()
+            .await
+            .unwrap()
+            .concat()
+    }
+
+    fn from_json(value: &serde_json::Value) -> ScanResponseItem {
+        ScanResponseItem {
+            key: value["key"].as_str().unwrap().into(),
+            value: value["value"].as_str().unwrap().into(),
+            value_type: serde_json::from_value(value["value_type"].clone()).unwrap(),
+            version: value["version"].as_u64().unwrap(),
+            written_at: value["written_at"].as_u64().unwrap(),
+        }
+    }
+
+    // minimal RFC 4180 reader
+    fn parse_csv(text: &str) -> Vec<Vec<String>> {
+        let mut rows = vec![];
+        let mut row = vec![];
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = !quoted,
+                ',' if !quoted => row.push(std::mem::take(&mut field)),
+                '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+                '\n' if !quoted => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                c => field.push(c),
+            }
+        }
+
+        rows
+    }
+
+    #[tokio::test]
+    async fn test_export_jsonl_roundtrip() {
+        let output = export(ExportFormat::Jsonl, items()).await;
+
+        let parsed: Vec<ScanResponseItem> = output
+            .lines()
+            .map(|line| from_json(&serde_json::from_str(line).unwrap()))
+            .collect();
+        assert_eq!(parsed, items());
+    }
+
+    #[tokio::test]
+    async fn test_export_json_roundtrip() {
+        let output = export(ExportFormat::Json, items()).await;
+
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+        let parsed: Vec<ScanResponseItem> = parsed.iter().map(from_json).collect();
+        assert_eq!(parsed, items());
+
+        // an empty table is still a valid array
+        let output = export(ExportFormat::Json, vec![]).await;
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+        assert!(parsed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_csv_roundtrip() {
+        let output = export(ExportFormat::Csv, items()).await;
+
+        let rows = parse_csv(&output);
+        assert_eq!(rows[0].join(","), CSV_HEADER);
+
+        let parsed: Vec<ScanResponseItem> = rows[1..]
+            .iter()
+            .map(|row| ScanResponseItem {
+                key: row[0].clone(),
+                value: row[1].clone().into_bytes(),
+                value_type: row[2].parse().unwrap(),
+                version: row[3].parse().unwrap(),
+                written_at: row[4].parse().unwrap(),
+            })
+            .collect();
+        assert_eq!(parsed, items());
+    }
+}
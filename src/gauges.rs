@@ -0,0 +1,156 @@
+use std::{
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context, Poll},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+// Open connections and in-flight requests of a transport (HTTP, gRPC), exposed via /metrics.
+#[derive(Debug, Default)]
+pub struct TransportGauges {
+    connections: AtomicU64,
+    requests: AtomicU64,
+}
+
+pub static HTTP_GAUGES: TransportGauges = TransportGauges::new();
+pub static GRPC_GAUGES: TransportGauges = TransportGauges::new();
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct TransportGaugesSnapshot {
+    pub active_connections: u64,
+    pub active_requests: u64,
+}
+
+impl TransportGauges {
+    pub const fn new() -> Self {
+        Self {
+            connections: AtomicU64::new(0),
+            requests: AtomicU64::new(0),
+        }
+    }
+
+    // counted until the guard is dropped
+    pub fn track_connection(&'static self) -> GaugeGuard {
+        GaugeGuard::increment(&self.connections)
+    }
+
+    // counted until the guard is dropped
+    pub fn track_request(&'static self) -> GaugeGuard {
+        GaugeGuard::increment(&self.requests)
+    }
+
+    pub fn snapshot(&self) -> TransportGaugesSnapshot {
+        TransportGaugesSnapshot {
+            active_connections: self.connections.load(Ordering::Relaxed),
+            active_requests: self.requests.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GaugeGuard(&'static AtomicU64);
+
+impl GaugeGuard {
+    fn increment(gauge: &'static AtomicU64) -> Self {
+        gauge.fetch_add(1, Ordering::Relaxed);
+        Self(gauge)
+    }
+}
+
+impl Drop for GaugeGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// Connection stream that stays counted until it is closed (dropped)
+#[derive(Debug)]
+pub struct CountedIo<T> {
+    inner: T,
+    _connection: GaugeGuard,
+}
+
+impl<T> CountedIo<T> {
+    pub fn new(inner: T, gauges: &'static TransportGauges) -> Self {
+        Self {
+            inner,
+            _connection: gauges.track_connection(),
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for CountedIo<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for CountedIo<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+impl<T: tonic::transport::server::Connected> tonic::transport::server::Connected for CountedIo<T> {
+    type ConnectInfo = T::ConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.inner.connect_info()
+    }
+}
+
+// axum listener whose connections are counted in `gauges`
+pub struct CountedListener {
+    inner: tokio::net::TcpListener,
+    gauges: &'static TransportGauges,
+}
+
+impl CountedListener {
+    pub fn new(inner: tokio::net::TcpListener, gauges: &'static TransportGauges) -> Self {
+        Self { inner, gauges }
+    }
+}
+
+impl axum::serve::Listener for CountedListener {
+    type Io = CountedIo<tokio::net::TcpStream>;
+    type Addr = std::net::SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        let (stream, addr) = axum::serve::Listener::accept(&mut self.inner).await;
+
+        (CountedIo::new(stream, self.gauges), addr)
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        self.inner.local_addr()
+    }
+}
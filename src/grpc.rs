@@ -1,8 +1,25 @@
-use std::sync::Arc;
-use tonic::{Request, Response, Status, transport::Server};
-
-use crate::config::GRPC_PORT;
-use crate::db::DBEngine;
+use std::{pin::Pin, sync::Arc};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::{Stream, StreamExt, wrappers::ReceiverStream};
+use tonic::{
+    Request, Response, Status,
+    codegen::http,
+    transport::{
+        Server,
+        server::{Router, TcpIncoming},
+    },
+};
+use tower::layer::util::{Identity, Stack};
+
+use crate::config::{GRPC_PORT, GRPC_WEB_ENABLED, SCAN_MAX_BYTES, SCAN_MAX_DURATION};
+use crate::db::{DBEngine, ScanOptions};
+use crate::disktable::table::{CreateTableOptions, TableKeyNormalization};
+use crate::errors::ErrorCodes;
+use crate::gauges::{CountedIo, GRPC_GAUGES};
+use crate::logging;
+use crate::os::{ShutdownReceiver, wait_for_shutdown};
+use crate::replication;
+use crate::value_type::{ValueType, value_to_text};
 
 // Include the generated proto code
 pub mod barus {
@@ -11,25 +28,58 @@ pub mod barus {
 
 use barus::barus_service_server::{BarusService, BarusServiceServer};
 use barus::{
-    CreateTableRequest, CreateTableResponse, DeleteRequest, DeleteResponse, DropTableRequest,
-    DropTableResponse, FlushMemtableRequest, FlushMemtableResponse, FlushWalRequest,
-    FlushWalResponse, GetDbStatusRequest, GetDbStatusResponse, GetRequest, GetResponse,
-    GetTableRequest, GetTableResponse, HealthRequest, HealthResponse, ListTablesRequest,
-    ListTablesResponse, PutRequest, PutResponse, TableInfo, TruncateRequest, TruncateResponse,
+    ChangeEvent, ChangeOp, CreateTableRequest, CreateTableResponse, DeleteRequest, DeleteResponse,
+    DropTableRequest, DropTableResponse, FlushMemtableRequest, FlushMemtableResponse,
+    FlushWalRequest, FlushWalResponse, GetDbStatusRequest, GetDbStatusResponse, GetRequest,
+    GetResponse, GetTableRequest, GetTableResponse, HealthRequest, HealthResponse,
+    ListTablesRequest, ListTablesResponse, MultiGetRequest, MultiGetResponse, MultiGetResult,
+    PutRequest, PutResponse, ScanItem, ScanRequest, ScanResponse, SubscribeRequest, TableInfo,
+    TruncateRequest, TruncateResponse,
 };
 
 pub struct BarusGrpcService {
     db: Arc<DBEngine>,
+    shutdown: ShutdownReceiver, // ends subscribe streams so graceful shutdown is not blocked by them
 }
 
 impl BarusGrpcService {
-    pub fn new(db: Arc<DBEngine>) -> Self {
-        Self { db }
+    pub fn new(db: Arc<DBEngine>, shutdown: ShutdownReceiver) -> Self {
+        Self { db, shutdown }
+    }
+}
+
+impl From<replication::ChangeEvent> for ChangeEvent {
+    fn from(event: replication::ChangeEvent) -> Self {
+        let op = match event.op {
+            replication::ChangeOp::Put => ChangeOp::Put,
+            replication::ChangeOp::Delete => ChangeOp::Delete,
+            replication::ChangeOp::Truncate => ChangeOp::Truncate,
+        };
+
+        let value_bytes = match event.value_type {
+            ValueType::Binary => event.value.clone(),
+            _ => None,
+        };
+
+        Self {
+            table: event.table,
+            key: event.key,
+            op: op.into(),
+            record_id: event.record_id,
+            value: event
+                .value
+                .map(|value| value_to_text(&value, event.value_type).into_owned()),
+            value_type: event.value_type.as_str().into(),
+            written_at: event.written_at,
+            value_bytes,
+        }
     }
 }
 
 #[tonic::async_trait]
 impl BarusService for BarusGrpcService {
+    type SubscribeStream = Pin<Box<dyn Stream<Item = Result<ChangeEvent, Status>> + Send>>;
+
     async fn list_tables(
         &self,
         _request: Request<ListTablesRequest>,
@@ -60,14 +110,35 @@ impl BarusService for BarusGrpcService {
             return Err(Status::invalid_argument("table name cannot be empty"));
         }
 
-        match self.db.create_table(&req.table).await {
+        match self
+            .db
+            .create_table(
+                &req.table,
+                CreateTableOptions {
+                    append_only: req.append_only,
+                    description: (!req.description.is_empty()).then_some(req.description),
+                    key_normalization: if req.case_insensitive_keys {
+                        TableKeyNormalization::Lowercase
+                    } else {
+                        TableKeyNormalization::None
+                    },
+                    keep_versions: req.keep_versions,
+                    ..Default::default()
+                },
+            )
+            .await
+        {
             Ok(_) => Ok(Response::new(CreateTableResponse {
                 message: format!("Table '{}' created successfully", req.table),
             })),
-            Err(e) => Err(Status::internal(format!(
-                "Failed to create table '{}': {:?}",
-                req.table, e
-            ))),
+            Err(e) => match e.error_code {
+                ErrorCodes::EngineReadOnly => Err(read_only_status()),
+                ErrorCodes::StorageUnavailable => Err(storage_unavailable_status()),
+                _ => Err(Status::internal(format!(
+                    "Failed to create table '{}': {:?}",
+                    req.table, e
+                ))),
+            },
         }
     }
 
@@ -84,6 +155,12 @@ impl BarusService for BarusGrpcService {
         match self.db.get_table(&req.table).await {
             Ok(table_info) => Ok(Response::new(GetTableResponse {
                 table_name: table_info.name,
+                append_only: table_info.append_only,
+                created_at: table_info.created_at.unwrap_or_default(),
+                description: table_info.description.unwrap_or_default(),
+                case_insensitive_keys: table_info.key_normalization
+                    == TableKeyNormalization::Lowercase,
+                keep_versions: table_info.keep_versions,
             })),
             Err(e) => Err(Status::internal(format!(
                 "Failed to get table '{}': {:?}",
@@ -106,10 +183,14 @@ impl BarusService for BarusGrpcService {
             Ok(_) => Ok(Response::new(DropTableResponse {
                 message: format!("Table '{}' dropped successfully", req.table),
             })),
-            Err(e) => Err(Status::internal(format!(
-                "Failed to drop table '{}': {:?}",
-                req.table, e
-            ))),
+            Err(e) => match e.error_code {
+                ErrorCodes::EngineReadOnly => Err(read_only_status()),
+                ErrorCodes::StorageUnavailable => Err(storage_unavailable_status()),
+                _ => Err(Status::internal(format!(
+                    "Failed to drop table '{}': {:?}",
+                    req.table, e
+                ))),
+            },
         }
     }
 
@@ -124,18 +205,119 @@ impl BarusService for BarusGrpcService {
             return Err(Status::invalid_argument("key cannot be empty"));
         }
 
-        match self.db.get_value(&req.table, &req.key).await {
+        match self
+            .db
+            .get_version(&req.table, &req.key, req.previous)
+            .await
+        {
             Ok(result) => {
-                let value = result.value;
+                let value_bytes = match result.value_type {
+                    ValueType::Binary => result.value.clone(),
+                    _ => vec![],
+                };
                 Ok(Response::new(GetResponse {
                     key: req.key,
-                    value,
+                    value: result.text().into_owned(),
+                    version: result.version,
+                    written_at: result.written_at,
+                    value_type: result.value_type.as_str().into(),
+                    value_bytes,
                 }))
             }
             Err(e) => Err(Status::internal(format!("Failed to get value: {:?}", e))),
         }
     }
 
+    async fn multi_get(
+        &self,
+        request: Request<MultiGetRequest>,
+    ) -> Result<Response<MultiGetResponse>, Status> {
+        let req = request.into_inner();
+
+        if req.table.is_empty() {
+            return Err(Status::invalid_argument("table name cannot be empty"));
+        }
+
+        if req.keys.iter().any(|key| key.is_empty()) {
+            return Err(Status::invalid_argument("key cannot be empty"));
+        }
+
+        match self.db.get_many(&req.table, &req.keys).await {
+            Ok(values) => {
+                let results = req
+                    .keys
+                    .into_iter()
+                    .zip(values)
+                    .map(|(key, value)| match value {
+                        Some(value) => MultiGetResult {
+                            key,
+                            found: true,
+                            value: value.text().into_owned(),
+                            version: value.version,
+                            written_at: value.written_at,
+                        },
+                        None => MultiGetResult {
+                            key,
+                            found: false,
+                            ..Default::default()
+                        },
+                    })
+                    .collect();
+
+                Ok(Response::new(MultiGetResponse { results }))
+            }
+            Err(e) => match e.error_code {
+                ErrorCodes::TooManyKeys => Err(Status::invalid_argument(e.to_string())),
+                ErrorCodes::TableNotFound => Err(Status::not_found(e.to_string())),
+                _ => Err(Status::internal(format!("Failed to get values: {:?}", e))),
+            },
+        }
+    }
+
+    async fn scan(&self, request: Request<ScanRequest>) -> Result<Response<ScanResponse>, Status> {
+        let req = request.into_inner();
+
+        if req.table.is_empty() {
+            return Err(Status::invalid_argument("table name cannot be empty"));
+        }
+
+        let options = ScanOptions {
+            modified_since: req.modified_since,
+            after: req.cursor,
+            max_bytes: *SCAN_MAX_BYTES,
+            max_duration: *SCAN_MAX_DURATION,
+        };
+
+        match self.db.scan(&req.table, options).await {
+            Ok(res) => {
+                let items = res
+                    .items
+                    .into_iter()
+                    .map(|item| ScanItem {
+                        value: value_to_text(&item.value, item.value_type).into_owned(),
+                        key: item.key,
+                        version: item.version,
+                        written_at: item.written_at,
+                        value_type: item.value_type.as_str().into(),
+                    })
+                    .collect();
+
+                Ok(Response::new(ScanResponse {
+                    items,
+                    truncated: res.truncated,
+                    next_cursor: res.next_cursor,
+                }))
+            }
+            Err(e) => match e.error_code {
+                ErrorCodes::TableNotFound => Err(Status::not_found(e.to_string())),
+                ErrorCodes::TableNameTooLong | ErrorCodes::TableNameIsInvalid => {
+                    Err(Status::invalid_argument(e.to_string()))
+                }
+                _ => Err(Status::internal(format!("Failed to scan table: {:?}", e))),
+            },
+        }
+    }
+
     async fn put(&self, request: Request<PutRequest>) -> Result<Response<PutResponse>, Status> {
         let req = request.into_inner();
 
@@ -147,11 +329,22 @@ impl BarusService for BarusGrpcService {
             return Err(Status::invalid_argument("key cannot be empty"));
         }
 
-        match self.db.put_value(req.table, req.key, req.value).await {
+        let result = match req.value_bytes {
+            Some(value_bytes) => self.db.put_bytes(req.table, req.key, &value_bytes).await,
+            None => self.db.put_value(req.table, req.key, req.value).await,
+        };
+
+        match result {
             Ok(_) => Ok(Response::new(PutResponse {
                 message: "Stored".to_string(),
             })),
-            Err(e) => Err(Status::internal(format!("Failed to put value: {:?}", e))),
+            Err(e) => match e.error_code {
+                ErrorCodes::EngineReadOnly => Err(read_only_status()),
+                ErrorCodes::StorageUnavailable => Err(storage_unavailable_status()),
+                ErrorCodes::TooManyWrites => Err(Status::resource_exhausted(e.to_string())),
+                ErrorCodes::TableIsAppendOnly => Err(Status::failed_precondition(e.to_string())),
+                _ => Err(Status::internal(format!("Failed to put value: {:?}", e))),
+            },
         }
     }
 
@@ -173,7 +366,13 @@ impl BarusService for BarusGrpcService {
             Ok(_) => Ok(Response::new(DeleteResponse {
                 message: "Deleted".to_string(),
             })),
-            Err(e) => Err(Status::internal(format!("Failed to delete value: {:?}", e))),
+            Err(e) => match e.error_code {
+                ErrorCodes::EngineReadOnly => Err(read_only_status()),
+                ErrorCodes::StorageUnavailable => Err(storage_unavailable_status()),
+                ErrorCodes::TooManyWrites => Err(Status::resource_exhausted(e.to_string())),
+                ErrorCodes::TableIsAppendOnly => Err(Status::failed_precondition(e.to_string())),
+                _ => Err(Status::internal(format!("Failed to delete value: {:?}", e))),
+            },
         }
     }
 
@@ -181,8 +380,14 @@ impl BarusService for BarusGrpcService {
         &self,
         _request: Request<HealthRequest>,
     ) -> Result<Response<HealthResponse>, Status> {
+        let status = if self.db.is_healthy() {
+            "OK"
+        } else {
+            "UNHEALTHY"
+        };
+
         Ok(Response::new(HealthResponse {
-            status: "OK".to_string(),
+            status: status.to_string(),
         }))
     }
 
@@ -224,7 +429,6 @@ impl BarusService for BarusGrpcService {
             })),
             Err(e) => {
                 // Check for specific error types
-                use crate::errors::ErrorCodes;
                 match e.error_code {
                     ErrorCodes::MemtableFlushAlreadyInProgress => {
                         Err(Status::already_exists("Memtable flush already in progress"))
@@ -248,12 +452,18 @@ impl BarusService for BarusGrpcService {
             return Err(Status::invalid_argument("table name cannot be empty"));
         }
 
+        // keep table options (secondary indexes) across recreation
+        let options = match self.db.get_table(&req.table).await {
+            Ok(table_info) => table_info.into(),
+            Err(_) => CreateTableOptions::default(),
+        };
+
         // For now, we'll implement truncate as dropping and recreating the table
         // This ensures all data (memtable, WAL, disk segments, indexes) is cleared
         match self.db.delete_table(&req.table).await {
             Ok(_) => {
                 // Recreate the table after deletion
-                match self.db.create_table(&req.table).await {
+                match self.db.create_table(&req.table, options).await {
                     Ok(_) => Ok(Response::new(TruncateResponse {
                         message: format!("Table '{}' truncated successfully", req.table),
                     })),
@@ -263,30 +473,289 @@ impl BarusService for BarusGrpcService {
                     ))),
                 }
             }
-            Err(e) => Err(Status::internal(format!(
-                "Failed to truncate table '{}': {:?}",
-                req.table, e
-            ))),
+            Err(e) => match e.error_code {
+                ErrorCodes::EngineReadOnly => Err(read_only_status()),
+                ErrorCodes::StorageUnavailable => Err(storage_unavailable_status()),
+                _ => Err(Status::internal(format!(
+                    "Failed to truncate table '{}': {:?}",
+                    req.table, e
+                ))),
+            },
         }
     }
+
+    async fn subscribe(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let req = request.into_inner();
+
+        // subscribe before reading the WAL, so no record falls between the two
+        let mut changes = self.db.subscribe_changes();
+        let backlog = match req.after_record_id {
+            Some(record_id) => match self.db.changes_after(record_id).await {
+                Ok(backlog) => backlog,
+                Err(e) => match e.error_code {
+                    ErrorCodes::WALRecordsUnavailable => {
+                        return Err(Status::out_of_range(e.to_string()));
+                    }
+                    _ => {
+                        return Err(Status::internal(format!(
+                            "Failed to read WAL records: {:?}",
+                            e
+                        )));
+                    }
+                },
+            },
+            None => vec![],
+        };
+
+        let mut last_sent = req.after_record_id.unwrap_or_default();
+        let mut shutdown = self.shutdown.clone();
+        let (sender, receiver) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            let table_matches =
+                |event: &replication::ChangeEvent| req.table.is_empty() || event.table == req.table;
+
+            for event in backlog {
+                last_sent = event.record_id;
+
+                if table_matches(&event) && sender.send(Ok(event.into())).await.is_err() {
+                    return;
+                }
+            }
+
+            loop {
+                let event = tokio::select! {
+                    _ = shutdown.wait_for(|is_shutdown| *is_shutdown) => break,
+                    _ = sender.closed() => break, // client disconnected
+                    event = changes.recv() => event,
+                };
+
+                let message = match event {
+                    // already sent from the WAL
+                    Ok(event) if event.record_id <= last_sent => continue,
+                    Ok(event) if table_matches(&event) => Ok(event.into()),
+                    Ok(_) => continue,
+                    // 놓친 이벤트가 있으므로 스트림을 끝냄. client는 다시 구독하고 재동기화해야 함
+                    Err(RecvError::Lagged(skipped)) => Err(Status::data_loss(format!(
+                        "Subscriber lagged behind, {} events were dropped",
+                        skipped
+                    ))),
+                    Err(RecvError::Closed) => break,
+                };
+
+                let is_error = message.is_err();
+                if sender.send(message).await.is_err() || is_error {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(receiver))))
+    }
+}
+
+fn read_only_status() -> Status {
+    Status::failed_precondition("Engine is in read-only mode")
+}
+
+fn storage_unavailable_status() -> Status {
+    Status::unavailable("Storage is unavailable (disk full or read-only filesystem)")
+}
+
+// Access log. Runs the call with its correlation ID (x-request-id metadata), and returns the ID in the response metadata.
+// For streaming calls the latency (and the in-flight request gauge) covers the time until the stream started.
+#[derive(Debug, Clone)]
+struct LogRequestLayer;
+
+impl<S> tower::Layer<S> for LogRequestLayer {
+    type Service = LogRequest<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LogRequest { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LogRequest<S> {
+    inner: S,
+}
+
+impl<S, RequestBody, ResponseBody> tower::Service<http::Request<RequestBody>> for LogRequest<S>
+where
+    S: tower::Service<http::Request<RequestBody>, Response = http::Response<ResponseBody>>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    RequestBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<RequestBody>) -> Self::Future {
+        // the clone may not be ready, so the ready service is the one that handles this call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let request_id = logging::request_id_from_header(
+            request
+                .headers()
+                .get(logging::REQUEST_ID_HEADER)
+                .and_then(|value| value.to_str().ok()),
+        );
+        let path = request.uri().path().to_string();
+        let started_at = std::time::Instant::now();
+        let active_request = GRPC_GAUGES.track_request();
+
+        Box::pin(logging::with_request_id(request_id.clone(), async move {
+            let mut result = inner.call(request).await;
+            drop(active_request);
+
+            if let Ok(response) = &mut result {
+                // errors returned before any message carry grpc-status in the headers, others in the trailers
+                let grpc_status = response
+                    .headers()
+                    .get("grpc-status")
+                    .and_then(|status| status.to_str().ok())
+                    .unwrap_or("0");
+
+                log::info!(
+                    path = path.as_str(),
+                    grpc_status = grpc_status,
+                    latency_ms = started_at.elapsed().as_millis() as u64;
+                    "gRPC request"
+                );
+
+                if let Ok(value) = http::HeaderValue::from_str(&request_id) {
+                    response
+                        .headers_mut()
+                        .insert(logging::REQUEST_ID_HEADER, value);
+                }
+            }
+
+            result
+        }))
+    }
 }
 
-pub async fn run_grpc_server(db_engine: Arc<DBEngine>) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn run_grpc_server(
+    db_engine: Arc<DBEngine>,
+    shutdown: ShutdownReceiver,
+) -> Result<(), Box<dyn std::error::Error>> {
     let addr = format!("0.0.0.0:{}", *GRPC_PORT).parse()?;
 
     log::info!("gRPC Server is running on {}", addr);
 
-    let service = BarusGrpcService::new(db_engine);
+    if *GRPC_WEB_ENABLED {
+        log::info!("gRPC-web is enabled");
+    }
 
-    Server::builder()
-        // 성능 최적화 설정
-        .tcp_nodelay(true) // Nagle 알고리즘 비활성화
-        .tcp_keepalive(Some(std::time::Duration::from_secs(60)))
-        .http2_keepalive_interval(Some(std::time::Duration::from_secs(30)))
-        .http2_keepalive_timeout(Some(std::time::Duration::from_secs(10)))
-        .add_service(BarusServiceServer::new(service))
-        .serve(addr)
+    let service = BarusGrpcService::new(db_engine, shutdown.clone());
+
+    // 성능 최적화 설정: Nagle 알고리즘 비활성화, TCP keepalive
+    // open connections are counted in GRPC_GAUGES
+    let incoming = TcpIncoming::new(addr, true, Some(std::time::Duration::from_secs(60)))
+        .map_err(|e| e as Box<dyn std::error::Error>)?
+        .map(|stream| stream.map(|stream| CountedIo::new(stream, &GRPC_GAUGES)));
+
+    router(service, *GRPC_WEB_ENABLED)
+        .serve_with_incoming_shutdown(incoming, wait_for_shutdown(shutdown))
         .await?;
 
     Ok(())
 }
+
+// grpc_web: also accept HTTP/1.1 and translate gRPC-web calls (with CORS) for browser clients
+fn router(service: BarusGrpcService, grpc_web: bool) -> Router<Stack<LogRequestLayer, Identity>> {
+    let mut server = Server::builder()
+        .http2_keepalive_interval(Some(std::time::Duration::from_secs(30)))
+        .http2_keepalive_timeout(Some(std::time::Duration::from_secs(10)))
+        .accept_http1(grpc_web)
+        .layer(LogRequestLayer);
+
+    if grpc_web {
+        server.add_service(tonic_web::enable(BarusServiceServer::new(service)))
+    } else {
+        server.add_service(BarusServiceServer::new(service))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    use super::{BarusGrpcService, router};
+    use crate::{
+        db::DBEngine,
+        os::{shutdown_channel, wait_for_shutdown},
+    };
+
+    async fn send(addr: std::net::SocketAddr, request: &[u8]) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(request).await.unwrap();
+
+        let mut response = vec![];
+        stream.read_to_end(&mut response).await.unwrap();
+
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_grpc_web_over_http1() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_grpc_web_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let db = Arc::new(DBEngine::initialize(base_path.clone()).await.unwrap());
+        let (shutdown_sender, shutdown_receiver) = shutdown_channel();
+        let service = BarusGrpcService::new(db, shutdown_receiver.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(router(service, true).serve_with_incoming_shutdown(
+            TcpListenerStream::new(listener),
+            wait_for_shutdown(shutdown_receiver),
+        ));
+
+        // CORS preflight
+        let response = send(
+            addr,
+            b"OPTIONS /barus.BarusService/Health HTTP/1.1\r\nHost: localhost\r\nOrigin: http://localhost:8080\r\nAccess-Control-Request-Method: POST\r\nAccess-Control-Request-Headers: content-type,x-grpc-web\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.contains("access-control-allow-origin: http://localhost:8080"));
+
+        // Health call: an empty HealthRequest message in a gRPC-web frame (flag 0, length 0)
+        let mut request = b"POST /barus.BarusService/Health HTTP/1.1\r\nHost: localhost\r\nOrigin: http://localhost:8080\r\nContent-Type: application/grpc-web+proto\r\nX-Grpc-Web: 1\r\nContent-Length: 5\r\nConnection: close\r\n\r\n".to_vec();
+        request.extend_from_slice(&[0, 0, 0, 0, 0]);
+
+        let response = send(addr, &request).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.contains("content-type: application/grpc-web+proto"));
+        assert!(response.contains("OK"));
+        assert!(response.contains("grpc-status:0"));
+
+        shutdown_sender.send(true).unwrap();
+        server.await.unwrap().unwrap();
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+}
@@ -0,0 +1,127 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+// Size distribution (bytes) of sampled keys or values.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct SizeHistogram {
+    pub count: u64,
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    pub p50: usize,
+    pub p90: usize,
+    pub p99: usize,
+    // power-of-two buckets up to the one holding max. empty buckets in between are kept
+    pub buckets: Vec<SizeBucket>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SizeBucket {
+    pub le: usize, // sizes in (le / 2, le], 0 for empty keys/values
+    pub count: u64,
+}
+
+impl SizeHistogram {
+    pub fn from_sizes(mut sizes: Vec<usize>) -> Self {
+        if sizes.is_empty() {
+            return Self::default();
+        }
+
+        sizes.sort_unstable();
+
+        let max = sizes[sizes.len() - 1];
+        let mut buckets = vec![SizeBucket { le: 0, count: 0 }];
+        let mut le = 1;
+        loop {
+            buckets.push(SizeBucket { le, count: 0 });
+            if le >= max {
+                break;
+            }
+            le *= 2;
+        }
+
+        for size in &sizes {
+            let index = match size {
+                0 => 0,
+                size => size.next_power_of_two().trailing_zeros() as usize + 1,
+            };
+            buckets[index].count += 1;
+        }
+
+        // nearest-rank percentile
+        let percentile = |percent: usize| {
+            let rank = (sizes.len() * percent).div_ceil(100).max(1);
+            sizes[rank - 1]
+        };
+
+        Self {
+            count: sizes.len() as u64,
+            min: sizes[0],
+            max,
+            mean: sizes.iter().sum::<usize>() as f64 / sizes.len() as f64,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            buckets,
+        }
+    }
+}
+
+// Deterministic sampling by key, so repeated runs look at the same records.
+pub fn is_sampled(key: &str, sample_rate: f64) -> bool {
+    if sample_rate >= 1.0 {
+        return true;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+
+    (hasher.finish() as f64 / u64::MAX as f64) < sample_rate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SizeBucket, SizeHistogram, is_sampled};
+
+    #[test]
+    fn test_size_histogram() {
+        // 0 x1, 1 x1, 3 x2, 4 x1, 100 x5
+        let histogram = SizeHistogram::from_sizes(vec![100, 3, 0, 100, 4, 100, 1, 100, 3, 100]);
+
+        assert_eq!(histogram.count, 10);
+        assert_eq!(histogram.min, 0);
+        assert_eq!(histogram.max, 100);
+        assert_eq!(histogram.mean, 51.1);
+        assert_eq!(histogram.p50, 4);
+        assert_eq!(histogram.p90, 100);
+        assert_eq!(histogram.p99, 100);
+
+        let bucket = |le, count| SizeBucket { le, count };
+        assert_eq!(
+            histogram.buckets,
+            vec![
+                bucket(0, 1),
+                bucket(1, 1),
+                bucket(2, 0),
+                bucket(4, 3),
+                bucket(8, 0),
+                bucket(16, 0),
+                bucket(32, 0),
+                bucket(64, 0),
+                bucket(128, 5),
+            ]
+        );
+
+        assert_eq!(SizeHistogram::from_sizes(vec![]), SizeHistogram::default());
+    }
+
+    #[test]
+    fn test_is_sampled() {
+        let keys: Vec<String> = (0..10000).map(|i| format!("key-{}", i)).collect();
+
+        assert!(keys.iter().all(|key| is_sampled(key, 1.0)));
+        assert!(keys.iter().all(|key| !is_sampled(key, 0.0)));
+
+        let sampled = keys.iter().filter(|key| is_sampled(key, 0.1)).count();
+        assert!((800..1200).contains(&sampled), "{}", sampled);
+    }
+}
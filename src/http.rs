@@ -6,38 +6,277 @@ use axum::{
     response::{IntoResponse, Response},
     routing::{delete, get, post, put},
 };
+use tokio_stream::StreamExt;
+use tower_http::compression::{
+    CompressionLayer, Predicate,
+    predicate::{DefaultPredicate, NotForContentType},
+};
+
+use crate::{
+    amplification::TableWriteAmplification,
+    bridge::status::FlushStatus,
+    config::{
+        ADMIN_API_ENABLED, HTTP_COMPRESSION, HTTP_PORT, HTTP_REQUEST_TIMEOUT, SCAN_MAX_BYTES,
+        SCAN_MAX_DURATION, SIZE_HISTOGRAM_SAMPLE_RATE, VACUUM_DEFAULT_MIN_DEAD_RATIO,
+    },
+    db::{self, BatchWrite, BatchWriteResult, DBEngine, ReadSource, ScanOptions},
+    disktable::{
+        index::BloomFilterStats,
+        segment::record::RecordStateFlags,
+        table::{
+            CreateTableOptions, SecondaryIndexInfo, TableCompression, TableIndexType,
+            TableKeyNormalization,
+        },
+        throttle::CompactionStats,
+    },
+    errors::{self, ErrorCodes},
+    export::ExportFormat,
+    gauges::{CountedListener, GRPC_GAUGES, HTTP_GAUGES, TransportGauges, TransportGaugesSnapshot},
+    histogram::SizeHistogram,
+    logging,
+    os::{ShutdownReceiver, wait_for_shutdown},
+    swagger,
+    value_type::{ValueType, decode_binary, value_to_text},
+    wal::status::WALFsyncStatus,
+};
+
+pub async fn run_server(
+    db_engine: Arc<DBEngine>,
+    shutdown: ShutdownReceiver,
+) -> errors::Result<()> {
+    let app = router(db_engine);
+
+    let addr = format!("0.0.0.0:{}", *HTTP_PORT);
+
+    log::info!("HTTP Server is running on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(&addr).await.map_err(|e| {
+        errors::Errors::new(ErrorCodes::ServerBindError)
+            .with_message(format!("Failed to bind HTTP server to {}: {}", addr, e))
+    })?;
 
-use crate::{config::HTTP_PORT, db::DBEngine, errors::ErrorCodes, swagger};
+    serve(listener, app, &HTTP_GAUGES, shutdown)
+        .await
+        .map_err(|e| {
+            errors::Errors::new(ErrorCodes::ServerError)
+                .with_message(format!("HTTP server error: {}", e))
+        })?;
 
-pub async fn run_server(db_engine: Arc<DBEngine>) {
-    use axum::Router;
+    Ok(())
+}
 
-    let app = Router::new()
+fn router(db_engine: Arc<DBEngine>) -> axum::Router {
+    let mut router = axum::Router::new()
         .route("/", get(root))
         .route("/status", get(get_db_status))
+        .route("/metrics", get(get_metrics))
         .route("/tables", get(list_tables))
         .route("/tables/{table}", get(get_table))
         .route("/tables/{table}", post(create_table))
         .route("/tables/{table}", delete(delete_table))
         .route("/tables/{table}/truncate", post(truncate_table))
+        .route("/tables/{table}/reindex", post(reindex_table))
+        .route("/tables/{table}/verify", post(verify_table))
+        .route("/tables/{table}/vacuum", post(vacuum_table))
         .route("/tables/{table}/value", get(get_value))
         .route("/tables/{table}/value", put(put_value))
         .route("/tables/{table}/value", delete(delete_value))
+        .route("/tables/{table}/delete-if", post(delete_if))
+        .route("/tables/{table}/append", post(append_value))
+        .route("/tables/{table}/batch", post(write_batch))
+        .route("/tables/{table}/indexes/{index}", get(find_by_index))
+        .route("/tables/{table}/scan", get(scan_table))
+        .route("/tables/{table}/export", get(export_table))
+        .route("/tables/{table}/key-map", get(get_key_map))
+        .route("/tables/{table}/record", get(get_record))
+        .route("/tables/{table}/size-histogram", get(get_size_histogram))
         .route("/wal/flush", post(flush_wal))
+        .route("/wal/rotate", post(rotate_wal))
+        .route("/wal/checkpoint", get(get_wal_checkpoint))
+        .route("/wal/checkpoint", post(move_wal_checkpoint))
         .route("/memtable/flush", post(trigger_memtable_flush))
-        .nest("/docs", swagger::axum::router())
-        .layer(axum::extract::Extension(db_engine));
+        .route("/memtable/flush/status", get(get_flush_status))
+        .route("/admin/readonly", post(set_read_only))
+        .nest("/docs", swagger::axum::router());
 
-    let addr = format!("0.0.0.0:{}", *HTTP_PORT);
+    if let Some(timeout) = *HTTP_REQUEST_TIMEOUT {
+        router = router.layer(axum::middleware::from_fn_with_state(
+            timeout,
+            request_timeout,
+        ));
+    }
 
-    log::info!("HTTP Server is running on {}", addr);
+    if *HTTP_COMPRESSION {
+        router = router.layer(compression_layer());
+    }
+
+    router
+        .layer(axum::middleware::from_fn(log_request))
+        .layer(axum::middleware::from_fn_with_state(
+            &HTTP_GAUGES,
+            track_active_requests,
+        ))
+        .layer(axum::extract::Extension(db_engine))
+}
+
+// Serves until shutdown is requested, then stops accepting connections and drains in-flight requests.
+// Open connections are counted in `gauges`.
+async fn serve(
+    listener: tokio::net::TcpListener,
+    app: axum::Router,
+    gauges: &'static TransportGauges,
+    shutdown: ShutdownReceiver,
+) -> std::io::Result<()> {
+    axum::serve(CountedListener::new(listener, gauges), app)
+        .with_graceful_shutdown(wait_for_shutdown(shutdown))
+        .await
+}
+
+// In-flight request gauge
+async fn track_active_requests(
+    axum::extract::State(gauges): axum::extract::State<&'static TransportGauges>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    let _request = gauges.track_request();
+
+    next.run(request).await
+}
+
+// Access log. Runs the request with its correlation ID (X-Request-Id), and returns the ID in the response.
+async fn log_request(request: axum::extract::Request, next: axum::middleware::Next) -> Response {
+    let request_id = logging::request_id_from_header(
+        request
+            .headers()
+            .get(logging::REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let started_at = std::time::Instant::now();
+
+    let mut response = logging::with_request_id(request_id.clone(), async move {
+        let response = next.run(request).await;
+
+        log::info!(
+            method = method.as_str(),
+            path = path.as_str(),
+            status = response.status().as_u16(),
+            latency_ms = started_at.elapsed().as_millis() as u64;
+            "HTTP request"
+        );
+
+        response
+    })
+    .await;
+
+    if let Ok(value) = axum::http::HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .insert(logging::REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
+// gzip for clients that accept it (BARUS_HTTP_COMPRESSION).
+// Skips responses that are already compressed (images, archives, binary, or with a Content-Encoding) and tiny ones.
+fn compression_layer() -> CompressionLayer<impl Predicate> {
+    let predicate = DefaultPredicate::new()
+        .and(NotForContentType::const_new("application/gzip"))
+        .and(NotForContentType::const_new("application/zip"))
+        .and(NotForContentType::const_new("application/octet-stream"));
+
+    CompressionLayer::new()
+        .gzip(true)
+        .no_br()
+        .no_deflate()
+        .no_zstd()
+        .compress_when(predicate)
+}
+
+// 504 when the handler runs longer than BARUS_HTTP_REQUEST_TIMEOUT.
+// Reads are cancelled. Writes keep running in the background,
+// since stopping one between the WAL append and the memtable update would leave the two out of sync.
+async fn request_timeout(
+    axum::extract::State(timeout): axum::extract::State<std::time::Duration>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    let cancellable = matches!(
+        *request.method(),
+        axum::http::Method::GET | axum::http::Method::HEAD
+    );
+    let request_id = logging::current_request_id().unwrap_or_else(logging::next_request_id);
+
+    let mut handler = tokio::spawn(logging::with_request_id(request_id, next.run(request)));
+
+    match tokio::time::timeout(timeout, &mut handler).await {
+        Ok(Ok(response)) => response,
+        Ok(Err(error)) => {
+            let error_message = format!("Request handler failed: {}", error);
+            Response::builder()
+                .status(500)
+                .body(error_message.into())
+                .unwrap()
+        }
+        Err(_) => {
+            if cancellable {
+                handler.abort();
+            }
 
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+            log::warn!(
+                timeout_ms = timeout.as_millis() as u64,
+                cancelled = cancellable;
+                "HTTP request timed out"
+            );
+
+            Response::builder()
+                .status(504)
+                .body("Request timed out".into())
+                .unwrap()
+        }
+    }
+}
+
+// Builds a 200 JSON response. Responds with 500 instead of panicking if serialization fails.
+fn json_response<T: serde::Serialize>(response: &T) -> Response<String> {
+    match serde_json::to_string(response) {
+        Ok(body) => Response::builder()
+            .status(200)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .unwrap(),
+        Err(e) => {
+            let error_message = format!("Failed to serialize response: {}", e);
+            Response::builder().status(500).body(error_message).unwrap()
+        }
+    }
 }
 
-async fn root() -> &'static str {
-    "OK"
+async fn root(Extension(db): Extension<Arc<DBEngine>>) -> impl IntoResponse {
+    if db.is_storage_degraded() {
+        return Response::builder()
+            .status(503)
+            .body("Storage is degraded (disk full or read-only filesystem)".to_string())
+            .unwrap();
+    }
+
+    let wal_fsync = db.wal_fsync_status();
+    if !wal_fsync.healthy {
+        return Response::builder()
+            .status(503)
+            .body(format!(
+                "WAL fsync is failing ({} consecutive failures)",
+                wal_fsync.consecutive_failures
+            ))
+            .unwrap();
+    }
+
+    Response::builder()
+        .status(200)
+        .body("OK".to_string())
+        .unwrap()
 }
 
 #[derive(serde::Serialize)]
@@ -58,11 +297,7 @@ async fn get_db_status(Extension(db): Extension<Arc<DBEngine>>) -> impl IntoResp
                 wal_total_size: status.wal_total_size,
             };
 
-            Response::builder()
-                .status(200)
-                .header("Content-Type", "application/json")
-                .body(serde_json::to_string(&response).unwrap())
-                .unwrap()
+            json_response(&response)
         }
         Err(err) => {
             let error_message = format!("Error getting database status: {:?}", err);
@@ -71,9 +306,85 @@ async fn get_db_status(Extension(db): Extension<Arc<DBEngine>>) -> impl IntoResp
     }
 }
 
+#[derive(serde::Serialize)]
+pub struct MetricsResponse {
+    pub max_concurrent_writes: usize,
+    pub available_write_permits: usize,
+    pub flush: FlushStatusResponse,
+    pub bloom_filter: BloomFilterStats,
+    pub open_segment_files: std::collections::BTreeMap<String, usize>, // per table
+    pub write_amplification: std::collections::BTreeMap<String, TableWriteAmplification>, // per table
+    pub wal_fsync: WALFsyncStatusResponse,
+    pub storage_degraded: bool,
+    pub flush_synced_files: u64,
+    pub read_promotions: u64,
+    pub compaction: CompactionStats,
+    pub http: TransportGaugesSnapshot,
+    pub grpc: TransportGaugesSnapshot,
+}
+
+#[derive(serde::Serialize)]
+pub struct WALFsyncStatusResponse {
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    pub total_failures: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<u128>,   // unix timestamp (ms)
+    pub last_success_at: Option<u128>, // unix timestamp (ms)
+}
+
+impl From<WALFsyncStatus> for WALFsyncStatusResponse {
+    fn from(status: WALFsyncStatus) -> Self {
+        let unix_millis = |time: std::time::SystemTime| {
+            time.duration_since(std::time::UNIX_EPOCH)
+                .map(|duration| duration.as_millis())
+                .unwrap_or_default()
+        };
+
+        Self {
+            healthy: status.healthy,
+            consecutive_failures: status.consecutive_failures,
+            total_failures: status.total_failures,
+            last_error: status.last_error,
+            last_error_at: status.last_error_at.map(unix_millis),
+            last_success_at: status.last_success_at.map(unix_millis),
+        }
+    }
+}
+
+async fn get_metrics(Extension(db): Extension<Arc<DBEngine>>) -> impl IntoResponse {
+    let metrics = db.get_metrics().await;
+
+    let response = MetricsResponse {
+        max_concurrent_writes: metrics.max_concurrent_writes,
+        available_write_permits: metrics.available_write_permits,
+        flush: FlushStatusResponse::from(metrics.flush_status),
+        bloom_filter: metrics.bloom_filter,
+        open_segment_files: metrics.open_segment_files,
+        write_amplification: metrics.write_amplification,
+        wal_fsync: WALFsyncStatusResponse::from(metrics.wal_fsync),
+        storage_degraded: metrics.storage_degraded,
+        flush_synced_files: metrics.flush_synced_files,
+        read_promotions: metrics.read_promotions,
+        compaction: metrics.compaction,
+        http: HTTP_GAUGES.snapshot(),
+        grpc: GRPC_GAUGES.snapshot(),
+    };
+
+    json_response(&response)
+}
+
 #[derive(serde::Serialize)]
 pub struct GetTableResponse {
     pub table_name: String,
+    pub secondary_indexes: Vec<SecondaryIndexInfo>,
+    pub append_only: bool,
+    pub created_at: Option<u64>, // unix timestamp (ms)
+    pub description: Option<String>,
+    pub compression: TableCompression,
+    pub index_type: TableIndexType,
+    pub key_normalization: TableKeyNormalization,
+    pub keep_versions: u32,
 }
 
 async fn get_table(
@@ -86,13 +397,17 @@ async fn get_table(
         Ok(table) => {
             let response = GetTableResponse {
                 table_name: table.name,
+                secondary_indexes: table.secondary_indexes,
+                append_only: table.append_only,
+                created_at: table.created_at,
+                description: table.description,
+                compression: table.compression,
+                index_type: table.index_type,
+                key_normalization: table.key_normalization,
+                keep_versions: table.keep_versions,
             };
 
-            Response::builder()
-                .status(200)
-                .header("Content-Type", "application/json")
-                .body(serde_json::to_string(&response).unwrap())
-                .unwrap()
+            json_response(&response)
         }
         Err(err) => match err.error_code {
             ErrorCodes::TableNotFound => {
@@ -144,11 +459,7 @@ async fn list_tables(Extension(db): Extension<Arc<DBEngine>>) -> impl IntoRespon
                 tables: tables_response_items,
             };
 
-            Response::builder()
-                .status(200)
-                .header("Content-Type", "application/json")
-                .body(serde_json::to_string(&response).unwrap())
-                .unwrap()
+            json_response(&response)
         }
         Err(e) => {
             let error_message = format!("Error listing tables: {:?}", e);
@@ -158,19 +469,53 @@ async fn list_tables(Extension(db): Extension<Arc<DBEngine>>) -> impl IntoRespon
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
-pub struct CreateTableRequest {}
+pub struct CreateTableRequest {
+    #[serde(default)]
+    pub secondary_indexes: Vec<SecondaryIndexInfo>,
+    #[serde(default)]
+    pub append_only: bool,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub compression: TableCompression,
+    #[serde(default)]
+    pub index_type: TableIndexType,
+    #[serde(default)]
+    pub key_normalization: TableKeyNormalization,
+    #[serde(default)]
+    pub keep_versions: u32,
+}
 
 async fn create_table(
     Extension(db): Extension<Arc<DBEngine>>,
     Path(table): Path<String>,
-    Json(_req): Json<CreateTableRequest>,
+    Json(req): Json<CreateTableRequest>,
 ) -> impl IntoResponse {
-    match db.create_table(&table).await {
+    let options = CreateTableOptions {
+        secondary_indexes: req.secondary_indexes,
+        append_only: req.append_only,
+        description: req.description,
+        compression: req.compression,
+        index_type: req.index_type,
+        key_normalization: req.key_normalization,
+        keep_versions: req.keep_versions,
+    };
+
+    match db.create_table(&table, options).await {
         Ok(_) => Response::builder()
             .status(200)
             .body(format!("Table '{}' created successfully", table))
             .unwrap(),
         Err(error) => match error.error_code {
+            ErrorCodes::EngineReadOnly => {
+                let error_message = "Engine is in read-only mode".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::StorageUnavailable => {
+                let error_message =
+                    "Storage is unavailable (disk full or read-only filesystem)".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
             ErrorCodes::TableNameIsEmpty => {
                 let error_message = "Table name is empty".to_string();
                 Response::builder().status(400).body(error_message).unwrap()
@@ -187,6 +532,10 @@ async fn create_table(
                 let error_message = format!("Table '{}' already exists", table);
                 Response::builder().status(409).body(error_message).unwrap()
             }
+            ErrorCodes::SecondaryIndexIsInvalid => {
+                let error_message = format!("Secondary index is invalid: {}", error);
+                Response::builder().status(400).body(error_message).unwrap()
+            }
             _ => {
                 let error_message = format!("Error creating table '{}': {:?}", table, error);
                 Response::builder().status(500).body(error_message).unwrap()
@@ -205,6 +554,15 @@ async fn delete_table(
             .body(format!("Table '{}' deleted successfully", table))
             .unwrap(),
         Err(e) => match e.error_code {
+            ErrorCodes::EngineReadOnly => {
+                let error_message = "Engine is in read-only mode".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::StorageUnavailable => {
+                let error_message =
+                    "Storage is unavailable (disk full or read-only filesystem)".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
             ErrorCodes::TableNameIsEmpty => {
                 let error_message = "Table name is empty".to_string();
                 Response::builder().status(400).body(error_message).unwrap()
@@ -235,6 +593,15 @@ async fn truncate_table(
             .body(format!("Table '{}' truncated successfully", table))
             .unwrap(),
         Err(e) => match e.error_code {
+            ErrorCodes::EngineReadOnly => {
+                let error_message = "Engine is in read-only mode".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::StorageUnavailable => {
+                let error_message =
+                    "Storage is unavailable (disk full or read-only filesystem)".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
             ErrorCodes::TableNameIsEmpty => {
                 let error_message = "Table name is empty".to_string();
                 Response::builder().status(400).body(error_message).unwrap()
@@ -255,10 +622,139 @@ async fn truncate_table(
     }
 }
 
+#[derive(serde::Serialize)]
+pub struct ReindexTableResponse {
+    pub indexed_records: usize,
+}
+
+async fn reindex_table(
+    Extension(db): Extension<Arc<DBEngine>>,
+    Path(table): Path<String>,
+) -> impl IntoResponse {
+    match db.reindex_table(&table).await {
+        Ok(indexed_records) => json_response(&ReindexTableResponse { indexed_records }),
+        Err(e) => match e.error_code {
+            ErrorCodes::TableNotFound => {
+                let error_message = format!("Table '{}' not found", table);
+                Response::builder().status(404).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsEmpty => {
+                let error_message = "Table name is empty".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameTooLong => {
+                let error_message = "Table name is too long".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsInvalid => {
+                let error_message = "Table name is invalid".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            _ => {
+                let error_message = format!("Error reindexing table '{}': {:?}", table, e);
+                Response::builder().status(500).body(error_message).unwrap()
+            }
+        },
+    }
+}
+
+async fn verify_table(
+    Extension(db): Extension<Arc<DBEngine>>,
+    Path(table): Path<String>,
+) -> impl IntoResponse {
+    match db.verify_table(&table).await {
+        Ok(report) => json_response(&report),
+        Err(e) => match e.error_code {
+            ErrorCodes::TableNotFound => {
+                let error_message = format!("Table '{}' not found", table);
+                Response::builder().status(404).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsEmpty => {
+                let error_message = "Table name is empty".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameTooLong => {
+                let error_message = "Table name is too long".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsInvalid => {
+                let error_message = "Table name is invalid".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            _ => {
+                let error_message = format!("Error verifying table '{}': {:?}", table, e);
+                Response::builder().status(500).body(error_message).unwrap()
+            }
+        },
+    }
+}
+
+async fn vacuum_table(
+    Query(params): Query<HashMap<String, String>>,
+    Path(table): Path<String>,
+    Extension(db): Extension<Arc<DBEngine>>,
+) -> impl IntoResponse {
+    if !*ADMIN_API_ENABLED {
+        return Response::builder()
+            .status(403)
+            .body("Admin API is disabled (BARUS_ADMIN_API=true to enable)".into())
+            .unwrap();
+    }
+
+    let min_dead_ratio = match params.get("min_dead_ratio").map(|v| v.parse::<f64>()) {
+        Some(Ok(ratio)) if (0.0..=1.0).contains(&ratio) => ratio,
+        Some(_) => {
+            return Response::builder()
+                .status(400)
+                .body("Invalid 'min_dead_ratio' parameter (0 <= ratio <= 1)".into())
+                .unwrap();
+        }
+        None => VACUUM_DEFAULT_MIN_DEAD_RATIO,
+    };
+
+    match db.vacuum_table(&table, min_dead_ratio).await {
+        Ok(report) => json_response(&report),
+        Err(e) => match e.error_code {
+            ErrorCodes::TableNotFound => {
+                let error_message = format!("Table '{}' not found", table);
+                Response::builder().status(404).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsEmpty => {
+                let error_message = "Table name is empty".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameTooLong => {
+                let error_message = "Table name is too long".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsInvalid => {
+                let error_message = "Table name is invalid".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::EngineReadOnly => {
+                let error_message = "Engine is in read-only mode".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::StorageUnavailable => {
+                let error_message =
+                    "Storage is unavailable (disk full or read-only filesystem)".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            _ => {
+                let error_message = format!("Error vacuuming table '{}': {:?}", table, e);
+                Response::builder().status(500).body(error_message).unwrap()
+            }
+        },
+    }
+}
+
 #[derive(serde::Serialize)]
 pub struct GetValueResponse<'a> {
     pub key: &'a str,
     pub value: String,
+    pub value_type: ValueType,
+    pub version: u64,
+    pub written_at: u64, // unix timestamp (ms), 0 if unknown
 }
 
 async fn get_value(
@@ -273,21 +769,71 @@ async fn get_value(
             .unwrap();
     };
 
-    let result = db.get_value(&table, key).await;
+    let if_version_gt = match params.get("if_version_gt").map(|v| v.parse::<u64>()) {
+        Some(Ok(version)) => Some(version),
+        Some(Err(_)) => {
+            return Response::builder()
+                .status(400)
+                .body("Invalid 'if_version_gt' parameter".into())
+                .unwrap();
+        }
+        None => None,
+    };
+
+    let source = match params.get("source").map(|v| v.parse::<ReadSource>()) {
+        Some(Ok(source)) => source,
+        Some(Err(_)) => {
+            return Response::builder()
+                .status(400)
+                .body("Invalid 'source' parameter (default, memtable, disk)".into())
+                .unwrap();
+        }
+        None => ReadSource::Default,
+    };
+
+    // n writes back. prior versions of tables with keep_versions
+    let previous = match params.get("previous").map(|v| v.parse::<u32>()) {
+        Some(Ok(previous)) => previous,
+        Some(Err(_)) => {
+            return Response::builder()
+                .status(400)
+                .body("Invalid 'previous' parameter".into())
+                .unwrap();
+        }
+        None => 0,
+    };
+
+    let result = match if_version_gt {
+        Some(_) if source != ReadSource::Default => {
+            return Response::builder()
+                .status(400)
+                .body("'if_version_gt' cannot be combined with 'source'".into())
+                .unwrap();
+        }
+        _ if previous > 0 && (if_version_gt.is_some() || source != ReadSource::Default) => {
+            return Response::builder()
+                .status(400)
+                .body("'previous' cannot be combined with 'if_version_gt' or 'source'".into())
+                .unwrap();
+        }
+        Some(version) => db.get_if_version_gt(&table, key, version).await,
+        None if previous > 0 => db.get_version(&table, key, previous).await.map(Some),
+        None => db.get_value_from(&table, key, source).await.map(Some),
+    };
 
     match result {
-        Ok(res) => {
+        Ok(Some(res)) => {
             let response = GetValueResponse {
                 key,
-                value: res.value,
+                value: res.text().into_owned(),
+                value_type: res.value_type,
+                version: res.version,
+                written_at: res.written_at,
             };
 
-            Response::builder()
-                .status(200)
-                .header("Content-Type", "application/json")
-                .body(serde_json::to_string(&response).unwrap())
-                .unwrap()
+            json_response(&response)
         }
+        Ok(None) => Response::builder().status(304).body(String::new()).unwrap(),
         Err(error) => match error.error_code {
             ErrorCodes::TableNotFound => {
                 let error_message = format!("Table '{}' not found", table);
@@ -325,57 +871,64 @@ async fn get_value(
     }
 }
 
-#[derive(serde::Deserialize)]
-pub struct PutValueRequest {
-    pub key: String,
-    pub value: String,
+#[derive(serde::Serialize)]
+pub struct ScanResponse {
+    pub items: Vec<ScanResponseItem>,
+    pub truncated: bool, // BARUS_SCAN_MAX_BYTES or BARUS_SCAN_MAX_DURATION_MS was reached
+    pub next_cursor: Option<String>, // pass as `cursor` to get the rest
 }
 
 #[derive(serde::Serialize)]
-pub struct PutValueResponse {
-    pub message: String,
+pub struct ScanResponseItem {
+    pub key: String,
+    pub value: String,
+    pub value_type: ValueType,
+    pub version: u64,
+    pub written_at: u64, // unix timestamp (ms), 0 if unknown
 }
 
-async fn put_value(
-    Extension(db): Extension<Arc<DBEngine>>,
+async fn scan_table(
+    Query(params): Query<HashMap<String, String>>,
     Path(table): Path<String>,
-    Json(req): Json<serde_json::Value>,
+    Extension(db): Extension<Arc<DBEngine>>,
 ) -> impl IntoResponse {
-    let Some(key) = req
-        .get("key")
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string())
-    else {
-        return Response::builder()
-            .status(400)
-            .body("Missing 'key' in request body".into())
-            .unwrap();
+    let modified_since = match params.get("modified_since").map(|v| v.parse::<u64>()) {
+        Some(Ok(modified_since)) => Some(modified_since),
+        Some(Err(_)) => {
+            return Response::builder()
+                .status(400)
+                .body("Invalid 'modified_since' parameter".into())
+                .unwrap();
+        }
+        None => None,
     };
 
-    let Some(value) = req
-        .get("value")
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string())
-    else {
-        return Response::builder()
-            .status(400)
-            .body("Missing 'value' in request body".into())
-            .unwrap();
+    let options = ScanOptions {
+        modified_since,
+        after: params.get("cursor").cloned(),
+        max_bytes: *SCAN_MAX_BYTES,
+        max_duration: *SCAN_MAX_DURATION,
     };
 
-    let result = db.put_value(table.clone(), key, value).await;
-
-    match result {
-        Ok(_) => {
-            let response = PutValueResponse {
-                message: "Stored".to_string(),
+    match db.scan(&table, options).await {
+        Ok(res) => {
+            let response = ScanResponse {
+                items: res
+                    .items
+                    .into_iter()
+                    .map(|item| ScanResponseItem {
+                        value: value_to_text(&item.value, item.value_type).into_owned(),
+                        key: item.key,
+                        value_type: item.value_type,
+                        version: item.version,
+                        written_at: item.written_at,
+                    })
+                    .collect(),
+                truncated: res.truncated,
+                next_cursor: res.next_cursor,
             };
 
-            Response::builder()
-                .status(200)
-                .header("Content-Type", "application/json")
-                .body(serde_json::to_string(&response).unwrap())
-                .unwrap()
+            json_response(&response)
         }
         Err(error) => match error.error_code {
             ErrorCodes::TableNotFound => {
@@ -394,55 +947,759 @@ async fn put_value(
                 let error_message = "Table name is invalid".to_string();
                 Response::builder().status(400).body(error_message).unwrap()
             }
-            ErrorCodes::KeyIsEmpty => Response::builder()
-                .status(400)
-                .body("Key cannot be empty".into())
-                .unwrap(),
+            _ => {
+                let error_message = format!("Error scanning table '{}': {:?}", table, error);
+                Response::builder().status(500).body(error_message).unwrap()
+            }
+        },
+    }
+}
+
+// Streams the live records of a table. The records are not sorted
+async fn export_table(
+    Query(params): Query<HashMap<String, String>>,
+    Path(table): Path<String>,
+    Extension(db): Extension<Arc<DBEngine>>,
+) -> Response {
+    let format = match params.get("format").map(|v| v.parse::<ExportFormat>()) {
+        Some(Ok(format)) => format,
+        Some(Err(_)) => {
+            return Response::builder()
+                .status(400)
+                .body("Invalid 'format' parameter (jsonl, json, csv)".into())
+                .unwrap();
+        }
+        None => ExportFormat::default(),
+    };
+
+    match db.scan_stream(&table).await {
+        Ok(records) => Response::builder()
+            .status(200)
+            .header("Content-Type", format.content_type())
+            .body(axum::body::Body::from_stream(format.encode_stream(records)))
+            .unwrap(),
+        Err(error) => match error.error_code {
+            ErrorCodes::TableNotFound => {
+                let error_message = format!("Table '{}' not found", table);
+                Response::builder()
+                    .status(404)
+                    .body(error_message.into())
+                    .unwrap()
+            }
+            ErrorCodes::TableNameIsEmpty => {
+                let error_message = "Table name is empty".to_string();
+                Response::builder()
+                    .status(400)
+                    .body(error_message.into())
+                    .unwrap()
+            }
+            ErrorCodes::TableNameTooLong => {
+                let error_message = "Table name is too long".to_string();
+                Response::builder()
+                    .status(400)
+                    .body(error_message.into())
+                    .unwrap()
+            }
+            ErrorCodes::TableNameIsInvalid => {
+                let error_message = "Table name is invalid".to_string();
+                Response::builder()
+                    .status(400)
+                    .body(error_message.into())
+                    .unwrap()
+            }
+            _ => {
+                let error_message = format!("Error exporting table '{}': {:?}", table, error);
+                Response::builder()
+                    .status(500)
+                    .body(error_message.into())
+                    .unwrap()
+            }
+        },
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct KeyMapEntry {
+    pub key: String,
+    pub segment_id: u64,
+    pub offset: u32,
+    pub state: &'static str, // alive, deleted, nothing, unknown
+}
+
+impl From<db::KeyLocation> for KeyMapEntry {
+    fn from(location: db::KeyLocation) -> Self {
+        Self {
+            key: location.key,
+            segment_id: location.position.segment_id,
+            offset: location.position.offset,
+            state: record_state_name(location.state),
+        }
+    }
+}
+
+fn record_state_name(state: RecordStateFlags) -> &'static str {
+    match state {
+        RecordStateFlags::Alive => "alive",
+        RecordStateFlags::Deleted => "deleted",
+        RecordStateFlags::Nothing => "nothing",
+        RecordStateFlags::Unknown => "unknown",
+    }
+}
+
+// One JSON object per line (KeyMapEntry), streamed
+async fn get_key_map(
+    Path(table): Path<String>,
+    Extension(db): Extension<Arc<DBEngine>>,
+) -> Response {
+    if !*ADMIN_API_ENABLED {
+        return Response::builder()
+            .status(403)
+            .body("Admin API is disabled (BARUS_ADMIN_API=true to enable)".into())
+            .unwrap();
+    }
+
+    match db.key_locations(&table).await {
+        Ok(locations) => {
+            let lines = locations.map(|location| {
+                let entry = KeyMapEntry::from(location?);
+                Ok::<_, errors::Errors>(format!("{}\n", serde_json::json!(entry)))
+            });
+
+            Response::builder()
+                .status(200)
+                .header("Content-Type", "application/x-ndjson")
+                .body(axum::body::Body::from_stream(lines))
+                .unwrap()
+        }
+        Err(error) => match error.error_code {
+            ErrorCodes::TableNotFound => {
+                let error_message = format!("Table '{}' not found", table);
+                Response::builder()
+                    .status(404)
+                    .body(error_message.into())
+                    .unwrap()
+            }
+            ErrorCodes::TableNameIsEmpty => {
+                let error_message = "Table name is empty".to_string();
+                Response::builder()
+                    .status(400)
+                    .body(error_message.into())
+                    .unwrap()
+            }
+            ErrorCodes::TableNameTooLong => {
+                let error_message = "Table name is too long".to_string();
+                Response::builder()
+                    .status(400)
+                    .body(error_message.into())
+                    .unwrap()
+            }
+            ErrorCodes::TableNameIsInvalid => {
+                let error_message = "Table name is invalid".to_string();
+                Response::builder()
+                    .status(400)
+                    .body(error_message.into())
+                    .unwrap()
+            }
+            _ => {
+                let error_message = format!(
+                    "Error listing key locations of table '{}': {:?}",
+                    table, error
+                );
+                Response::builder()
+                    .status(500)
+                    .body(error_message.into())
+                    .unwrap()
+            }
+        },
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct RecordResponse {
+    pub segment_id: u64,
+    pub offset: u32,
+    pub state: &'static str, // alive, deleted, nothing, unknown
+    pub key: String,
+    pub value: String,
+    pub value_type: ValueType,
+    pub version: u64,
+    pub written_at: u64,
+}
+
+// The record at ?segment=&offset= (as listed by the key map), deleted or not
+async fn get_record(
+    Query(params): Query<HashMap<String, String>>,
+    Path(table): Path<String>,
+    Extension(db): Extension<Arc<DBEngine>>,
+) -> impl IntoResponse {
+    if !*ADMIN_API_ENABLED {
+        return Response::builder()
+            .status(403)
+            .body("Admin API is disabled (BARUS_ADMIN_API=true to enable)".to_string())
+            .unwrap();
+    }
+
+    let Some(Ok(segment_id)) = params.get("segment").map(|v| v.parse::<u64>()) else {
+        return Response::builder()
+            .status(400)
+            .body("Missing or invalid 'segment' parameter".to_string())
+            .unwrap();
+    };
+    let Some(Ok(offset)) = params.get("offset").map(|v| v.parse::<u32>()) else {
+        return Response::builder()
+            .status(400)
+            .body("Missing or invalid 'offset' parameter".to_string())
+            .unwrap();
+    };
+
+    match db.record_at(&table, segment_id, offset).await {
+        Ok(record) => {
+            let response = RecordResponse {
+                segment_id: record.position.segment_id,
+                offset: record.position.offset,
+                state: record_state_name(record.state),
+                value: value_to_text(&record.value, record.value_type).into_owned(),
+                key: record.key,
+                value_type: record.value_type,
+                version: record.version,
+                written_at: record.written_at,
+            };
+
+            json_response(&response)
+        }
+        Err(error) => match error.error_code {
+            ErrorCodes::TableNotFound => {
+                let error_message = format!("Table '{}' not found", table);
+                Response::builder().status(404).body(error_message).unwrap()
+            }
+            ErrorCodes::RecordPositionInvalid => Response::builder()
+                .status(400)
+                .body(error.message.unwrap_or_default())
+                .unwrap(),
+            ErrorCodes::TableNameIsEmpty => {
+                let error_message = "Table name is empty".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameTooLong => {
+                let error_message = "Table name is too long".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsInvalid => {
+                let error_message = "Table name is invalid".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            _ => {
+                let error_message = format!(
+                    "Error reading record of table '{}' at segment {} offset {}: {:?}",
+                    table, segment_id, offset, error
+                );
+                Response::builder().status(500).body(error_message).unwrap()
+            }
+        },
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct SizeHistogramResponse {
+    pub sample_rate: f64,
+    pub total_records: u64,
+    pub key_sizes: SizeHistogram,
+    pub value_sizes: SizeHistogram,
+}
+
+async fn get_size_histogram(
+    Query(params): Query<HashMap<String, String>>,
+    Path(table): Path<String>,
+    Extension(db): Extension<Arc<DBEngine>>,
+) -> impl IntoResponse {
+    let sample_rate = match params.get("sample_rate").map(|v| v.parse::<f64>()) {
+        Some(Ok(sample_rate)) if sample_rate > 0.0 && sample_rate <= 1.0 => sample_rate,
+        Some(_) => {
+            return Response::builder()
+                .status(400)
+                .body("Invalid 'sample_rate' parameter (0 < rate <= 1)".into())
+                .unwrap();
+        }
+        None => *SIZE_HISTOGRAM_SAMPLE_RATE,
+    };
+
+    match db.size_histogram(&table, sample_rate).await {
+        Ok(res) => {
+            let response = SizeHistogramResponse {
+                sample_rate: res.sample_rate,
+                total_records: res.total_records,
+                key_sizes: res.key_sizes,
+                value_sizes: res.value_sizes,
+            };
+
+            json_response(&response)
+        }
+        Err(error) => match error.error_code {
+            ErrorCodes::TableNotFound => {
+                let error_message = format!("Table '{}' not found", table);
+                Response::builder().status(404).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsEmpty => {
+                let error_message = "Table name is empty".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameTooLong => {
+                let error_message = "Table name is too long".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsInvalid => {
+                let error_message = "Table name is invalid".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            _ => {
+                let error_message = format!(
+                    "Error building size histogram of table '{}': {:?}",
+                    table, error
+                );
+                Response::builder().status(500).body(error_message).unwrap()
+            }
+        },
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct FindByIndexResponse {
+    pub items: Vec<FindByIndexResponseItem>,
+}
+
+#[derive(serde::Serialize)]
+pub struct FindByIndexResponseItem {
+    pub key: String,
+    pub value: String,
+}
+
+async fn find_by_index(
+    Query(params): Query<HashMap<String, String>>,
+    Path((table, index)): Path<(String, String)>,
+    Extension(db): Extension<Arc<DBEngine>>,
+) -> impl IntoResponse {
+    let Some(value) = params.get("value") else {
+        return Response::builder()
+            .status(400)
+            .body("Missing 'value' parameter".into())
+            .unwrap();
+    };
+
+    match db.find_by_index(&table, &index, value).await {
+        Ok(res) => {
+            let response = FindByIndexResponse {
+                items: res
+                    .items
+                    .into_iter()
+                    .map(|item| FindByIndexResponseItem {
+                        key: item.key,
+                        value: item.value,
+                    })
+                    .collect(),
+            };
+
+            json_response(&response)
+        }
+        Err(error) => match error.error_code {
+            ErrorCodes::TableNotFound => {
+                let error_message = format!("Table '{}' not found", table);
+                Response::builder().status(404).body(error_message).unwrap()
+            }
+            ErrorCodes::SecondaryIndexNotFound => {
+                let error_message = format!("Index '{}' not found", index);
+                Response::builder().status(404).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsEmpty => {
+                let error_message = "Table name is empty".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameTooLong => {
+                let error_message = "Table name is too long".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsInvalid => {
+                let error_message = "Table name is invalid".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            _ => {
+                let error_message = format!("Error searching index '{}': {:?}", index, error);
+                Response::builder().status(500).body(error_message).unwrap()
+            }
+        },
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct PutValueRequest {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct PutValueResponse {
+    pub message: String,
+    pub written: bool, // false: ?if_changed=true and the value was already the same
+}
+
+async fn put_value(
+    Extension(db): Extension<Arc<DBEngine>>,
+    Path(table): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    Json(req): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    let if_changed = match params.get("if_changed").map(|v| v.parse::<bool>()) {
+        None => false,
+        Some(Ok(if_changed)) => if_changed,
+        Some(Err(_)) => {
+            return Response::builder()
+                .status(400)
+                .body("Invalid 'if_changed' parameter (true|false)".into())
+                .unwrap();
+        }
+    };
+
+    let Some(key) = req
+        .get("key")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+    else {
+        return Response::builder()
+            .status(400)
+            .body("Missing 'key' in request body".into())
+            .unwrap();
+    };
+
+    let value = req.get("value").and_then(|v| v.as_str());
+    // binary values are sent base64 encoded and stored as value_type binary
+    let value_base64 = req.get("value_base64").and_then(|v| v.as_str());
+
+    let value = match (value, value_base64) {
+        (Some(value), None) => value.to_string(),
+        (None, Some(value_base64)) => value_base64.to_string(),
+        (Some(_), Some(_)) => {
+            return Response::builder()
+                .status(400)
+                .body("Only one of 'value' and 'value_base64' can be given".into())
+                .unwrap();
+        }
+        (None, None) => {
+            return Response::builder()
+                .status(400)
+                .body("Missing 'value' in request body".into())
+                .unwrap();
+        }
+    };
+
+    let value_type = match req
+        .get("value_type")
+        .map(|v| v.as_str().unwrap_or_default())
+    {
+        Some(value_type) => match value_type.parse::<ValueType>() {
+            Ok(value_type) => value_type,
+            Err(_) => {
+                return Response::builder()
+                    .status(400)
+                    .body("Invalid 'value_type' in request body (raw, int, json, binary)".into())
+                    .unwrap();
+            }
+        },
+        None if value_base64.is_some() => ValueType::Binary,
+        None => ValueType::Raw,
+    };
+
+    if value_base64.is_some() && value_type != ValueType::Binary {
+        return Response::builder()
+            .status(400)
+            .body("'value_base64' can only be stored as value_type binary".into())
+            .unwrap();
+    }
+
+    let result = match value_type {
+        ValueType::Raw if if_changed => db.put_if_changed(table.clone(), key, value).await,
+        _ if if_changed => {
+            return Response::builder()
+                .status(400)
+                .body("'if_changed' cannot be combined with 'value_type'".into())
+                .unwrap();
+        }
+        // a binary value is given in its base64 text form, as GET returns it
+        ValueType::Binary => match decode_binary(&value) {
+            Ok(bytes) => db
+                .put_typed_value(table.clone(), key, bytes, ValueType::Binary)
+                .await
+                .map(|_| true),
+            Err(_) => {
+                return Response::builder()
+                    .status(400)
+                    .body("Invalid base64 in binary value".into())
+                    .unwrap();
+            }
+        },
+        value_type => db
+            .put_typed_value(table.clone(), key, value.into_bytes(), value_type)
+            .await
+            .map(|_| true),
+    };
+
+    match result {
+        Ok(written) => {
+            let response = PutValueResponse {
+                message: if written { "Stored" } else { "Unchanged" }.to_string(),
+                written,
+            };
+
+            json_response(&response)
+        }
+        Err(error) => match error.error_code {
+            ErrorCodes::TooManyWrites => {
+                let error_message = "Too many concurrent writes".to_string();
+                Response::builder().status(429).body(error_message).unwrap()
+            }
+            ErrorCodes::EngineReadOnly => {
+                let error_message = "Engine is in read-only mode".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::StorageUnavailable => {
+                let error_message =
+                    "Storage is unavailable (disk full or read-only filesystem)".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNotFound => {
+                let error_message = format!("Table '{}' not found", table);
+                Response::builder().status(404).body(error_message).unwrap()
+            }
+            ErrorCodes::TableIsAppendOnly => {
+                let error_message = format!("Table '{}' is append-only", table);
+                Response::builder().status(409).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsEmpty => {
+                let error_message = "Table name is empty".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameTooLong => {
+                let error_message = "Table name is too long".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsInvalid => {
+                let error_message = "Table name is invalid".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::KeyIsEmpty => Response::builder()
+                .status(400)
+                .body("Key cannot be empty".into())
+                .unwrap(),
+            ErrorCodes::KeySizeTooLarge => Response::builder()
+                .status(400)
+                .body("Key size is too large".into())
+                .unwrap(),
+            ErrorCodes::ValueSizeTooLarge => Response::builder()
+                .status(400)
+                .body("Value size is too large".into())
+                .unwrap(),
+            ErrorCodes::ValueTypeMismatch => Response::builder()
+                .status(400)
+                .body(error.message.unwrap_or_default())
+                .unwrap(),
+            _ => {
+                let error_message = format!("Error storing key: {:?}", error);
+                Response::builder().status(500).body(error_message).unwrap()
+            }
+        },
+    }
+}
+
+async fn delete_value(
+    Query(mut params): Query<HashMap<String, String>>,
+    Path(table): Path<String>,
+    Extension(db): Extension<Arc<DBEngine>>,
+) -> impl IntoResponse {
+    let Some(key) = params.remove("key") else {
+        return Response::builder()
+            .status(400)
+            .body("Missing 'key' parameter".to_string())
+            .unwrap();
+    };
+
+    let result = db.delete_value(table.clone(), key).await;
+
+    match result {
+        Ok(_) => {
+            let response = "Deleted".to_string();
+
+            Response::builder()
+                .status(200)
+                .header("Content-Type", "text/plain")
+                .body(response)
+                .unwrap()
+        }
+        Err(error) => match error.error_code {
+            ErrorCodes::TooManyWrites => {
+                let error_message = "Too many concurrent writes".to_string();
+                Response::builder().status(429).body(error_message).unwrap()
+            }
+            ErrorCodes::EngineReadOnly => {
+                let error_message = "Engine is in read-only mode".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::StorageUnavailable => {
+                let error_message =
+                    "Storage is unavailable (disk full or read-only filesystem)".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNotFound => {
+                let error_message = format!("Table '{}' not found", table);
+                Response::builder().status(404).body(error_message).unwrap()
+            }
+            ErrorCodes::TableIsAppendOnly => {
+                let error_message = format!("Table '{}' is append-only", table);
+                Response::builder().status(409).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsEmpty => {
+                let error_message = "Table name is empty".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameTooLong => {
+                let error_message = "Table name is too long".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsInvalid => {
+                let error_message = "Table name is invalid".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::KeyIsEmpty => Response::builder()
+                .status(400)
+                .body("Key cannot be empty".into())
+                .unwrap(),
             ErrorCodes::KeySizeTooLarge => Response::builder()
                 .status(400)
-                .body("Key size is too large".into())
+                .body("Key size is too large".into())
+                .unwrap(),
+            _ => {
+                let error_message = format!("Error deleting key: {:?}", error);
+                Response::builder().status(500).body(error_message).unwrap()
+            }
+        },
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct DeleteIfRequest {
+    pub key: String,
+    pub expected: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct DeleteIfResponse {
+    pub deleted: bool,
+}
+
+async fn delete_if(
+    Extension(db): Extension<Arc<DBEngine>>,
+    Path(table): Path<String>,
+    Json(req): Json<DeleteIfRequest>,
+) -> impl IntoResponse {
+    let result = db.delete_if(table.clone(), req.key, req.expected).await;
+
+    match result {
+        Ok(deleted) => {
+            let response = DeleteIfResponse { deleted };
+
+            json_response(&response)
+        }
+        Err(error) => match error.error_code {
+            ErrorCodes::TooManyWrites => {
+                let error_message = "Too many concurrent writes".to_string();
+                Response::builder().status(429).body(error_message).unwrap()
+            }
+            ErrorCodes::EngineReadOnly => {
+                let error_message = "Engine is in read-only mode".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::StorageUnavailable => {
+                let error_message =
+                    "Storage is unavailable (disk full or read-only filesystem)".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNotFound => {
+                let error_message = format!("Table '{}' not found", table);
+                Response::builder().status(404).body(error_message).unwrap()
+            }
+            ErrorCodes::TableIsAppendOnly => {
+                let error_message = format!("Table '{}' is append-only", table);
+                Response::builder().status(409).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsEmpty => {
+                let error_message = "Table name is empty".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameTooLong => {
+                let error_message = "Table name is too long".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::TableNameIsInvalid => {
+                let error_message = "Table name is invalid".to_string();
+                Response::builder().status(400).body(error_message).unwrap()
+            }
+            ErrorCodes::KeyIsEmpty => Response::builder()
+                .status(400)
+                .body("Key cannot be empty".into())
                 .unwrap(),
-            ErrorCodes::ValueSizeTooLarge => Response::builder()
+            ErrorCodes::KeySizeTooLarge => Response::builder()
                 .status(400)
-                .body("Value size is too large".into())
+                .body("Key size is too large".into())
                 .unwrap(),
             _ => {
-                let error_message = format!("Error storing key: {:?}", error);
+                let error_message = format!("Error deleting key: {:?}", error);
                 Response::builder().status(500).body(error_message).unwrap()
             }
         },
     }
 }
 
-async fn delete_value(
-    Query(mut params): Query<HashMap<String, String>>,
-    Path(table): Path<String>,
+#[derive(serde::Deserialize)]
+pub struct AppendValueRequest {
+    pub key: String,
+    pub suffix: String,
+}
+
+async fn append_value(
     Extension(db): Extension<Arc<DBEngine>>,
+    Path(table): Path<String>,
+    Json(req): Json<AppendValueRequest>,
 ) -> impl IntoResponse {
-    let Some(key) = params.remove("key") else {
-        return Response::builder()
-            .status(400)
-            .body("Missing 'key' parameter".to_string())
-            .unwrap();
-    };
-
-    let result = db.delete_value(table.clone(), key).await;
+    let result = db.append_value(table.clone(), req.key, req.suffix).await;
 
     match result {
         Ok(_) => {
-            let response = "Deleted".to_string();
+            let response = PutValueResponse {
+                message: "Appended".to_string(),
+                written: true,
+            };
 
-            Response::builder()
-                .status(200)
-                .header("Content-Type", "text/plain")
-                .body(response)
-                .unwrap()
+            json_response(&response)
         }
         Err(error) => match error.error_code {
+            ErrorCodes::TooManyWrites => {
+                let error_message = "Too many concurrent writes".to_string();
+                Response::builder().status(429).body(error_message).unwrap()
+            }
+            ErrorCodes::EngineReadOnly => {
+                let error_message = "Engine is in read-only mode".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::StorageUnavailable => {
+                let error_message =
+                    "Storage is unavailable (disk full or read-only filesystem)".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
             ErrorCodes::TableNotFound => {
                 let error_message = format!("Table '{}' not found", table);
                 Response::builder().status(404).body(error_message).unwrap()
             }
+            ErrorCodes::TableIsAppendOnly => {
+                let error_message = format!("Table '{}' is append-only", table);
+                Response::builder().status(409).body(error_message).unwrap()
+            }
             ErrorCodes::TableNameIsEmpty => {
                 let error_message = "Table name is empty".to_string();
                 Response::builder().status(400).body(error_message).unwrap()
@@ -463,8 +1720,103 @@ async fn delete_value(
                 .status(400)
                 .body("Key size is too large".into())
                 .unwrap(),
+            ErrorCodes::ValueSizeTooLarge => Response::builder()
+                .status(400)
+                .body("Value size is too large".into())
+                .unwrap(),
+            ErrorCodes::ValueTypeMismatch => Response::builder()
+                .status(400)
+                .body(error.message.unwrap_or_default())
+                .unwrap(),
             _ => {
-                let error_message = format!("Error deleting key: {:?}", error);
+                let error_message = format!("Error appending value: {:?}", error);
+                Response::builder().status(500).body(error_message).unwrap()
+            }
+        },
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct BatchPutItem {
+    pub key: String,
+    pub value: String,
+    #[serde(default)]
+    pub value_type: ValueType,
+}
+
+#[derive(serde::Deserialize)]
+pub struct WriteBatchRequest {
+    #[serde(default)]
+    pub puts: Vec<BatchPutItem>,
+    #[serde(default)]
+    pub deletes: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct WriteBatchResponse {
+    pub results: Vec<BatchWriteResult>, // puts first, then deletes, in request order
+}
+
+async fn write_batch(
+    Extension(db): Extension<Arc<DBEngine>>,
+    Path(table): Path<String>,
+    Json(req): Json<WriteBatchRequest>,
+) -> impl IntoResponse {
+    let mut puts = Vec::with_capacity(req.puts.len());
+    for item in req.puts {
+        // binary values are given base64 encoded
+        let value = match item.value_type {
+            ValueType::Binary => match decode_binary(&item.value) {
+                Ok(bytes) => bytes,
+                Err(_) => {
+                    return Response::builder()
+                        .status(400)
+                        .body(format!("Invalid base64 in binary value of '{}'", item.key))
+                        .unwrap();
+                }
+            },
+            _ => item.value.into_bytes(),
+        };
+        puts.push(BatchWrite {
+            table: table.clone(),
+            key: item.key,
+            value: Some(value),
+            value_type: item.value_type,
+        });
+    }
+    let deletes = req.deletes.into_iter().map(|key| BatchWrite {
+        table: table.clone(),
+        key,
+        value: None,
+        value_type: ValueType::Raw,
+    });
+
+    let result = db
+        .write_batch(puts.into_iter().chain(deletes).collect())
+        .await;
+
+    match result {
+        Ok(results) => json_response(&WriteBatchResponse { results }),
+        Err(error) => match error.error_code {
+            ErrorCodes::TooManyWrites => {
+                let error_message = "Too many concurrent writes".to_string();
+                Response::builder().status(429).body(error_message).unwrap()
+            }
+            ErrorCodes::TooManyKeys | ErrorCodes::BatchKeyConflict => Response::builder()
+                .status(400)
+                .body(error.message.unwrap_or_default())
+                .unwrap(),
+            ErrorCodes::EngineReadOnly => {
+                let error_message = "Engine is in read-only mode".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            ErrorCodes::StorageUnavailable => {
+                let error_message =
+                    "Storage is unavailable (disk full or read-only filesystem)".to_string();
+                Response::builder().status(503).body(error_message).unwrap()
+            }
+            _ => {
+                let error_message = format!("Error writing batch: {:?}", error);
                 Response::builder().status(500).body(error_message).unwrap()
             }
         },
@@ -484,6 +1836,122 @@ async fn flush_wal(Extension(db): Extension<Arc<DBEngine>>) -> impl IntoResponse
     }
 }
 
+#[derive(serde::Serialize)]
+pub struct RotateWALResponse {
+    pub segment: String, // new segment file name
+}
+
+async fn rotate_wal(Extension(db): Extension<Arc<DBEngine>>) -> impl IntoResponse {
+    match db.rotate_wal().await {
+        Ok(segment) => json_response(&RotateWALResponse { segment }),
+        Err(e) => {
+            let error_message = format!("Error rotating WAL: {:?}", e);
+            Response::builder().status(500).body(error_message).unwrap()
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct WALCheckpointResponse {
+    pub last_checkpoint_segment_id: u64,
+    pub last_checkpoint_record_id: u64,
+    pub last_segment_id: u64,
+    pub last_record_id: u64,
+    pub last_flushed_record_id: u64,
+    pub last_flushed_segment_id: u64,
+}
+
+impl From<db::WALCheckpointResponse> for WALCheckpointResponse {
+    fn from(checkpoint: db::WALCheckpointResponse) -> Self {
+        Self {
+            last_checkpoint_segment_id: checkpoint.last_checkpoint_segment_id,
+            last_checkpoint_record_id: checkpoint.last_checkpoint_record_id,
+            last_segment_id: checkpoint.last_segment_id,
+            last_record_id: checkpoint.last_record_id,
+            last_flushed_record_id: checkpoint.last_flushed_record_id,
+            last_flushed_segment_id: checkpoint.last_flushed_segment_id,
+        }
+    }
+}
+
+async fn get_wal_checkpoint(Extension(db): Extension<Arc<DBEngine>>) -> impl IntoResponse {
+    json_response(&WALCheckpointResponse::from(db.wal_checkpoint().await))
+}
+
+// Expert tool for recovery. Admin API only. The checkpoint cannot move past the last flushed record
+#[derive(serde::Deserialize)]
+pub struct MoveWALCheckpointRequest {
+    pub segment_id: u64,
+    pub record_id: u64,
+}
+
+async fn move_wal_checkpoint(
+    Extension(db): Extension<Arc<DBEngine>>,
+    Json(req): Json<MoveWALCheckpointRequest>,
+) -> impl IntoResponse {
+    if !*ADMIN_API_ENABLED {
+        return Response::builder()
+            .status(403)
+            .body("Admin API is disabled (BARUS_ADMIN_API=true to enable)".into())
+            .unwrap();
+    }
+
+    match db.move_wal_checkpoint(req.segment_id, req.record_id).await {
+        Ok(_) => json_response(&WALCheckpointResponse::from(db.wal_checkpoint().await)),
+        Err(error) => match error.error_code {
+            ErrorCodes::WALCheckpointOutOfRange => Response::builder()
+                .status(400)
+                .body(error.message.unwrap_or_default())
+                .unwrap(),
+            _ => {
+                let error_message = format!("Error moving WAL checkpoint: {:?}", error);
+                Response::builder().status(500).body(error_message).unwrap()
+            }
+        },
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct FlushStatusResponse {
+    pub state: &'static str,      // "idle" | "flushing"
+    pub started_at: Option<u128>, // unix timestamp (ms)
+    pub flushing_bytes: u64,
+    pub last_completed_at: Option<u128>, // unix timestamp (ms)
+    pub last_duration_ms: Option<u128>,
+    pub last_flushed_bytes: u64,
+    pub last_error: Option<String>,
+}
+
+impl From<FlushStatus> for FlushStatusResponse {
+    fn from(status: FlushStatus) -> Self {
+        let unix_millis = |time: std::time::SystemTime| {
+            time.duration_since(std::time::UNIX_EPOCH)
+                .map(|duration| duration.as_millis())
+                .unwrap_or_default()
+        };
+
+        Self {
+            state: if status.is_flushing() {
+                "flushing"
+            } else {
+                "idle"
+            },
+            started_at: status.started_at.map(unix_millis),
+            flushing_bytes: status.flushing_bytes,
+            last_completed_at: status.last_completed_at.map(unix_millis),
+            last_duration_ms: status.last_duration.map(|duration| duration.as_millis()),
+            last_flushed_bytes: status.last_flushed_bytes,
+            last_error: status.last_error,
+        }
+    }
+}
+
+async fn get_flush_status(Extension(db): Extension<Arc<DBEngine>>) -> impl IntoResponse {
+    let response = FlushStatusResponse::from(db.flush_status().await);
+
+    json_response(&response)
+}
+
 pub async fn trigger_memtable_flush(Extension(db): Extension<Arc<DBEngine>>) -> impl IntoResponse {
     match db.trigger_memtable_flush().await {
         Ok(_) => Response::builder()
@@ -502,3 +1970,470 @@ pub async fn trigger_memtable_flush(Extension(db): Extension<Arc<DBEngine>>) ->
         },
     }
 }
+
+#[derive(serde::Serialize)]
+pub struct SetReadOnlyResponse {
+    pub read_only: bool,
+}
+
+async fn set_read_only(
+    Query(params): Query<HashMap<String, String>>,
+    Extension(db): Extension<Arc<DBEngine>>,
+) -> impl IntoResponse {
+    let enabled = match params.get("enabled").map(|v| v.parse::<bool>()) {
+        Some(Ok(enabled)) => enabled,
+        _ => {
+            return Response::builder()
+                .status(400)
+                .body("Missing or invalid 'enabled' parameter (true|false)".into())
+                .unwrap();
+        }
+    };
+
+    db.set_read_only(enabled);
+
+    json_response(&SetReadOnlyResponse {
+        read_only: db.is_read_only(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Extension, Json, Router, extract::Path, response::IntoResponse, routing::get};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
+    use std::sync::Arc;
+
+    use super::{
+        BatchPutItem, WriteBatchRequest, compression_layer, request_timeout, root, router, serve,
+        track_active_requests, write_batch,
+    };
+    use crate::{
+        db::DBEngine,
+        disktable::table::{CreateTableOptions, TableKeyNormalization},
+        errors,
+        gauges::{HTTP_GAUGES, TransportGauges, TransportGaugesSnapshot},
+        os::shutdown_channel,
+        value_type::ValueType,
+    };
+
+    // (path, method) of every `.route("/path", method(handler))` in router()
+    fn routes() -> Vec<(String, String)> {
+        let source = include_str!("http.rs");
+        let router_start = source.find("\nfn router(").unwrap();
+        let router_end = router_start + source[router_start..].find("\n}\n").unwrap();
+
+        source[router_start..router_end]
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix(".route(\""))
+            .map(|route| {
+                let (path, rest) = route.split_once("\", ").unwrap();
+                let (method, _) = rest.split_once('(').unwrap();
+                (path.to_string(), method.to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_every_route_has_swagger_entry() {
+        let spec: serde_json::Value =
+            serde_json::from_str(include_str!("swagger/swagger.json")).unwrap();
+
+        let routes = routes();
+        assert!(routes.len() > 10);
+
+        for (path, method) in &routes {
+            let operation = &spec["paths"][path][method];
+            assert!(
+                operation["responses"].is_object(),
+                "{} {} has no swagger entry",
+                method.to_uppercase(),
+                path
+            );
+        }
+
+        // and no entries for routes that do not exist
+        for (path, operations) in spec["paths"].as_object().unwrap() {
+            for method in operations.as_object().unwrap().keys() {
+                assert!(
+                    routes.contains(&(path.clone(), method.clone())),
+                    "swagger entry {} {} has no route",
+                    method.to_uppercase(),
+                    path
+                );
+            }
+        }
+    }
+
+    // raw HTTP/1.1 GET. returns (status line + headers, body)
+    async fn http_get(addr: std::net::SocketAddr, path: &str) -> (String, Vec<u8>) {
+        http_get_with_headers(addr, path, &[]).await
+    }
+
+    async fn http_get_with_headers(
+        addr: std::net::SocketAddr,
+        path: &str,
+        headers: &[&str],
+    ) -> (String, Vec<u8>) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                format!(
+                    "GET {} HTTP/1.1\r\nHost: localhost\r\n{}Connection: close\r\n\r\n",
+                    path,
+                    headers
+                        .iter()
+                        .map(|header| format!("{}\r\n", header))
+                        .collect::<String>()
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+
+        let mut response = vec![];
+        stream.read_to_end(&mut response).await.unwrap();
+
+        let header_end = response
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .unwrap();
+
+        (
+            String::from_utf8_lossy(&response[..header_end]).into_owned(),
+            response[header_end + 4..].to_vec(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_swagger_docs_are_served() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_http_docs_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let db = Arc::new(DBEngine::initialize(base_path.clone()).await.unwrap());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_sender, shutdown_receiver) = shutdown_channel();
+        let server = tokio::spawn(serve(listener, router(db), &HTTP_GAUGES, shutdown_receiver));
+
+        let (head, body) = http_get(addr, "/docs").await;
+        assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+        assert!(head.contains("content-type: text/html"));
+        assert!(String::from_utf8_lossy(&body).contains("<title>Barus - Swagger UI</title>"));
+
+        let (head, _) = http_get(addr, "/docs/swagger.json").await;
+        assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+
+        for favicon in ["/docs/favicon-32x32.png", "/docs/favicon-16x16.png"] {
+            let (head, body) = http_get(addr, favicon).await;
+            assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+            assert!(head.contains("content-type: image/png"));
+            assert!(
+                body.starts_with(b"\x89PNG\r\n\x1a\n"),
+                "{} is not a PNG",
+                favicon
+            );
+        }
+
+        shutdown_sender.send(true).unwrap();
+        server.await.unwrap().unwrap();
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_batch_rejects_key_both_put_and_deleted() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_http_batch_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let db = Arc::new(DBEngine::initialize(base_path.clone()).await.unwrap());
+        let options = CreateTableOptions {
+            key_normalization: TableKeyNormalization::Lowercase,
+            ..Default::default()
+        };
+        db.create_table("foo", options).await.unwrap();
+
+        let request = |puts: &[&str], deletes: &[&str]| WriteBatchRequest {
+            puts: puts
+                .iter()
+                .map(|key| BatchPutItem {
+                    key: key.to_string(),
+                    value: "v".into(),
+                    value_type: ValueType::Raw,
+                })
+                .collect(),
+            deletes: deletes.iter().map(|key| key.to_string()).collect(),
+        };
+        let status = |request| {
+            let db = db.clone();
+            async move {
+                write_batch(Extension(db), Path("foo".into()), Json(request))
+                    .await
+                    .into_response()
+                    .status()
+            }
+        };
+
+        // 정규화 후 같은 키
+        assert_eq!(status(request(&["a", "B"], &["b"])).await, 400);
+        assert!(db.get_value("foo", "a").await.is_err());
+
+        assert_eq!(status(request(&["a", "b"], &["c"])).await, 200);
+        assert!(db.get_value("foo", "a").await.is_ok());
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_root_reports_unhealthy_cause() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_http_root_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let db = Arc::new(DBEngine::initialize(base_path.clone()).await.unwrap());
+        let response = || {
+            let db = db.clone();
+            async move {
+                let response = root(Extension(db)).await.into_response();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            }
+        };
+
+        assert_eq!(response().await.0, 200);
+
+        // 디스크 full
+        let io_error = std::io::Error::from_raw_os_error(libc::ENOSPC);
+        db.observe_storage_error(&errors::Errors::new(errors::io_error_code(
+            &io_error,
+            errors::ErrorCodes::FileWriteError,
+        )));
+
+        let (status, body) = response().await;
+        assert_eq!(status, 503);
+        assert!(body.starts_with("Storage is degraded"), "{}", body);
+
+        db.set_read_only(false);
+        assert_eq!(response().await.0, 200);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_gzip_scan_response() {
+        use std::io::Read;
+
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_http_gzip_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let db = Arc::new(DBEngine::initialize(base_path.clone()).await.unwrap());
+        db.create_table("items", Default::default()).await.unwrap();
+        for i in 0..200 {
+            db.put_value(
+                "items".into(),
+                format!("item{}", i),
+                "text value ".repeat(20),
+            )
+            .await
+            .unwrap();
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_sender, shutdown_receiver) = shutdown_channel();
+        let app = router(db).layer(compression_layer());
+        let server = tokio::spawn(serve(listener, app, &HTTP_GAUGES, shutdown_receiver));
+
+        let (head, plain) = http_get(addr, "/tables/items/scan").await;
+        assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+        assert!(!head.contains("content-encoding"), "{}", head);
+
+        let (head, body) =
+            http_get_with_headers(addr, "/tables/items/scan", &["Accept-Encoding: gzip"]).await;
+        assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+        assert!(head.contains("content-encoding: gzip"), "{}", head);
+        assert!(head.contains("transfer-encoding: chunked"), "{}", head);
+
+        // chunked body: <size hex>\r\n<data>\r\n ... 0\r\n\r\n
+        let mut body = body.as_slice();
+        let mut compressed = vec![];
+        loop {
+            let line_end = body
+                .windows(2)
+                .position(|window| window == b"\r\n")
+                .unwrap();
+            let size =
+                usize::from_str_radix(std::str::from_utf8(&body[..line_end]).unwrap(), 16).unwrap();
+            if size == 0 {
+                break;
+            }
+            compressed.extend_from_slice(&body[line_end + 2..line_end + 2 + size]);
+            body = &body[line_end + 2 + size + 2..];
+        }
+        assert!(
+            compressed.len() * 10 < plain.len(),
+            "{} / {}",
+            compressed.len(),
+            plain.len()
+        );
+
+        let mut decompressed = vec![];
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, plain);
+
+        // already compressed content is sent as is
+        let (head, body) =
+            http_get_with_headers(addr, "/docs/favicon-32x32.png", &["Accept-Encoding: gzip"])
+                .await;
+        assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+        assert!(!head.contains("content-encoding"), "{}", head);
+        assert!(body.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        shutdown_sender.send(true).unwrap();
+        server.await.unwrap().unwrap();
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    "done"
+                }),
+            )
+            .route("/fast", get(|| async { "done" }))
+            .layer(axum::middleware::from_fn_with_state(
+                std::time::Duration::from_millis(100),
+                request_timeout,
+            ));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_sender, shutdown_receiver) = shutdown_channel();
+        let server = tokio::spawn(serve(listener, app, &HTTP_GAUGES, shutdown_receiver));
+
+        let (head, body) = http_get(addr, "/slow").await;
+        assert!(head.starts_with("HTTP/1.1 504"), "{}", head);
+        assert_eq!(body, b"Request timed out");
+
+        let (head, body) = http_get(addr, "/fast").await;
+        assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+        assert_eq!(body, b"done");
+
+        shutdown_sender.send(true).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_request_completes_during_shutdown() {
+        let app = Router::new().route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+                "done"
+            }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_sender, shutdown_receiver) = shutdown_channel();
+        let server = tokio::spawn(serve(listener, app, &HTTP_GAUGES, shutdown_receiver));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+
+        // 요청이 처리 중인 상태에서 종료 요청
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        shutdown_sender.send(true).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("done"));
+
+        // 서버는 진행 중인 요청을 마친 뒤 종료되고, 새 연결은 받지 않음
+        server.await.unwrap().unwrap();
+        assert!(TcpStream::connect(addr).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_active_connection_and_request_gauges() {
+        static GAUGES: TransportGauges = TransportGauges::new();
+
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+                    "done"
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                &GAUGES,
+                track_active_requests,
+            ));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_sender, shutdown_receiver) = shutdown_channel();
+        let server = tokio::spawn(serve(listener, app, &GAUGES, shutdown_receiver));
+
+        let idle = TransportGaugesSnapshot {
+            active_connections: 0,
+            active_requests: 0,
+        };
+        assert_eq!(GAUGES.snapshot(), idle);
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+
+        // 요청 처리 중
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert_eq!(
+            GAUGES.snapshot(),
+            TransportGaugesSnapshot {
+                active_connections: 1,
+                active_requests: 1,
+            }
+        );
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.ends_with("done"));
+
+        // the connection is released once the server side has closed it
+        for _ in 0..50 {
+            if GAUGES.snapshot() == idle {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(GAUGES.snapshot(), idle);
+
+        shutdown_sender.send(true).unwrap();
+        server.await.unwrap().unwrap();
+    }
+}
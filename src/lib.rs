@@ -1,3 +1,62 @@
-pub mod client {
-    tonic::include_proto!("barus");
-}
+//! barus key-value store.
+//!
+//! The `barus` binary serves the engine over HTTP and gRPC. The same engine can be embedded
+//! in another Rust program without a network hop:
+//!
+//! ```no_run
+//! use barus::{CreateTableOptions, DBEngine};
+//!
+//! # async fn run() -> barus::Result<()> {
+//! let db = DBEngine::initialize("data".into()).await?;
+//!
+//! db.create_table("users", CreateTableOptions::default()).await?;
+//! db.put_value("users".into(), "user1".into(), "Alice".into()).await?;
+//!
+//! let user = db.get_value("users", "user1").await?;
+//! assert_eq!(user.text(), "Alice");
+//!
+//! // flushes the WAL and the memtable
+//! db.shutdown().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See `examples/embedded.rs` for a complete program.
+
+pub mod amplification;
+pub mod bridge;
+pub mod checksum;
+pub mod cli;
+pub mod command;
+pub mod config;
+pub mod db;
+pub mod disktable;
+pub mod errors;
+pub mod export;
+pub mod gauges;
+pub mod grpc;
+pub mod histogram;
+pub mod http;
+pub mod lock;
+pub mod logging;
+pub mod memtable;
+pub mod os;
+pub mod replication;
+pub mod ring;
+pub mod scheduler;
+pub mod slowlog;
+pub mod storage;
+pub mod swagger;
+pub mod system;
+pub mod validate;
+pub mod value_type;
+pub mod wal;
+
+// gRPC messages and client, generated from proto/barus.proto
+pub use grpc::barus as client;
+
+pub use db::{DBEngine, GetResponse, ScanOptions, ScanResponse, ScanResponseItem};
+pub use disktable::table::{CreateTableOptions, TableInfo};
+pub use errors::{ErrorCodes, Errors, Result};
+pub use ring::HashRing;
+pub use value_type::ValueType;
@@ -1,8 +1,135 @@
-use std::sync::{
-    Arc,
-    atomic::{AtomicBool, Ordering},
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
+use tokio::sync::{Mutex, MutexGuard, OwnedRwLockWriteGuard, RwLock};
+
+// Global lock acquisition order of the storage engine.
+// A task may only take a lock whose level is higher than every lock it already holds, so two tasks
+// can never wait for each other. Locks internal to a component (segment files, B-tree pages, bloom
+// filters) are taken below Table and never call back into the levels above.
+//
+//   Key -> MemtableMap -> FlushingMemtableMap -> Table -> Memtable -> WALWriteHandle -> WALState -> WALStateFile
+//
+// ex) put: Key, then the WAL append (WALWriteHandle -> WALState) is released before MemtableMap -> Memtable.
+// ex) flush worker: Table -> Memtable for each table, then WALState -> WALStateFile to move the checkpoint.
+// Debug builds check the order at runtime (see `ordered`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LockLevel {
+    Key,                 // DBEngine::key_locks
+    MemtableMap,         // MemtableManager::memtable_map
+    FlushingMemtableMap, // MemtableManager::flushing_memtable_map
+    Table,               // DiskTableManager::table_locks
+    Memtable,            // one table's Memtable
+    WALWriteHandle,      // WALManager::wal_write_handles
+    WALState,            // WALManager::wal_state
+    WALStateFile,        // WALManager::wal_state_write_handles
+}
+
+// A lock guard registered at its LockLevel until it is dropped.
+pub struct Ordered<G> {
+    guard: G,
+    _order: LockOrderGuard,
+}
+
+impl<G: std::ops::Deref> std::ops::Deref for Ordered<G> {
+    type Target = G::Target;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<G: std::ops::DerefMut> std::ops::DerefMut for Ordered<G> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+// Acquire a lock at the given level. ex) ordered(LockLevel::WALState, self.wal_state.lock()).await
+// In debug builds, panics if the current task already holds a lock of the same or a higher level.
+pub async fn ordered<G>(level: LockLevel, lock: impl Future<Output = G>) -> Ordered<G> {
+    // checked before waiting, so a violation is reported instead of hanging
+    let order = LockOrderGuard::enter(level);
+
+    Ordered {
+        guard: lock.await,
+        _order: order,
+    }
+}
+
+#[cfg(debug_assertions)]
+static HELD_LOCKS: std::sync::LazyLock<std::sync::Mutex<HashMap<tokio::task::Id, Vec<LockLevel>>>> =
+    std::sync::LazyLock::new(Default::default);
+
+pub struct LockOrderGuard {
+    #[cfg(debug_assertions)]
+    held: Option<(tokio::task::Id, LockLevel)>,
+}
+
+impl LockOrderGuard {
+    #[cfg(not(debug_assertions))]
+    fn enter(_level: LockLevel) -> Self {
+        Self {}
+    }
+
+    #[cfg(debug_assertions)]
+    fn enter(level: LockLevel) -> Self {
+        // outside of a tokio task (ex: sync tests) there is nothing to track
+        let Some(task_id) = tokio::task::try_id() else {
+            return Self { held: None };
+        };
+
+        let violation = {
+            let mut held_locks = HELD_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+            let held = held_locks.entry(task_id).or_default();
+
+            if held.iter().any(|held_level| *held_level >= level) {
+                Some(held.clone())
+            } else {
+                held.push(level);
+                None
+            }
+        };
+
+        if let Some(held) = violation {
+            panic!(
+                "lock order violation: {:?} acquired while holding {:?}",
+                level, held
+            );
+        }
+
+        Self {
+            held: Some((task_id, level)),
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Drop for LockOrderGuard {
+    fn drop(&mut self) {
+        let Some((task_id, level)) = self.held else {
+            return;
+        };
+
+        let mut held_locks = HELD_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(held) = held_locks.get_mut(&task_id) {
+            if let Some(index) = held.iter().rposition(|held_level| *held_level == level) {
+                held.remove(index);
+            }
+            if held.is_empty() {
+                held_locks.remove(&task_id);
+            }
+        }
+    }
+}
+
 // A simple try-lock implementation using AtomicBool
 #[derive(Clone)]
 pub struct TryLock {
@@ -58,9 +185,86 @@ impl TryLock {
     }
 }
 
+// Striped per-key lock.
+// Serializes writes to the same (table, key) so read-modify-write operations are atomic.
+#[derive(Debug)]
+pub struct KeyLock {
+    stripes: Vec<Mutex<()>>,
+}
+
+impl KeyLock {
+    pub fn new(stripe_count: usize) -> Self {
+        Self {
+            stripes: (0..stripe_count.max(1)).map(|_| Mutex::new(())).collect(),
+        }
+    }
+
+    fn stripe_index(&self, table: &str, key: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        table.hash(&mut hasher);
+        key.hash(&mut hasher);
+
+        (hasher.finish() % self.stripes.len() as u64) as usize
+    }
+
+    // Acquire the lock for the given key. Different keys may share a stripe.
+    pub async fn lock(&self, table: &str, key: &str) -> Ordered<MutexGuard<'_, ()>> {
+        let index = self.stripe_index(table, key);
+
+        ordered(LockLevel::Key, self.stripes[index].lock()).await
+    }
+
+    // Acquire the locks of several keys (batch writes).
+    // Stripes are taken in index order, so two batches can't deadlock on each other.
+    pub async fn lock_many<'a>(
+        &self,
+        keys: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Ordered<Vec<MutexGuard<'_, ()>>> {
+        let mut indexes: Vec<_> = keys
+            .into_iter()
+            .map(|(table, key)| self.stripe_index(table, key))
+            .collect();
+        indexes.sort_unstable();
+        indexes.dedup();
+
+        ordered(LockLevel::Key, async {
+            let mut guards = Vec::with_capacity(indexes.len());
+            for index in indexes {
+                guards.push(self.stripes[index].lock().await);
+            }
+            guards
+        })
+        .await
+    }
+}
+
+// Per-table maintenance lock.
+// Flush, truncate, delete and reindex of the same table take it for writing so they never overlap.
+#[derive(Debug, Default)]
+pub struct TableLock {
+    tables: std::sync::Mutex<HashMap<String, Arc<RwLock<()>>>>,
+}
+
+impl TableLock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get(&self, table: &str) -> Arc<RwLock<()>> {
+        let mut tables = self.tables.lock().unwrap_or_else(|e| e.into_inner());
+
+        tables.entry(table.to_string()).or_default().clone()
+    }
+
+    // Acquire the lock for the given table exclusively.
+    pub async fn write(&self, table: &str) -> Ordered<OwnedRwLockWriteGuard<()>> {
+        ordered(LockLevel::Table, self.get(table).write_owned()).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::TryLock;
+    use super::{LockLevel, TryLock, ordered};
     use std::sync::{Arc, atomic::Ordering};
 
     #[test]
@@ -153,4 +357,31 @@ mod tests {
         // 모든 증가 연산이 완료되었는지 확인
         assert_eq!(shared_value.load(Ordering::Relaxed), 5);
     }
+
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    async fn test_lock_order_violation_is_detected() {
+        let memtable_map = Arc::new(tokio::sync::Mutex::new(()));
+        let wal_state = Arc::new(tokio::sync::Mutex::new(()));
+
+        // in order
+        tokio::spawn({
+            let memtable_map = memtable_map.clone();
+            let wal_state = wal_state.clone();
+            async move {
+                let _memtable_map = ordered(LockLevel::MemtableMap, memtable_map.lock()).await;
+                let _wal_state = ordered(LockLevel::WALState, wal_state.lock()).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // reversed: the task panics instead of waiting
+        let result = tokio::spawn(async move {
+            let _wal_state = ordered(LockLevel::WALState, wal_state.lock()).await;
+            let _memtable_map = ordered(LockLevel::MemtableMap, memtable_map.lock()).await;
+        })
+        .await;
+        assert!(result.unwrap_err().is_panic());
+    }
 }
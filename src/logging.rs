@@ -0,0 +1,181 @@
+use std::{
+    io::Write,
+    sync::{
+        LazyLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use log::kv::{Key, Value, VisitSource};
+
+use crate::{
+    config::{LOG_FORMAT, LogFormat},
+    system::unix_millis_now,
+};
+
+// RUST_LOG sets the level (default: info). BARUS_LOG_FORMAT=json switches to JSON lines.
+pub fn init() {
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+
+    match *LOG_FORMAT {
+        LogFormat::Text => {
+            builder.format_key_values(write_text_key_values);
+        }
+        LogFormat::Json => {
+            builder.format(write_json_line);
+        }
+    }
+
+    builder.init();
+}
+
+// {"ts":1700000000000,"level":"INFO","target":"barus::http","message":"...","request_id":"..."}
+// Structured fields (log::info!(key = value; "...")) become top-level keys.
+// key=value pairs of the default format, plus the request ID of the current task
+fn write_text_key_values(
+    buf: &mut env_logger::fmt::Formatter,
+    fields: &dyn log::kv::Source,
+) -> std::io::Result<()> {
+    env_logger::fmt::default_kv_format(buf, fields)?;
+
+    if let Some(request_id) = current_request_id() {
+        write!(buf, " request_id={}", request_id)?;
+    }
+
+    Ok(())
+}
+
+fn write_json_line(
+    buf: &mut env_logger::fmt::Formatter,
+    record: &log::Record,
+) -> std::io::Result<()> {
+    writeln!(buf, "{}", json_line(record))
+}
+
+fn json_line(record: &log::Record) -> serde_json::Value {
+    let mut line = serde_json::Map::new();
+
+    line.insert("ts".into(), unix_millis_now().into());
+    line.insert("level".into(), record.level().as_str().into());
+    line.insert("target".into(), record.target().into());
+    line.insert("message".into(), record.args().to_string().into());
+
+    if let Some(request_id) = current_request_id() {
+        line.insert("request_id".into(), request_id.into());
+    }
+
+    let _ = record.key_values().visit(&mut JsonFields(&mut line));
+
+    serde_json::Value::Object(line)
+}
+
+struct JsonFields<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        let value = if let Some(value) = value.to_u64() {
+            value.into()
+        } else if let Some(value) = value.to_i64() {
+            value.into()
+        } else if let Some(value) = value.to_bool() {
+            value.into()
+        } else {
+            value.to_string().into()
+        };
+
+        self.0.insert(key.to_string(), value);
+
+        Ok(())
+    }
+}
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+const REQUEST_ID_MAX_LENGTH: usize = 128;
+
+tokio::task_local! {
+    // correlation ID of the HTTP/gRPC request handled by the current task
+    static REQUEST_ID: String;
+}
+
+// 프로세스마다 다른 prefix + 순번 (ex: 18bcfe2a-1f)
+static REQUEST_ID_PREFIX: LazyLock<u32> = LazyLock::new(|| unix_millis_now() as u32);
+static REQUEST_ID_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+pub fn next_request_id() -> String {
+    let sequence = REQUEST_ID_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+
+    format!("{:08x}-{:x}", *REQUEST_ID_PREFIX, sequence)
+}
+
+// The caller's X-Request-Id if it is usable, a new ID otherwise
+pub fn request_id_from_header(header: Option<&str>) -> String {
+    match header {
+        Some(request_id)
+            if !request_id.is_empty()
+                && request_id.len() <= REQUEST_ID_MAX_LENGTH
+                && request_id.bytes().all(|byte| byte.is_ascii_graphic()) =>
+        {
+            request_id.to_string()
+        }
+        _ => next_request_id(),
+    }
+}
+
+// Every log line written while `future` runs carries `request_id`.
+// Work handed to other tasks (ex: memtable flush) is not attributed.
+pub async fn with_request_id<F: Future>(request_id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(request_id, future).await
+}
+
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|request_id| request_id.clone()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{current_request_id, json_line, request_id_from_header, with_request_id};
+
+    #[test]
+    fn test_json_line() {
+        let fields: &[(&str, log::kv::Value)] =
+            &[("table", "items".into()), ("elapsed_ms", 12u64.into())];
+
+        let line = json_line(
+            &log::Record::builder()
+                .args(format_args!("Memtable Flush Completed"))
+                .level(log::Level::Info)
+                .target("barus::disktable")
+                .key_values(&fields)
+                .build(),
+        );
+
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["target"], "barus::disktable");
+        assert_eq!(line["message"], "Memtable Flush Completed");
+        assert_eq!(line["table"], "items");
+        assert_eq!(line["elapsed_ms"], 12);
+        assert!(line["ts"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_request_id_scope() {
+        assert_eq!(request_id_from_header(Some("abc-123")), "abc-123");
+        assert_ne!(request_id_from_header(Some("bad id")), "bad id");
+        assert_ne!(request_id_from_header(None), request_id_from_header(None));
+
+        assert_eq!(current_request_id(), None);
+
+        let line = with_request_id("abc-123".into(), async {
+            json_line(
+                &log::Record::builder()
+                    .args(format_args!("Put logged to WAL"))
+                    .build(),
+            )
+        })
+        .await;
+        assert_eq!(line["request_id"], "abc-123");
+
+        assert_eq!(current_request_id(), None);
+    }
+}
@@ -1,38 +1,56 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
+    ops::Bound,
     sync::{
         Arc,
         atomic::{AtomicBool, AtomicU64, Ordering},
     },
 };
 
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 
 use crate::{
-    bridge::event::{MemtableFlushEvent, MemtableFlushEventSender},
+    bridge::event::{FlushCompletion, MemtableFlushEvent, MemtableFlushEventSender},
+    config::{MEMTABLE_ORDERED, MEMTABLE_SHARDS},
     errors::{self, ErrorCodes},
-    memtable::table::{Memtable, MemtableGetValueResult},
+    lock::{LockLevel, ordered},
+    memtable::{
+        shard::ShardedMemtable,
+        table::{MemtableGetValueResult, MemtableValue, entry_size},
+    },
     system::SystemInfo,
+    value_type::ValueType,
     wal::{
         SharedWALState, WALManager,
         record::{RecordType, WALRecord},
+        state::WALPosition,
     },
 };
 
+pub mod shard;
 pub mod table;
 
-pub type MemtableMap = Arc<RwLock<HashMap<String, Arc<RwLock<Memtable>>>>>;
+pub type MemtableMap = Arc<RwLock<HashMap<String, Arc<ShardedMemtable>>>>;
 
 #[derive(Debug)]
 pub struct MemtableManager {
     pub(crate) memtable_map: MemtableMap,
     pub(crate) memtable_current_size: Arc<AtomicU64>,
     pub(crate) flushing_memtable_map: MemtableMap,
+    // shards per table memtable (BARUS_MEMTABLE_SHARDS)
+    shard_count: usize,
+    // table memtables keep their keys sorted for range scans (BARUS_MEMTABLE_ORDERED)
+    ordered: bool,
     pub(crate) block_write: Arc<AtomicBool>,
+    write_unblocked: Arc<Notify>, // signaled when block_write is cleared
+    // a flush is queued or running. the next one waits, since it would swap the memtable being flushed
+    flush_pending: Arc<AtomicBool>,
+    flush_done: Arc<Notify>, // signaled when flush_pending is cleared
     #[allow(dead_code)]
     memtable_size_soft_limit: usize,
     memtable_size_hard_limit: usize,
-    pub(crate) memtable_flush_sender: MemtableFlushEventSender,
+    // installed by BridgeController::new. None until then, and flushes fail instead of being lost
+    memtable_flush_sender: Option<MemtableFlushEventSender>,
 
     // borrowed from WALManager
     pub(crate) wal_state: SharedWALState,
@@ -49,20 +67,42 @@ impl MemtableManager {
         let memtable_size_hard_limit =
             (total_memory as f64 * crate::config::MEMTABLE_SIZE_HARD_LIMIT_RATE) as usize;
 
-        let (fake_sender, _) = tokio::sync::mpsc::channel(1);
-
         Self {
             memtable_map: Arc::new(RwLock::new(HashMap::new())),
             flushing_memtable_map: Arc::new(RwLock::new(HashMap::new())),
+            shard_count: MEMTABLE_SHARDS.unwrap_or(system_info.cpu_count).max(1),
+            ordered: *MEMTABLE_ORDERED,
             memtable_current_size: Arc::new(AtomicU64::new(0)),
             block_write: Arc::new(AtomicBool::new(false)),
+            write_unblocked: Arc::new(Notify::new()),
+            flush_pending: Arc::new(AtomicBool::new(false)),
+            flush_done: Arc::new(Notify::new()),
             memtable_size_soft_limit,
             memtable_size_hard_limit,
-            memtable_flush_sender: fake_sender,
+            memtable_flush_sender: None,
             wal_state: wal_manager.wal_state.clone(),
         }
     }
 
+    // Memtables created from now on keep their keys sorted (or not)
+    pub fn with_ordered_memtables(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    fn new_memtable(&self) -> Arc<ShardedMemtable> {
+        if self.ordered {
+            Arc::new(ShardedMemtable::ordered(self.shard_count))
+        } else {
+            Arc::new(ShardedMemtable::new(self.shard_count))
+        }
+    }
+
+    // Wire the flush events to the task that writes them to the disktable
+    pub(crate) fn attach_flush_sender(&mut self, sender: MemtableFlushEventSender) {
+        self.memtable_flush_sender = Some(sender);
+    }
+
     // Get current memtable size
     pub fn get_memtable_current_size(&self) -> errors::Result<u64> {
         let memtable_current_size = self.memtable_current_size.load(Ordering::Relaxed);
@@ -90,13 +130,24 @@ impl MemtableManager {
                         payload.table,
                         payload.key,
                         payload.value.unwrap_or_default(),
+                        payload.value_type,
+                        record.record_id.into(),
+                        payload.written_at,
                     )
                     .await?;
                 }
                 RecordType::Delete => {
                     let payload = record.data;
 
-                    match self.delete_value(payload.table, payload.key).await {
+                    match self
+                        .delete_value(
+                            payload.table,
+                            payload.key,
+                            record.record_id.into(),
+                            payload.written_at,
+                        )
+                        .await
+                    {
                         Ok(_) => (),
                         Err(error) => {
                             match error.error_code {
@@ -124,7 +175,7 @@ impl MemtableManager {
 
     // List all tables in memtables
     pub async fn list_tables(&self) -> errors::Result<Vec<String>> {
-        let memtable_map = self.memtable_map.read().await;
+        let memtable_map = ordered(LockLevel::MemtableMap, self.memtable_map.read()).await;
 
         let table_names = memtable_map.keys().cloned().collect();
 
@@ -133,13 +184,17 @@ impl MemtableManager {
 
     // Create table in memtables
     pub async fn create_table(&self, table: &str) -> errors::Result<()> {
-        let mut memtable_map = self.memtable_map.write().await;
+        let mut memtable_map = ordered(LockLevel::MemtableMap, self.memtable_map.write()).await;
 
-        if !memtable_map.contains_key(table) {
-            let memtable = Arc::new(RwLock::new(Memtable::new()));
-            memtable_map.insert(table.to_string(), memtable);
+        // checked and inserted under the same write lock, so one of concurrent creates wins
+        if memtable_map.contains_key(table) {
+            return Err(errors::Errors::new(ErrorCodes::TableAlreadyExists)
+                .with_message(format!("Table '{}' already exists", table)));
         }
 
+        let memtable = self.new_memtable();
+        memtable_map.insert(table.to_string(), memtable);
+
         Ok(())
     }
 
@@ -147,114 +202,255 @@ impl MemtableManager {
     pub async fn delete_table(&self, table: &str) -> errors::Result<()> {
         // 1. Delete the table from the map
         let delete_result = {
-            let mut memtable_map = self.memtable_map.write().await;
+            let mut memtable_map = ordered(LockLevel::MemtableMap, self.memtable_map.write()).await;
 
             memtable_map.remove(table)
         };
 
         // 2. Decrement the current size
         if let Some(deleted_table) = delete_result {
-            let reclaimed: u64 = deleted_table
-                .read()
-                .await
-                .kv_map
-                .values()
-                .filter_map(|e| e.value.as_ref().map(|v| v.len() as u64))
-                .sum();
+            let reclaimed = deleted_table.total_size().await;
 
-            if reclaimed > 0 {
-                self.memtable_current_size
-                    .fetch_sub(reclaimed, Ordering::SeqCst);
-            }
+            self.sub_current_size(reclaimed as u64);
         }
 
         Ok(())
     }
 
     // trigger memtable flush (move active memtable to flushing memtable and send flush event)
+    // Never waits for the flush worker: while the previous flush is queued or running, fails with MemtableFlushAlreadyInProgress.
     pub async fn trigger_flush(&self) -> errors::Result<()> {
+        // 채널이 없으면 memtable을 옮기지 않고 실패 (옮기면 flush되지 않은 채 남음)
+        let Some(memtable_flush_sender) = &self.memtable_flush_sender else {
+            log::error!("Memtable flush triggered before the flush channel was attached");
+
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::MemtableFlushUnavailable)
+                    .with_message("Memtable flush channel is not attached yet".to_string()),
+            );
+        };
+
+        if memtable_flush_sender.is_closed() {
+            log::error!("Memtable flush task is not running. The memtable was not flushed");
+
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::MemtableFlushUnavailable)
+                    .with_message("Memtable flush task is not running".to_string()),
+            );
+        }
+
+        if self
+            .flush_pending
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(errors::Errors::new(
+                errors::ErrorCodes::MemtableFlushAlreadyInProgress,
+            ));
+        }
+
+        // clears flush_pending when the worker is done, or right away if the event is not sent
+        let completion = FlushCompletion::new(self.flush_pending.clone(), self.flush_done.clone());
+
         if self
             .block_write
             .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
-            .is_ok()
+            .is_err()
         {
-            self.memtable_current_size.store(0, Ordering::SeqCst);
+            return Err(errors::Errors::new(
+                errors::ErrorCodes::MemtableFlushAlreadyInProgress,
+            ));
+        }
 
-            {
-                let mut memtable_map = self.memtable_map.write().await;
+        let flushing_size = self.memtable_current_size.swap(0, Ordering::SeqCst);
 
-                let mut flushing_memtable = self.flushing_memtable_map.write().await;
-                for table in memtable_map.keys() {
-                    flushing_memtable.insert(table.clone(), Arc::new(RwLock::new(Memtable::new())));
-                }
+        let flushed_up_to = {
+            let mut memtable_map = ordered(LockLevel::MemtableMap, self.memtable_map.write()).await;
 
-                std::mem::swap(&mut *memtable_map, &mut *flushing_memtable);
+            let mut flushing_memtable = ordered(
+                LockLevel::FlushingMemtableMap,
+                self.flushing_memtable_map.write(),
+            )
+            .await;
+            for table in memtable_map.keys() {
+                flushing_memtable.insert(table.clone(), self.new_memtable());
             }
 
-            let _ = self
-                .memtable_flush_sender
-                .send(MemtableFlushEvent {
-                    memtable: self.flushing_memtable_map.clone(),
-                    wal_state: self.wal_state.clone(),
-                })
-                .await;
+            // records logged from now on go to the new active memtable
+            let flushed_up_to = {
+                let wal_state = ordered(LockLevel::WALState, self.wal_state.lock()).await;
+                WALPosition {
+                    segment_id: wal_state.last_segment_id.clone(),
+                    record_id: wal_state.last_record_id,
+                }
+            };
 
-            self.block_write.store(false, Ordering::SeqCst);
-        } else {
-            return Err(errors::Errors::new(
-                errors::ErrorCodes::MemtableFlushAlreadyInProgress,
-            ));
+            std::mem::swap(&mut *memtable_map, &mut *flushing_memtable);
+
+            flushed_up_to
+        };
+
+        // at most one event is in flight, so the channel (capacity 1) always has room
+        let send_result = memtable_flush_sender.try_send(MemtableFlushEvent {
+            memtable: self.flushing_memtable_map.clone(),
+            active_memtable: self.memtable_map.clone(),
+            memtable_current_size: self.memtable_current_size.clone(),
+            wal_state: self.wal_state.clone(),
+            flushed_up_to,
+            size: flushing_size,
+            completion,
+        });
+
+        self.unblock_write();
+
+        // the flush task is gone. the records stay readable in the flushing memtable and in the WAL
+        if send_result.is_err() {
+            log::error!("Memtable flush task is not running. The memtable was not flushed");
+
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::MemtableFlushUnavailable)
+                    .with_message("Memtable flush task is not running".to_string()),
+            );
         }
 
         Ok(())
     }
 
+    // Flush the memtable and wait until the flush worker is done with it (ex: shutdown).
+    // A flush already in progress is waited for first
+    pub async fn flush(&self) -> errors::Result<()> {
+        loop {
+            match self.trigger_flush().await {
+                Ok(()) => break,
+                Err(error)
+                    if matches!(error.error_code, ErrorCodes::MemtableFlushAlreadyInProgress) =>
+                {
+                    self.wait_flush_done().await;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        self.wait_flush_done().await;
+
+        Ok(())
+    }
+
+    // Wait until the queued or running flush is done
+    async fn wait_flush_done(&self) {
+        loop {
+            let notified = self.flush_done.notified();
+            tokio::pin!(notified);
+
+            notified.as_mut().enable();
+
+            if !self.flush_pending.load(Ordering::SeqCst) {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+
+    // Decrease current size without wrapping below zero (the size can be reset by flush/truncate)
+    fn sub_current_size(&self, bytes: u64) {
+        let _ = self.memtable_current_size.fetch_update(
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+            |current| Some(current.saturating_sub(bytes)),
+        );
+    }
+
+    // Clear block_write and wake up all blocked writers
+    fn unblock_write(&self) {
+        self.block_write.store(false, Ordering::SeqCst);
+        self.write_unblocked.notify_waiters();
+    }
+
+    // Wait until block_write is cleared
+    async fn wait_write_unblocked(&self) {
+        loop {
+            let notified = self.write_unblocked.notified();
+            tokio::pin!(notified);
+
+            // register as a waiter before checking the flag, so a notification between the check and the await is not lost
+            notified.as_mut().enable();
+
+            if !self.block_write.load(Ordering::SeqCst) {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+
     // Truncate table in both active and flushing memtables
     pub async fn truncate_table(&self, table_name: &str) -> errors::Result<()> {
-        // 1. remove from memtable_map
+        // 1. remove from memtable_map (and reclaim its size)
         {
-            let mut memtable_map = self.memtable_map.write().await;
+            let mut memtable_map = ordered(LockLevel::MemtableMap, self.memtable_map.write()).await;
 
             if let Some(table_map) = memtable_map.get_mut(table_name) {
-                table_map.write().await.clear();
+                for shard in table_map.shards() {
+                    let mut shard = ordered(LockLevel::Memtable, shard.write()).await;
+
+                    self.sub_current_size(shard.total_size() as u64);
+                    shard.clear();
+                }
             }
         }
 
-        // 2. remove from flushing_memtable_map
+        // 2. remove from flushing_memtable_map (not counted in current size. reset on trigger_flush)
         {
-            let mut flushing_memtable_map = self.flushing_memtable_map.write().await;
+            let mut flushing_memtable_map = ordered(
+                LockLevel::FlushingMemtableMap,
+                self.flushing_memtable_map.write(),
+            )
+            .await;
 
             if let Some(table_map) = flushing_memtable_map.get_mut(table_name) {
-                table_map.write().await.clear();
+                table_map.clear().await;
             }
         }
 
-        // 3. clear current size
-        self.memtable_current_size.store(0, Ordering::SeqCst);
-
         Ok(())
     }
 
-    pub async fn put(&self, table: String, key: String, value: String) -> errors::Result<()> {
-        let bytes = key.len() + value.len();
+    pub async fn put(
+        &self,
+        table: String,
+        key: String,
+        value: Vec<u8>,
+        value_type: ValueType,
+        version: u64,
+        written_at: u64,
+    ) -> errors::Result<()> {
+        let bytes = entry_size(&key, Some(&value));
 
         // 1. increment the current size, and check if it exceeds the hard limit
         // send a flush event if it exceeds the hard limit
         loop {
-            let is_blocked = self.block_write.load(Ordering::Relaxed);
-            if is_blocked {
-                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
-                continue;
-            }
+            self.wait_write_unblocked().await;
 
             let current_memtable_size = self.memtable_current_size.load(Ordering::SeqCst);
 
             let new_size_value = current_memtable_size + (bytes as u64);
 
             if new_size_value > self.memtable_size_hard_limit as u64 {
-                self.trigger_flush().await?;
+                match self.trigger_flush().await {
+                    Ok(()) => {}
+                    // the memtable is full again before the previous flush finished. wait for it (backpressure)
+                    Err(error)
+                        if matches!(
+                            error.error_code,
+                            ErrorCodes::MemtableFlushAlreadyInProgress
+                        ) =>
+                    {
+                        self.wait_flush_done().await;
+                    }
+                    Err(error) => return Err(error),
+                }
 
-                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
                 continue;
             }
 
@@ -279,7 +475,7 @@ impl MemtableManager {
 
         // 2. get memtable for the table
         let memtable = {
-            let memtable_map = self.memtable_map.read().await;
+            let memtable_map = ordered(LockLevel::MemtableMap, self.memtable_map.read()).await;
 
             match memtable_map.get(&table) {
                 Some(memtable) => memtable.clone(),
@@ -291,13 +487,12 @@ impl MemtableManager {
         };
 
         // 3. put the key-value into the memtable
-        let mut memtable_lock = memtable.write().await;
-        let old_value_size = memtable_lock.put(key, value);
+        let mut memtable_lock = ordered(LockLevel::Memtable, memtable.shard(&key).write()).await;
+        let old_entry_size = memtable_lock.put(key, value, value_type, version, written_at);
 
-        // 4. adjust current size if there was an old value
-        if let Some(old_size) = old_value_size {
-            self.memtable_current_size
-                .fetch_sub(old_size as u64, Ordering::SeqCst);
+        // 4. replace the old entry size if the key was already in the memtable
+        if let Some(old_entry_size) = old_entry_size {
+            self.sub_current_size(old_entry_size as u64);
         }
 
         Ok(())
@@ -309,11 +504,11 @@ impl MemtableManager {
         table: &str,
         key: &str,
     ) -> errors::Result<MemtableGetValueResult> {
-        let memtable_map = self.memtable_map.read().await;
+        let memtable_map = ordered(LockLevel::MemtableMap, self.memtable_map.read()).await;
 
         match memtable_map.get(table) {
             Some(memtable) => {
-                let memtable_lock = memtable.read().await;
+                let memtable_lock = ordered(LockLevel::Memtable, memtable.shard(key).read()).await;
 
                 Ok(memtable_lock.get(key))
             }
@@ -327,11 +522,15 @@ impl MemtableManager {
         table: &str,
         key: &str,
     ) -> errors::Result<MemtableGetValueResult> {
-        let memtable_map = self.flushing_memtable_map.read().await;
+        let memtable_map = ordered(
+            LockLevel::FlushingMemtableMap,
+            self.flushing_memtable_map.read(),
+        )
+        .await;
 
         match memtable_map.get(table) {
             Some(memtable) => {
-                let memtable_lock = memtable.read().await;
+                let memtable_lock = ordered(LockLevel::Memtable, memtable.shard(key).read()).await;
 
                 Ok(memtable_lock.get(key))
             }
@@ -339,28 +538,180 @@ impl MemtableManager {
         }
     }
 
-    // Delete key from memtable
-    pub async fn delete_value(&self, table: String, key: String) -> errors::Result<()> {
-        // 1. check if the write is blocked
-        loop {
-            let is_blocked = self.block_write.load(Ordering::Relaxed);
+    // All entries (including tombstones) of a table in the active and flushing memtables.
+    // the newest version of a key wins.
+    // active is read before flushing, so an entry moved by a concurrent flush is not missed.
+    pub async fn scan_entries(
+        &self,
+        table: &str,
+    ) -> errors::Result<HashMap<String, MemtableValue>> {
+        let mut entries: HashMap<String, MemtableValue> = HashMap::new();
+
+        for (memtable_map, level) in [
+            (&self.memtable_map, LockLevel::MemtableMap),
+            (&self.flushing_memtable_map, LockLevel::FlushingMemtableMap),
+        ] {
+            let memtable_map = ordered(level, memtable_map.read()).await;
+
+            let Some(memtable) = memtable_map.get(table) else {
+                continue;
+            };
+
+            for shard in memtable.shards() {
+                let memtable_lock = ordered(LockLevel::Memtable, shard.read()).await;
+
+                for (key, entry) in memtable_lock.kv_map.iter() {
+                    match entries.get(key) {
+                        Some(existing) if existing.version >= entry.version => {}
+                        _ => {
+                            entries.insert(key.clone(), entry.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(entries)
+    }
 
-            if is_blocked {
-                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    // Up to limit entries of the active and flushing memtables with keys in the bounds, in key order.
+    // the newest version of a key wins. Deleted and OnDisk entries are returned too, so they can shadow the disktable
+    pub async fn scan(
+        &self,
+        table: &str,
+        start: Bound<String>,
+        end: Bound<String>,
+        limit: usize,
+    ) -> errors::Result<Vec<(String, MemtableGetValueResult)>> {
+        let mut entries: BTreeMap<String, MemtableValue> = BTreeMap::new();
+
+        // active is read before flushing, like scan_entries
+        for (memtable_map, level) in [
+            (&self.memtable_map, LockLevel::MemtableMap),
+            (&self.flushing_memtable_map, LockLevel::FlushingMemtableMap),
+        ] {
+            let memtable_map = ordered(level, memtable_map.read()).await;
+
+            let Some(memtable) = memtable_map.get(table) else {
                 continue;
+            };
+
+            let scanned = memtable
+                .scan(
+                    start.as_ref().map(String::as_str),
+                    end.as_ref().map(String::as_str),
+                    limit,
+                )
+                .await;
+            for (key, entry) in scanned {
+                match entries.get(&key) {
+                    Some(existing) if existing.version >= entry.version => {}
+                    _ => {
+                        entries.insert(key, entry);
+                    }
+                }
             }
+        }
+
+        Ok(entries
+            .into_iter()
+            .take(limit)
+            .map(|(key, entry)| {
+                let result = entry.get_result();
+                (key, result)
+            })
+            .collect())
+    }
 
-            break;
+    // Keys in active and flushing memtables whose live value satisfies the predicate
+    pub async fn find_keys(
+        &self,
+        table: &str,
+        predicate: impl Fn(&[u8]) -> bool,
+    ) -> errors::Result<Vec<String>> {
+        let mut keys = vec![];
+
+        for (memtable_map, level) in [
+            (&self.memtable_map, LockLevel::MemtableMap),
+            (&self.flushing_memtable_map, LockLevel::FlushingMemtableMap),
+        ] {
+            let memtable_map = ordered(level, memtable_map.read()).await;
+
+            let Some(memtable) = memtable_map.get(table) else {
+                continue;
+            };
+
+            for shard in memtable.shards() {
+                let memtable_lock = ordered(LockLevel::Memtable, shard.read()).await;
+
+                for (key, entry) in memtable_lock.kv_map.iter() {
+                    if let Some(value) = &entry.value
+                        && predicate(value.as_slice())
+                    {
+                        keys.push(key.clone());
+                    }
+                }
+            }
         }
 
+        Ok(keys)
+    }
+
+    // Delete key from memtable
+    pub async fn delete_value(
+        &self,
+        table: String,
+        key: String,
+        version: u64,
+        written_at: u64,
+    ) -> errors::Result<()> {
+        self.put_marker(table, key, version, written_at, false)
+            .await
+    }
+
+    // Record that the value of the key was written through to the disktable
+    pub async fn mark_on_disk(
+        &self,
+        table: String,
+        key: String,
+        version: u64,
+        written_at: u64,
+    ) -> errors::Result<()> {
+        self.put_marker(table, key, version, written_at, true).await
+    }
+
+    async fn put_marker(
+        &self,
+        table: String,
+        key: String,
+        version: u64,
+        written_at: u64,
+        on_disk: bool,
+    ) -> errors::Result<()> {
+        // 1. wait if the write is blocked
+        self.wait_write_unblocked().await;
+
         // 2. check if the memtable exists
-        let memtable_map = self.memtable_map.read().await;
+        let memtable_map = ordered(LockLevel::MemtableMap, self.memtable_map.read()).await;
 
         match memtable_map.get(&table) {
             Some(memtable) => {
-                let mut memtable_lock = memtable.write().await;
+                let mut memtable_lock =
+                    ordered(LockLevel::Memtable, memtable.shard(&key).write()).await;
+
+                // 3. replace the old entry size with the marker (tombstone) size
+                self.memtable_current_size
+                    .fetch_add(entry_size(&key, None) as u64, Ordering::SeqCst);
 
-                let _ = memtable_lock.delete(&key);
+                let old_entry_size = if on_disk {
+                    memtable_lock.mark_on_disk(&key, version, written_at)
+                } else {
+                    memtable_lock.delete(&key, version, written_at)
+                };
+
+                if let Some(old_entry_size) = old_entry_size {
+                    self.sub_current_size(old_entry_size as u64);
+                }
 
                 Ok(())
             }
@@ -369,3 +720,473 @@ impl MemtableManager {
         }
     }
 }
+
+// Copy the entries left in the flushing memtables back into the active ones after a flush gave up,
+// so they go out with the next flush. Their WAL records were not checkpointed.
+// Entries the active memtable has a newer version of are skipped. Returns the number of entries copied.
+// The flushing memtables keep their copy until the next trigger_flush replaces them: reads check active before
+// flushing, so clearing them here could hide an entry from a read that checked active just before the copy.
+pub(crate) async fn restore_unflushed(
+    active_memtable_map: &MemtableMap,
+    flushing_memtable_map: &MemtableMap,
+    memtable_current_size: &AtomicU64,
+) -> usize {
+    let memtable_map = ordered(LockLevel::MemtableMap, active_memtable_map.read()).await;
+    let flushing_memtable_map =
+        ordered(LockLevel::FlushingMemtableMap, flushing_memtable_map.read()).await;
+
+    let mut restored = 0;
+    for (table_name, flushing_memtable) in flushing_memtable_map.iter() {
+        // dropped meanwhile
+        let Some(memtable) = memtable_map.get(table_name) else {
+            continue;
+        };
+
+        for flushing_shard in flushing_memtable.shards() {
+            let entries: Vec<_> = ordered(LockLevel::Memtable, flushing_shard.read())
+                .await
+                .kv_map
+                .iter()
+                .map(|(key, entry)| (key.clone(), entry.clone()))
+                .collect();
+
+            for (key, entry) in entries {
+                let mut memtable = ordered(LockLevel::Memtable, memtable.shard(&key).write()).await;
+                if memtable
+                    .kv_map
+                    .get(&key)
+                    .is_some_and(|active| active.version >= entry.version)
+                {
+                    continue;
+                }
+
+                memtable_current_size.fetch_add(
+                    entry_size(&key, entry.value.as_deref()) as u64,
+                    Ordering::SeqCst,
+                );
+                memtable.kv_map.insert(key, entry);
+                restored += 1;
+            }
+        }
+    }
+
+    restored
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ops::Bound,
+        sync::{Arc, atomic::Ordering},
+    };
+
+    use super::MemtableManager;
+    use crate::{
+        bridge::event::MemtableFlushEvent,
+        errors::ErrorCodes,
+        memtable::table::MemtableGetValueResult,
+        system::get_system_info,
+        value_type::ValueType,
+        wal::encode::WALRecordBincodeCodec,
+        wal::{WALManager, WALOptions},
+    };
+
+    #[tokio::test]
+    async fn test_blocked_write_wakes_up_on_unblock() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_write_unblock_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let wal_manager = WALManager::initialize(
+            Box::new(WALRecordBincodeCodec {}),
+            base_path.clone(),
+            WALOptions::default(),
+        )
+        .await
+        .unwrap();
+        let manager = Arc::new(MemtableManager::new(&get_system_info(), &wal_manager));
+        manager.create_table("items").await.unwrap();
+
+        // 시계를 멈추면 sleep 기반 polling은 시간이 흘러야만 깨어남
+        tokio::time::pause();
+        manager.block_write.store(true, Ordering::SeqCst);
+
+        let handle = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                manager
+                    .put(
+                        "items".into(),
+                        "key1".into(),
+                        "value".into(),
+                        ValueType::Raw,
+                        1,
+                        0,
+                    )
+                    .await
+            }
+        });
+
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert!(!handle.is_finished());
+
+        let start = tokio::time::Instant::now();
+        manager.unblock_write();
+        handle.await.unwrap().unwrap();
+
+        // 알림으로 즉시 깨어나므로 시간이 흐르지 않음
+        assert_eq!(start.elapsed(), std::time::Duration::ZERO);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_delete_reclaims_value_size() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_delete_size_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let wal_manager = WALManager::initialize(
+            Box::new(WALRecordBincodeCodec {}),
+            base_path.clone(),
+            WALOptions::default(),
+        )
+        .await
+        .unwrap();
+        let manager = MemtableManager::new(&get_system_info(), &wal_manager);
+        manager.create_table("items").await.unwrap();
+
+        let value = vec![b'v'; 1024 * 1024];
+        manager
+            .put(
+                "items".into(),
+                "key1".into(),
+                value.clone(),
+                ValueType::Raw,
+                1,
+                0,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            manager.get_memtable_current_size().unwrap(),
+            ("key1".len() + value.len()) as u64
+        );
+
+        // 같은 키를 덮어쓰면 키 크기가 중복으로 계산되지 않음
+        manager
+            .put(
+                "items".into(),
+                "key1".into(),
+                value.clone(),
+                ValueType::Raw,
+                2,
+                0,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            manager.get_memtable_current_size().unwrap(),
+            ("key1".len() + value.len()) as u64
+        );
+
+        // 삭제하면 tombstone 키 크기만 남음
+        manager
+            .delete_value("items".into(), "key1".into(), 3, 0)
+            .await
+            .unwrap();
+        assert_eq!(
+            manager.get_memtable_current_size().unwrap(),
+            "key1".len() as u64
+        );
+
+        // memtable에 없는 키 삭제는 tombstone 키 크기만큼 증가
+        manager
+            .delete_value("items".into(), "key2".into(), 4, 0)
+            .await
+            .unwrap();
+        assert_eq!(
+            manager.get_memtable_current_size().unwrap(),
+            ("key1".len() + "key2".len()) as u64
+        );
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_current_size_matches_entries_after_random_writes() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_size_property_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let wal_manager = WALManager::initialize(
+            Box::new(WALRecordBincodeCodec {}),
+            base_path.clone(),
+            WALOptions::default(),
+        )
+        .await
+        .unwrap();
+        let manager = MemtableManager::new(&get_system_info(), &wal_manager);
+        manager.create_table("items").await.unwrap();
+
+        // xorshift (고정 seed로 재현 가능)
+        let mut seed = 0x2545F4914F6CDD1D_u64;
+        let mut next_random = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        for version in 0..2000 {
+            let key = format!("key{}", next_random() % 50);
+
+            if next_random() % 3 == 0 {
+                manager
+                    .delete_value("items".into(), key, version, 0)
+                    .await
+                    .unwrap();
+            } else {
+                let value = vec![b'v'; (next_random() % 100) as usize];
+                manager
+                    .put("items".into(), key, value, ValueType::Raw, version, 0)
+                    .await
+                    .unwrap();
+            }
+
+            let expected = {
+                let memtable_map = manager.memtable_map.read().await;
+                memtable_map["items"].total_size().await as u64
+            };
+            assert_eq!(manager.get_memtable_current_size().unwrap(), expected);
+        }
+
+        manager.delete_table("items").await.unwrap();
+        assert_eq!(manager.get_memtable_current_size().unwrap(), 0);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_flush_before_flush_channel_is_attached() {
+        let base_path = std::env::temp_dir().join(format!(
+            "barus_test_flush_unattached_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let wal_manager = WALManager::initialize(
+            Box::new(WALRecordBincodeCodec {}),
+            base_path.clone(),
+            WALOptions::default(),
+        )
+        .await
+        .unwrap();
+        let mut manager = MemtableManager::new(&get_system_info(), &wal_manager);
+        manager.create_table("items").await.unwrap();
+        manager
+            .put(
+                "items".into(),
+                "key1".into(),
+                "value".into(),
+                ValueType::Raw,
+                1,
+                0,
+            )
+            .await
+            .unwrap();
+
+        // 채널 연결 전: 실패하고, 데이터는 active memtable에 그대로 남음
+        let error = manager.trigger_flush().await.unwrap_err();
+        assert!(matches!(
+            error.error_code,
+            ErrorCodes::MemtableFlushUnavailable
+        ));
+        assert!(!manager.block_write.load(Ordering::SeqCst));
+        assert!(matches!(
+            manager.get_value("items", "key1").await.unwrap(),
+            MemtableGetValueResult::Found { .. }
+        ));
+
+        // 연결 후에는 flush event가 전달됨
+        let (sender, mut receiver) = MemtableFlushEvent::make_channel();
+        manager.attach_flush_sender(sender);
+        manager.trigger_flush().await.unwrap();
+
+        let event = receiver.recv().await.unwrap();
+        assert!(event.size > 0);
+        assert!(matches!(
+            manager.get_value("items", "key1").await.unwrap(),
+            MemtableGetValueResult::NotFound
+        ));
+
+        // flush task가 사라지면 실패를 알림
+        drop(event);
+        drop(receiver);
+        assert!(manager.trigger_flush().await.is_err());
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_rapid_flush_triggers_do_not_deadlock() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_rapid_flush_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let wal_manager = WALManager::initialize(
+            Box::new(WALRecordBincodeCodec {}),
+            base_path.clone(),
+            WALOptions::default(),
+        )
+        .await
+        .unwrap();
+        let mut manager = MemtableManager::new(&get_system_info(), &wal_manager);
+        manager.memtable_size_hard_limit = 500; // puts hit the hard limit every few entries
+        manager.create_table("items").await.unwrap();
+
+        let (sender, mut receiver) = MemtableFlushEvent::make_channel();
+        manager.attach_flush_sender(sender);
+        let manager = Arc::new(manager);
+
+        // slow flush worker
+        let worker = tokio::spawn(async move {
+            let mut flushed = 0;
+            while let Some(event) = receiver.recv().await {
+                tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+                for memtable in event.memtable.read().await.values() {
+                    memtable.clear().await;
+                }
+                flushed += 1;
+            }
+            flushed
+        });
+
+        let mut tasks = vec![];
+        for writer in 0..4 {
+            let manager = manager.clone();
+            tasks.push(tokio::spawn(async move {
+                for i in 0..100 {
+                    manager
+                        .put(
+                            "items".into(),
+                            format!("{}-{}", writer, i),
+                            vec![b'v'; 50],
+                            ValueType::Raw,
+                            1,
+                            0,
+                        )
+                        .await
+                        .unwrap();
+                }
+            }));
+        }
+        tasks.push(tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                for _ in 0..100 {
+                    if let Err(error) = manager.trigger_flush().await {
+                        assert!(matches!(
+                            error.error_code,
+                            ErrorCodes::MemtableFlushAlreadyInProgress
+                        ));
+                    }
+                    tokio::task::yield_now().await;
+                }
+            }
+        }));
+
+        tokio::time::timeout(std::time::Duration::from_secs(10), async {
+            for task in tasks {
+                task.await.unwrap();
+            }
+        })
+        .await
+        .expect("flush triggers deadlocked");
+
+        drop(manager);
+        assert!(worker.await.unwrap() > 1);
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+
+    #[tokio::test]
+    async fn test_scan_range() {
+        let base_path =
+            std::env::temp_dir().join(format!("barus_test_memtable_scan_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base_path);
+
+        let wal_manager = WALManager::initialize(
+            Box::new(WALRecordBincodeCodec {}),
+            base_path.clone(),
+            WALOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        for ordered in [false, true] {
+            let manager = MemtableManager::new(&get_system_info(), &wal_manager)
+                .with_ordered_memtables(ordered);
+            manager.create_table("items").await.unwrap();
+
+            for i in 0..20u64 {
+                manager
+                    .put(
+                        "items".into(),
+                        format!("key{:02}", i),
+                        format!("value{}", i).into_bytes(),
+                        ValueType::Raw,
+                        i + 1,
+                        0,
+                    )
+                    .await
+                    .unwrap();
+            }
+            manager
+                .delete_value("items".into(), "key05".into(), 100, 0)
+                .await
+                .unwrap();
+
+            let entries = manager
+                .scan(
+                    "items",
+                    Bound::Included("key03".into()),
+                    Bound::Excluded("key10".into()),
+                    5,
+                )
+                .await
+                .unwrap();
+            let keys: Vec<_> = entries.iter().map(|(key, _)| key.as_str()).collect();
+            assert_eq!(keys, ["key03", "key04", "key05", "key06", "key07"]);
+            // tombstones are returned
+            assert!(matches!(entries[2].1, MemtableGetValueResult::Deleted));
+            assert!(matches!(
+                &entries[0].1,
+                MemtableGetValueResult::Found { value, .. } if value == b"value3"
+            ));
+
+            let entries = manager
+                .scan(
+                    "items",
+                    Bound::Excluded("key17".into()),
+                    Bound::Unbounded,
+                    10,
+                )
+                .await
+                .unwrap();
+            assert_eq!(entries.len(), 2);
+
+            assert!(
+                manager
+                    .scan("missing", Bound::Unbounded, Bound::Unbounded, 10)
+                    .await
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+
+        let _ = std::fs::remove_dir_all(&base_path);
+    }
+}
@@ -0,0 +1,138 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Bound,
+};
+
+use tokio::sync::RwLock;
+
+use crate::{
+    lock::{LockLevel, ordered},
+    memtable::table::{MEMTABLE_DEFAULT_CAPACITY, Memtable, MemtableValue},
+};
+
+// Memtable of a table, split into shards by key hash. Each shard has its own lock,
+// so writes to different keys of the same table do not wait for each other.
+// Shard locks share LockLevel::Memtable: hold at most one of them at a time.
+#[derive(Debug)]
+pub struct ShardedMemtable {
+    shards: Vec<RwLock<Memtable>>,
+}
+
+impl ShardedMemtable {
+    pub fn new(shard_count: usize) -> Self {
+        let shard_count = shard_count.max(1);
+
+        Self {
+            shards: (0..shard_count)
+                .map(|_| {
+                    RwLock::new(Memtable::with_capacity(
+                        MEMTABLE_DEFAULT_CAPACITY / shard_count,
+                    ))
+                })
+                .collect(),
+        }
+    }
+
+    // Shards keep their keys sorted (BARUS_MEMTABLE_ORDERED)
+    pub fn ordered(shard_count: usize) -> Self {
+        Self {
+            shards: (0..shard_count.max(1))
+                .map(|_| RwLock::new(Memtable::ordered()))
+                .collect(),
+        }
+    }
+
+    // The shard holding the key
+    pub fn shard(&self, key: &str) -> &RwLock<Memtable> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+
+        let index = (hasher.finish() % self.shards.len() as u64) as usize;
+
+        &self.shards[index]
+    }
+
+    pub fn shards(&self) -> &[RwLock<Memtable>] {
+        &self.shards
+    }
+
+    // Sum of entry sizes of all shards
+    pub async fn total_size(&self) -> usize {
+        let mut total_size = 0;
+        for shard in &self.shards {
+            total_size += ordered(LockLevel::Memtable, shard.read())
+                .await
+                .total_size();
+        }
+
+        total_size
+    }
+
+    // Up to limit entries with keys in the bounds, in key order.
+    // each shard gives at most limit entries, so the first limit of the merge are complete
+    pub async fn scan(
+        &self,
+        start: Bound<&str>,
+        end: Bound<&str>,
+        limit: usize,
+    ) -> Vec<(String, MemtableValue)> {
+        let mut entries = vec![];
+        for shard in &self.shards {
+            entries.extend(
+                ordered(LockLevel::Memtable, shard.read())
+                    .await
+                    .scan(start, end, limit),
+            );
+        }
+
+        // a key lives in one shard only
+        entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        entries.truncate(limit);
+        entries
+    }
+
+    pub async fn clear(&self) {
+        for shard in &self.shards {
+            ordered(LockLevel::Memtable, shard.write()).await.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShardedMemtable;
+    use crate::value_type::ValueType;
+
+    #[tokio::test]
+    async fn test_keys_spread_over_shards() {
+        let memtable = ShardedMemtable::new(8);
+
+        for i in 0..1000 {
+            let key = format!("key{}", i);
+            memtable
+                .shard(&key)
+                .write()
+                .await
+                .put(key, "value".into(), ValueType::Raw, i, 0);
+        }
+
+        // a key always maps to the same shard
+        for i in 0..1000 {
+            let key = format!("key{}", i);
+            assert!(memtable.shard(&key).read().await.kv_map.contains_key(&key));
+        }
+
+        for shard in memtable.shards() {
+            assert!(!shard.read().await.kv_map.is_empty());
+        }
+        assert_eq!(
+            memtable.total_size().await,
+            (0..1000)
+                .map(|i| format!("key{}", i).len() + 5)
+                .sum::<usize>()
+        );
+
+        memtable.clear().await;
+        assert_eq!(memtable.total_size().await, 0);
+    }
+}
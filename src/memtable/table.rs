@@ -1,17 +1,130 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::{Bound, RangeBounds},
+};
+
+use crate::value_type::ValueType;
 
 pub const MEMTABLE_DEFAULT_CAPACITY: usize = 100000;
 
 // Value type stored in the Memtable
 #[derive(Clone, Debug)]
 pub struct MemtableValue {
-    pub value: Option<String>,
+    pub value: Option<Vec<u8>>,
+    pub value_type: ValueType,
+    pub version: u64,    // record ID of the write that produced this entry
+    pub written_at: u64, // unix timestamp (ms), as logged in the WAL record of the write
+    // the value was written through to the disktable (large value). value is None
+    pub on_disk: bool,
+}
+
+impl MemtableValue {
+    // What a get of the key returns for this entry
+    pub fn get_result(&self) -> MemtableGetValueResult {
+        match &self.value {
+            Some(value) => MemtableGetValueResult::Found {
+                value: value.clone(),
+                value_type: self.value_type,
+                version: self.version,
+                written_at: self.written_at,
+            },
+            None if self.on_disk => MemtableGetValueResult::OnDisk {
+                version: self.version,
+            },
+            None => MemtableGetValueResult::Deleted,
+        }
+    }
+}
+
+// Bytes charged to the memtable size for an entry. A tombstone (None) is charged its key bytes.
+pub fn entry_size(key: &str, value: Option<&[u8]>) -> usize {
+    key.len() + value.map(|v| v.len()).unwrap_or(0)
+}
+
+// Entries of a memtable. Ordered keeps the keys sorted, so range scans don't sort (BARUS_MEMTABLE_ORDERED)
+#[derive(Debug)]
+pub enum MemtableEntries {
+    Hash(HashMap<String, MemtableValue>),
+    Ordered(BTreeMap<String, MemtableValue>),
+}
+
+impl MemtableEntries {
+    pub fn get(&self, key: &str) -> Option<&MemtableValue> {
+        match self {
+            Self::Hash(map) => map.get(key),
+            Self::Ordered(map) => map.get(key),
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut MemtableValue> {
+        match self {
+            Self::Hash(map) => map.get_mut(key),
+            Self::Ordered(map) => map.get_mut(key),
+        }
+    }
+
+    pub fn insert(&mut self, key: String, value: MemtableValue) -> Option<MemtableValue> {
+        match self {
+            Self::Hash(map) => map.insert(key, value),
+            Self::Ordered(map) => map.insert(key, value),
+        }
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Hash(map) => map.len(),
+            Self::Ordered(map) => map.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        match self {
+            Self::Hash(map) => map.clear(),
+            Self::Ordered(map) => map.clear(),
+        }
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (&String, &MemtableValue)> + Send + '_> {
+        match self {
+            Self::Hash(map) => Box::new(map.iter()),
+            Self::Ordered(map) => Box::new(map.iter()),
+        }
+    }
+
+    // Up to limit entries with keys in the bounds, in key order
+    pub fn range(
+        &self,
+        start: Bound<&str>,
+        end: Bound<&str>,
+        limit: usize,
+    ) -> Vec<(&String, &MemtableValue)> {
+        match self {
+            Self::Hash(map) => {
+                let mut entries: Vec<_> = map
+                    .iter()
+                    .filter(|(key, _)| RangeBounds::<str>::contains(&(start, end), key.as_str()))
+                    .collect();
+                entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                entries.truncate(limit);
+                entries
+            }
+            Self::Ordered(map) => map.range::<str, _>((start, end)).take(limit).collect(),
+        }
+    }
 }
 
 // In-memory key-value store
 #[derive(Debug)]
 pub struct Memtable {
-    pub(crate) kv_map: HashMap<String, MemtableValue>,
+    pub(crate) kv_map: MemtableEntries,
 }
 
 impl Memtable {
@@ -19,13 +132,30 @@ impl Memtable {
     pub fn clear(&mut self) {
         self.kv_map.clear();
     }
+
+    // Sum of entry sizes
+    pub fn total_size(&self) -> usize {
+        self.kv_map
+            .iter()
+            .map(|(key, entry)| entry_size(key, entry.value.as_deref()))
+            .sum()
+    }
 }
 
 // Result of a get operation from Memtable
 pub enum MemtableGetValueResult {
-    Found(String),
+    Found {
+        value: Vec<u8>,
+        value_type: ValueType,
+        version: u64,
+        written_at: u64,
+    },
     NotFound,
     Deleted,
+    // the latest value is in the disktable
+    OnDisk {
+        version: u64,
+    },
 }
 
 impl Default for Memtable {
@@ -37,22 +167,66 @@ impl Default for Memtable {
 impl Memtable {
     // Create a new empty Memtable
     pub fn new() -> Self {
+        Self::with_capacity(MEMTABLE_DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            kv_map: HashMap::with_capacity(MEMTABLE_DEFAULT_CAPACITY),
+            kv_map: MemtableEntries::Hash(HashMap::with_capacity(capacity)),
         }
     }
 
-    // Returns previous value size if key existed
-    pub fn put(&mut self, key: String, value: String) -> Option<usize> {
+    // Memtable keeping its keys sorted
+    pub fn ordered() -> Self {
+        Self {
+            kv_map: MemtableEntries::Ordered(BTreeMap::new()),
+        }
+    }
+
+    // Up to limit entries (including markers) with keys in the bounds, in key order
+    pub fn scan(
+        &self,
+        start: Bound<&str>,
+        end: Bound<&str>,
+        limit: usize,
+    ) -> Vec<(String, MemtableValue)> {
+        self.kv_map
+            .range(start, end, limit)
+            .into_iter()
+            .map(|(key, entry)| (key.clone(), entry.clone()))
+            .collect()
+    }
+
+    // Returns previous entry size if key existed
+    pub fn put(
+        &mut self,
+        key: String,
+        value: Vec<u8>,
+        value_type: ValueType,
+        version: u64,
+        written_at: u64,
+    ) -> Option<usize> {
         match self.kv_map.get_mut(&key) {
             Some(entry) => {
-                let prev = entry.value.as_ref().map(|v| v.len()).unwrap_or(0);
+                let prev = entry_size(&key, entry.value.as_deref());
                 entry.value = Some(value);
+                entry.value_type = value_type;
+                entry.version = version;
+                entry.written_at = written_at;
+                entry.on_disk = false;
                 Some(prev)
             }
             None => {
-                self.kv_map
-                    .insert(key, MemtableValue { value: Some(value) });
+                self.kv_map.insert(
+                    key,
+                    MemtableValue {
+                        value: Some(value),
+                        value_type,
+                        version,
+                        written_at,
+                        on_disk: false,
+                    },
+                );
                 None
             }
         }
@@ -61,24 +235,49 @@ impl Memtable {
     // Get value for a key
     pub fn get(&self, key: &str) -> MemtableGetValueResult {
         match self.kv_map.get(key) {
-            Some(entry) => match &entry.value {
-                Some(value) => MemtableGetValueResult::Found(value.clone()),
-                None => MemtableGetValueResult::Deleted,
-            },
+            Some(entry) => entry.get_result(),
             None => MemtableGetValueResult::NotFound,
         }
     }
 
-    // Delete a key, returning previous value size if existed
-    pub fn delete(&mut self, key: &str) -> Option<usize> {
+    // Delete a key, returning previous entry size if existed
+    pub fn delete(&mut self, key: &str, version: u64, written_at: u64) -> Option<usize> {
+        self.replace_with_marker(key, version, written_at, false)
+    }
+
+    // Mark a key as written through to disk, returning previous entry size if existed.
+    // Shadows older memtable entries of the key like a tombstone does.
+    pub fn mark_on_disk(&mut self, key: &str, version: u64, written_at: u64) -> Option<usize> {
+        self.replace_with_marker(key, version, written_at, true)
+    }
+
+    fn replace_with_marker(
+        &mut self,
+        key: &str,
+        version: u64,
+        written_at: u64,
+        on_disk: bool,
+    ) -> Option<usize> {
         if let Some(entry) = self.kv_map.get_mut(key) {
-            let old_size = entry.value.as_ref().map(|v| v.len()).unwrap_or(0);
+            let old_size = entry_size(key, entry.value.as_deref());
 
             entry.value = None;
+            entry.value_type = ValueType::Raw;
+            entry.version = version;
+            entry.written_at = written_at;
+            entry.on_disk = on_disk;
             Some(old_size)
         } else {
-            self.kv_map
-                .insert(key.to_string(), MemtableValue { value: None });
+            self.kv_map.insert(
+                key.to_string(),
+                MemtableValue {
+                    value: None,
+                    value_type: ValueType::Raw,
+                    version,
+                    written_at,
+                    on_disk,
+                },
+            );
 
             None
         }
@@ -1,6 +1,25 @@
 use crate::errors;
 use tokio::fs::File;
 
+// sync_data a file written with tokio::fs::write (ex: index metadata)
+pub async fn sync_file(path: &std::path::Path) -> errors::Result<()> {
+    let file = File::open(path).await.map_err(|e| {
+        errors::Errors::new(errors::ErrorCodes::FileOpenError).with_message(format!(
+            "Failed to open '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    file.sync_data().await.map_err(|e| {
+        errors::Errors::new(errors::io_error_code(
+            &e,
+            errors::ErrorCodes::FileWriteError,
+        ))
+        .with_message(format!("Failed to sync '{}': {}", path.display(), e))
+    })
+}
+
 #[cfg(target_os = "linux")]
 pub async fn file_resize_and_set_zero(file: &mut File, size: u32) -> errors::Result<()> {
     use std::os::fd::{AsFd, AsRawFd};
@@ -25,12 +44,16 @@ pub async fn file_resize_and_set_zero(file: &mut File, size: u32) -> errors::Res
     };
 
     if result != 0 {
-        return Err(
-            errors::Errors::new(errors::ErrorCodes::FileOpenError).with_message(format!(
-                "Failed to zero-fill new WAL segment file: {}",
-                std::io::Error::last_os_error()
-            )),
-        );
+        let error = std::io::Error::last_os_error();
+
+        return Err(errors::Errors::new(errors::io_error_code(
+            &error,
+            errors::ErrorCodes::FileOpenError,
+        ))
+        .with_message(format!(
+            "Failed to zero-fill new WAL segment file: {}",
+            error
+        )));
     }
 
     Ok(())
@@ -49,7 +72,11 @@ pub async fn file_resize_and_set_zero(file: &mut File, size: u32) -> errors::Res
     };
 
     file.set_len(file_size + size as u64).await.map_err(|e| {
-        errors::Errors::new(errors::ErrorCodes::WALSegmentFileOpenError).with_message(format!(
+        errors::Errors::new(errors::io_error_code(
+            &e,
+            errors::ErrorCodes::WALSegmentFileOpenError,
+        ))
+        .with_message(format!(
             "Failed to set length for new WAL segment file: {}",
             e
         ))
@@ -65,7 +92,7 @@ pub async fn file_resize_and_set_zero(file: &mut File, size: u32) -> errors::Res
         })?;
 
     file.write_all(&zero_bytes).await.map_err(|e| {
-        errors::Errors::new(errors::ErrorCodes::FileOpenError)
+        errors::Errors::new(errors::io_error_code(&e, errors::ErrorCodes::FileOpenError))
             .with_message(format!("Failed to zero-fill new WAL segment file: {}", e))
     })?;
 
@@ -100,3 +127,30 @@ pub async fn handle_shutdown() {
         }
     };
 }
+
+// Force-exits with code 1 if the graceful shutdown is not done within `timeout`.
+// Runs on a plain thread: a stalled fsync blocks the runtime thread, and a tokio timer might never fire.
+pub fn start_shutdown_watchdog(timeout: std::time::Duration) {
+    std::thread::spawn(move || {
+        std::thread::sleep(timeout);
+
+        log::warn!(
+            "Graceful shutdown did not finish within {:?}. Exiting without waiting for the WAL flush",
+            timeout
+        );
+        std::process::exit(1);
+    });
+}
+
+pub type ShutdownSender = tokio::sync::watch::Sender<bool>;
+pub type ShutdownReceiver = tokio::sync::watch::Receiver<bool>;
+
+// Channel used to tell the servers to stop accepting connections and drain in-flight requests.
+pub fn shutdown_channel() -> (ShutdownSender, ShutdownReceiver) {
+    tokio::sync::watch::channel(false)
+}
+
+// Resolves once shutdown is requested (or the sender is gone).
+pub async fn wait_for_shutdown(mut receiver: ShutdownReceiver) {
+    let _ = receiver.wait_for(|is_shutdown| *is_shutdown).await;
+}
@@ -0,0 +1,224 @@
+use std::sync::Arc;
+
+use tokio::sync::broadcast::{
+    self,
+    error::{RecvError, TryRecvError},
+};
+
+use crate::{
+    config::REPLICATION_RECONNECT_MAX_BACKOFF,
+    db::DBEngine,
+    grpc::barus::{self, SubscribeRequest, barus_service_client::BarusServiceClient},
+    os::{ShutdownReceiver, wait_for_shutdown},
+    value_type::ValueType,
+    wal::record::{RecordType, WALPayload, WALRecord},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeOp {
+    Put,
+    Delete,
+    Truncate,
+}
+
+// A write logged to the WAL (change data capture)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeEvent {
+    pub table: String,
+    pub key: String, // empty for Truncate
+    pub op: ChangeOp,
+    pub record_id: u64,         // version of the write
+    pub value: Option<Vec<u8>>, // Put only
+    pub value_type: ValueType,  // Put only
+    pub written_at: u64,        // unix timestamp (ms) of the write. 0 if unknown
+}
+
+impl From<WALRecord> for ChangeEvent {
+    fn from(record: WALRecord) -> Self {
+        let op = match record.record_type {
+            RecordType::Put => ChangeOp::Put,
+            RecordType::Delete => ChangeOp::Delete,
+            RecordType::Truncate => ChangeOp::Truncate,
+        };
+
+        Self {
+            table: record.data.table,
+            key: record.data.key,
+            op,
+            record_id: record.record_id.into(),
+            value: record.data.value,
+            value_type: record.data.value_type,
+            written_at: record.data.written_at,
+        }
+    }
+}
+
+impl From<ChangeEvent> for WALRecord {
+    fn from(event: ChangeEvent) -> Self {
+        let record_type = match event.op {
+            ChangeOp::Put => RecordType::Put,
+            ChangeOp::Delete => RecordType::Delete,
+            ChangeOp::Truncate => RecordType::Truncate,
+        };
+
+        Self {
+            record_id: event.record_id.into(),
+            record_type,
+            data: WALPayload {
+                table: event.table,
+                key: event.key,
+                value: event.value,
+                value_type: event.value_type,
+                written_at: event.written_at,
+            },
+        }
+    }
+}
+
+// Receives the writes applied after subscribing, in record ID order.
+// A subscriber that falls more than CHANGE_STREAM_CAPACITY events behind gets RecvError::Lagged.
+#[derive(Debug)]
+pub struct ChangeSubscriber {
+    receiver: broadcast::Receiver<WALRecord>,
+}
+
+impl ChangeSubscriber {
+    pub fn new(receiver: broadcast::Receiver<WALRecord>) -> Self {
+        Self { receiver }
+    }
+
+    pub async fn recv(&mut self) -> Result<ChangeEvent, RecvError> {
+        self.receiver.recv().await.map(ChangeEvent::from)
+    }
+
+    pub fn try_recv(&mut self) -> Result<ChangeEvent, TryRecvError> {
+        self.receiver.try_recv().map(ChangeEvent::from)
+    }
+}
+
+enum FollowResult {
+    Disconnected(String),
+    // the leader no longer has the records after our last one. retrying won't help
+    Unrecoverable(String),
+    Shutdown,
+}
+
+// Follower mode: applies the leader's WAL records (Subscribe stream) to the local engine.
+// Resumes after the last record in the local WAL, reconnecting with backoff until shutdown.
+pub async fn run_follower(db: Arc<DBEngine>, leader: String, shutdown: ShutdownReceiver) {
+    let mut backoff = std::time::Duration::from_secs(1);
+
+    loop {
+        let result = tokio::select! {
+            _ = wait_for_shutdown(shutdown.clone()) => FollowResult::Shutdown,
+            result = follow(&db, &leader) => result,
+        };
+
+        match result {
+            FollowResult::Shutdown => return,
+            FollowResult::Unrecoverable(message) => {
+                log::error!(
+                    "Replication stopped: {}. Re-seed this follower from a copy of the leader's data directory",
+                    message
+                );
+                return;
+            }
+            FollowResult::Disconnected(message) => {
+                log::warn!(
+                    "Replication from {} interrupted: {}. Reconnecting in {:?}",
+                    leader,
+                    message,
+                    backoff
+                );
+            }
+        }
+
+        tokio::select! {
+            _ = wait_for_shutdown(shutdown.clone()) => return,
+            _ = tokio::time::sleep(backoff) => {}
+        }
+
+        backoff = (backoff * 2).min(REPLICATION_RECONNECT_MAX_BACKOFF);
+    }
+}
+
+async fn follow(db: &DBEngine, leader: &str) -> FollowResult {
+    let mut client = match BarusServiceClient::connect(leader.to_string()).await {
+        Ok(client) => client,
+        Err(error) => return FollowResult::Disconnected(error.to_string()),
+    };
+
+    let last_record_id = db.last_record_id().await;
+    log::info!(
+        "Replicating from {} after record {}",
+        leader,
+        last_record_id
+    );
+
+    let request = SubscribeRequest {
+        table: String::new(),
+        after_record_id: Some(last_record_id),
+    };
+
+    let mut stream = match client.subscribe(request).await {
+        Ok(response) => response.into_inner(),
+        Err(status) => return status_result(status),
+    };
+
+    loop {
+        let event = match stream.message().await {
+            Ok(Some(event)) => event,
+            Ok(None) => return FollowResult::Disconnected("stream closed by leader".to_string()),
+            Err(status) => return status_result(status),
+        };
+
+        let record_id = event.record_id;
+        let record = match change_event_to_record(event) {
+            Some(record) => record,
+            None => {
+                return FollowResult::Unrecoverable(format!(
+                    "unknown change operation in record {}",
+                    record_id
+                ));
+            }
+        };
+
+        if let Err(error) = db.apply_replicated_record(record).await {
+            return FollowResult::Disconnected(format!(
+                "failed to apply record {}: {}",
+                record_id, error
+            ));
+        }
+    }
+}
+
+fn status_result(status: tonic::Status) -> FollowResult {
+    match status.code() {
+        tonic::Code::OutOfRange => FollowResult::Unrecoverable(status.message().to_string()),
+        _ => FollowResult::Disconnected(status.to_string()),
+    }
+}
+
+fn change_event_to_record(event: barus::ChangeEvent) -> Option<WALRecord> {
+    let op = match barus::ChangeOp::try_from(event.op).ok()? {
+        barus::ChangeOp::Put => ChangeOp::Put,
+        barus::ChangeOp::Delete => ChangeOp::Delete,
+        barus::ChangeOp::Truncate => ChangeOp::Truncate,
+    };
+
+    Some(
+        ChangeEvent {
+            table: event.table,
+            key: event.key,
+            op,
+            record_id: event.record_id,
+            // binary values come as value_bytes, value is their text form
+            value: event.value_bytes.or(event.value.map(String::into_bytes)),
+            // empty from leaders that predate value types
+            value_type: event.value_type.parse().unwrap_or_default(),
+            // 0 from leaders that predate write times
+            written_at: event.written_at,
+        }
+        .into(),
+    )
+}
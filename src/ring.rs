@@ -0,0 +1,221 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::disktable::index::bloom::fnv1a_64;
+
+// Consistent hashing over barus instance addresses, for clients that spread keys across several servers.
+// Each node is placed on the ring at `virtual_nodes` points and a key goes to the first point at or after its hash,
+// so adding or removing a node only moves the keys of the ring ranges it gains or loses (about 1/n of them).
+// The hash is stable across builds and platforms: every client with the same node list routes a key the same way.
+#[derive(Debug, Clone)]
+pub struct HashRing {
+    virtual_nodes: usize,
+    points: BTreeMap<u64, String>,
+    nodes: BTreeSet<String>,
+}
+
+impl HashRing {
+    pub const DEFAULT_VIRTUAL_NODES: usize = 160;
+
+    pub fn new<I, S>(nodes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::with_virtual_nodes(Self::DEFAULT_VIRTUAL_NODES, nodes)
+    }
+
+    // more virtual nodes spread the keys more evenly, at the cost of a larger ring
+    pub fn with_virtual_nodes<I, S>(virtual_nodes: usize, nodes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut ring = Self {
+            virtual_nodes: virtual_nodes.max(1),
+            points: BTreeMap::new(),
+            nodes: BTreeSet::new(),
+        };
+
+        for node in nodes {
+            ring.add_node(node);
+        }
+
+        ring
+    }
+
+    // Returns false if the node is already on the ring
+    pub fn add_node(&mut self, address: impl Into<String>) -> bool {
+        let address = address.into();
+        if !self.nodes.insert(address.clone()) {
+            return false;
+        }
+
+        self.place(&address);
+
+        true
+    }
+
+    // Returns false if the node is not on the ring
+    pub fn remove_node(&mut self, address: &str) -> bool {
+        if !self.nodes.remove(address) {
+            return false;
+        }
+
+        self.points.retain(|_, node| node != address);
+
+        // points the removed node won in a hash collision go back to the other node
+        let nodes: Vec<_> = self.nodes.iter().cloned().collect();
+        for node in &nodes {
+            self.place(node);
+        }
+
+        true
+    }
+
+    // Address of the instance that owns the key. None if the ring is empty
+    pub fn route(&self, key: &str) -> Option<&str> {
+        let hash = ring_hash(key.as_bytes());
+
+        self.points
+            .range(hash..)
+            .next()
+            .or_else(|| self.points.iter().next())
+            .map(|(_, node)| node.as_str())
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn place(&mut self, address: &str) {
+        for replica in 0..self.virtual_nodes {
+            let point = ring_hash(format!("{}#{}", address, replica).as_bytes());
+
+            // on a collision the smaller address wins, so the ring doesn't depend on the order nodes were added
+            self.points
+                .entry(point)
+                .and_modify(|node| {
+                    if address < node.as_str() {
+                        *node = address.to_string();
+                    }
+                })
+                .or_insert_with(|| address.to_string());
+        }
+    }
+}
+
+// FNV-1a spreads similar inputs (ex: "node#1", "node#2") poorly on its own, so the result is mixed (murmur3 finalizer)
+fn ring_hash(bytes: &[u8]) -> u64 {
+    let mut hash = fnv1a_64(bytes);
+
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^= hash >> 33;
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::HashRing;
+
+    const KEY_COUNT: usize = 20000;
+
+    fn nodes(count: usize) -> Vec<String> {
+        (1..=count)
+            .map(|i| format!("http://10.0.0.{}:53001", i))
+            .collect()
+    }
+
+    fn routes(ring: &HashRing) -> Vec<String> {
+        (0..KEY_COUNT)
+            .map(|i| ring.route(&format!("user{}", i)).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_route_distribution() {
+        let ring = HashRing::new(nodes(5));
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for node in routes(&ring) {
+            *counts.entry(node).or_default() += 1;
+        }
+
+        assert_eq!(counts.len(), 5);
+        let mean = KEY_COUNT / 5;
+        for (node, count) in counts {
+            assert!(
+                count > mean * 3 / 4 && count < mean * 5 / 4,
+                "{} got {} of {} keys",
+                node,
+                count,
+                KEY_COUNT
+            );
+        }
+
+        assert_eq!(HashRing::new(Vec::<String>::new()).route("user1"), None);
+    }
+
+    #[test]
+    fn test_route_is_independent_of_node_order() {
+        let mut reversed = nodes(5);
+        reversed.reverse();
+
+        assert_eq!(
+            routes(&HashRing::new(nodes(5))),
+            routes(&HashRing::new(reversed))
+        );
+    }
+
+    #[test]
+    fn test_node_changes_move_few_keys() {
+        let mut ring = HashRing::new(nodes(4));
+        let before = routes(&ring);
+
+        // adding: keys only move to the new node, about 1/5 of them
+        let added = "http://10.0.0.100:53001";
+        assert!(ring.add_node(added));
+        assert!(!ring.add_node(added));
+        let after = routes(&ring);
+
+        let moved: Vec<_> = before
+            .iter()
+            .zip(&after)
+            .filter(|(before, after)| before != after)
+            .collect();
+        assert!(moved.iter().all(|(_, after)| after.as_str() == added));
+        assert!(
+            moved.len() < KEY_COUNT * 3 / 10,
+            "{} keys moved",
+            moved.len()
+        );
+
+        // removing: only the removed node's keys move, and the ring is back to where it was
+        assert!(ring.remove_node(added));
+        assert!(!ring.remove_node(added));
+        assert_eq!(routes(&ring), before);
+
+        let removed = nodes(4)[0].clone();
+        ring.remove_node(&removed);
+        let after = routes(&ring);
+        for (before, after) in before.iter().zip(&after) {
+            if *before != removed {
+                assert_eq!(before, after);
+            }
+        }
+        assert_eq!(ring.len(), 3);
+    }
+}
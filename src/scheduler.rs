@@ -0,0 +1,161 @@
+use std::{
+    hash::{BuildHasher, RandomState},
+    time::Duration,
+};
+
+use tokio::task::JoinHandle;
+
+use crate::config::{SCHEDULER_DISABLED_TASKS, SCHEDULER_JITTER};
+
+// Runs periodic background tasks (ex: WAL fsync).
+// Every delay is randomized by ±jitter so tasks started together do not keep firing at the same moment.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    jitter: f64,           // 0.1: ±10% of the delay
+    disabled: Vec<String>, // task names that are not started
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new(*SCHEDULER_JITTER, SCHEDULER_DISABLED_TASKS.clone())
+    }
+}
+
+impl Scheduler {
+    pub fn new(jitter: f64, disabled: Vec<String>) -> Self {
+        Self {
+            jitter: jitter.clamp(0.0, 1.0),
+            disabled,
+        }
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.iter().any(|disabled| disabled == name)
+    }
+
+    // Runs `task` every `interval` until the runtime shuts down.
+    // The task returns the delay until its next run (ex: a longer one to back off after a failure).
+    // Returns None when the task is disabled, either by BARUS_SCHEDULER_DISABLED_TASKS or by a None interval.
+    pub fn spawn<F, Fut>(
+        &self,
+        name: &'static str,
+        interval: Option<Duration>,
+        mut task: F,
+    ) -> Option<JoinHandle<()>>
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Duration> + Send,
+    {
+        let Some(interval) = interval else {
+            log::info!("Background task {} disabled", name);
+            return None;
+        };
+
+        if !self.is_enabled(name) {
+            log::info!(
+                "Background task {} disabled by BARUS_SCHEDULER_DISABLED_TASKS",
+                name
+            );
+            return None;
+        }
+
+        log::info!(
+            "Background task {} interval: {:?} (jitter ±{}%)",
+            name,
+            interval,
+            (self.jitter * 100.0).round()
+        );
+
+        let jitter = self.jitter;
+
+        Some(tokio::spawn(async move {
+            let mut delay = interval;
+
+            loop {
+                tokio::time::sleep(with_jitter(delay, jitter)).await;
+
+                delay = task().await;
+            }
+        }))
+    }
+}
+
+// delay * (1 ± jitter)
+fn with_jitter(delay: Duration, jitter: f64) -> Duration {
+    if jitter <= 0.0 {
+        return delay;
+    }
+
+    // [-1, 1]
+    let random = RandomState::new().hash_one(0u8) as f64 / u64::MAX as f64 * 2.0 - 1.0;
+
+    delay.mul_f64(1.0 + jitter * random)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use super::{Scheduler, with_jitter};
+
+    #[test]
+    fn test_with_jitter() {
+        let delay = Duration::from_millis(1000);
+
+        assert_eq!(with_jitter(delay, 0.0), delay);
+
+        let delays: Vec<Duration> = (0..100).map(|_| with_jitter(delay, 0.2)).collect();
+        assert!(delays.iter().all(|delay| {
+            (Duration::from_millis(800)..=Duration::from_millis(1200)).contains(delay)
+        }));
+        assert!(delays.iter().any(|other| *other != delays[0]));
+    }
+
+    #[tokio::test]
+    async fn test_tasks_run_at_configured_cadence() {
+        let scheduler = Scheduler::new(0.2, vec!["disabled".into()]);
+        let interval = Duration::from_millis(50);
+
+        let runs = Arc::new(Mutex::new(vec![]));
+        let handle = scheduler
+            .spawn("test", Some(interval), {
+                let runs = runs.clone();
+                move || {
+                    runs.lock().unwrap().push(Instant::now());
+                    async move { interval }
+                }
+            })
+            .unwrap();
+
+        assert!(
+            scheduler
+                .spawn("disabled", Some(interval), move || async move { interval })
+                .is_none()
+        );
+        assert!(
+            scheduler
+                .spawn("no interval", None, move || async move { interval })
+                .is_none()
+        );
+
+        let started_at = Instant::now();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        handle.abort();
+
+        let runs = runs.lock().unwrap();
+        // 500ms / 50ms, give or take the jitter and timer slack
+        assert!((7..=11).contains(&runs.len()), "{} runs", runs.len());
+
+        // each gap is 40ms ~ 60ms, plus timer slack
+        let mut previous = started_at;
+        for run in runs.iter() {
+            let gap = run.duration_since(previous);
+            assert!(gap >= Duration::from_millis(39), "{:?}", gap);
+            assert!(gap <= Duration::from_millis(90), "{:?}", gap);
+            previous = *run;
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::config::{
+    SLOW_BATCH_THRESHOLD, SLOW_COMPACTION_THRESHOLD, SLOW_DELETE_THRESHOLD, SLOW_FLUSH_THRESHOLD,
+    SLOW_GET_THRESHOLD, SLOW_PUT_THRESHOLD,
+};
+
+// Operations timed by the slow operation log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlowOp {
+    Put,
+    Get,
+    Delete,
+    Flush,
+    Compaction,
+    Batch,
+}
+
+impl SlowOp {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SlowOp::Put => "put",
+            SlowOp::Get => "get",
+            SlowOp::Delete => "delete",
+            SlowOp::Flush => "flush",
+            SlowOp::Compaction => "compaction",
+            SlowOp::Batch => "batch",
+        }
+    }
+}
+
+// Logs operations slower than the threshold of their kind at warn level (BARUS_SLOW_*_MS).
+// Cheaper than tracing every operation: only an Instant per call.
+#[derive(Debug)]
+pub struct SlowOpLog {
+    thresholds: [Option<Duration>; 6],
+    logged: AtomicU64, // slow operations logged since startup
+}
+
+impl Default for SlowOpLog {
+    fn default() -> Self {
+        Self {
+            thresholds: [
+                *SLOW_PUT_THRESHOLD,
+                *SLOW_GET_THRESHOLD,
+                *SLOW_DELETE_THRESHOLD,
+                *SLOW_FLUSH_THRESHOLD,
+                *SLOW_COMPACTION_THRESHOLD,
+                *SLOW_BATCH_THRESHOLD,
+            ],
+            logged: AtomicU64::new(0),
+        }
+    }
+}
+
+impl SlowOpLog {
+    // None disables the log of the operation
+    pub fn with_threshold(mut self, op: SlowOp, threshold: Option<Duration>) -> Self {
+        self.thresholds[op as usize] = threshold;
+        self
+    }
+
+    pub fn threshold(&self, op: SlowOp) -> Option<Duration> {
+        self.thresholds[op as usize]
+    }
+
+    // Run the operation and log it if it was slow. key_len is 0 for operations without a key
+    pub async fn time<T>(
+        &self,
+        op: SlowOp,
+        table: &str,
+        key_len: usize,
+        operation: impl Future<Output = T>,
+    ) -> T {
+        let started_at = Instant::now();
+        let output = operation.await;
+        self.record(op, table, key_len, started_at.elapsed());
+
+        output
+    }
+
+    // Log the operation if it took longer than its threshold. Returns whether it was logged
+    pub fn record(&self, op: SlowOp, table: &str, key_len: usize, elapsed: Duration) -> bool {
+        let Some(threshold) = self.threshold(op) else {
+            return false;
+        };
+        if elapsed <= threshold {
+            return false;
+        }
+
+        self.logged.fetch_add(1, Ordering::Relaxed);
+        log::warn!(
+            op = op.name(), table = table, key_len = key_len as u64, duration_ms = elapsed.as_millis() as u64;
+            "Slow {} on table '{}' took {:?} (threshold {:?})",
+            op.name(),
+            table,
+            elapsed,
+            threshold
+        );
+
+        true
+    }
+
+    pub fn logged(&self) -> u64 {
+        self.logged.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{SlowOp, SlowOpLog};
+
+    #[tokio::test]
+    async fn test_slow_operation_is_logged() {
+        let slow_ops = SlowOpLog::default()
+            .with_threshold(SlowOp::Put, Some(Duration::from_millis(10)))
+            .with_threshold(SlowOp::Get, None);
+
+        slow_ops.time(SlowOp::Put, "items", 4, async {}).await;
+        assert_eq!(slow_ops.logged(), 0);
+
+        slow_ops
+            .time(
+                SlowOp::Put,
+                "items",
+                4,
+                tokio::time::sleep(Duration::from_millis(30)),
+            )
+            .await;
+        assert_eq!(slow_ops.logged(), 1);
+
+        // 비활성화된 작업은 기록하지 않음
+        assert!(!slow_ops.record(SlowOp::Get, "items", 4, Duration::from_secs(60)));
+        assert_eq!(slow_ops.logged(), 1);
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::errors;
+
+// Degraded mode: a full disk or a read-only filesystem. Writes are rejected until an operator clears it.
+// Shared by the foreground writes, the memtable flush and the background WAL fsync.
+#[derive(Debug, Default)]
+pub struct StorageHealth {
+    degraded: AtomicBool,
+}
+
+impl StorageHealth {
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::SeqCst)
+    }
+
+    // Enters degraded mode if the error is StorageUnavailable. Logged once per degradation
+    pub fn observe(&self, error: &errors::Errors) {
+        if matches!(error.error_code, errors::ErrorCodes::StorageUnavailable)
+            && !self.degraded.swap(true, Ordering::SeqCst)
+        {
+            log::error!(
+                error:% = error;
+                "Storage unavailable, rejecting writes until POST /admin/readonly?enabled=false: {}",
+                error
+            );
+        }
+    }
+
+    // Returns whether the storage was degraded
+    pub fn recover(&self) -> bool {
+        self.degraded.swap(false, Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StorageHealth;
+    use crate::errors;
+
+    #[test]
+    fn test_only_storage_errors_degrade() {
+        let health = StorageHealth::default();
+
+        health.observe(&errors::Errors::new(errors::ErrorCodes::FileWriteError));
+        assert!(!health.is_degraded());
+
+        let io_error = std::io::Error::from_raw_os_error(libc::EROFS);
+        health.observe(&errors::Errors::new(errors::io_error_code(
+            &io_error,
+            errors::ErrorCodes::FileWriteError,
+        )));
+        assert!(health.is_degraded());
+
+        assert!(health.recover());
+        assert!(!health.is_degraded());
+        assert!(!health.recover());
+    }
+}
@@ -1,13 +1,14 @@
+use std::sync::LazyLock;
+
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::{Router, response::Response};
+use base64::Engine;
 
 use super::favicon::{FAVICON_16, FAVICON_32};
 use super::{html, swagger_json, swagger_ui_bundle, swagger_ui_css};
 
 pub fn router() -> Router {
-    
-
     Router::new()
         .route("/", get(get_docs))
         .route("/favicon-32x32.png", get(get_favicon32))
@@ -25,23 +26,29 @@ async fn get_docs(_: axum::http::Request<axum::body::Body>) -> impl IntoResponse
         .unwrap()
 }
 
-async fn get_favicon32() -> impl IntoResponse {
-    let base64 = FAVICON_32.to_string();
+// favicons are embedded as base64 text. decoded once, served as PNG bytes
+static FAVICON_32_PNG: LazyLock<Vec<u8>> = LazyLock::new(|| decode_png(FAVICON_32));
+static FAVICON_16_PNG: LazyLock<Vec<u8>> = LazyLock::new(|| decode_png(FAVICON_16));
 
+fn decode_png(base64: &str) -> Vec<u8> {
+    base64::engine::general_purpose::STANDARD
+        .decode(base64.trim())
+        .expect("embedded favicon is not valid base64")
+}
+
+async fn get_favicon32() -> impl IntoResponse {
     Response::builder()
         .status(200)
         .header("Content-Type", "image/png")
-        .body(base64.to_string())
+        .body(axum::body::Body::from(FAVICON_32_PNG.as_slice()))
         .unwrap()
 }
 
 async fn get_favicon16() -> impl IntoResponse {
-    let base64 = FAVICON_16.to_string();
-
     Response::builder()
         .status(200)
         .header("Content-Type", "image/png")
-        .body(base64.to_string())
+        .body(axum::body::Body::from(FAVICON_16_PNG.as_slice()))
         .unwrap()
 }
 
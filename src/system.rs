@@ -18,3 +18,11 @@ pub fn get_system_info() -> SystemInfo {
         cpu_count,
     }
 }
+
+// current unix timestamp (ms)
+pub fn unix_millis_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
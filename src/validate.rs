@@ -1,5 +1,6 @@
 use crate::{
     config::{KEY_BYTES_MAX_SIZE, TABLE_NAME_MAX_SIZE},
+    disktable::table::SecondaryIndexInfo,
     errors,
 };
 
@@ -35,10 +36,43 @@ pub fn validate_key(key: &str) -> errors::Result<()> {
     Ok(())
 }
 
-pub fn validate_value(value: &str) -> errors::Result<()> {
+pub fn validate_value(value: &[u8]) -> errors::Result<()> {
     if value.len() > crate::config::VALUE_BYTES_MAX_SIZE {
         return Err(errors::Errors::new(errors::ErrorCodes::ValueSizeTooLarge));
     }
 
     Ok(())
 }
+
+pub fn validate_secondary_indexes(indexes: &[SecondaryIndexInfo]) -> errors::Result<()> {
+    for (i, index) in indexes.iter().enumerate() {
+        // 1. Index name follows the same rule as table name
+        if index.name.is_empty()
+            || index.name.len() > TABLE_NAME_MAX_SIZE
+            || !index.name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        {
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::SecondaryIndexIsInvalid)
+                    .with_message(format!("Invalid index name: '{}'", index.name)),
+            );
+        }
+
+        // 2. JSON path must not have empty segments
+        if index.json_path.split('.').any(|field| field.is_empty()) {
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::SecondaryIndexIsInvalid)
+                    .with_message(format!("Invalid JSON path: '{}'", index.json_path)),
+            );
+        }
+
+        // 3. Duplicate Check
+        if indexes[..i].iter().any(|other| other.name == index.name) {
+            return Err(
+                errors::Errors::new(errors::ErrorCodes::SecondaryIndexIsInvalid)
+                    .with_message(format!("Duplicate index name: '{}'", index.name)),
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use std::borrow::Cow;
+
+use base64::Engine;
+
+use crate::errors;
+
+// Encoding of a stored value, set on write and stored with the record.
+// Lets server-side operations check the type without parsing the value first.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    bincode::Encode,
+    bincode::Decode,
+)]
+pub enum ValueType {
+    // any string. values written before types existed are Raw
+    #[default]
+    #[serde(rename = "raw")]
+    Raw,
+    // signed 64-bit integer in decimal
+    #[serde(rename = "int")]
+    Int,
+    // a JSON document
+    #[serde(rename = "json")]
+    Json,
+    // arbitrary bytes. base64 (encode_binary) where values are exchanged as text (HTTP, JSON exports)
+    #[serde(rename = "binary")]
+    Binary,
+}
+
+impl ValueType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValueType::Raw => "raw",
+            ValueType::Int => "int",
+            ValueType::Json => "json",
+            ValueType::Binary => "binary",
+        }
+    }
+
+    // Fails with ValueTypeMismatch if `value` is not a valid value of this type.
+    // Every type but Binary is UTF-8 text
+    pub fn validate(&self, value: &[u8]) -> errors::Result<()> {
+        let is_valid = match self {
+            ValueType::Raw => std::str::from_utf8(value).is_ok(),
+            ValueType::Int => std::str::from_utf8(value).is_ok_and(|v| v.parse::<i64>().is_ok()),
+            ValueType::Json => serde_json::from_slice::<serde::de::IgnoredAny>(value).is_ok(),
+            ValueType::Binary => true,
+        };
+
+        if !is_valid {
+            return Err(errors::Errors::new(errors::ErrorCodes::ValueTypeMismatch)
+                .with_message(format!("Value is not a valid {} value", self.as_str())));
+        }
+
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for ValueType {
+    type Err = errors::Errors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(ValueType::Raw),
+            "int" => Ok(ValueType::Int),
+            "json" => Ok(ValueType::Json),
+            "binary" => Ok(ValueType::Binary),
+            _ => Err(errors::Errors::new(errors::ErrorCodes::InvalidValueType)
+                .with_message(format!("Unknown value type: '{}'", s))),
+        }
+    }
+}
+
+// Text form of a stored value: base64 for Binary values, the UTF-8 string otherwise
+pub fn value_to_text(value: &[u8], value_type: ValueType) -> Cow<'_, str> {
+    match value_type {
+        ValueType::Binary => Cow::Owned(encode_binary(value)),
+        _ => String::from_utf8_lossy(value),
+    }
+}
+
+// Text form of a Binary value
+pub fn encode_binary(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+// Bytes of a Binary value in text form. Fails with ValueTypeMismatch if it is not valid base64
+pub fn decode_binary(value: &str) -> errors::Result<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(value)
+        .map_err(|e| {
+            errors::Errors::new(errors::ErrorCodes::ValueTypeMismatch)
+                .with_message(format!("Value is not valid base64: {}", e))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValueType;
+    use crate::errors::ErrorCodes;
+
+    #[test]
+    fn test_validate() {
+        assert!(ValueType::Raw.validate(b"anything").is_ok());
+        assert!(ValueType::Int.validate(b"-42").is_ok());
+        assert!(ValueType::Json.validate(br#"{"a": [1, 2]}"#).is_ok());
+        assert!(ValueType::Binary.validate(&[0x00, 0xff, 0x10]).is_ok());
+
+        for (value_type, value) in [
+            (ValueType::Raw, &[0xff, 0xfe][..]),
+            (ValueType::Int, b"4.2"),
+            (ValueType::Int, b"99999999999999999999"),
+            (ValueType::Json, b"{"),
+        ] {
+            let error = value_type.validate(value).unwrap_err();
+            assert!(matches!(error.error_code, ErrorCodes::ValueTypeMismatch));
+        }
+
+        assert!(matches!(
+            "text".parse::<ValueType>().unwrap_err().error_code,
+            ErrorCodes::InvalidValueType
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::broadcast;
+
+use crate::{
+    config::CHANGE_STREAM_CAPACITY,
+    wal::{record::WALRecord, record_id::WALRecordID},
+};
+
+// Publishes appended records to the subscribers (change data capture).
+// A record is published once the caller has applied it (PendingChange::publish), and only after
+// every earlier record was published or dropped, so subscribers still see record ID order.
+pub struct ChangePublisher {
+    sender: broadcast::Sender<WALRecord>,
+    // records appended while someone was subscribed, in record ID order. None: not applied yet
+    pending: Mutex<VecDeque<(WALRecordID, Option<Option<WALRecord>>)>>,
+}
+
+impl Default for ChangePublisher {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(CHANGE_STREAM_CAPACITY).0,
+            pending: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl ChangePublisher {
+    pub fn subscribe(&self) -> broadcast::Receiver<WALRecord> {
+        self.sender.subscribe()
+    }
+
+    // Must be called in record ID order (with the WAL write lock held)
+    pub fn register(self: &Arc<Self>, record: WALRecord) -> PendingChange {
+        let record_id = record.record_id;
+
+        let mut pending = self.pending.lock().unwrap();
+        if self.sender.receiver_count() == 0 && pending.is_empty() {
+            return PendingChange::untracked(record_id);
+        }
+        pending.push_back((record_id, None));
+
+        PendingChange {
+            record_id,
+            record: Some(record),
+            publisher: Some(self.clone()),
+        }
+    }
+
+    // record: None drops the change (the write failed or was cancelled)
+    fn resolve(&self, record_id: WALRecordID, record: Option<WALRecord>) {
+        let mut pending = self.pending.lock().unwrap();
+
+        if let Some(entry) = pending.iter_mut().find(|(id, _)| *id == record_id) {
+            entry.1 = Some(record);
+        }
+
+        while pending.front().is_some_and(|(_, state)| state.is_some()) {
+            if let Some((_, Some(Some(record)))) = pending.pop_front() {
+                let _ = self.sender.send(record);
+            }
+        }
+    }
+}
+
+// A record appended to the WAL and not yet applied by the caller.
+// Dropping it without publish() drops the change, so a failed write never reaches subscribers.
+pub struct PendingChange {
+    record_id: WALRecordID,
+    record: Option<WALRecord>,
+    publisher: Option<Arc<ChangePublisher>>,
+}
+
+impl PendingChange {
+    // nobody to publish to (no subscribers, or a replicated record)
+    pub fn untracked(record_id: WALRecordID) -> Self {
+        Self {
+            record_id,
+            record: None,
+            publisher: None,
+        }
+    }
+
+    pub fn record_id(&self) -> WALRecordID {
+        self.record_id
+    }
+
+    pub fn publish(mut self) {
+        if let Some(publisher) = self.publisher.take() {
+            publisher.resolve(self.record_id, self.record.take());
+        }
+    }
+}
+
+impl Drop for PendingChange {
+    fn drop(&mut self) {
+        if let Some(publisher) = self.publisher.take() {
+            publisher.resolve(self.record_id, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::ChangePublisher;
+    use crate::{
+        value_type::ValueType,
+        wal::record::{RecordType, WALPayload, WALRecord},
+    };
+
+    fn record(record_id: u64) -> WALRecord {
+        WALRecord {
+            record_id: record_id.into(),
+            record_type: RecordType::Put,
+            data: WALPayload {
+                table: "foo".into(),
+                key: format!("key{}", record_id),
+                value: Some("value".into()),
+                value_type: ValueType::Raw,
+                written_at: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_changes_are_published_in_order_after_apply() {
+        let publisher = Arc::new(ChangePublisher::default());
+        let mut receiver = publisher.subscribe();
+
+        let first = publisher.register(record(1));
+        let second = publisher.register(record(2));
+        let third = publisher.register(record(3));
+
+        // 먼저 적용된 뒤 레코드도 앞 레코드가 끝날 때까지 대기
+        second.publish();
+        assert!(receiver.try_recv().is_err());
+
+        // 실패한 쓰기는 건너뜀
+        drop(first);
+        assert_eq!(u64::from(receiver.try_recv().unwrap().record_id), 2);
+        assert!(receiver.try_recv().is_err());
+
+        third.publish();
+        assert_eq!(u64::from(receiver.try_recv().unwrap().record_id), 3);
+    }
+}
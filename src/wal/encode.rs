@@ -1,6 +1,9 @@
 use bincode::config::{Configuration, Fixint, LittleEndian, NoLimit};
 
-use crate::{errors, wal::record::WALRecord};
+use crate::{
+    errors,
+    wal::record::{LegacyWALRecord, UntimedWALRecord, WALRecord},
+};
 
 pub trait WALRecordCodec {
     fn encode(&self, record: &WALRecord, buf: &mut [u8]) -> errors::Result<usize>;
@@ -26,11 +29,87 @@ impl WALRecordCodec for WALRecordBincodeCodec {
 
     fn decode(&self, data: &[u8]) -> errors::Result<WALRecord> {
         // bincode 2.x uses decode_from_slice with config
-        let (decoded, _len): (WALRecord, usize) = bincode::decode_from_slice(data, Self::CONFIG)
-            .map_err(|e| {
-                errors::Errors::new(errors::ErrorCodes::WALRecordDecodeError)
-                    .with_message(e.to_string())
-            })?;
-        Ok(decoded)
+        match bincode::decode_from_slice::<WALRecord, _>(data, Self::CONFIG) {
+            Ok((decoded, len)) if len == data.len() => Ok(decoded),
+            current_result => {
+                // Records written before write times have no trailing write time field.
+                if let Ok((decoded, len)) =
+                    bincode::decode_from_slice::<UntimedWALRecord, _>(data, Self::CONFIG)
+                    && len == data.len()
+                {
+                    return Ok(decoded.into());
+                }
+
+                // Records written before value types have no trailing value type field either.
+                if let Ok((decoded, len)) =
+                    bincode::decode_from_slice::<LegacyWALRecord, _>(data, Self::CONFIG)
+                    && len == data.len()
+                {
+                    return Ok(decoded.into());
+                }
+
+                let message = match current_result {
+                    Ok((_, len)) => format!("Unexpected record length: {} of {}", len, data.len()),
+                    Err(e) => e.to_string(),
+                };
+
+                Err(
+                    errors::Errors::new(errors::ErrorCodes::WALRecordDecodeError)
+                        .with_message(message),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WALRecordBincodeCodec, WALRecordCodec};
+    use crate::{
+        value_type::ValueType,
+        wal::record::{
+            LegacyWALPayload, LegacyWALRecord, RecordType, UntimedWALPayload, UntimedWALRecord,
+        },
+    };
+
+    #[test]
+    fn test_decode_legacy_record() {
+        let legacy = LegacyWALRecord {
+            record_id: 7.into(),
+            record_type: RecordType::Put,
+            data: LegacyWALPayload {
+                table: "items".into(),
+                key: "key".into(),
+                value: Some("value".into()),
+            },
+        };
+        let bytes = bincode::encode_to_vec(&legacy, WALRecordBincodeCodec::CONFIG).unwrap();
+
+        let decoded = WALRecordBincodeCodec.decode(&bytes).unwrap();
+        assert_eq!(u64::from(decoded.record_id), 7);
+        assert_eq!(decoded.data.key, "key");
+        assert_eq!(decoded.data.value.as_deref(), Some(b"value".as_slice()));
+        assert_eq!(decoded.data.value_type, ValueType::Raw);
+        assert_eq!(decoded.data.written_at, 0);
+    }
+
+    #[test]
+    fn test_decode_untimed_record() {
+        let untimed = UntimedWALRecord {
+            record_id: 7.into(),
+            record_type: RecordType::Put,
+            data: UntimedWALPayload {
+                table: "items".into(),
+                key: "key".into(),
+                value: Some("42".into()),
+                value_type: ValueType::Int,
+            },
+        };
+        let bytes = bincode::encode_to_vec(&untimed, WALRecordBincodeCodec::CONFIG).unwrap();
+
+        let decoded = WALRecordBincodeCodec.decode(&bytes).unwrap();
+        assert_eq!(decoded.data.value.as_deref(), Some(b"42".as_slice()));
+        assert_eq!(decoded.data.value_type, ValueType::Int);
+        assert_eq!(decoded.data.written_at, 0);
     }
 }
@@ -4,6 +4,7 @@ use crate::errors;
 
 pub struct WALSegmentFileWriteHandle {
     pub(crate) mmap: MmapMut,
+    pub(crate) synced_offset: usize, // bytes before this offset are known to be fsynced
 }
 
 impl WALSegmentFileWriteHandle {
@@ -11,6 +12,7 @@ impl WALSegmentFileWriteHandle {
     pub fn empty() -> Self {
         Self {
             mmap: MmapMut::map_anon(0).unwrap(),
+            synced_offset: 0,
         }
     }
 
@@ -26,13 +28,31 @@ impl WALSegmentFileWriteHandle {
             })?
         };
 
-        Ok(Self { mmap })
+        Ok(Self {
+            mmap,
+            synced_offset: 0,
+        })
+    }
+
+    // flush only the given byte range
+    pub fn flush_range(&self, offset: usize, len: usize) -> errors::Result<()> {
+        self.mmap.flush_range(offset, len).map_err(|e| {
+            errors::Errors::new(errors::io_error_code(
+                &e,
+                errors::ErrorCodes::WALRecordWriteError,
+            ))
+            .with_message(format!("Failed to flush WAL segment mmap range: {}", e))
+        })?;
+        Ok(())
     }
 
     pub fn flush(&self) -> errors::Result<()> {
         self.mmap.flush().map_err(|e| {
-            errors::Errors::new(errors::ErrorCodes::WALRecordWriteError)
-                .with_message(format!("Failed to flush WAL segment mmap: {}", e))
+            errors::Errors::new(errors::io_error_code(
+                &e,
+                errors::ErrorCodes::WALRecordWriteError,
+            ))
+            .with_message(format!("Failed to flush WAL segment mmap: {}", e))
         })?;
         Ok(())
     }
@@ -1,4 +1,4 @@
-use crate::wal::record_id::WALRecordID;
+use crate::{value_type::ValueType, wal::record_id::WALRecordID};
 
 // WAL Payload - The actual data stored in a WAL record.
 #[derive(
@@ -7,7 +7,9 @@ use crate::wal::record_id::WALRecordID;
 pub struct WALPayload {
     pub table: String,
     pub key: String,
-    pub value: Option<String>,
+    pub value: Option<Vec<u8>>,
+    pub value_type: ValueType, // Put only. Raw for other record types
+    pub written_at: u64, // unix timestamp (ms) of the write. 0 in records logged before write times
 }
 
 impl WALPayload {
@@ -19,8 +21,72 @@ impl WALPayload {
             None => 0,
         };
 
-        // 8 bytes for table length, 8 bytes for key length, 8 bytes for value length
-        8 + table_size + 8 + key_size + 8 + value_size
+        // 8 bytes for table length, 8 bytes for key length, 8 bytes for value length, 4 bytes for value type,
+        // 8 bytes for write time
+        8 + table_size + 8 + key_size + 8 + value_size + 4 + 8
+    }
+}
+
+// Payload layout written before value types existed.
+#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+pub struct LegacyWALPayload {
+    pub table: String,
+    pub key: String,
+    pub value: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+pub struct LegacyWALRecord {
+    pub record_id: WALRecordID,
+    pub record_type: RecordType,
+    pub data: LegacyWALPayload,
+}
+
+impl From<LegacyWALRecord> for WALRecord {
+    fn from(legacy: LegacyWALRecord) -> Self {
+        Self {
+            record_id: legacy.record_id,
+            record_type: legacy.record_type,
+            data: WALPayload {
+                table: legacy.data.table,
+                key: legacy.data.key,
+                value: legacy.data.value,
+                value_type: ValueType::Raw,
+                written_at: 0,
+            },
+        }
+    }
+}
+
+// Payload layout written before write times were logged.
+#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+pub struct UntimedWALPayload {
+    pub table: String,
+    pub key: String,
+    pub value: Option<Vec<u8>>,
+    pub value_type: ValueType,
+}
+
+#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
+pub struct UntimedWALRecord {
+    pub record_id: WALRecordID,
+    pub record_type: RecordType,
+    pub data: UntimedWALPayload,
+}
+
+impl From<UntimedWALRecord> for WALRecord {
+    fn from(untimed: UntimedWALRecord) -> Self {
+        Self {
+            record_id: untimed.record_id,
+            record_type: untimed.record_type,
+            data: WALPayload {
+                table: untimed.data.table,
+                key: untimed.data.key,
+                value: untimed.data.value,
+                value_type: untimed.data.value_type,
+                written_at: 0,
+            },
+        }
     }
 }
 
@@ -40,6 +40,12 @@ impl cmp::Ord for WALRecordID {
     }
 }
 
+impl From<WALRecordID> for u64 {
+    fn from(val: WALRecordID) -> Self {
+        val.0
+    }
+}
+
 impl From<u64> for WALRecordID {
     fn from(val: u64) -> Self {
         WALRecordID(val)
@@ -14,6 +14,19 @@ pub struct WALGlobalState {
     pub last_segment_id: WALSegmentID,
     pub last_checkpoint_segment_id: WALSegmentID,
     pub last_segment_file_offset: usize,
+    // records up to here are in the disktable, and this segment holds the last of them.
+    // the checkpoint can be moved back below them, but not past them
+    #[serde(default)]
+    pub last_flushed_record_id: WALRecordID,
+    #[serde(default)]
+    pub last_flushed_segment_id: WALSegmentID,
+}
+
+// A record in the WAL: the record ID and the segment that holds it
+#[derive(Debug, Clone, Default)]
+pub struct WALPosition {
+    pub segment_id: WALSegmentID,
+    pub record_id: WALRecordID,
 }
 
 impl WALGlobalState {
@@ -29,10 +42,18 @@ impl WALGlobalState {
         let data = std::fs::read(wal_state_path).map_err(|e| {
             errors::Errors::new(errors::ErrorCodes::WALStateReadError).with_message(e.to_string())
         })?;
-        let state = serde_json::from_slice(&data).map_err(|e| {
+        let mut state: Self = serde_json::from_slice(&data).map_err(|e| {
             errors::Errors::new(errors::ErrorCodes::WALStateDecodeError).with_message(e.to_string())
         })?;
 
+        // state files written before last_flushed_record_id: the checkpoint was only moved by flushes
+        if state.last_flushed_record_id < state.last_checkpoint_record_id {
+            state.last_flushed_record_id = state.last_checkpoint_record_id;
+        }
+        if state.last_flushed_segment_id < state.last_checkpoint_segment_id {
+            state.last_flushed_segment_id = state.last_checkpoint_segment_id.clone();
+        }
+
         Ok(state)
     }
 
@@ -84,11 +105,19 @@ impl WALGlobalState {
             })?;
 
         file_handle.write_all(&data).await.map_err(|e| {
-            errors::Errors::new(errors::ErrorCodes::WALStateWriteError).with_message(e.to_string())
+            errors::Errors::new(errors::io_error_code(
+                &e,
+                errors::ErrorCodes::WALStateWriteError,
+            ))
+            .with_message(e.to_string())
         })?;
 
         file_handle.set_len(data.len() as u64).await.map_err(|e| {
-            errors::Errors::new(errors::ErrorCodes::WALStateWriteError).with_message(e.to_string())
+            errors::Errors::new(errors::io_error_code(
+                &e,
+                errors::ErrorCodes::WALStateWriteError,
+            ))
+            .with_message(e.to_string())
         })?;
 
         Ok(())
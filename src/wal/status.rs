@@ -0,0 +1,129 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use crate::config::{WAL_FSYNC_MAX_BACKOFF, WAL_FSYNC_UNHEALTHY_THRESHOLD};
+
+// Background WAL fsync health
+#[derive(Debug, Clone)]
+pub struct WALFsyncStatus {
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    pub total_failures: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<SystemTime>,
+    pub last_success_at: Option<SystemTime>,
+}
+
+impl Default for WALFsyncStatus {
+    fn default() -> Self {
+        Self {
+            healthy: true,
+            consecutive_failures: 0,
+            total_failures: 0,
+            last_error: None,
+            last_error_at: None,
+            last_success_at: None,
+        }
+    }
+}
+
+// Shared between the background fsync task (writer) and DBEngine (reader)
+#[derive(Debug, Clone, Default)]
+pub struct WALFsyncStatusTracker {
+    status: Arc<Mutex<WALFsyncStatus>>,
+}
+
+impl WALFsyncStatusTracker {
+    pub fn get(&self) -> WALFsyncStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn record_success(&self) {
+        let mut status = self.status.lock().unwrap();
+
+        if !status.healthy {
+            log::info!(
+                "WAL fsync recovered after {} consecutive failures",
+                status.consecutive_failures
+            );
+        }
+
+        status.healthy = true;
+        status.consecutive_failures = 0;
+        status.last_success_at = Some(SystemTime::now());
+    }
+
+    // Returns how long to wait before the next attempt.
+    // The interval doubles on each consecutive failure, up to WAL_FSYNC_MAX_BACKOFF.
+    pub fn record_failure(&self, error: String, interval: Duration) -> Duration {
+        let mut status = self.status.lock().unwrap();
+
+        status.consecutive_failures = status.consecutive_failures.saturating_add(1);
+        status.total_failures += 1;
+        status.last_error = Some(error);
+        status.last_error_at = Some(SystemTime::now());
+
+        if status.healthy && status.consecutive_failures >= WAL_FSYNC_UNHEALTHY_THRESHOLD {
+            status.healthy = false;
+            log::error!(
+                "WAL fsync failed {} times in a row. Marking the engine unhealthy",
+                status.consecutive_failures
+            );
+        }
+
+        let multiplier = 1_u32 << status.consecutive_failures.min(16);
+        interval
+            .saturating_mul(multiplier)
+            .min(WAL_FSYNC_MAX_BACKOFF.max(interval))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::WALFsyncStatusTracker;
+    use crate::config::{WAL_FSYNC_MAX_BACKOFF, WAL_FSYNC_UNHEALTHY_THRESHOLD};
+
+    #[test]
+    fn test_fsync_backoff_and_health() {
+        let tracker = WALFsyncStatusTracker::default();
+        let interval = Duration::from_secs(1);
+
+        assert_eq!(
+            tracker.record_failure("disk full".to_string(), interval),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            tracker.record_failure("disk full".to_string(), interval),
+            Duration::from_secs(4)
+        );
+        assert!(tracker.get().healthy);
+
+        for _ in 2..WAL_FSYNC_UNHEALTHY_THRESHOLD {
+            tracker.record_failure("disk full".to_string(), interval);
+        }
+        let status = tracker.get();
+        assert!(!status.healthy);
+        assert_eq!(status.consecutive_failures, WAL_FSYNC_UNHEALTHY_THRESHOLD);
+        assert_eq!(status.last_error.as_deref(), Some("disk full"));
+
+        // backoff는 상한을 넘지 않음
+        for _ in 0..32 {
+            assert!(
+                tracker.record_failure("disk full".to_string(), interval) <= WAL_FSYNC_MAX_BACKOFF
+            );
+        }
+
+        tracker.record_success();
+        let status = tracker.get();
+        assert!(status.healthy);
+        assert_eq!(status.consecutive_failures, 0);
+        assert_eq!(
+            status.total_failures,
+            32 + WAL_FSYNC_UNHEALTHY_THRESHOLD as u64
+        );
+    }
+}
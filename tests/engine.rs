@@ -0,0 +1,76 @@
+// Integration tests against the public library API
+use std::path::PathBuf;
+
+use barus::{CreateTableOptions, DBEngine, ErrorCodes, ScanOptions};
+
+fn test_base_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("barus_it_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}
+
+#[tokio::test]
+async fn test_put_get_delete() {
+    let base_path = test_base_path("put_get_delete");
+    let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+
+    db.create_table("users", CreateTableOptions::default())
+        .await
+        .unwrap();
+
+    let error = db
+        .create_table("users", CreateTableOptions::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(error.error_code, ErrorCodes::TableAlreadyExists));
+
+    db.put_value("users".into(), "user1".into(), "Alice".into())
+        .await
+        .unwrap();
+    db.put_value("users".into(), "user2".into(), "Bob".into())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        db.get_value("users", "user1").await.unwrap().text(),
+        "Alice"
+    );
+
+    db.delete_value("users".into(), "user1".into())
+        .await
+        .unwrap();
+    let error = db.get_value("users", "user1").await.unwrap_err();
+    assert!(matches!(error.error_code, ErrorCodes::ValueNotFound));
+
+    let scan = db.scan("users", ScanOptions::default()).await.unwrap();
+    let keys: Vec<_> = scan.items.iter().map(|item| item.key.as_str()).collect();
+    assert_eq!(keys, ["user2"]);
+
+    db.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&base_path);
+}
+
+#[tokio::test]
+async fn test_values_survive_restart() {
+    let base_path = test_base_path("restart");
+
+    let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+    db.create_table("users", CreateTableOptions::default())
+        .await
+        .unwrap();
+    for i in 0..100 {
+        db.put_value("users".into(), format!("user{}", i), format!("value{}", i))
+            .await
+            .unwrap();
+    }
+    db.shutdown().await.unwrap();
+    drop(db);
+
+    let db = DBEngine::initialize(base_path.clone()).await.unwrap();
+    for i in 0..100 {
+        let value = db.get_value("users", &format!("user{}", i)).await.unwrap();
+        assert_eq!(value.text(), format!("value{}", i));
+    }
+    db.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&base_path);
+}